@@ -0,0 +1,759 @@
+//! Batched edits to a `Rope`, with position mapping.
+
+use std::ops::Range;
+
+use crate::rope::Rope;
+use crate::str_utils::{char_to_byte_idx, count_chars};
+use crate::{Error, Result};
+
+/// A single run of a [`ChangeSet`](struct.ChangeSet.html).
+///
+/// All lengths are in chars.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Keeps the next `n` chars of the document unchanged.
+    Retain(usize),
+
+    /// Removes the next `n` chars of the document.
+    Delete(usize),
+
+    /// Inserts text at the current position.
+    Insert(String),
+}
+
+/// Which side of an insertion a position sticks to when the insertion
+/// happens exactly at that position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Assoc {
+    /// The position stays before the inserted text.
+    Before,
+
+    /// The position moves to after the inserted text.
+    After,
+}
+
+/// A set of changes to a document, described as a sequence of
+/// retain/delete/insert runs covering the whole document.
+///
+/// Unlike a sequence of `Rope::insert()`/`Rope::remove()` calls, all of the
+/// changes in a `ChangeSet` are expressed in terms of the document *before*
+/// any of them are applied, so there's no need to manually shift the
+/// positions of later edits.  Positions (such as cursors) can be carried
+/// through the changes with `map_pos()`.
+///
+/// A `ChangeSet` is always normalized: adjacent runs of the same kind are
+/// merged, empty runs are dropped, and an insertion adjacent to a deletion
+/// always comes first.  Two `ChangeSet`s that make the same changes are
+/// therefore equal.
+///
+/// # Example
+///
+/// ```
+/// # use ropey::{Assoc, ChangeSet, Rope};
+/// let mut rope = Rope::from_str("Hello world!");
+/// let changes = ChangeSet::from_changes(
+///     rope.len_chars(),
+///     vec![(0..0, Some("Oh, ")), (5..11, Some(", everyone"))],
+/// );
+///
+/// let cursor = changes.map_pos(11, Assoc::Before);
+/// changes.apply(&mut rope);
+/// assert_eq!(rope, "Oh, Hello, everyone!");
+/// assert_eq!(cursor, 19);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ChangeSet {
+    ops: Vec<Operation>,
+    len_before: usize,
+    len_after: usize,
+}
+
+impl ChangeSet {
+    /// Creates an empty `ChangeSet`, to be filled in with `retain()`,
+    /// `delete()`, and `insert()`.
+    #[inline]
+    pub fn new() -> ChangeSet {
+        ChangeSet::default()
+    }
+
+    /// Creates a `ChangeSet` that leaves a document of `len` chars
+    /// unchanged.
+    #[inline]
+    pub fn identity(len: usize) -> ChangeSet {
+        let mut changes = ChangeSet::new();
+        changes.retain(len);
+        changes
+    }
+
+    /// Creates a `ChangeSet` from a list of changes to a document of `len`
+    /// chars.
+    ///
+    /// Each change replaces a char range of the document with the given
+    /// text, or with nothing if the text is `None`.  The ranges must be
+    /// sorted and non-overlapping.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range is reversed, out of bounds, or overlaps or comes
+    /// before the previous one.
+    pub fn from_changes<'a, I>(len: usize, changes: I) -> ChangeSet
+    where
+        I: IntoIterator<Item = (Range<usize>, Option<&'a str>)>,
+    {
+        let mut change_set = ChangeSet::new();
+        let mut pos = 0;
+        for (range, text) in changes {
+            assert!(
+                pos <= range.start && range.start <= range.end && range.end <= len,
+                "Invalid change range {}..{}: ranges must be sorted, \
+                 non-overlapping, and within the document length {}",
+                range.start,
+                range.end,
+                len
+            );
+            change_set.retain(range.start - pos);
+            change_set.delete(range.end - range.start);
+            if let Some(text) = text {
+                change_set.insert(text);
+            }
+            pos = range.end;
+        }
+        change_set.retain(len - pos);
+        change_set
+    }
+
+    /// Returns the runs making up the `ChangeSet`.
+    #[inline]
+    pub fn ops(&self) -> &[Operation] {
+        &self.ops
+    }
+
+    /// Returns the length in chars of the document the `ChangeSet` applies
+    /// to.
+    #[inline]
+    pub fn len_before(&self) -> usize {
+        self.len_before
+    }
+
+    /// Returns the length in chars of the document after applying the
+    /// `ChangeSet`.
+    #[inline]
+    pub fn len_after(&self) -> usize {
+        self.len_after
+    }
+
+    /// Returns whether the `ChangeSet` leaves the document unchanged.
+    #[inline]
+    pub fn is_identity(&self) -> bool {
+        self.ops
+            .iter()
+            .all(|op| matches!(*op, Operation::Retain(_)))
+    }
+
+    //-----------------------------------------------------------------------
+    // Building
+
+    /// Appends a run keeping the next `n` chars unchanged.
+    pub fn retain(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        self.len_before += n;
+        self.len_after += n;
+        if let Some(Operation::Retain(ref mut count)) = self.ops.last_mut() {
+            *count += n;
+            return;
+        }
+        self.ops.push(Operation::Retain(n));
+    }
+
+    /// Appends a run removing the next `n` chars.
+    pub fn delete(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        self.len_before += n;
+        if let Some(Operation::Delete(ref mut count)) = self.ops.last_mut() {
+            *count += n;
+            return;
+        }
+        self.ops.push(Operation::Delete(n));
+    }
+
+    /// Appends a run inserting `text`.
+    pub fn insert(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.len_after += count_chars(text);
+
+        // Insertions go before an adjacent deletion, so that there's only
+        // one way to represent a replacement.
+        let len = self.ops.len();
+        let idx = match self.ops.last() {
+            Some(Operation::Delete(_)) => len - 1,
+            _ => len,
+        };
+        if idx > 0 {
+            if let Operation::Insert(ref mut s) = self.ops[idx - 1] {
+                s.push_str(text);
+                return;
+            }
+        }
+        self.ops.insert(idx, Operation::Insert(text.into()));
+    }
+
+    //-----------------------------------------------------------------------
+    // Operations
+
+    /// Applies the changes to `rope`.
+    ///
+    /// Runs in O(K log N) time, where K is the number of runs and N is the
+    /// length of the `Rope`, plus the length of any inserted text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `rope` isn't `len_before()`.
+    #[inline]
+    pub fn apply(&self, rope: &mut Rope) {
+        self.try_apply(rope).unwrap()
+    }
+
+    /// Returns a `ChangeSet` that undoes these changes.
+    ///
+    /// `original` must be the document *before* the changes were applied,
+    /// since the inverse needs to re-insert any deleted text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `original` isn't `len_before()`.
+    #[inline]
+    pub fn invert(&self, original: &Rope) -> ChangeSet {
+        self.try_invert(original).unwrap()
+    }
+
+    /// Combines these changes with `other`, which is applied after them,
+    /// into a single `ChangeSet`.
+    ///
+    /// Applying the result is equivalent to applying `self` and then
+    /// `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other.len_before()` isn't `self.len_after()`.
+    #[inline]
+    pub fn compose(&self, other: &ChangeSet) -> ChangeSet {
+        self.try_compose(other).unwrap()
+    }
+
+    /// Transforms two concurrent `ChangeSet`s, both made against the same
+    /// document, so that each can be applied after the other.
+    ///
+    /// Returns `(a', b')`, where `a'` applies after `other` and `b'` applies
+    /// after `self`.  Applying `self` and then `b'` gives the same document
+    /// as applying `other` and then `a'`.
+    ///
+    /// When both insert text at the same position, the text inserted by
+    /// `self` ends up first.  Text deleted by both is only deleted once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other.len_before()` isn't `self.len_before()`.
+    #[inline]
+    pub fn transform(&self, other: &ChangeSet) -> (ChangeSet, ChangeSet) {
+        self.try_transform(other).unwrap()
+    }
+
+    /// Maps char index `char_idx` in the document before the changes to the
+    /// corresponding char index after them.
+    ///
+    /// Positions inside deleted text map to where the deletion happened.
+    /// When text is inserted exactly at the position, `assoc` determines
+    /// whether the position ends up before or after it.
+    ///
+    /// Runs in O(K) time, where K is the number of runs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx >
+    /// len_before()`).
+    #[inline]
+    pub fn map_pos(&self, char_idx: usize, assoc: Assoc) -> usize {
+        self.try_map_pos(char_idx, assoc).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Fallible versions of the above methods
+
+    /// Non-panicking version of [`apply()`](#method.apply).
+    ///
+    /// On failure `rope` is left unmodified.
+    pub fn try_apply(&self, rope: &mut Rope) -> Result<()> {
+        self.check_len(rope.len_chars())?;
+
+        let mut pos = 0;
+        for op in self.ops.iter() {
+            match *op {
+                Operation::Retain(n) => pos += n,
+                Operation::Delete(n) => rope.remove(pos..(pos + n)),
+                Operation::Insert(ref text) => {
+                    rope.insert(pos, text);
+                    pos += count_chars(text);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Non-panicking version of [`invert()`](#method.invert).
+    pub fn try_invert(&self, original: &Rope) -> Result<ChangeSet> {
+        self.check_len(original.len_chars())?;
+
+        let mut inverted = ChangeSet::new();
+        let mut pos = 0;
+        for op in self.ops.iter() {
+            match *op {
+                Operation::Retain(n) => {
+                    inverted.retain(n);
+                    pos += n;
+                }
+                Operation::Delete(n) => {
+                    for chunk in original.slice(pos..(pos + n)).chunks() {
+                        inverted.insert(chunk);
+                    }
+                    pos += n;
+                }
+                Operation::Insert(ref text) => inverted.delete(count_chars(text)),
+            }
+        }
+
+        Ok(inverted)
+    }
+
+    /// Non-panicking version of [`compose()`](#method.compose).
+    pub fn try_compose(&self, other: &ChangeSet) -> Result<ChangeSet> {
+        if other.len_before != self.len_after {
+            return Err(Error::ChangeSetLengthMismatch(
+                other.len_before,
+                self.len_after,
+            ));
+        }
+
+        let mut composed = ChangeSet::new();
+        let mut ops_a = self.ops.iter().cloned();
+        let mut ops_b = other.ops.iter().cloned();
+        let mut head_a = ops_a.next();
+        let mut head_b = ops_b.next();
+
+        loop {
+            match (head_a.take(), head_b.take()) {
+                (None, None) => break,
+
+                // Deletions in `self` and insertions in `other` don't
+                // interact with the other side.
+                (Some(Operation::Delete(n)), b) => {
+                    composed.delete(n);
+                    head_a = ops_a.next();
+                    head_b = b;
+                }
+                (a, Some(Operation::Insert(text))) => {
+                    composed.insert(&text);
+                    head_a = a;
+                    head_b = ops_b.next();
+                }
+
+                (Some(Operation::Retain(n)), Some(Operation::Retain(m))) => {
+                    composed.retain(n.min(m));
+                    head_a = remainder(Operation::Retain(n - n.min(m)), &mut ops_a);
+                    head_b = remainder(Operation::Retain(m - n.min(m)), &mut ops_b);
+                }
+                (Some(Operation::Retain(n)), Some(Operation::Delete(m))) => {
+                    composed.delete(n.min(m));
+                    head_a = remainder(Operation::Retain(n - n.min(m)), &mut ops_a);
+                    head_b = remainder(Operation::Delete(m - n.min(m)), &mut ops_b);
+                }
+                (Some(Operation::Insert(text)), Some(Operation::Retain(m))) => {
+                    let n = count_chars(&text);
+                    let split = char_to_byte_idx(&text, n.min(m));
+                    composed.insert(&text[..split]);
+                    head_a = remainder(Operation::Insert(text[split..].into()), &mut ops_a);
+                    head_b = remainder(Operation::Retain(m - n.min(m)), &mut ops_b);
+                }
+                (Some(Operation::Insert(text)), Some(Operation::Delete(m))) => {
+                    // Text inserted by `self` and then deleted by `other`
+                    // cancels out.
+                    let n = count_chars(&text);
+                    let split = char_to_byte_idx(&text, n.min(m));
+                    head_a = remainder(Operation::Insert(text[split..].into()), &mut ops_a);
+                    head_b = remainder(Operation::Delete(m - n.min(m)), &mut ops_b);
+                }
+
+                // Guaranteed by the length check above.
+                (None, Some(_)) | (Some(_), None) => unreachable!(),
+            }
+        }
+
+        Ok(composed)
+    }
+
+    /// Non-panicking version of [`transform()`](#method.transform).
+    pub fn try_transform(&self, other: &ChangeSet) -> Result<(ChangeSet, ChangeSet)> {
+        if other.len_before != self.len_before {
+            return Err(Error::ChangeSetLengthMismatch(
+                self.len_before,
+                other.len_before,
+            ));
+        }
+
+        let mut a_prime = ChangeSet::new();
+        let mut b_prime = ChangeSet::new();
+        let mut ops_a = self.ops.iter().cloned();
+        let mut ops_b = other.ops.iter().cloned();
+        let mut head_a = ops_a.next();
+        let mut head_b = ops_b.next();
+
+        loop {
+            match (head_a.take(), head_b.take()) {
+                (None, None) => break,
+
+                // Insertions don't consume any of the original document, so
+                // they're handled on their own.  Checking `self` first is
+                // what gives its insertions priority.
+                (Some(Operation::Insert(text)), b) => {
+                    b_prime.retain(count_chars(&text));
+                    a_prime.insert(&text);
+                    head_a = ops_a.next();
+                    head_b = b;
+                }
+                (a, Some(Operation::Insert(text))) => {
+                    a_prime.retain(count_chars(&text));
+                    b_prime.insert(&text);
+                    head_a = a;
+                    head_b = ops_b.next();
+                }
+
+                (Some(Operation::Retain(n)), Some(Operation::Retain(m))) => {
+                    a_prime.retain(n.min(m));
+                    b_prime.retain(n.min(m));
+                    head_a = remainder(Operation::Retain(n - n.min(m)), &mut ops_a);
+                    head_b = remainder(Operation::Retain(m - n.min(m)), &mut ops_b);
+                }
+                (Some(Operation::Delete(n)), Some(Operation::Delete(m))) => {
+                    // Already deleted by the other side.
+                    head_a = remainder(Operation::Delete(n - n.min(m)), &mut ops_a);
+                    head_b = remainder(Operation::Delete(m - n.min(m)), &mut ops_b);
+                }
+                (Some(Operation::Delete(n)), Some(Operation::Retain(m))) => {
+                    a_prime.delete(n.min(m));
+                    head_a = remainder(Operation::Delete(n - n.min(m)), &mut ops_a);
+                    head_b = remainder(Operation::Retain(m - n.min(m)), &mut ops_b);
+                }
+                (Some(Operation::Retain(n)), Some(Operation::Delete(m))) => {
+                    b_prime.delete(n.min(m));
+                    head_a = remainder(Operation::Retain(n - n.min(m)), &mut ops_a);
+                    head_b = remainder(Operation::Delete(m - n.min(m)), &mut ops_b);
+                }
+
+                // Guaranteed by the length check above.
+                (None, Some(_)) | (Some(_), None) => unreachable!(),
+            }
+        }
+
+        Ok((a_prime, b_prime))
+    }
+
+    /// Non-panicking version of [`map_pos()`](#method.map_pos).
+    pub fn try_map_pos(&self, char_idx: usize, assoc: Assoc) -> Result<usize> {
+        // Bounds check
+        if char_idx > self.len_before {
+            return Err(Error::CharIndexOutOfBounds(char_idx, self.len_before));
+        }
+
+        let mut old_pos = 0;
+        let mut new_pos = 0;
+        for op in self.ops.iter() {
+            match *op {
+                Operation::Retain(n) => {
+                    if char_idx < old_pos + n {
+                        return Ok(new_pos + (char_idx - old_pos));
+                    }
+                    old_pos += n;
+                    new_pos += n;
+                }
+                Operation::Delete(n) => {
+                    if char_idx < old_pos + n {
+                        return Ok(new_pos);
+                    }
+                    old_pos += n;
+                }
+                Operation::Insert(ref text) => {
+                    if char_idx == old_pos && assoc == Assoc::Before {
+                        return Ok(new_pos);
+                    }
+                    new_pos += count_chars(text);
+                }
+            }
+        }
+
+        Ok(new_pos)
+    }
+
+    //-----------------------------------------------------------------------
+    // Internal utilities
+
+    fn check_len(&self, len: usize) -> Result<()> {
+        if len == self.len_before {
+            Ok(())
+        } else {
+            Err(Error::ChangeSetLengthMismatch(self.len_before, len))
+        }
+    }
+}
+
+/// Returns `op` if it's non-empty, and otherwise the next op from `ops`.
+fn remainder<I: Iterator<Item = Operation>>(op: Operation, ops: &mut I) -> Option<Operation> {
+    match op {
+        Operation::Retain(0) | Operation::Delete(0) => ops.next(),
+        Operation::Insert(ref text) if text.is_empty() => ops.next(),
+        op => Some(op),
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::Operation::*;
+    use crate::{Assoc, ChangeSet, Rope};
+
+    const TEXT: &str = "Hello there!  How're you doing?\r\nIt's \
+                        a fine day, isn't it?\r\nAren't you glad \
+                        we're alive?\r\nこんにちは、みんなさん！";
+
+    #[test]
+    fn build_01() {
+        let mut cs = ChangeSet::new();
+        cs.retain(2);
+        cs.retain(0);
+        cs.retain(3);
+        cs.delete(1);
+        cs.insert("ab");
+        cs.delete(2);
+        cs.insert("");
+        cs.insert("c");
+        cs.retain(1);
+
+        assert_eq!(
+            cs.ops(),
+            &[Retain(5), Insert("abc".into()), Delete(3), Retain(1)]
+        );
+        assert_eq!(cs.len_before(), 9);
+        assert_eq!(cs.len_after(), 9);
+        assert!(!cs.is_identity());
+    }
+
+    #[test]
+    fn build_02() {
+        let cs = ChangeSet::from_changes(
+            10,
+            vec![(2..4, None), (4..4, Some("xy")), (10..10, Some("z"))],
+        );
+
+        assert_eq!(
+            cs.ops(),
+            &[
+                Retain(2),
+                Insert("xy".into()),
+                Delete(2),
+                Retain(6),
+                Insert("z".into())
+            ]
+        );
+        assert_eq!(cs.len_before(), 10);
+        assert_eq!(cs.len_after(), 11);
+        assert!(ChangeSet::identity(10).is_identity());
+    }
+
+    #[test]
+    #[should_panic]
+    fn build_03() {
+        ChangeSet::from_changes(10, vec![(2..4, None), (3..5, None)]);
+    }
+
+    #[test]
+    fn apply_01() {
+        let mut r = Rope::from_str(TEXT);
+        let cs = ChangeSet::from_changes(
+            r.len_chars(),
+            vec![
+                (0..5, Some("Goodbye")),
+                (33..33, Some("Really?\r\n")),
+                (90..r.len_chars(), None),
+            ],
+        );
+        cs.apply(&mut r);
+
+        let mut expected: String = TEXT.chars().take(90).collect();
+        expected.insert_str(expected.char_indices().nth(33).unwrap().0, "Really?\r\n");
+        expected.replace_range(0..5, "Goodbye");
+        assert_eq!(r, expected);
+        assert_eq!(r.len_chars(), cs.len_after());
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    fn apply_02() {
+        let mut r = Rope::from_str(TEXT);
+        let cs = ChangeSet::identity(5);
+
+        assert!(cs.try_apply(&mut r).is_err());
+        assert_eq!(r, TEXT);
+    }
+
+    #[test]
+    fn invert_01() {
+        let mut r = Rope::from_str(TEXT);
+        let original = r.clone();
+        let cs = ChangeSet::from_changes(
+            r.len_chars(),
+            vec![
+                (3..10, Some("abc")),
+                (40..41, None),
+                (90..100, Some("ねこ")),
+            ],
+        );
+        let inverted = cs.invert(&r);
+        cs.apply(&mut r);
+        inverted.apply(&mut r);
+
+        assert_eq!(r, original);
+
+        let mut after = original.clone();
+        cs.apply(&mut after);
+        assert_eq!(inverted.invert(&after), cs);
+    }
+
+    #[test]
+    fn compose_01() {
+        let mut r1 = Rope::from_str(TEXT);
+        let mut r2 = r1.clone();
+
+        let a = ChangeSet::from_changes(
+            r1.len_chars(),
+            vec![(3..10, Some("abcdef")), (40..50, None)],
+        );
+        let b = ChangeSet::from_changes(
+            a.len_after(),
+            vec![(0..5, Some("x")), (6..6, Some("yz")), (35..80, None)],
+        );
+        let ab = a.compose(&b);
+
+        a.apply(&mut r1);
+        b.apply(&mut r1);
+        ab.apply(&mut r2);
+
+        assert_eq!(r1, r2);
+        assert_eq!(ab.len_before(), a.len_before());
+        assert_eq!(ab.len_after(), b.len_after());
+    }
+
+    #[test]
+    fn compose_02() {
+        // Composing with the inverse gives back an identity, and inserted
+        // text that gets deleted again cancels out entirely.
+        let r = Rope::from_str(TEXT);
+        let a = ChangeSet::from_changes(r.len_chars(), vec![(5..5, Some("こんにちは"))]);
+        let b = ChangeSet::from_changes(a.len_after(), vec![(6..9, None)]);
+
+        assert!(a.compose(&a.invert(&r)).is_identity());
+        assert_eq!(
+            a.compose(&b).ops(),
+            &[Retain(5), Insert("こは".into()), Retain(r.len_chars() - 5)]
+        );
+        assert!(a.try_compose(&a).is_err());
+    }
+
+    #[test]
+    fn transform_01() {
+        let r = Rope::from_str(TEXT);
+        let a = ChangeSet::from_changes(r.len_chars(), vec![(3..10, Some("abc")), (40..45, None)]);
+        let b = ChangeSet::from_changes(
+            r.len_chars(),
+            vec![(5..12, Some("xy")), (42..42, Some("z"))],
+        );
+        let (a2, b2) = a.transform(&b);
+
+        let mut r1 = r.clone();
+        a.apply(&mut r1);
+        b2.apply(&mut r1);
+        let mut r2 = r.clone();
+        b.apply(&mut r2);
+        a2.apply(&mut r2);
+
+        assert_eq!(r1, r2);
+        assert_eq!(&r1.to_string()[..13], "Helabcxy  How");
+    }
+
+    #[test]
+    fn transform_02() {
+        // Inserts at the same position: the first change set's text goes
+        // first, regardless of the order they're applied in.
+        let r = Rope::from_str("abc");
+        let a = ChangeSet::from_changes(3, vec![(1..1, Some("x"))]);
+        let b = ChangeSet::from_changes(3, vec![(1..2, Some("y"))]);
+        let (a2, b2) = a.transform(&b);
+
+        let mut r1 = r.clone();
+        a.apply(&mut r1);
+        b2.apply(&mut r1);
+        let mut r2 = r.clone();
+        b.apply(&mut r2);
+        a2.apply(&mut r2);
+
+        assert_eq!(r1, "axyc");
+        assert_eq!(r2, "axyc");
+        assert!(a.try_transform(&ChangeSet::identity(4)).is_err());
+    }
+
+    #[test]
+    fn map_pos_01() {
+        let cs = ChangeSet::from_changes(
+            10,
+            vec![(2..2, Some("ab")), (4..7, None), (8..9, Some("c"))],
+        );
+
+        assert_eq!(cs.map_pos(0, Assoc::Before), 0);
+        assert_eq!(cs.map_pos(2, Assoc::Before), 2);
+        assert_eq!(cs.map_pos(2, Assoc::After), 4);
+        assert_eq!(cs.map_pos(3, Assoc::Before), 5);
+        assert_eq!(cs.map_pos(4, Assoc::Before), 6);
+        assert_eq!(cs.map_pos(5, Assoc::After), 6);
+        assert_eq!(cs.map_pos(7, Assoc::Before), 6);
+        assert_eq!(cs.map_pos(8, Assoc::Before), 7);
+        assert_eq!(cs.map_pos(8, Assoc::After), 8);
+        assert_eq!(cs.map_pos(9, Assoc::Before), 8);
+        assert_eq!(cs.map_pos(10, Assoc::After), 9);
+        assert!(cs.try_map_pos(11, Assoc::After).is_err());
+    }
+
+    #[test]
+    fn map_pos_02() {
+        // Positions are mapped consistently with `apply()`.
+        let mut r = Rope::from_str(TEXT);
+        let cs = ChangeSet::from_changes(
+            r.len_chars(),
+            vec![(10..20, Some("xyz")), (50..50, Some("uvw"))],
+        );
+        let before: Vec<char> = r.chars().collect();
+        cs.apply(&mut r);
+
+        for i in (0..10).chain(20..50).chain(51..before.len()) {
+            assert_eq!(r.char(cs.map_pos(i, Assoc::Before)), before[i]);
+            assert_eq!(r.char(cs.map_pos(i, Assoc::After)), before[i]);
+        }
+        assert_eq!(r.char(cs.map_pos(50, Assoc::Before)), 'u');
+        assert_eq!(r.char(cs.map_pos(50, Assoc::After)), before[50]);
+    }
+}
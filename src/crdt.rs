@@ -0,0 +1,804 @@
+//! A sequence CRDT for collaborative text editing, backed by a `Rope`.
+
+use std::collections::HashSet;
+use std::ops::RangeBounds;
+
+use crate::rope::Rope;
+use crate::{Error, Result};
+
+/// The largest char id counter accepted from other replicas.
+const MAX_REMOTE_COUNTER: u64 = u64::MAX / 2;
+
+/// A unique identifier for a char inserted into a
+/// [`CrdtText`](struct.CrdtText.html).
+///
+/// `counter` is a Lamport timestamp and `site` identifies the replica that
+/// inserted the char.  Ids are ordered by counter first, then by site, and
+/// that order decides the placement of concurrent insertions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OpId {
+    pub counter: u64,
+    pub site: u64,
+}
+
+/// An operation on a [`CrdtText`](struct.CrdtText.html), to be sent to
+/// other replicas.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CrdtOp {
+    /// Inserts `text` after the char with id `origin`, or at the start of
+    /// the document if it's `None`.
+    ///
+    /// The chars of `text` get consecutive ids starting with `id`, each
+    /// one inserted after the previous.
+    Insert {
+        id: OpId,
+        origin: Option<OpId>,
+        text: String,
+    },
+
+    /// Deletes the chars with the given ids.
+    Delete { ids: Vec<OpId> },
+}
+
+/// Text that can be edited concurrently by several replicas, and merged
+/// without conflicts.
+///
+/// This is a replicated growable array (RGA): every char gets a unique
+/// [`OpId`](struct.OpId.html) when inserted, and is placed relative to the
+/// char it was inserted after.  Deleted chars are kept as tombstones so
+/// that later operations can still refer to them.
+///
+/// Local edits made with `insert()` and `remove()` return a
+/// [`CrdtOp`](enum.CrdtOp.html) to send to the other replicas, which
+/// integrate it with `apply()`.  Applying an operation more than once has
+/// no further effect, and operations can be applied in any order:
+/// operations whose dependencies haven't been applied yet are held back
+/// until they have.  Once all replicas have applied the same set of
+/// operations, their texts are identical.
+///
+/// The visible text is kept in a `Rope`, available through `rope()`, so
+/// all of `Rope`'s queries work on it directly.
+///
+/// Each op runs in O(N) time in the total number of chars ever inserted
+/// (including tombstones).
+///
+/// # Example
+///
+/// ```
+/// # use ropey::CrdtText;
+/// let mut alice = CrdtText::new(1);
+/// let mut bob = CrdtText::new(2);
+///
+/// let op = alice.insert(0, "Hello!");
+/// bob.apply(op);
+///
+/// let op_a = alice.insert(5, " world");
+/// let op_b = bob.insert(5, " there");
+/// alice.apply(op_b);
+/// bob.apply(op_a);
+///
+/// assert_eq!(alice.rope(), bob.rope());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CrdtText {
+    site: u64,
+    clock: u64,
+    rope: Rope,
+    elements: Vec<Element>, // All chars in document order, including tombstones.
+    known: HashSet<OpId>,
+    pending: Vec<CrdtOp>, // Remote ops waiting on their dependencies.
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Element {
+    id: OpId,
+    deleted: bool,
+}
+
+impl CrdtText {
+    /// Creates a new, empty replica with the given site id.
+    ///
+    /// Every replica editing the same text must have a distinct site id.
+    pub fn new(site: u64) -> CrdtText {
+        CrdtText {
+            site: site,
+            clock: 0,
+            rope: Rope::new(),
+            elements: Vec::new(),
+            known: HashSet::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the site id of this replica.
+    #[inline]
+    pub fn site(&self) -> u64 {
+        self.site
+    }
+
+    /// Returns the visible text.
+    #[inline]
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+
+    /// Returns the number of received operations that are waiting for
+    /// their dependencies to be applied.
+    #[inline]
+    pub fn len_pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    //-----------------------------------------------------------------------
+    // Editing
+
+    /// Inserts `text` at char index `char_idx` of the visible text, and
+    /// returns the operation to send to other replicas.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn insert(&mut self, char_idx: usize, text: &str) -> CrdtOp {
+        self.try_insert(char_idx, text).unwrap()
+    }
+
+    /// Removes the text in the given char index range of the visible text,
+    /// and returns the operation to send to other replicas.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if the
+    /// end is out of bounds (i.e. `end > len_chars()`).
+    #[inline]
+    pub fn remove<R>(&mut self, char_range: R) -> CrdtOp
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_remove(char_range).unwrap()
+    }
+
+    /// Integrates an operation from another replica (or this one).
+    ///
+    /// Operations that were already applied are ignored.  Operations that
+    /// refer to chars this replica doesn't know about yet are held back
+    /// until those chars are inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `op` is an insertion whose char ids are too large (see
+    /// [`try_apply()`](#method.try_apply)).
+    #[inline]
+    pub fn apply(&mut self, op: CrdtOp) {
+        self.try_apply(op).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Fallible versions of the above methods
+
+    /// Non-panicking version of [`insert()`](#method.insert).
+    ///
+    /// Also returns an error if the ids of the new chars would overflow a
+    /// `u64`, which would take more insertions than can ever be made.
+    pub fn try_insert(&mut self, char_idx: usize, text: &str) -> Result<CrdtOp> {
+        // Bounds check
+        if char_idx > self.rope.len_chars() {
+            return Err(Error::CharIndexOutOfBounds(char_idx, self.rope.len_chars()));
+        }
+
+        // The new chars get the ids `clock + 1` through `clock + len`.  An
+        // empty insertion still uses up the first of them, since it's the
+        // id of the op.
+        let len = text.chars().count();
+        let clock = match self.clock.checked_add(len.max(1) as u64) {
+            Some(clock) => clock,
+            None => return Err(Error::OpIdOverflow(self.clock.saturating_add(1))),
+        };
+
+        // The new chars go directly after the visible char before
+        // `char_idx`.  Since their ids are greater than any id seen so far,
+        // that's also where `integrate_insert()` would place them.
+        let (elem_idx, origin) = if char_idx == 0 {
+            (0, None)
+        } else {
+            let idx = self.visible_to_element_idx(char_idx - 1);
+            (idx + 1, Some(self.elements[idx].id))
+        };
+
+        let id = OpId {
+            counter: self.clock + 1,
+            site: self.site,
+        };
+        self.insert_elements(elem_idx, id, len);
+        self.rope.insert(char_idx, text);
+        self.clock = clock;
+
+        Ok(CrdtOp::Insert {
+            id: id,
+            origin: origin,
+            text: text.into(),
+        })
+    }
+
+    /// Non-panicking version of [`remove()`](#method.remove).
+    pub fn try_remove<R>(&mut self, char_range: R) -> Result<CrdtOp>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = self.rope.check_char_range(char_range)?;
+
+        let mut ids = Vec::with_capacity(end - start);
+        if start < end {
+            let mut elem_idx = self.visible_to_element_idx(start);
+            while ids.len() < end - start {
+                let e = &mut self.elements[elem_idx];
+                if !e.deleted {
+                    e.deleted = true;
+                    ids.push(e.id);
+                }
+                elem_idx += 1;
+            }
+            self.rope.remove(start..end);
+        }
+
+        Ok(CrdtOp::Delete { ids: ids })
+    }
+
+    /// Non-panicking version of [`apply()`](#method.apply).
+    ///
+    /// Returns an error, and leaves the text unchanged, if `op` is an
+    /// insertion with a char id counter greater than `u64::MAX / 2`.  The
+    /// counters are Lamport timestamps, which only get that large if
+    /// they've been tampered with, and limiting them leaves room for the
+    /// ids of this replica's own insertions.
+    pub fn try_apply(&mut self, op: CrdtOp) -> Result<()> {
+        if let CrdtOp::Insert { id, ref text, .. } = op {
+            let len = text.chars().count() as u64;
+            let in_range = id
+                .counter
+                .checked_add(len.saturating_sub(1))
+                .is_some_and(|last| last <= MAX_REMOTE_COUNTER);
+            if !in_range {
+                return Err(Error::OpIdOverflow(id.counter));
+            }
+        }
+
+        if !self.try_integrate(&op) {
+            self.pending.push(op);
+            return Ok(());
+        }
+
+        // Applying an op may have unblocked others.
+        let mut progress = true;
+        while progress {
+            progress = false;
+            let mut i = 0;
+            while i < self.pending.len() {
+                let op = self.pending[i].clone();
+                if self.try_integrate(&op) {
+                    self.pending.swap_remove(i);
+                    progress = true;
+                } else {
+                    i += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------
+    // Internal utilities
+
+    /// Inserts the elements for `len` new chars at `elem_idx`, with
+    /// consecutive ids starting with `id`.
+    fn insert_elements(&mut self, elem_idx: usize, id: OpId, len: usize) {
+        let ids = (0..len).map(|i| OpId {
+            counter: id.counter + i as u64,
+            site: id.site,
+        });
+        self.known.extend(ids.clone());
+        self.elements.splice(
+            elem_idx..elem_idx,
+            ids.map(|id| Element {
+                id: id,
+                deleted: false,
+            }),
+        );
+    }
+
+    /// Returns the index in `elements` of the visible char at `char_idx`.
+    fn visible_to_element_idx(&self, char_idx: usize) -> usize {
+        let mut visible = 0;
+        for (i, e) in self.elements.iter().enumerate() {
+            if !e.deleted {
+                if visible == char_idx {
+                    return i;
+                }
+                visible += 1;
+            }
+        }
+        unreachable!()
+    }
+
+    /// Integrates `op` if all of its dependencies are known, returning
+    /// whether it did (or whether it was already integrated).
+    fn try_integrate(&mut self, op: &CrdtOp) -> bool {
+        match *op {
+            CrdtOp::Insert {
+                id,
+                origin,
+                ref text,
+            } => {
+                if let Some(origin) = origin {
+                    if !self.known.contains(&origin) {
+                        return false;
+                    }
+                }
+                if !self.known.contains(&id) {
+                    self.integrate_insert(id, origin, text);
+                }
+                true
+            }
+
+            CrdtOp::Delete { ref ids } => {
+                if !ids.iter().all(|id| self.known.contains(id)) {
+                    return false;
+                }
+                self.integrate_delete(ids);
+                true
+            }
+        }
+    }
+
+    fn integrate_insert(&mut self, id: OpId, origin: Option<OpId>, text: &str) {
+        // Find the origin, counting the visible chars up to it.
+        let mut elem_idx = 0;
+        let mut char_idx = 0;
+        if let Some(origin) = origin {
+            while self.elements[elem_idx].id != origin {
+                char_idx += !self.elements[elem_idx].deleted as usize;
+                elem_idx += 1;
+            }
+            char_idx += !self.elements[elem_idx].deleted as usize;
+            elem_idx += 1;
+        }
+
+        // Skip over chars that were inserted concurrently at the same spot
+        // and win the tie, along with everything inserted after them (which
+        // necessarily has even greater ids).  The rest of the new chars
+        // each go directly after the one before, since nothing can have
+        // been inserted after them yet.
+        while elem_idx < self.elements.len() && self.elements[elem_idx].id > id {
+            char_idx += !self.elements[elem_idx].deleted as usize;
+            elem_idx += 1;
+        }
+
+        // Can't overflow, since `try_apply()` checked the op's ids.
+        let len = text.chars().count();
+        self.insert_elements(elem_idx, id, len);
+        self.rope.insert(char_idx, text);
+        if len > 0 {
+            self.clock = self.clock.max(id.counter + len as u64 - 1);
+        }
+    }
+
+    fn integrate_delete(&mut self, ids: &[OpId]) {
+        let ids: HashSet<OpId> = ids.iter().cloned().collect();
+
+        // Collect the visible ranges to remove, in document order.
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        let mut char_idx = 0;
+        for e in self.elements.iter_mut() {
+            if e.deleted {
+                continue;
+            }
+            if ids.contains(&e.id) {
+                e.deleted = true;
+                match ranges.last_mut() {
+                    Some(&mut (_, ref mut end)) if *end == char_idx => *end += 1,
+                    _ => ranges.push((char_idx, char_idx + 1)),
+                }
+            }
+            char_idx += 1;
+        }
+
+        for &(start, end) in ranges.iter().rev() {
+            self.rope.remove(start..end);
+        }
+    }
+}
+
+//==============================================================
+// Binary encoding
+
+impl CrdtOp {
+    /// Appends a compact binary encoding of the operation to `out`.
+    ///
+    /// The encoding uses variable-length integers, and is meant for
+    /// exchanging operations between processes running the same version of
+    /// Ropey.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match *self {
+            CrdtOp::Insert {
+                id,
+                origin,
+                ref text,
+            } => {
+                out.push(0);
+                encode_id(id, out);
+                match origin {
+                    Some(origin) => {
+                        out.push(1);
+                        encode_id(origin, out);
+                    }
+                    None => out.push(0),
+                }
+                encode_varint(text.len() as u64, out);
+                out.extend_from_slice(text.as_bytes());
+            }
+
+            CrdtOp::Delete { ref ids } => {
+                out.push(1);
+                encode_varint(ids.len() as u64, out);
+                for &id in ids.iter() {
+                    encode_id(id, out);
+                }
+            }
+        }
+    }
+
+    /// Decodes an operation encoded with [`encode()`](#method.encode) from
+    /// the start of `bytes`.
+    ///
+    /// Returns the operation and the number of bytes it took up, so that a
+    /// sequence of encoded operations can be decoded one after another.
+    pub fn decode(bytes: &[u8]) -> Result<(CrdtOp, usize)> {
+        let mut pos = 0;
+        let op = match read_byte(bytes, &mut pos)? {
+            0 => {
+                let id = decode_id(bytes, &mut pos)?;
+                let origin = match read_byte(bytes, &mut pos)? {
+                    0 => None,
+                    1 => Some(decode_id(bytes, &mut pos)?),
+                    _ => return Err(Error::InvalidOpEncoding(pos - 1)),
+                };
+                let len = decode_varint(bytes, &mut pos)? as usize;
+                if len > bytes.len() - pos {
+                    return Err(Error::InvalidOpEncoding(bytes.len()));
+                }
+                let text = std::str::from_utf8(&bytes[pos..(pos + len)])
+                    .map_err(|e| Error::InvalidOpEncoding(pos + e.valid_up_to()))?;
+                pos += len;
+                CrdtOp::Insert {
+                    id: id,
+                    origin: origin,
+                    text: text.into(),
+                }
+            }
+
+            1 => {
+                let len = decode_varint(bytes, &mut pos)? as usize;
+                let mut ids = Vec::with_capacity(len.min(bytes.len()));
+                for _ in 0..len {
+                    ids.push(decode_id(bytes, &mut pos)?);
+                }
+                CrdtOp::Delete { ids: ids }
+            }
+
+            _ => return Err(Error::InvalidOpEncoding(0)),
+        };
+
+        Ok((op, pos))
+    }
+}
+
+fn encode_id(id: OpId, out: &mut Vec<u8>) {
+    encode_varint(id.counter, out);
+    encode_varint(id.site, out);
+}
+
+fn decode_id(bytes: &[u8], pos: &mut usize) -> Result<OpId> {
+    Ok(OpId {
+        counter: decode_varint(bytes, pos)?,
+        site: decode_varint(bytes, pos)?,
+    })
+}
+
+/// LEB128 encoding: 7 bits per byte, with the high bit set on all but the
+/// last byte.
+fn encode_varint(mut n: u64, out: &mut Vec<u8>) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn decode_varint(bytes: &[u8], pos: &mut usize) -> Result<u64> {
+    let start = *pos;
+    let mut n = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = read_byte(bytes, pos)?;
+        if shift > 63 || (shift == 63 && byte > 1) {
+            return Err(Error::InvalidOpEncoding(start));
+        }
+        n |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(n);
+        }
+        shift += 7;
+    }
+}
+
+fn read_byte(bytes: &[u8], pos: &mut usize) -> Result<u8> {
+    match bytes.get(*pos) {
+        Some(&byte) => {
+            *pos += 1;
+            Ok(byte)
+        }
+        None => Err(Error::InvalidOpEncoding(*pos)),
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn crdt_01() {
+        let mut a = CrdtText::new(1);
+        a.insert(0, "Hello world!");
+        a.insert(5, ",");
+        a.remove(7..13);
+        a.insert(7, "everyone!\r\n");
+
+        assert_eq!(a.rope(), "Hello, everyone!\r\n");
+        assert_eq!(a.rope().len_lines(), 2);
+    }
+
+    #[test]
+    fn crdt_02() {
+        // Concurrent inserts at the same position converge.
+        let mut a = CrdtText::new(1);
+        let mut b = CrdtText::new(2);
+        let op = a.insert(0, "ac");
+        b.apply(op);
+
+        let op_a = a.insert(1, "1");
+        let op_b = b.insert(1, "22");
+        a.apply(op_b.clone());
+        b.apply(op_a.clone());
+
+        assert_eq!(a.rope(), b.rope());
+        assert_eq!(a.rope(), "a221c");
+
+        // Idempotent.
+        a.apply(op_a);
+        a.apply(op_b);
+        assert_eq!(a.rope(), "a221c");
+    }
+
+    #[test]
+    fn crdt_03() {
+        // Concurrent deletes and inserts into deleted text.
+        let mut a = CrdtText::new(1);
+        let mut b = CrdtText::new(2);
+        let op = a.insert(0, "abcdef");
+        b.apply(op);
+
+        let op_a = a.remove(1..5);
+        let op_b1 = b.remove(2..4);
+        let op_b2 = b.insert(2, "xy");
+        a.apply(op_b1);
+        a.apply(op_b2);
+        b.apply(op_a);
+
+        assert_eq!(a.rope(), b.rope());
+        assert_eq!(a.rope(), "axyf");
+    }
+
+    #[test]
+    fn crdt_04() {
+        // Ops arriving before their dependencies are held back.
+        let mut a = CrdtText::new(1);
+        let mut b = CrdtText::new(2);
+        let op1 = a.insert(0, "abc");
+        let op2 = a.insert(3, "def");
+        let op3 = a.remove(2..4);
+
+        b.apply(op3);
+        b.apply(op2);
+        assert_eq!(b.rope(), "");
+        assert_eq!(b.len_pending(), 2);
+
+        b.apply(op1);
+        assert_eq!(b.len_pending(), 0);
+        assert_eq!(b.rope(), "abef");
+    }
+
+    #[test]
+    fn crdt_05() {
+        // Lots of random concurrent edits on three sites, with the ops
+        // delivered in a different random order to each site.
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut sites: Vec<CrdtText> = (1..4).map(CrdtText::new).collect();
+        let mut ops = Vec::new();
+
+        for round in 0..20 {
+            for site in sites.iter_mut() {
+                for _ in 0..5 {
+                    let len = site.rope().len_chars();
+                    if len > 0 && rng.gen_range(0, 3) == 0 {
+                        let start = rng.gen_range(0, len);
+                        let end = rng.gen_range(start, start + (len - start).min(5) + 1);
+                        ops.push(site.remove(start..end));
+                    } else {
+                        let text = ["a", "bc", "こん", "\r\n", "xyz"][rng.gen_range(0, 5)];
+                        ops.push(site.insert(rng.gen_range(0, len + 1), text));
+                    }
+                }
+            }
+
+            // Sync everything every few rounds.
+            if round % 4 == 3 {
+                for site in sites.iter_mut() {
+                    let mut order: Vec<usize> = (0..ops.len()).collect();
+                    order.shuffle(&mut rng);
+                    for &i in order.iter() {
+                        site.apply(ops[i].clone());
+                    }
+                    assert_eq!(site.len_pending(), 0);
+                }
+                assert_eq!(sites[0].rope(), sites[1].rope());
+                assert_eq!(sites[1].rope(), sites[2].rope());
+                sites[0].rope().assert_integrity();
+                sites[0].rope().assert_invariants();
+            }
+        }
+    }
+
+    #[test]
+    fn encode_01() {
+        let mut a = CrdtText::new(300);
+        let ops = [
+            a.insert(0, "Hello こんにちは\r\n"),
+            a.insert(3, "xyz"),
+            a.remove(2..8),
+            a.remove(0..0),
+        ];
+
+        let mut bytes = Vec::new();
+        for op in ops.iter() {
+            op.encode(&mut bytes);
+        }
+
+        let mut pos = 0;
+        for op in ops.iter() {
+            let (decoded, len) = CrdtOp::decode(&bytes[pos..]).unwrap();
+            assert_eq!(&decoded, op);
+            pos += len;
+        }
+        assert_eq!(pos, bytes.len());
+    }
+
+    #[test]
+    fn encode_02() {
+        let mut bytes = Vec::new();
+        CrdtOp::Insert {
+            id: OpId {
+                counter: u64::MAX - 2,
+                site: 1,
+            },
+            origin: None,
+            text: "abc".into(),
+        }
+        .encode(&mut bytes);
+
+        // Truncated.
+        for i in 0..bytes.len() {
+            assert!(CrdtOp::decode(&bytes[..i]).is_err());
+        }
+        assert!(CrdtOp::decode(&bytes).is_ok());
+
+        // Invalid utf8 and tags.
+        let last = bytes.len() - 1;
+        bytes[last] = 0xFF;
+        assert!(CrdtOp::decode(&bytes).is_err());
+        assert!(CrdtOp::decode(&[2]).is_err());
+        assert!(CrdtOp::decode(&[
+            1, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn overflow_01() {
+        // Remote ops with ids too large to leave room for local insertions
+        // are rejected, even if they can be encoded.
+        let op = CrdtOp::Insert {
+            id: OpId {
+                counter: u64::MAX - 2,
+                site: 1,
+            },
+            origin: None,
+            text: "abc".into(),
+        };
+        let mut bytes = Vec::new();
+        op.encode(&mut bytes);
+        assert_eq!(CrdtOp::decode(&bytes).unwrap().0, op);
+
+        let mut a = CrdtText::new(2);
+        match a.try_apply(op) {
+            Err(Error::OpIdOverflow(counter)) => assert_eq!(counter, u64::MAX - 2),
+            _ => panic!(),
+        }
+        assert!(a
+            .try_apply(CrdtOp::Insert {
+                id: OpId {
+                    counter: MAX_REMOTE_COUNTER - 1,
+                    site: 1,
+                },
+                origin: None,
+                text: "abc".into(),
+            })
+            .is_err());
+        assert_eq!(a.rope(), "");
+        assert_eq!(a.len_pending(), 0);
+
+        // Ids up to the limit are fine, and local insertions still work
+        // after them.
+        a.apply(CrdtOp::Insert {
+            id: OpId {
+                counter: MAX_REMOTE_COUNTER - 2,
+                site: 1,
+            },
+            origin: None,
+            text: "abc".into(),
+        });
+        assert_eq!(a.rope(), "abc");
+        let op = a.insert(1, "x");
+        assert_eq!(a.rope(), "axbc");
+        match op {
+            CrdtOp::Insert { id, .. } => assert_eq!(id.counter, MAX_REMOTE_COUNTER + 1),
+            _ => panic!(),
+        }
+
+        // The local clock itself can't overflow either.
+        a.clock = u64::MAX - 1;
+        assert!(a.try_insert(0, "y").is_ok());
+        assert!(a.try_insert(0, "z").is_err());
+        assert!(a.try_insert(0, "").is_err());
+        assert_eq!(a.rope(), "yaxbc");
+    }
+
+    #[test]
+    fn empty_insert_01() {
+        // Empty insertions still get unique ids.
+        let mut a = CrdtText::new(1);
+        let mut b = CrdtText::new(2);
+        let ops = [a.insert(0, ""), a.insert(0, "ab"), a.insert(1, "")];
+        let ids: Vec<OpId> = ops
+            .iter()
+            .map(|op| match *op {
+                CrdtOp::Insert { id, .. } => id,
+                _ => panic!(),
+            })
+            .collect();
+        assert!(ids[0] < ids[1]);
+        assert!(ids[1].counter + 1 < ids[2].counter);
+
+        for op in ops.iter().rev() {
+            b.apply(op.clone());
+        }
+        assert_eq!(b.len_pending(), 0);
+        assert_eq!(a.rope(), "ab");
+        assert_eq!(b.rope(), "ab");
+    }
+}
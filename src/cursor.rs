@@ -0,0 +1,391 @@
+//! A stateful cursor over a `Rope`'s text.
+
+use crate::iter::Chunks;
+use crate::slice::RopeSlice;
+use crate::str_utils::{byte_to_line_idx, char_to_byte_idx, count_chars, count_line_breaks};
+use crate::{Error, Result};
+
+/// The number of chunks a cursor will walk over when moving, before it
+/// gives up and instead seeks to the new position from the root of the
+/// tree.
+const MAX_CHUNK_STEPS: usize = 4;
+
+/// A cursor into a `Rope` or `RopeSlice`, for cheap sequential position
+/// queries.
+///
+/// Methods like `Rope::char()` and `Rope::char_to_line()` descend the tree
+/// from the root on every call, which is wasteful when repeatedly querying
+/// positions that are close to each other, as is common in tokenizers and
+/// parsers.  A `Cursor` instead keeps track of the chunk it's currently in,
+/// making moves to nearby positions and queries about the current position
+/// amortized O(1).
+///
+/// Like the iterators in Ropey, a `Cursor` is positioned *between* chars,
+/// and its position is always a char index in `[0, len_chars()]`.
+///
+/// A `Cursor` can be created with the `cursor()` and `cursor_at()` methods
+/// of `Rope` and `RopeSlice`.
+#[derive(Debug, Clone)]
+pub struct Cursor<'a> {
+    slice: RopeSlice<'a>,
+    chunks: Chunks<'a>, // Always positioned just after `chunk`.
+    chunk: &'a str,
+    chunk_byte_idx: usize, // The cursor's position within `chunk`.
+    chunk_chars: usize,
+    chunk_start_byte: usize,
+    chunk_start_char: usize,
+    chunk_start_line: usize,
+    char_idx: usize,
+    line_idx: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(slice: RopeSlice<'a>, char_idx: usize) -> Cursor<'a> {
+        debug_assert!(char_idx <= slice.len_chars());
+
+        let mut cursor = Cursor {
+            slice: slice,
+            chunks: Chunks::from_str("", false),
+            chunk: "",
+            chunk_byte_idx: 0,
+            chunk_chars: 0,
+            chunk_start_byte: 0,
+            chunk_start_char: 0,
+            chunk_start_line: 0,
+            char_idx: 0,
+            line_idx: 0,
+        };
+        cursor.seek(char_idx);
+        cursor
+    }
+
+    //-----------------------------------------------------------------------
+    // Position queries
+
+    /// Returns the char index of the cursor's position.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn current_char(&self) -> usize {
+        self.char_idx
+    }
+
+    /// Returns the byte index of the cursor's position.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn current_byte(&self) -> usize {
+        self.chunk_start_byte + self.chunk_byte_idx
+    }
+
+    /// Returns the index of the line the cursor's position is on.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn current_line(&self) -> usize {
+        self.line_idx
+    }
+
+    /// Returns the char just after the cursor's position, without moving
+    /// the cursor.
+    ///
+    /// Returns `None` if the cursor is at the end of the text.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn peek_char(&self) -> Option<char> {
+        self.chunk[self.chunk_byte_idx..].chars().next()
+    }
+
+    //-----------------------------------------------------------------------
+    // Movement
+
+    /// Moves the cursor to char index `char_idx`.
+    ///
+    /// Runs in amortized O(M) time for nearby positions, where M is the
+    /// distance moved, and worst-case O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn move_to_char(&mut self, char_idx: usize) {
+        self.try_move_to_char(char_idx).unwrap()
+    }
+
+    /// Moves the cursor by `n` chars, forward if `n` is positive and
+    /// backward if it's negative.
+    ///
+    /// Runs in amortized O(|n|) time for small `n`, and worst-case O(log N)
+    /// time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new position would be before the start or after the
+    /// end of the text.
+    #[inline]
+    pub fn move_by(&mut self, n: isize) {
+        let char_idx = if n < 0 {
+            self.char_idx.checked_sub(n.wrapping_neg() as usize)
+        } else {
+            self.char_idx.checked_add(n as usize)
+        };
+        self.move_to_char(char_idx.expect("Attempt to move cursor before the start of the text"));
+    }
+
+    /// Non-panicking version of [`move_to_char()`](#method.move_to_char).
+    ///
+    /// On failure the cursor is left where it was.
+    pub fn try_move_to_char(&mut self, char_idx: usize) -> Result<()> {
+        // Bounds check
+        if char_idx > self.slice.len_chars() {
+            return Err(Error::CharIndexOutOfBounds(
+                char_idx,
+                self.slice.len_chars(),
+            ));
+        }
+
+        // Walk over to the chunk containing `char_idx` if it's nearby,
+        // otherwise seek to it from the root.
+        let mut steps = 0;
+        while char_idx < self.chunk_start_char {
+            if steps == MAX_CHUNK_STEPS || !self.prev_chunk() {
+                self.seek(char_idx);
+                return Ok(());
+            }
+            steps += 1;
+        }
+        while char_idx > (self.chunk_start_char + self.chunk_chars) {
+            if steps == MAX_CHUNK_STEPS || !self.next_chunk() {
+                self.seek(char_idx);
+                return Ok(());
+            }
+            steps += 1;
+        }
+
+        // Move within the chunk.
+        if char_idx >= self.char_idx {
+            let text = &self.chunk[self.chunk_byte_idx..];
+            let byte_idx = char_to_byte_idx(text, char_idx - self.char_idx);
+            self.line_idx += byte_to_line_idx(text, byte_idx);
+            self.chunk_byte_idx += byte_idx;
+        } else {
+            let end = self.chunk_byte_idx;
+            self.chunk_byte_idx = self.chunk[..end]
+                .char_indices()
+                .rev()
+                .nth(self.char_idx - char_idx - 1)
+                .unwrap()
+                .0;
+            let text = &self.chunk[self.chunk_byte_idx..];
+            self.line_idx -= byte_to_line_idx(text, end - self.chunk_byte_idx);
+        }
+        self.char_idx = char_idx;
+        self.normalize();
+
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------
+    // Internal utilities
+
+    /// Positions the cursor at `char_idx` from scratch.
+    fn seek(&mut self, char_idx: usize) {
+        // To position the cursor at the end of the text, seek to the
+        // last chunk rather than past it.
+        let len_chars = self.slice.len_chars();
+        let seek_idx = if char_idx == len_chars && len_chars > 0 {
+            char_idx - 1
+        } else {
+            char_idx
+        };
+
+        let (mut chunks, byte_start, char_start, line_start) = self.slice.chunks_at_char(seek_idx);
+        self.chunk = chunks.next().unwrap_or("");
+        self.chunks = chunks;
+        self.chunk_chars = count_chars(self.chunk);
+        self.chunk_start_byte = byte_start;
+        self.chunk_start_char = char_start;
+        self.chunk_start_line = line_start;
+
+        self.chunk_byte_idx = char_to_byte_idx(self.chunk, char_idx - char_start);
+        self.char_idx = char_idx;
+        self.line_idx = line_start + byte_to_line_idx(self.chunk, self.chunk_byte_idx);
+        self.normalize();
+    }
+
+    /// Makes sure the cursor is only ever at the end of its chunk if it's
+    /// at the end of the text, so that `peek_char()` can just look at the
+    /// current chunk.
+    #[inline]
+    fn normalize(&mut self) {
+        while self.chunk_byte_idx == self.chunk.len() && self.next_chunk() {}
+    }
+
+    /// Moves the cursor to the start of the next chunk.
+    ///
+    /// Returns false and leaves the cursor unchanged if there is no next
+    /// chunk.
+    fn next_chunk(&mut self) -> bool {
+        if let Some(chunk) = self.chunks.next() {
+            self.chunk_start_byte += self.chunk.len();
+            self.chunk_start_char += self.chunk_chars;
+            self.chunk_start_line += count_line_breaks(self.chunk);
+            self.chunk = chunk;
+            self.chunk_chars = count_chars(chunk);
+
+            self.chunk_byte_idx = 0;
+            self.char_idx = self.chunk_start_char;
+            self.line_idx = self.chunk_start_line;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Moves the cursor to the end of the previous chunk.
+    ///
+    /// Returns false and leaves the cursor unchanged if there is no previous
+    /// chunk.
+    fn prev_chunk(&mut self) -> bool {
+        // Step back over the current chunk to get to the previous one, and
+        // then forward again to restore the `chunks` invariant.
+        self.chunks.prev();
+        let chunk = self.chunks.prev();
+        self.chunks.next();
+
+        if let Some(chunk) = chunk {
+            let line_breaks = count_line_breaks(chunk);
+            self.chunk = chunk;
+            self.chunk_chars = count_chars(chunk);
+            self.chunk_start_byte -= chunk.len();
+            self.chunk_start_char -= self.chunk_chars;
+            self.chunk_start_line -= line_breaks;
+
+            self.chunk_byte_idx = chunk.len();
+            self.char_idx = self.chunk_start_char + self.chunk_chars;
+            self.line_idx = self.chunk_start_line + line_breaks;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+//===========================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::Rope;
+
+    const TEXT: &str = "Hello there!  How're you doing?\r\nIt's \
+                        a fine day, isn't it?\r\nAren't you glad \
+                        we're alive?\r\nこんにちは、みんなさん！";
+
+    #[test]
+    fn cursor_01() {
+        let r = Rope::from_str(TEXT);
+        let mut cursor = r.cursor();
+
+        for (i, c) in r.chars().enumerate() {
+            assert_eq!(cursor.current_char(), i);
+            assert_eq!(cursor.current_byte(), r.char_to_byte(i));
+            assert_eq!(cursor.current_line(), r.char_to_line(i));
+            assert_eq!(cursor.peek_char(), Some(c));
+            cursor.move_by(1);
+        }
+
+        assert_eq!(cursor.current_char(), r.len_chars());
+        assert_eq!(cursor.current_byte(), r.len_bytes());
+        assert_eq!(cursor.current_line(), r.len_lines() - 1);
+        assert_eq!(cursor.peek_char(), None);
+    }
+
+    #[test]
+    fn cursor_02() {
+        let r = Rope::from_str(TEXT);
+        let mut cursor = r.cursor_at(r.len_chars());
+
+        for i in (0..r.len_chars()).rev() {
+            cursor.move_by(-1);
+            assert_eq!(cursor.current_char(), i);
+            assert_eq!(cursor.current_byte(), r.char_to_byte(i));
+            assert_eq!(cursor.current_line(), r.char_to_line(i));
+            assert_eq!(cursor.peek_char(), Some(r.char(i)));
+        }
+    }
+
+    #[test]
+    fn cursor_03() {
+        let r = Rope::from_str(TEXT);
+        let mut cursor = r.cursor();
+
+        // Jumping around, both near and far.
+        for &i in &[5, 100, 3, 33, 34, 32, 67, 0, 101, 66, 68, 1, 99] {
+            cursor.move_to_char(i);
+            assert_eq!(cursor.current_char(), i);
+            assert_eq!(cursor.current_byte(), r.char_to_byte(i));
+            assert_eq!(cursor.current_line(), r.char_to_line(i));
+            assert_eq!(cursor.peek_char(), r.try_char(i).ok());
+        }
+    }
+
+    #[test]
+    fn cursor_04() {
+        let r = Rope::from_str(TEXT);
+        let s = r.slice(20..90);
+        let mut cursor = s.cursor_at(10);
+
+        for i in 10..s.len_chars() {
+            assert_eq!(cursor.current_char(), i);
+            assert_eq!(cursor.current_byte(), s.char_to_byte(i));
+            assert_eq!(cursor.current_line(), s.char_to_line(i));
+            assert_eq!(cursor.peek_char(), Some(s.char(i)));
+            cursor.move_by(1);
+        }
+        assert_eq!(cursor.peek_char(), None);
+
+        cursor.move_by(-70);
+        assert_eq!(cursor.current_char(), 0);
+        assert_eq!(cursor.peek_char(), Some(s.char(0)));
+    }
+
+    #[test]
+    fn cursor_05() {
+        let r = Rope::from_str("");
+        let mut cursor = r.cursor();
+
+        assert_eq!(cursor.current_char(), 0);
+        assert_eq!(cursor.current_byte(), 0);
+        assert_eq!(cursor.current_line(), 0);
+        assert_eq!(cursor.peek_char(), None);
+
+        cursor.move_to_char(0);
+        assert_eq!(cursor.current_char(), 0);
+    }
+
+    #[test]
+    fn cursor_06() {
+        let r = Rope::from_str(TEXT);
+        let mut cursor = r.cursor_at(10);
+
+        assert!(cursor.try_move_to_char(r.len_chars()).is_ok());
+        assert!(cursor.try_move_to_char(r.len_chars() + 1).is_err());
+        assert_eq!(cursor.current_char(), r.len_chars());
+    }
+
+    #[test]
+    #[should_panic]
+    fn cursor_07() {
+        let r = Rope::from_str(TEXT);
+        let mut cursor = r.cursor_at(10);
+        cursor.move_by(-11);
+    }
+
+    #[test]
+    #[should_panic]
+    fn cursor_08() {
+        let r = Rope::from_str(TEXT);
+        r.cursor_at(r.len_chars() + 1);
+    }
+}
@@ -0,0 +1,293 @@
+//! Extended grapheme cluster segmentation of a `RopeSlice`.
+//!
+//! This drives `unicode-segmentation`'s `GraphemeCursor` over the slice's
+//! chunks, feeding it the neighboring chunks whenever it needs more text, so
+//! grapheme clusters that straddle chunk boundaries are handled correctly
+//! without copying the text.
+
+use unicode_segmentation::{GraphemeCursor, GraphemeIncomplete};
+
+use crate::slice::RopeSlice;
+
+/// Gives `cursor` the text ending at byte index `byte_idx` that it asked
+/// for as pre-context.
+#[inline]
+fn provide_context(slice: &RopeSlice, cursor: &mut GraphemeCursor, byte_idx: usize) {
+    let (chunk, chunk_start, _, _) = slice.chunk_at_byte(byte_idx - 1);
+    cursor.provide_context(&chunk[..(byte_idx - chunk_start)], chunk_start);
+}
+
+/// Returns whether `cursor`'s position is a grapheme boundary.  `chunk` must
+/// contain the position, or end at it.
+fn cursor_is_boundary(
+    slice: &RopeSlice,
+    cursor: &mut GraphemeCursor,
+    chunk: &str,
+    chunk_start: usize,
+) -> bool {
+    loop {
+        match cursor.is_boundary(chunk, chunk_start) {
+            Ok(is_boundary) => return is_boundary,
+            Err(GraphemeIncomplete::PreContext(n)) => provide_context(slice, cursor, n),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Returns the byte index of the grapheme boundary after `byte_idx`, or the
+/// length of the slice if there is none.
+///
+/// `chunk` and `chunk_start` are the chunk containing `byte_idx` and its
+/// byte index, and are updated to the chunk containing the boundary.
+fn next_boundary_from<'a>(
+    slice: &RopeSlice<'a>,
+    chunk: &mut &'a str,
+    chunk_start: &mut usize,
+    byte_idx: usize,
+) -> usize {
+    let len = slice.len_bytes();
+    let mut cursor = GraphemeCursor::new(byte_idx, len, true);
+    loop {
+        match cursor.next_boundary(chunk, *chunk_start) {
+            Ok(None) => return len,
+            Ok(Some(n)) => return n,
+            Err(GraphemeIncomplete::NextChunk) => {
+                *chunk_start += chunk.len();
+                *chunk = slice.chunk_at_byte(*chunk_start).0;
+
+                // When resumed at the start of a chunk, `GraphemeCursor`
+                // counts the regional indicators before it twice, so start
+                // over with a fresh cursor at the chunk seam instead.
+                cursor = GraphemeCursor::new(*chunk_start, len, true);
+                if cursor_is_boundary(slice, &mut cursor, chunk, *chunk_start) {
+                    return *chunk_start;
+                }
+            }
+            Err(GraphemeIncomplete::PreContext(n)) => provide_context(slice, &mut cursor, n),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Returns the byte index of the grapheme boundary before `byte_idx`, or 0
+/// if there is none.
+///
+/// `chunk` and `chunk_start` are the chunk containing or ending at
+/// `byte_idx` and its byte index, and are updated to the chunk containing
+/// or ending at the boundary.
+fn prev_boundary_from<'a>(
+    slice: &RopeSlice<'a>,
+    chunk: &mut &'a str,
+    chunk_start: &mut usize,
+    byte_idx: usize,
+) -> usize {
+    let mut cursor = GraphemeCursor::new(byte_idx, slice.len_bytes(), true);
+    loop {
+        match cursor.prev_boundary(chunk, *chunk_start) {
+            Ok(None) => return 0,
+            Ok(Some(n)) => return n,
+            Err(GraphemeIncomplete::PrevChunk) => {
+                let (c, s, _, _) = slice.chunk_at_byte(*chunk_start - 1);
+                *chunk = c;
+                *chunk_start = s;
+            }
+            Err(GraphemeIncomplete::PreContext(n)) => provide_context(slice, &mut cursor, n),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Returns the byte index of the grapheme boundary before `byte_idx`, or 0
+/// if there is none.
+pub(crate) fn prev_boundary(slice: &RopeSlice, byte_idx: usize) -> usize {
+    let (mut chunk, mut chunk_start, _, _) = slice.chunk_at_byte(byte_idx);
+    prev_boundary_from(slice, &mut chunk, &mut chunk_start, byte_idx)
+}
+
+/// Returns the byte index of the grapheme boundary after `byte_idx`, or the
+/// length of the slice if there is none.
+pub(crate) fn next_boundary(slice: &RopeSlice, byte_idx: usize) -> usize {
+    let (mut chunk, mut chunk_start, _, _) = slice.chunk_at_byte(byte_idx);
+    next_boundary_from(slice, &mut chunk, &mut chunk_start, byte_idx)
+}
+
+/// Returns whether `byte_idx` is a grapheme boundary.
+pub(crate) fn is_boundary(slice: &RopeSlice, byte_idx: usize) -> bool {
+    let (chunk, chunk_start, _, _) = slice.chunk_at_byte(byte_idx);
+    let mut cursor = GraphemeCursor::new(byte_idx, slice.len_bytes(), true);
+    cursor_is_boundary(slice, &mut cursor, chunk, chunk_start)
+}
+
+//==============================================================
+
+/// An iterator over the extended grapheme clusters of a `Rope` or
+/// `RopeSlice`.
+///
+/// Like Ropey's other iterators, it can move both forwards and backwards,
+/// via `next()` and `prev()`.  Each grapheme is yielded as a `RopeSlice`,
+/// since a grapheme may span chunk boundaries.
+///
+/// Created with [`RopeSlice::graphemes()`](struct.RopeSlice.html#method.graphemes)
+/// or [`RopeSlice::graphemes_at()`](struct.RopeSlice.html#method.graphemes_at),
+/// and the methods of the same names on `Rope`.
+#[derive(Debug, Clone)]
+pub struct Graphemes<'a> {
+    slice: RopeSlice<'a>,
+    chunk: &'a str,
+    chunk_start: usize, // Byte index of the start of `chunk`.
+    byte_idx: usize,    // Always a grapheme boundary.
+}
+
+impl<'a> Graphemes<'a> {
+    /// `byte_idx` must be a grapheme boundary.
+    pub(crate) fn new(slice: RopeSlice<'a>, byte_idx: usize) -> Graphemes<'a> {
+        let (chunk, chunk_start, _, _) = slice.chunk_at_byte(byte_idx);
+        Graphemes {
+            slice: slice,
+            chunk: chunk,
+            chunk_start: chunk_start,
+            byte_idx: byte_idx,
+        }
+    }
+
+    /// Advances the iterator backwards and returns the previous value.
+    ///
+    /// Runs in amortized O(1) time and worst-case O(log N) time, for
+    /// graphemes of bounded length.
+    pub fn prev(&mut self) -> Option<RopeSlice<'a>> {
+        if self.byte_idx == 0 {
+            return None;
+        }
+        let end = self.byte_idx;
+        self.byte_idx =
+            prev_boundary_from(&self.slice, &mut self.chunk, &mut self.chunk_start, end);
+        Some(self.grapheme(self.byte_idx, end))
+    }
+
+    /// Returns the grapheme between the given byte indices.
+    #[inline]
+    fn grapheme(&self, start: usize, end: usize) -> RopeSlice<'a> {
+        let chunk_end = self.chunk_start + self.chunk.len();
+        if start >= self.chunk_start && end <= chunk_end {
+            (&self.chunk[(start - self.chunk_start)..(end - self.chunk_start)]).into()
+        } else {
+            self.slice.byte_slice(start..end)
+        }
+    }
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = RopeSlice<'a>;
+
+    /// Advances the iterator forward and returns the next value.
+    ///
+    /// Runs in amortized O(1) time and worst-case O(log N) time, for
+    /// graphemes of bounded length.
+    fn next(&mut self) -> Option<RopeSlice<'a>> {
+        if self.byte_idx == self.slice.len_bytes() {
+            return None;
+        }
+        let start = self.byte_idx;
+        self.byte_idx =
+            next_boundary_from(&self.slice, &mut self.chunk, &mut self.chunk_start, start);
+        Some(self.grapheme(start, self.byte_idx))
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    use crate::Rope;
+
+    // Combining marks, CRLF pairs, flags, and emoji ZWJ sequences, which
+    // end up straddling chunk boundaries.  Runs of flags in particular trip
+    // up `GraphemeCursor` at chunk seams.
+    const TEXT: &str = "He\u{303}\u{303}\u{303}llo\r\n \u{1F1EC}\u{1F1E7}\u{1F1EF}\u{1F1F5}\
+                        \u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467} wo\u{301}\u{302}\u{303}\u{304}\
+                        rld!\r\nこんにちは、\u{1F1FA}\u{1F1F8}\u{1F1EB}みんなさん\u{301}！";
+
+    #[test]
+    fn graphemes_01() {
+        let r = Rope::from_str(&TEXT.repeat(3));
+        for s in &[r.slice(..), r.slice(5..90), r.slice(3..4), r.slice(7..7)] {
+            let text = s.to_string();
+            let expected: Vec<_> = text.graphemes(true).collect();
+
+            let mut iter = s.graphemes();
+            let mut found = Vec::new();
+            for g in iter.by_ref() {
+                found.push(g.to_string());
+            }
+            assert_eq!(found, expected);
+
+            // And back again.
+            found.clear();
+            while let Some(g) = iter.prev() {
+                found.push(g.to_string());
+            }
+            found.reverse();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn graphemes_at_01() {
+        let r = Rope::from_str(&TEXT.repeat(3));
+        let s = r.slice(2..140);
+        let text = s.to_string();
+        let expected: Vec<_> = text.grapheme_indices(true).collect();
+        for (i, &(byte_idx, _)) in expected.iter().enumerate() {
+            let mut iter = s.graphemes_at(s.byte_to_char(byte_idx));
+            assert_eq!(iter.next().unwrap(), expected[i].1);
+            iter.prev();
+            if i > 0 {
+                assert_eq!(iter.prev().unwrap(), expected[i - 1].1);
+            } else {
+                assert_eq!(iter.prev(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn boundaries_01() {
+        let r = Rope::from_str(&TEXT.repeat(3));
+        for s in &[r.slice(..), r.slice(5..90)] {
+            let text = s.to_string();
+            let mut boundaries: Vec<_> = text
+                .grapheme_indices(true)
+                .map(|(i, _)| s.byte_to_char(i))
+                .collect();
+            boundaries.push(s.len_chars());
+
+            for char_idx in 0..=s.len_chars() {
+                assert_eq!(
+                    s.is_grapheme_boundary(char_idx),
+                    boundaries.contains(&char_idx)
+                );
+                let next = boundaries.iter().find(|&&b| b > char_idx);
+                assert_eq!(
+                    s.next_grapheme_boundary(char_idx),
+                    *next.unwrap_or(&s.len_chars())
+                );
+                let prev = boundaries.iter().rev().find(|&&b| b < char_idx);
+                assert_eq!(s.prev_grapheme_boundary(char_idx), *prev.unwrap_or(&0));
+            }
+        }
+    }
+
+    #[test]
+    fn boundaries_02() {
+        let r = Rope::from_str("a\r\nb");
+        let s = r.slice(..);
+        assert!(!s.is_grapheme_boundary(2));
+        assert_eq!(s.next_grapheme_boundary(1), 3);
+        assert_eq!(s.prev_grapheme_boundary(3), 1);
+        assert_eq!(s.prev_grapheme_boundary(0), 0);
+        assert_eq!(s.next_grapheme_boundary(4), 4);
+        assert!(s.try_is_grapheme_boundary(5).is_err());
+        assert!(s.try_graphemes_at(5).is_err());
+    }
+}
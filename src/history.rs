@@ -0,0 +1,868 @@
+//! Undo/redo history for a `Rope`.
+
+use std::collections::HashSet;
+use std::mem::size_of;
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+use crate::rope::Rope;
+use crate::tree::Node;
+use crate::{Error, Result};
+
+/// How a [`History`](struct.History.html) stores its revisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryMode {
+    /// Each revision stores a clone of the `Rope`.
+    ///
+    /// Since clones share all unmodified nodes, each revision only costs
+    /// roughly the memory of the parts of the text that were edited, and
+    /// undo/redo are O(1) regardless of the size of the transaction.
+    Snapshots,
+
+    /// Each revision stores the list of edits that produced it, along with
+    /// any removed text, and undo/redo replay them.
+    ///
+    /// This uses less memory than `Snapshots` for small edits to large
+    /// documents, but undo/redo take time proportional to the size of the
+    /// transaction.
+    Deltas,
+}
+
+/// An undo/redo history for a `Rope`.
+///
+/// Edits made through `insert()` and `remove()` are applied to the rope and
+/// recorded as part of the current transaction.  Calling `commit()` closes
+/// the transaction, turning it into a new revision that can then be undone
+/// and redone as a unit.
+///
+/// The history is a tree rather than a list: making new edits after an undo
+/// starts a new branch instead of discarding the undone revisions.  `redo()`
+/// follows the most recently visited branch, and `goto()` can jump to any
+/// revision.
+///
+/// All edits to the rope must go through the `History` for it to stay in
+/// sync.
+///
+/// # Example
+///
+/// ```
+/// # use ropey::{History, HistoryMode, Rope};
+/// let mut rope = Rope::from_str("Hello world!");
+/// let mut history = History::new(&rope, HistoryMode::Snapshots);
+///
+/// history.remove(&mut rope, 5..11);
+/// history.insert(&mut rope, 5, ", everyone");
+/// history.commit(&rope);
+/// assert_eq!(rope, "Hello, everyone!");
+///
+/// history.undo(&mut rope);
+/// assert_eq!(rope, "Hello world!");
+///
+/// history.redo(&mut rope);
+/// assert_eq!(rope, "Hello, everyone!");
+/// ```
+#[derive(Debug, Clone)]
+pub struct History {
+    mode: HistoryMode,
+    revisions: Vec<Revision>, // Sorted by id, the root is always first.
+    current: usize,           // Index into `revisions`.
+    pending: Vec<Edit>,       // Only used in `Deltas` mode.
+    has_pending: bool,
+    next_id: usize,
+    max_revisions: usize,
+    max_bytes: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Revision {
+    id: usize,
+    parent: Option<usize>,     // Index into `revisions`.
+    last_child: Option<usize>, // Index into `revisions`, for `redo()`.
+    edits: Vec<Edit>,          // The edits from the parent to this revision.
+    snapshot: Option<Rope>,
+    bytes: usize, // Memory used on top of the parent, zero for the root.
+}
+
+#[derive(Debug, Clone)]
+enum Edit {
+    Insert(usize, String),
+    Remove(usize, String),
+}
+
+impl Edit {
+    fn apply(&self, rope: &mut Rope) {
+        match *self {
+            Edit::Insert(char_idx, ref text) => rope.insert(char_idx, text),
+            Edit::Remove(char_idx, ref text) => {
+                rope.remove(char_idx..(char_idx + text.chars().count()))
+            }
+        }
+    }
+
+    fn text(&self) -> &str {
+        match *self {
+            Edit::Insert(_, ref text) | Edit::Remove(_, ref text) => text,
+        }
+    }
+
+    fn unapply(&self, rope: &mut Rope) {
+        match *self {
+            Edit::Insert(char_idx, ref text) => {
+                rope.remove(char_idx..(char_idx + text.chars().count()))
+            }
+            Edit::Remove(char_idx, ref text) => rope.insert(char_idx, text),
+        }
+    }
+}
+
+impl History {
+    /// Creates a new history, with `rope` as its initial revision.
+    pub fn new(rope: &Rope, mode: HistoryMode) -> History {
+        History {
+            mode: mode,
+            revisions: vec![Revision {
+                id: 0,
+                parent: None,
+                last_child: None,
+                edits: Vec::new(),
+                snapshot: if mode == HistoryMode::Snapshots {
+                    Some(rope.clone())
+                } else {
+                    None
+                },
+                bytes: 0,
+            }],
+            current: 0,
+            pending: Vec::new(),
+            has_pending: false,
+            next_id: 1,
+            max_revisions: usize::MAX,
+            max_bytes: usize::MAX,
+        }
+    }
+
+    /// Returns the mode the history was created with.
+    #[inline]
+    pub fn mode(&self) -> HistoryMode {
+        self.mode
+    }
+
+    /// Limits the number of revisions kept, including the current one.
+    ///
+    /// When a commit would exceed the limit, revisions on branches other
+    /// than the current one are discarded first, oldest first, and then the
+    /// oldest ancestors of the current revision.  The limit is clamped to a
+    /// minimum of 2, so the current revision can always be undone.
+    ///
+    /// To bound the memory used by the history instead, see
+    /// [`set_max_bytes()`](#method.set_max_bytes).
+    pub fn set_max_revisions(&mut self, max_revisions: usize) {
+        self.max_revisions = max_revisions.max(2);
+        self.prune();
+    }
+
+    /// Limits the memory used by the revisions, in bytes.
+    ///
+    /// Each revision counts the memory it takes on top of its parent
+    /// revision: the text inserted and removed by its edits in `Deltas`
+    /// mode, and the tree nodes that its snapshot doesn't share with its
+    /// parent's in `Snapshots` mode.  The oldest revision, which the others
+    /// are built on, counts as zero.
+    ///
+    /// When a commit would exceed the limit, revisions are discarded in the
+    /// same order as for [`set_max_revisions()`](#method.set_max_revisions).
+    /// Unlike that limit, this one also discards the current revision's
+    /// parent if the current revision alone is too large, in which case it
+    /// can't be undone.
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+        self.prune();
+    }
+
+    /// Returns the number of revisions currently kept.
+    #[inline]
+    pub fn len_revisions(&self) -> usize {
+        self.revisions.len()
+    }
+
+    /// Returns the memory used by the revisions currently kept, in bytes, as
+    /// counted by [`set_max_bytes()`](#method.set_max_bytes).
+    #[inline]
+    pub fn len_revision_bytes(&self) -> usize {
+        self.revisions.iter().map(|rev| rev.bytes).sum()
+    }
+
+    /// Returns the id of the current revision.
+    ///
+    /// Ids are assigned in increasing order as revisions are committed,
+    /// starting with 0 for the initial revision, and are never reused.
+    #[inline]
+    pub fn current_revision(&self) -> usize {
+        self.revisions[self.current].id
+    }
+
+    /// Returns whether there are uncommitted edits.
+    #[inline]
+    pub fn has_pending(&self) -> bool {
+        self.has_pending
+    }
+
+    /// Returns whether there is a revision to undo to, counting uncommitted
+    /// edits.
+    #[inline]
+    pub fn can_undo(&self) -> bool {
+        self.has_pending || self.revisions[self.current].parent.is_some()
+    }
+
+    /// Returns whether there is a revision to redo to.
+    #[inline]
+    pub fn can_redo(&self) -> bool {
+        !self.has_pending && self.revisions[self.current].last_child.is_some()
+    }
+
+    //-----------------------------------------------------------------------
+    // Editing
+
+    /// Inserts `text` into `rope` at char index `char_idx`, and records it
+    /// in the current transaction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn insert(&mut self, rope: &mut Rope, char_idx: usize, text: &str) {
+        self.try_insert(rope, char_idx, text).unwrap()
+    }
+
+    /// Removes the text in the given char index range from `rope`, and
+    /// records it in the current transaction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if the
+    /// end is out of bounds (i.e. `end > len_chars()`).
+    #[inline]
+    pub fn remove<R>(&mut self, rope: &mut Rope, char_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_remove(rope, char_range).unwrap()
+    }
+
+    /// Closes the current transaction, making it a new revision that is a
+    /// child of the current one.
+    ///
+    /// `rope` must be the rope that the edits were made to.  Does nothing
+    /// if there are no uncommitted edits.
+    pub fn commit(&mut self, rope: &Rope) {
+        if !self.has_pending {
+            return;
+        }
+
+        let edits = std::mem::take(&mut self.pending);
+        let (snapshot, bytes) = match self.mode {
+            HistoryMode::Snapshots => {
+                let parent = self.revisions[self.current].snapshot.as_ref().unwrap();
+                (Some(rope.clone()), unshared_bytes(rope, parent))
+            }
+            HistoryMode::Deltas => (None, edits.iter().map(|edit| edit.text().len()).sum()),
+        };
+
+        let idx = self.revisions.len();
+        self.revisions.push(Revision {
+            id: self.next_id,
+            parent: Some(self.current),
+            last_child: None,
+            edits: edits,
+            snapshot: snapshot,
+            bytes: bytes,
+        });
+        self.revisions[self.current].last_child = Some(idx);
+        self.current = idx;
+        self.next_id += 1;
+        self.has_pending = false;
+
+        self.prune();
+    }
+
+    //-----------------------------------------------------------------------
+    // Undo/redo
+
+    /// Reverts `rope` to the parent of the current revision.
+    ///
+    /// Any uncommitted edits are committed first, so that they can be
+    /// redone.  Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self, rope: &mut Rope) -> bool {
+        self.commit(rope);
+        match self.revisions[self.current].parent {
+            Some(parent) => {
+                self.revisions[parent].last_child = Some(self.current);
+                self.step_up(rope);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves `rope` forward to the most recently visited child of the
+    /// current revision.
+    ///
+    /// Returns `false` if there was nothing to redo, or if there are
+    /// uncommitted edits.
+    pub fn redo(&mut self, rope: &mut Rope) -> bool {
+        if self.has_pending {
+            return false;
+        }
+        match self.revisions[self.current].last_child {
+            Some(child) => {
+                self.step_down(rope, child);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves `rope` to the revision with id `revision`, which may be on a
+    /// different branch.
+    ///
+    /// Any uncommitted edits are committed first.  The branches taken
+    /// become the ones followed by `redo()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no revision with that id, e.g. because it was
+    /// discarded to stay within `set_max_revisions()`.
+    #[inline]
+    pub fn goto(&mut self, rope: &mut Rope, revision: usize) {
+        self.try_goto(rope, revision).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Fallible versions of the above methods
+
+    /// Non-panicking version of [`insert()`](#method.insert).
+    pub fn try_insert(&mut self, rope: &mut Rope, char_idx: usize, text: &str) -> Result<()> {
+        rope.try_insert(char_idx, text)?;
+        if !text.is_empty() {
+            if self.mode == HistoryMode::Deltas {
+                self.pending.push(Edit::Insert(char_idx, text.into()));
+            }
+            self.has_pending = true;
+        }
+        Ok(())
+    }
+
+    /// Non-panicking version of [`remove()`](#method.remove).
+    pub fn try_remove<R>(&mut self, rope: &mut Rope, char_range: R) -> Result<()>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = rope.check_char_range(char_range)?;
+        if start < end {
+            if self.mode == HistoryMode::Deltas {
+                let removed = rope.slice(start..end).to_string();
+                self.pending.push(Edit::Remove(start, removed));
+            }
+            rope.remove(start..end);
+            self.has_pending = true;
+        }
+        Ok(())
+    }
+
+    /// Non-panicking version of [`goto()`](#method.goto).
+    pub fn try_goto(&mut self, rope: &mut Rope, revision: usize) -> Result<()> {
+        let target = match self.revisions.binary_search_by_key(&revision, |r| r.id) {
+            Ok(idx) => idx,
+            Err(_) => return Err(Error::RevisionNotFound(revision)),
+        };
+        self.commit(rope);
+
+        // Find the path from the common ancestor down to the target.
+        let mut path = Vec::new();
+        let mut idx = target;
+        while !self.is_ancestor(idx, self.current) {
+            path.push(idx);
+            idx = self.revisions[idx].parent.unwrap();
+        }
+
+        // Go up to the common ancestor, then down to the target.
+        while self.current != idx {
+            self.step_up(rope);
+        }
+        for &child in path.iter().rev() {
+            self.revisions[self.current].last_child = Some(child);
+            self.step_down(rope, child);
+        }
+
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------
+    // Internal utilities
+
+    /// Moves from the current revision to its parent.
+    fn step_up(&mut self, rope: &mut Rope) {
+        let parent = self.revisions[self.current].parent.unwrap();
+        match self.mode {
+            HistoryMode::Snapshots => {
+                *rope = self.revisions[parent].snapshot.clone().unwrap();
+            }
+            HistoryMode::Deltas => {
+                for edit in self.revisions[self.current].edits.iter().rev() {
+                    edit.unapply(rope);
+                }
+            }
+        }
+        self.current = parent;
+    }
+
+    /// Moves from the current revision to its child `child`.
+    fn step_down(&mut self, rope: &mut Rope, child: usize) {
+        debug_assert_eq!(self.revisions[child].parent, Some(self.current));
+        match self.mode {
+            HistoryMode::Snapshots => {
+                *rope = self.revisions[child].snapshot.clone().unwrap();
+            }
+            HistoryMode::Deltas => {
+                for edit in self.revisions[child].edits.iter() {
+                    edit.apply(rope);
+                }
+            }
+        }
+        self.current = child;
+    }
+
+    /// Returns whether revision `a` is an ancestor of (or is) revision `b`.
+    fn is_ancestor(&self, a: usize, mut b: usize) -> bool {
+        // Parents always come before their children.
+        while b > a {
+            b = self.revisions[b].parent.unwrap();
+        }
+        a == b
+    }
+
+    /// Discards revisions until there are no more than `max_revisions` of
+    /// them, using no more than `max_bytes`.
+    ///
+    /// Revisions on other branches than the current one go first, oldest
+    /// first, and only then the oldest ancestors of the current revision.
+    fn prune(&mut self) {
+        let len = self.revisions.len();
+        let mut count = len;
+        let mut bytes = self.len_revision_bytes();
+        if count <= self.max_revisions && bytes <= self.max_bytes {
+            return;
+        }
+        let mut removed = vec![false; len];
+
+        // The current revision and its ancestors, from the root down.
+        let mut path = Vec::new();
+        let mut idx = Some(self.current);
+        while let Some(i) = idx {
+            path.push(i);
+            idx = self.revisions[i].parent;
+        }
+        path.reverse();
+        let mut on_path = vec![false; len];
+        for &i in path.iter() {
+            on_path[i] = true;
+        }
+
+        // Dead branches, a leaf at a time so that the tree stays connected.
+        let mut child_count = vec![0; len];
+        for rev in self.revisions.iter() {
+            if let Some(p) = rev.parent {
+                child_count[p] += 1;
+            }
+        }
+        while count > self.max_revisions || bytes > self.max_bytes {
+            let leaf = (0..len).find(|&i| !removed[i] && !on_path[i] && child_count[i] == 0);
+            match leaf {
+                Some(i) => {
+                    removed[i] = true;
+                    child_count[self.revisions[i].parent.unwrap()] -= 1;
+                    count -= 1;
+                    bytes -= self.revisions[i].bytes;
+                }
+                None => break,
+            }
+        }
+
+        // Then the oldest ancestors, each time making the next one the root,
+        // which doesn't count towards `max_bytes`.  Only the path is left at
+        // this point, and since `max_revisions` is at least 2, only
+        // `max_bytes` can discard the current revision's parent.
+        let mut first = 0;
+        while (count > self.max_revisions || bytes > self.max_bytes) && first + 1 < path.len() {
+            removed[path[first]] = true;
+            count -= 1;
+            bytes -= self.revisions[path[first + 1]].bytes;
+            first += 1;
+        }
+
+        // Compact what's left.  Since parents always come before their
+        // children, one pass is enough.
+        let mut remap = vec![None; len];
+        let mut kept: Vec<Revision> = Vec::with_capacity(len);
+        let mut lost_child = Vec::with_capacity(len);
+        for (idx, mut rev) in self.revisions.drain(..).enumerate() {
+            if removed[idx] {
+                continue;
+            }
+            remap[idx] = Some(kept.len());
+            rev.parent = rev.parent.and_then(|p| remap[p]);
+            if rev.parent.is_none() {
+                rev.edits = Vec::new();
+                rev.bytes = 0;
+            }
+            lost_child.push(rev.last_child.is_some_and(|c| removed[c]));
+            kept.push(rev);
+        }
+        for i in 0..kept.len() {
+            kept[i].last_child = kept[i].last_child.and_then(|c| remap[c]);
+            // If `redo()` would have followed a discarded branch, follow the
+            // newest remaining one instead.
+            if let Some(p) = kept[i].parent {
+                if lost_child[p] {
+                    kept[p].last_child = Some(i);
+                }
+            }
+        }
+        self.current = remap[self.current].unwrap();
+        self.revisions = kept;
+    }
+}
+
+/// Returns roughly how much memory `rope`'s tree uses that it doesn't share
+/// with `base`'s tree, in bytes: the size of the nodes of `rope` that aren't
+/// also in `base`.
+///
+/// A subtree shared by both trees has the same height in each, so this
+/// compares the trees a level at a time, only descending into the nodes
+/// that aren't shared.  That makes it proportional to the size of the
+/// difference, rather than to the size of the trees.
+fn unshared_bytes(rope: &Rope, base: &Rope) -> usize {
+    fn children<'a>(nodes: &[&'a Arc<Node>]) -> Vec<&'a Arc<Node>> {
+        let mut children = Vec::new();
+        for node in nodes.iter() {
+            if let Node::Internal(ref c) = ***node {
+                children.extend(c.nodes().iter());
+            }
+        }
+        children
+    }
+
+    // A node, along with the two counters of its `Arc`.
+    let node_bytes = size_of::<Node>() + 2 * size_of::<usize>();
+
+    let mut nodes = vec![&rope.root];
+    let mut height = rope.root.depth();
+    let mut base_nodes = vec![&base.root];
+    let mut base_height = base.root.depth();
+    let mut bytes = 0;
+    loop {
+        while base_height > height {
+            base_nodes = children(&base_nodes);
+            base_height -= 1;
+        }
+        if base_height == height {
+            let ptrs: HashSet<*const Node> = nodes.iter().map(|n| Arc::as_ptr(n)).collect();
+            let base_ptrs: HashSet<*const Node> =
+                base_nodes.iter().map(|n| Arc::as_ptr(n)).collect();
+            nodes.retain(|n| !base_ptrs.contains(&Arc::as_ptr(n)));
+            base_nodes.retain(|n| !ptrs.contains(&Arc::as_ptr(n)));
+        }
+
+        bytes += nodes.len() * node_bytes;
+        if nodes.is_empty() || height == 0 {
+            return bytes;
+        }
+        nodes = children(&nodes);
+        height -= 1;
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::{History, HistoryMode, Rope};
+
+    const TEXT: &str = "Hello there!  How're you doing?\r\nIt's \
+                        a fine day, isn't it?\r\nAren't you glad \
+                        we're alive?\r\nこんにちは、みんなさん！";
+
+    const MODES: [HistoryMode; 2] = [HistoryMode::Snapshots, HistoryMode::Deltas];
+
+    #[test]
+    fn history_01() {
+        for &mode in MODES.iter() {
+            let mut r = Rope::from_str(TEXT);
+            let mut h = History::new(&r, mode);
+            assert!(!h.can_undo());
+            assert!(!h.can_redo());
+
+            h.insert(&mut r, 5, "!!");
+            h.remove(&mut r, 0..2);
+            h.commit(&r);
+            assert_eq!(h.current_revision(), 1);
+            assert_eq!(&r.to_string()[..8], "llo!! th");
+
+            assert!(h.undo(&mut r));
+            assert_eq!(r, TEXT);
+            assert!(!h.undo(&mut r));
+            assert_eq!(r, TEXT);
+
+            assert!(h.redo(&mut r));
+            assert_eq!(&r.to_string()[..8], "llo!! th");
+            assert!(!h.redo(&mut r));
+        }
+    }
+
+    #[test]
+    fn history_02() {
+        // Undo with uncommitted edits commits them first.
+        for &mode in MODES.iter() {
+            let mut r = Rope::from_str(TEXT);
+            let mut h = History::new(&r, mode);
+
+            h.remove(&mut r, 10..50);
+            let edited = r.clone();
+            assert!(h.has_pending());
+            assert!(!h.can_redo());
+
+            assert!(h.undo(&mut r));
+            assert_eq!(r, TEXT);
+            assert!(h.redo(&mut r));
+            assert_eq!(r, edited);
+        }
+    }
+
+    #[test]
+    fn history_03() {
+        // Branching.
+        for &mode in MODES.iter() {
+            let mut r = Rope::from_str("abc");
+            let mut h = History::new(&r, mode);
+
+            h.insert(&mut r, 3, "d");
+            h.commit(&r);
+            h.insert(&mut r, 4, "e");
+            h.commit(&r);
+            assert_eq!(r, "abcde");
+
+            h.undo(&mut r);
+            h.undo(&mut r);
+            h.insert(&mut r, 0, "x");
+            h.commit(&r);
+            assert_eq!(r, "xabc");
+            assert_eq!(h.current_revision(), 3);
+
+            // Redo follows the most recent branch.
+            h.undo(&mut r);
+            h.redo(&mut r);
+            assert_eq!(r, "xabc");
+
+            h.goto(&mut r, 2);
+            assert_eq!(r, "abcde");
+            h.goto(&mut r, 0);
+            assert_eq!(r, "abc");
+            h.redo(&mut r);
+            assert_eq!(r, "abcd");
+
+            h.goto(&mut r, 3);
+            assert_eq!(r, "xabc");
+            h.goto(&mut r, 3);
+            assert_eq!(r, "xabc");
+
+            assert!(h.try_goto(&mut r, 4).is_err());
+        }
+    }
+
+    #[test]
+    fn history_04() {
+        // Empty edits aren't recorded.
+        for &mode in MODES.iter() {
+            let mut r = Rope::from_str(TEXT);
+            let mut h = History::new(&r, mode);
+
+            h.insert(&mut r, 5, "");
+            h.remove(&mut r, 5..5);
+            h.commit(&r);
+            assert!(!h.can_undo());
+            assert_eq!(h.current_revision(), 0);
+        }
+    }
+
+    #[test]
+    fn history_05() {
+        // Failed edits leave everything untouched.
+        for &mode in MODES.iter() {
+            let mut r = Rope::from_str("abc");
+            let mut h = History::new(&r, mode);
+
+            assert!(h.try_insert(&mut r, 4, "d").is_err());
+            assert!(h.try_remove(&mut r, 2..4).is_err());
+            assert!(!h.has_pending());
+            assert_eq!(r, "abc");
+        }
+    }
+
+    #[test]
+    fn history_06() {
+        // Bounded number of revisions.
+        for &mode in MODES.iter() {
+            let mut r = Rope::new();
+            let mut h = History::new(&r, mode);
+            h.set_max_revisions(4);
+
+            for i in 0..10 {
+                h.insert(&mut r, i, "a");
+                h.commit(&r);
+            }
+            assert_eq!(h.len_revisions(), 4);
+            assert_eq!(h.current_revision(), 10);
+
+            while h.undo(&mut r) {}
+            assert_eq!(r, "aaaaaaa");
+            assert_eq!(h.current_revision(), 7);
+            assert!(h.try_goto(&mut r, 6).is_err());
+
+            // Pruning drops other branches before the current revision's
+            // ancestors.
+            h.insert(&mut r, 0, "b");
+            h.commit(&r);
+            assert_eq!(h.len_revisions(), 4);
+            assert_eq!(h.current_revision(), 11);
+            assert!(h.can_undo());
+            assert!(h.try_goto(&mut r, 10).is_err());
+            h.goto(&mut r, 9);
+            assert_eq!(r, "aaaaaaaaa");
+            h.goto(&mut r, 11);
+            assert_eq!(r, "baaaaaaa");
+
+            h.insert(&mut r, 0, "b");
+            h.commit(&r);
+            assert_eq!(h.len_revisions(), 4);
+            assert!(h.try_goto(&mut r, 9).is_err());
+            assert!(h.undo(&mut r));
+            assert!(h.undo(&mut r));
+            assert_eq!(r, "aaaaaaa");
+            assert!(!h.undo(&mut r));
+
+            // With only the current branch left, the oldest ancestors go.
+            h.goto(&mut r, 12);
+            for _ in 0..2 {
+                h.insert(&mut r, 0, "c");
+                h.commit(&r);
+            }
+            assert_eq!(h.len_revisions(), 4);
+            assert!(h.try_goto(&mut r, 8).is_err());
+            assert!(h.try_goto(&mut r, 7).is_err());
+            while h.undo(&mut r) {}
+            assert_eq!(h.current_revision(), 11);
+            assert_eq!(r, "baaaaaaa");
+        }
+    }
+
+    #[test]
+    fn history_07() {
+        // Lots of random edits and undos/redos, checked against the text at
+        // each revision.
+        for &mode in MODES.iter() {
+            let mut r = Rope::from_str(TEXT);
+            let mut h = History::new(&r, mode);
+            let mut texts = vec![r.to_string()];
+
+            for i in 0..50 {
+                let len = r.len_chars();
+                h.insert(&mut r, (i * 7) % (len + 1), "xyz\r\n");
+                let len = r.len_chars();
+                let start = (i * 13) % len;
+                h.remove(&mut r, start..(start + 3).min(len));
+                h.commit(&r);
+                texts.push(r.to_string());
+            }
+
+            for i in (0..50).rev() {
+                h.undo(&mut r);
+                assert_eq!(r, texts[i]);
+            }
+            for &i in [25usize, 3, 50, 0, 49].iter() {
+                h.goto(&mut r, i);
+                assert_eq!(r, texts[i]);
+                r.assert_integrity();
+                r.assert_invariants();
+            }
+        }
+    }
+
+    #[test]
+    fn history_08() {
+        // Bounded memory.
+        for &mode in MODES.iter() {
+            let mut r = Rope::from_str(TEXT);
+            let mut h = History::new(&r, mode);
+            assert_eq!(h.len_revision_bytes(), 0);
+
+            h.insert(&mut r, 0, "abc");
+            h.commit(&r);
+            let bytes = h.len_revision_bytes();
+            assert!(bytes >= 3);
+
+            h.set_max_bytes(bytes * 4);
+            for i in 0..20 {
+                h.insert(&mut r, i, "abc");
+                h.commit(&r);
+                assert!(h.len_revision_bytes() <= bytes * 4);
+                assert!(h.can_undo());
+            }
+            assert!(h.len_revisions() < 21);
+
+            // A single revision over the limit leaves nothing to undo.
+            let big = "x".repeat(bytes * 8);
+            h.insert(&mut r, 0, &big);
+            h.commit(&r);
+            assert_eq!(h.len_revisions(), 1);
+            assert_eq!(h.len_revision_bytes(), 0);
+            assert!(!h.can_undo());
+            assert_eq!(&r.to_string()[..big.len()], big);
+
+            // Removed text counts too in `Deltas` mode, while a snapshot
+            // after a removal shares most of its nodes.
+            h.remove(&mut r, 0..big.len());
+            h.commit(&r);
+            assert_eq!(h.can_undo(), mode == HistoryMode::Snapshots);
+
+            // Lowering the limit prunes right away.
+            h.set_max_bytes(usize::MAX);
+            let len = h.len_revisions();
+            for i in 0..4 {
+                h.insert(&mut r, i, "abc");
+                h.commit(&r);
+            }
+            assert_eq!(h.len_revisions(), len + 4);
+            h.set_max_bytes(0);
+            assert_eq!(h.len_revisions(), 1);
+            assert_eq!(h.len_revision_bytes(), 0);
+        }
+    }
+
+    #[test]
+    fn history_09() {
+        // Snapshots only count the nodes they don't share with their parent.
+        let text = TEXT.repeat(200);
+        let mut r = Rope::from_str(&text);
+        let mut h = History::new(&r, HistoryMode::Snapshots);
+
+        h.insert(&mut r, text.len() / 2, "a");
+        h.commit(&r);
+        let bytes = h.len_revision_bytes();
+        assert!(bytes > 0);
+        assert!(bytes < text.len() / 4);
+
+        h.insert(&mut r, 0, &text);
+        h.commit(&r);
+        assert!(h.len_revision_bytes() - bytes > text.len());
+    }
+}
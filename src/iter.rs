@@ -50,10 +50,10 @@
 use std::str;
 use std::sync::Arc;
 
-use crate::slice::RopeSlice;
+use crate::slice::{RSEnum, RopeSlice};
 use crate::str_utils::{
     byte_to_line_idx, char_to_byte_idx, count_chars, ends_with_line_break, line_to_byte_idx,
-    line_to_char_idx, prev_line_end_char_idx,
+    prev_line_end_char_idx,
 };
 use crate::tree::{Node, TextInfo};
 
@@ -400,11 +400,6 @@ impl<'a> ExactSizeIterator for Chars<'a> {}
 
 //==========================================================
 
-// TODO: the lines iterator is currently O(log N) per iteration, and generally
-// is fairly slow.  It should be possible to make this linear, or close to
-// linear, and much faster.  The implementation will likely be complex / subtle,
-// but it should be worth it.
-
 /// An iterator over a `Rope`'s lines.
 ///
 /// The returned lines include the line-break at the end.
@@ -418,8 +413,12 @@ pub struct Lines<'a>(LinesEnum<'a>);
 enum LinesEnum<'a> {
     Full {
         node: &'a Arc<Node>,
-        start_char: usize,
-        end_char: usize,
+        chunks: Chunks<'a>,        // Always positioned just after `cur_chunk`.
+        cur_chunk: &'a str,        // The chunk containing the iterator's position.
+        cur_chunk_byte_idx: usize, // The position of the iterator within `cur_chunk`.
+        pos_info: TextInfo,        // Text info at the iterator's position, relative to `node`.
+        start_info: TextInfo,      // Text info at the start of the data range.
+        end_info: TextInfo,        // Text info at the end of the data range.
         start_line: usize,
         total_line_breaks: usize,
         line_idx: usize,
@@ -435,39 +434,93 @@ enum LinesEnum<'a> {
 
 impl<'a> Lines<'a> {
     pub(crate) fn new(node: &Arc<Node>) -> Lines {
-        Lines(LinesEnum::Full {
-            node: node,
-            start_char: 0,
-            end_char: node.text_info().chars as usize,
-            start_line: 0,
-            total_line_breaks: node.line_break_count(),
-            line_idx: 0,
-        })
+        let info = node.text_info();
+        Lines::new_with_range_at(
+            node,
+            0,
+            (0, info.bytes as usize),
+            (0, info.chars as usize),
+            (0, info.line_breaks as usize + 1),
+        )
     }
 
     pub(crate) fn new_with_range(
         node: &Arc<Node>,
+        byte_idx_range: (usize, usize),
         char_idx_range: (usize, usize),
         line_break_idx_range: (usize, usize),
     ) -> Lines {
         Lines::new_with_range_at(
             node,
             line_break_idx_range.0,
+            byte_idx_range,
             char_idx_range,
             line_break_idx_range,
         )
     }
 
+    /// Creates a new `Lines` iterator from the given node, bounded by the
+    /// given ranges, and positioned such that `next()` yields line `at_line`.
+    ///
+    /// `at_line` is relative to the start of the node, not the range.
     pub(crate) fn new_with_range_at(
         node: &Arc<Node>,
         at_line: usize,
+        byte_idx_range: (usize, usize),
         char_idx_range: (usize, usize),
         line_break_idx_range: (usize, usize),
     ) -> Lines {
+        debug_assert!(at_line >= line_break_idx_range.0);
+        debug_assert!(at_line <= line_break_idx_range.1);
+
+        let start_info = if byte_idx_range.0 == 0 {
+            TextInfo::new()
+        } else {
+            node.byte_to_text_info(byte_idx_range.0)
+        };
+        let end_info = if byte_idx_range.1 == node.byte_count() {
+            node.text_info()
+        } else {
+            node.byte_to_text_info(byte_idx_range.1)
+        };
+
+        // Find the text info at the start of the line.
+        let pos_info = if at_line == line_break_idx_range.0 {
+            start_info
+        } else if at_line == line_break_idx_range.1 {
+            end_info
+        } else {
+            let (chunk, chunk_info) = node.get_chunk_at_line_break(at_line);
+            node.byte_to_text_info(
+                chunk_info.bytes as usize
+                    + line_to_byte_idx(chunk, at_line - chunk_info.line_breaks as usize),
+            )
+        };
+
+        // Set up the chunks iterator and the current chunk.
+        let (mut chunks, chunk_start_byte, _, _) = Chunks::new_with_range_at_byte(
+            node,
+            pos_info.bytes as usize,
+            byte_idx_range,
+            char_idx_range,
+            line_break_idx_range,
+        );
+        let (cur_chunk, cur_chunk_byte_idx) = if let Some(chunk) = chunks.next() {
+            (chunk, pos_info.bytes as usize - chunk_start_byte)
+        } else {
+            let chunk = chunks.prev().unwrap_or("");
+            chunks.next();
+            (chunk, chunk.len())
+        };
+
         Lines(LinesEnum::Full {
             node: node,
-            start_char: char_idx_range.0,
-            end_char: char_idx_range.1,
+            chunks: chunks,
+            cur_chunk: cur_chunk,
+            cur_chunk_byte_idx: cur_chunk_byte_idx,
+            pos_info: pos_info,
+            start_info: start_info,
+            end_info: end_info,
             start_line: line_break_idx_range.0,
             total_line_breaks: line_break_idx_range.1 - line_break_idx_range.0 - 1,
             line_idx: at_line,
@@ -494,44 +547,94 @@ impl<'a> Lines<'a> {
 
     /// Advances the iterator backwards and returns the previous value.
     ///
-    /// Runs in O(log N) time.
+    /// Runs in time proportional to the length of the returned line, and
+    /// worst-case O(log N) time.
     pub fn prev(&mut self) -> Option<RopeSlice<'a>> {
         match *self {
             Lines(LinesEnum::Full {
-                ref mut node,
-                start_char,
-                end_char,
+                node,
+                ref mut chunks,
+                ref mut cur_chunk,
+                ref mut cur_chunk_byte_idx,
+                ref mut pos_info,
+                start_info,
                 start_line,
+                total_line_breaks,
                 ref mut line_idx,
                 ..
             }) => {
                 if *line_idx == start_line {
                     return None;
+                }
+                *line_idx -= 1;
+
+                let end_info = *pos_info;
+                let end_chunk = *cur_chunk;
+                let end_byte_idx = *cur_chunk_byte_idx;
+
+                if *line_idx == start_line {
+                    // First line: it starts at the start of the range.
+                    while let Some(chunk) = prev_chunk(chunks) {
+                        *cur_chunk = chunk;
+                    }
+                    *cur_chunk_byte_idx = 0;
+                    *pos_info = start_info;
+                } else if *line_idx == start_line + total_line_breaks {
+                    // Last line: it isn't terminated by a line break, so
+                    // look up where it starts rather than searching for it.
+                    let (chunk, chunk_info) = node.get_chunk_at_line_break(*line_idx);
+                    *pos_info = node.byte_to_text_info(
+                        chunk_info.bytes as usize
+                            + line_to_byte_idx(chunk, *line_idx - chunk_info.line_breaks as usize),
+                    );
+                    let mut chunk_start_byte = end_info.bytes as usize - *cur_chunk_byte_idx;
+                    while chunk_start_byte > pos_info.bytes as usize {
+                        *cur_chunk = prev_chunk(chunks).unwrap();
+                        chunk_start_byte -= cur_chunk.len();
+                    }
+                    *cur_chunk_byte_idx = pos_info.bytes as usize - chunk_start_byte;
                 } else {
-                    *line_idx -= 1;
+                    // Search backwards for the end of the previous line,
+                    // skipping over the line break that ends this line.
+                    let mut skip_line_break = true;
+                    loop {
+                        let text = &cur_chunk[..*cur_chunk_byte_idx];
+                        if !text.is_empty() {
+                            let idx = if !skip_line_break && ends_with_line_break(text) {
+                                text.len()
+                            } else {
+                                prev_line_end_char_idx(text)
+                            };
+                            skip_line_break = false;
 
-                    let a = {
-                        // Find the char that corresponds to the start of the line.
-                        let (chunk, chunk_info) = node.get_chunk_at_line_break(*line_idx);
-                        (chunk_info.chars as usize
-                            + line_to_char_idx(chunk, *line_idx - chunk_info.line_breaks as usize))
-                        .max(start_char)
-                    };
+                            *pos_info -= TextInfo::from_str(&text[idx..]);
+                            *cur_chunk_byte_idx = idx;
+                            if idx > 0 {
+                                break;
+                            }
+                        }
 
-                    let b = if *line_idx < node.line_break_count() {
-                        // Find the char that corresponds to the end of the line.
-                        let (chunk, chunk_info) = node.get_chunk_at_line_break(*line_idx + 1);
-                        chunk_info.chars as usize
-                            + line_to_char_idx(
-                                chunk,
-                                *line_idx + 1 - chunk_info.line_breaks as usize,
-                            )
-                    } else {
-                        node.char_count()
+                        if let Some(chunk) = prev_chunk(chunks) {
+                            *cur_chunk = chunk;
+                            *cur_chunk_byte_idx = chunk.len();
+                        } else {
+                            break;
+                        }
                     }
-                    .min(end_char);
+                }
 
-                    return Some(RopeSlice::new_with_range(node, a, b));
+                if std::ptr::eq(*cur_chunk, end_chunk) {
+                    return Some(light_slice(
+                        &cur_chunk[*cur_chunk_byte_idx..end_byte_idx],
+                        *pos_info,
+                        end_info,
+                    ));
+                } else {
+                    return Some(RopeSlice(RSEnum::Full {
+                        node: node,
+                        start_info: *pos_info,
+                        end_info: end_info,
+                    }));
                 }
             }
             Lines(LinesEnum::Light {
@@ -554,6 +657,7 @@ impl<'a> Lines<'a> {
                 let start_idx = prev_line_end_char_idx(&text[..end_idx]);
                 *byte_idx = start_idx;
                 *line_idx -= 1;
+                *at_end = false;
 
                 return Some((&text[start_idx..end_idx]).into());
             }
@@ -566,50 +670,71 @@ impl<'a> Iterator for Lines<'a> {
 
     /// Advances the iterator forward and returns the next value.
     ///
-    /// Runs in O(log N) time.
+    /// Runs in time proportional to the length of the returned line, so
+    /// iterating over all lines is O(N).
     fn next(&mut self) -> Option<RopeSlice<'a>> {
         match *self {
             Lines(LinesEnum::Full {
-                ref mut node,
-                start_char,
-                end_char,
+                node,
+                ref mut chunks,
+                ref mut cur_chunk,
+                ref mut cur_chunk_byte_idx,
+                ref mut pos_info,
+                end_info,
+                start_line,
+                total_line_breaks,
                 ref mut line_idx,
                 ..
             }) => {
-                if *line_idx > node.line_break_count() {
+                if *line_idx > start_line + total_line_breaks {
                     return None;
+                }
+
+                let start_info = *pos_info;
+                let start_chunk = *cur_chunk;
+                let start_byte_idx = *cur_chunk_byte_idx;
+
+                if *line_idx == start_line + total_line_breaks {
+                    // Last line: it runs to the end of the range.
+                    for chunk in chunks.by_ref() {
+                        *cur_chunk = chunk;
+                    }
+                    *cur_chunk_byte_idx = cur_chunk.len();
+                    *pos_info = end_info;
                 } else {
-                    let a = {
-                        // Find the char that corresponds to the start of the line.
-                        let (chunk, chunk_info) = node.get_chunk_at_line_break(*line_idx);
-                        let a = (chunk_info.chars as usize
-                            + line_to_char_idx(chunk, *line_idx - chunk_info.line_breaks as usize))
-                        .max(start_char);
-
-                        // Early out if we're past the specified end char
-                        if a > end_char {
-                            return None;
+                    // Search forward for the end of the line.
+                    loop {
+                        let text = &cur_chunk[*cur_chunk_byte_idx..];
+                        let idx = line_to_byte_idx(text, 1);
+                        *pos_info += TextInfo::from_str(&text[..idx]);
+                        *cur_chunk_byte_idx += idx;
+                        if idx < text.len() || (idx > 0 && ends_with_line_break(text)) {
+                            break;
                         }
 
-                        a
-                    };
-
-                    let b = if *line_idx < node.line_break_count() {
-                        // Find the char that corresponds to the end of the line.
-                        let (chunk, chunk_info) = node.get_chunk_at_line_break(*line_idx + 1);
-                        chunk_info.chars as usize
-                            + line_to_char_idx(
-                                chunk,
-                                *line_idx + 1 - chunk_info.line_breaks as usize,
-                            )
-                    } else {
-                        node.char_count()
+                        if let Some(chunk) = chunks.next() {
+                            *cur_chunk = chunk;
+                            *cur_chunk_byte_idx = 0;
+                        } else {
+                            break;
+                        }
                     }
-                    .min(end_char);
+                }
 
-                    *line_idx += 1;
+                *line_idx += 1;
 
-                    return Some(RopeSlice::new_with_range(node, a, b));
+                if std::ptr::eq(*cur_chunk, start_chunk) {
+                    return Some(light_slice(
+                        &cur_chunk[start_byte_idx..*cur_chunk_byte_idx],
+                        start_info,
+                        *pos_info,
+                    ));
+                } else {
+                    return Some(RopeSlice(RSEnum::Full {
+                        node: node,
+                        start_info: start_info,
+                        end_info: *pos_info,
+                    }));
                 }
             }
             Lines(LinesEnum::Light {
@@ -662,6 +787,31 @@ impl<'a> Iterator for Lines<'a> {
 
 impl<'a> ExactSizeIterator for Lines<'a> {}
 
+/// Steps a `Chunks` iterator that is positioned just after its current
+/// chunk back by one chunk, and returns the new current chunk.
+///
+/// Returns `None` and leaves the iterator unchanged if there is no previous
+/// chunk.
+#[inline]
+fn prev_chunk<'a>(chunks: &mut Chunks<'a>) -> Option<&'a str> {
+    chunks.prev();
+    let chunk = chunks.prev();
+    chunks.next();
+    chunk
+}
+
+/// Creates a light slice of `text`, using the given text infos of its start
+/// and end to avoid re-counting its contents.
+#[inline]
+fn light_slice<'a>(text: &'a str, start_info: TextInfo, end_info: TextInfo) -> RopeSlice<'a> {
+    RopeSlice(RSEnum::Light {
+        text: text,
+        char_count: end_info.chars - start_info.chars,
+        utf16_surrogate_count: end_info.utf16_surrogates - start_info.utf16_surrogates,
+        line_break_count: end_info.line_breaks - start_info.line_breaks,
+    })
+}
+
 //==========================================================
 
 /// An iterator over a `Rope`'s contiguous `str` chunks.
@@ -1799,6 +1949,69 @@ mod tests {
         assert!(lines.is_empty());
     }
 
+    #[test]
+    fn lines_18() {
+        // Lines that span many chunks.
+        let mut text = String::new();
+        for i in 0..20 {
+            text.push_str(&"x".repeat(i * 7));
+            text.push_str(if i % 2 == 0 { "\n" } else { "\r\n" });
+        }
+        let r = Rope::from_str(&text);
+        let s = r.slice(3..(r.len_chars() - 3));
+
+        let lines: Vec<_> = s.lines().collect();
+        assert_eq!(lines.len(), s.len_lines());
+        for (i, line) in lines.iter().enumerate() {
+            assert_eq!(*line, s.line(i));
+        }
+
+        let mut itr = s.lines_at(s.len_lines());
+        for i in (0..s.len_lines()).rev() {
+            assert_eq!(itr.prev().unwrap(), s.line(i));
+        }
+        assert_eq!(None, itr.prev());
+    }
+
+    #[test]
+    fn lines_19() {
+        // Alternating between forward and backward iteration.
+        let r = Rope::from_str(TEXT);
+        let s = r.slice(34..301);
+        let mut itr = s.lines();
+
+        for i in 0..s.len_lines() {
+            assert_eq!(itr.next().unwrap(), s.line(i));
+            assert_eq!(itr.prev().unwrap(), s.line(i));
+            assert_eq!(itr.next().unwrap(), s.line(i));
+        }
+        assert_eq!(None, itr.next());
+        assert_eq!(itr.prev().unwrap(), s.line(s.len_lines() - 1));
+    }
+
+    #[test]
+    #[cfg(feature = "cr_lines")]
+    fn lines_20() {
+        // A lone CR in the middle of a line.
+        let r = Rope::from_str("ab\rc\nd");
+        let mut itr = r.lines_at(3);
+
+        assert_eq!("d", itr.prev().unwrap());
+        assert_eq!("c\n", itr.prev().unwrap());
+        assert_eq!("ab\r", itr.prev().unwrap());
+        assert_eq!(None, itr.prev());
+    }
+
+    #[test]
+    fn lines_21() {
+        let r = Rope::from_str("a");
+        let mut itr = r.lines_at(1);
+
+        assert_eq!("a", itr.prev().unwrap());
+        assert_eq!("a", itr.next().unwrap());
+        assert_eq!(None, itr.next());
+    }
+
     #[test]
     fn lines_at_01() {
         let r = Rope::from_str(TEXT);
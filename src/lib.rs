@@ -111,9 +111,9 @@
 //!
 //! # A Note About Line Endings
 //!
-//! Some of Ropey's APIs use the concept of line breaks or lines of text.  In
-//! all such APIs, Ropey treats the following unicode sequences as line
-//! breaks:
+//! Some of Ropey's APIs use the concept of line breaks or lines of text.  By
+//! default, in all such APIs Ropey treats the following unicode sequences as
+//! line breaks:
 //!
 //! - `U+000A`          &mdash; LF (Line Feed)
 //! - `U+000B`          &mdash; VT (Vertical Tab)
@@ -130,6 +130,19 @@
 //!
 //! CRLF pairs are always treated as a single line break, and are never split
 //! across chunks.  Note, however, that slicing can still split them.
+//!
+//! The set of recognized line breaks can be narrowed with cargo features, for
+//! applications that need to match the line numbering of other tools:
+//!
+//! - `unicode_lines` (enabled by default): all of the line breaks listed
+//!   above.  Implies `cr_lines`.
+//! - `cr_lines`: only LF, CR, and CRLF.
+//! - With neither feature enabled (`default-features = false`), only LF is
+//!   a line break.  A CRLF pair is then still a single line break, since
+//!   it ends in an LF.
+//!
+//! The choice affects everything that deals with lines, including
+//! `len_lines()`, `line()`, `char_to_line()`, and the `Lines` iterator.
 
 #![allow(clippy::collapsible_if)]
 #![allow(clippy::inline_always)]
@@ -137,17 +150,342 @@
 #![allow(clippy::redundant_field_names)]
 #![allow(clippy::type_complexity)]
 
+#[cfg(test)]
+extern crate rand;
+#[cfg(feature = "regex")]
+extern crate regex_automata;
+#[cfg(feature = "serde")]
+extern crate serde;
 extern crate smallvec;
+#[cfg(feature = "unicode-segmentation")]
+extern crate unicode_segmentation;
+#[cfg(feature = "unicode-width")]
+extern crate unicode_width;
 
+mod change_set;
+mod crdt;
 mod crlf;
+mod cursor;
+#[cfg(feature = "unicode-segmentation")]
+mod graphemes;
+mod history;
+mod marks;
+mod metric;
+mod reader;
+#[cfg(feature = "regex")]
+mod regex;
 mod rope;
 mod rope_builder;
+mod search;
+#[cfg(feature = "unicode-segmentation")]
+mod segmentation;
+#[cfg(feature = "serde")]
+mod serde_impls;
 mod slice;
+mod spans;
 mod tree;
+#[cfg(feature = "unicode-width")]
+mod visual;
+#[cfg(feature = "unicode-width")]
+mod wrap;
 
 pub mod iter;
 pub mod str_utils;
 
+pub use crate::change_set::{Assoc, ChangeSet, Operation};
+pub use crate::crdt::{CrdtOp, CrdtText, OpId};
+pub use crate::cursor::Cursor;
+#[cfg(feature = "unicode-segmentation")]
+pub use crate::graphemes::Graphemes;
+pub use crate::history::{History, HistoryMode};
+pub use crate::marks::{MarkId, Marks, MarksIter};
+pub use crate::metric::{Metric, MetricIndex};
+pub use crate::reader::RopeReader;
+#[cfg(feature = "regex")]
+pub use crate::regex::{FindRegexIter, Regex, RegexError, RegexMatch};
 pub use crate::rope::Rope;
 pub use crate::rope_builder::RopeBuilder;
+pub use crate::search::{FindIter, Matches};
+#[cfg(feature = "unicode-segmentation")]
+pub use crate::segmentation::{Sentences, Words};
 pub use crate::slice::RopeSlice;
+pub use crate::spans::{Spans, SpansIter};
+#[cfg(feature = "unicode-width")]
+pub use crate::visual::{ColumnRounding, VisualColumns};
+#[cfg(feature = "unicode-width")]
+pub use crate::wrap::WrapIndex;
+
+//==============================================================
+// Position type.
+
+/// A line/column position in a `Rope` or `RopeSlice`.
+///
+/// Both the line and column are zero-indexed.  The unit of `column` depends
+/// on the method used to create or consume the `Position`: for example
+/// [`Rope::char_to_position()`](struct.Rope.html#method.char_to_position)
+/// measures it in chars, while
+/// [`Rope::byte_to_position()`](struct.Rope.html#method.byte_to_position)
+/// measures it in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Creates a new `Position` from a line and column index.
+    #[inline]
+    pub fn new(line: usize, column: usize) -> Position {
+        Position { line, column }
+    }
+}
+
+//==============================================================
+// Error reporting types.
+
+/// Ropey's result type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Ropey's error type.
+///
+/// Returned by the non-panicking `try_*` variants of `Rope`'s and
+/// `RopeSlice`'s methods.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Indicates that the passed byte index was out of bounds.
+    ///
+    /// Contains the index attempted and the actual length of the
+    /// `Rope`/`RopeSlice` in bytes, in that order.
+    ByteIndexOutOfBounds(usize, usize),
+
+    /// Indicates that the passed char index was out of bounds.
+    ///
+    /// Contains the index attempted and the actual length of the
+    /// `Rope`/`RopeSlice` in chars, in that order.
+    CharIndexOutOfBounds(usize, usize),
+
+    /// Indicates that the passed line index was out of bounds.
+    ///
+    /// Contains the index attempted and the actual length of the
+    /// `Rope`/`RopeSlice` in lines, in that order.
+    LineIndexOutOfBounds(usize, usize),
+
+    /// Indicates that the passed utf16 code-unit index was out of
+    /// bounds.
+    ///
+    /// Contains the index attempted and the actual length of the
+    /// `Rope`/`RopeSlice` in utf16 code units, in that order.
+    Utf16IndexOutOfBounds(usize, usize),
+
+    /// Indicates that the column of a passed `Position` was past the end
+    /// of its line.
+    ///
+    /// Contains the column attempted and the actual length of the line
+    /// (including its line break, if any), in that order.  Both are in the
+    /// same units as the column.
+    ColumnIndexOutOfBounds(usize, usize),
+
+    /// Indicates that the passed byte index was not on a char boundary.
+    ///
+    /// Contains the index attempted.
+    ByteIndexNotCharBoundary(usize),
+
+    /// Indicates that the passed utf16 code-unit index was in the middle
+    /// of a surrogate pair.
+    ///
+    /// Contains the index attempted.
+    Utf16IndexNotCharBoundary(usize),
+
+    /// Indicates that a reversed byte-index range (end < start) was
+    /// encountered.
+    ///
+    /// Contains the [start, end) byte indices of the range, in that order.
+    ByteRangeInvalid(usize, usize),
+
+    /// Indicates that a reversed char-index range (end < start) was
+    /// encountered.
+    ///
+    /// Contains the [start, end) char indices of the range, in that order.
+    CharRangeInvalid(usize, usize),
+
+    /// Indicates that the passed char-index range was partially or fully
+    /// out of bounds.
+    ///
+    /// Contains the [start, end) char indices of the range and the actual
+    /// length of the `Rope`/`RopeSlice` in chars, in that order.  When
+    /// either the start or end are `None`, that indicates a half-open range.
+    CharRangeOutOfBounds(Option<usize>, Option<usize>, usize),
+
+    /// Indicates that the passed byte-index range was partially or fully
+    /// out of bounds.
+    ///
+    /// Contains the [start, end) byte indices of the range and the actual
+    /// length of the `Rope`/`RopeSlice` in bytes, in that order.  When
+    /// either the start or end are `None`, that indicates a half-open range.
+    ByteRangeOutOfBounds(Option<usize>, Option<usize>, usize),
+
+    /// Indicates that a reversed utf16 code-unit range (end < start) was
+    /// encountered.
+    ///
+    /// Contains the [start, end) utf16 code-unit indices of the range, in
+    /// that order.
+    Utf16RangeInvalid(usize, usize),
+
+    /// Indicates that the passed utf16 code-unit range was partially or
+    /// fully out of bounds.
+    ///
+    /// Contains the [start, end) utf16 code-unit indices of the range and
+    /// the actual length of the `Rope`/`RopeSlice` in utf16 code units, in
+    /// that order.  When either the start or end are `None`, that indicates
+    /// a half-open range.
+    Utf16RangeOutOfBounds(Option<usize>, Option<usize>, usize),
+
+    /// Indicates that the passed revision id doesn't exist in a `History`,
+    /// either because it was never created or because it was discarded.
+    ///
+    /// Contains the revision id attempted.
+    RevisionNotFound(usize),
+
+    /// Indicates that a `ChangeSet` was used with a document (or another
+    /// `ChangeSet`) of the wrong length.
+    ///
+    /// Contains the expected length and the actual length in chars, in that
+    /// order.
+    ChangeSetLengthMismatch(usize, usize),
+
+    /// Indicates that the bytes passed to `CrdtOp::decode()` weren't a
+    /// valid encoded operation.
+    ///
+    /// Contains the byte offset at which the problem was found.
+    InvalidOpEncoding(usize),
+
+    /// Indicates that the char ids of an insertion into a `CrdtText` would
+    /// be too large: past `u64::MAX` for a local insertion, or past half of
+    /// that for an operation from another replica, which leaves room for
+    /// the ids of local insertions.
+    ///
+    /// Contains the counter of the insertion's first char id.
+    OpIdOverflow(u64),
+
+    /// Indicates that the passed visual row index was out of bounds.
+    ///
+    /// Contains the index attempted and the actual number of visual rows,
+    /// in that order.
+    VisualRowOutOfBounds(usize, usize),
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Error::ByteIndexOutOfBounds(index, len) => write!(
+                f,
+                "Byte index out of bounds: byte index {}, Rope/RopeSlice byte length {}",
+                index, len
+            ),
+            Error::CharIndexOutOfBounds(index, len) => write!(
+                f,
+                "Char index out of bounds: char index {}, Rope/RopeSlice char length {}",
+                index, len
+            ),
+            Error::LineIndexOutOfBounds(index, len) => write!(
+                f,
+                "Line index out of bounds: line index {}, Rope/RopeSlice line count {}",
+                index, len
+            ),
+            Error::Utf16IndexOutOfBounds(index, len) => write!(
+                f,
+                "Utf16 code-unit index out of bounds: utf16 index {}, Rope/RopeSlice utf16 length {}",
+                index, len
+            ),
+            Error::ColumnIndexOutOfBounds(index, len) => write!(
+                f,
+                "Column index out of bounds: column index {}, line length {}",
+                index, len
+            ),
+            Error::ByteIndexNotCharBoundary(index) => write!(
+                f,
+                "Byte index is not a valid char boundary: byte index {}",
+                index
+            ),
+            Error::Utf16IndexNotCharBoundary(index) => write!(
+                f,
+                "Utf16 code-unit index is in the middle of a surrogate pair: utf16 index {}",
+                index
+            ),
+            Error::ByteRangeInvalid(start_idx, end_idx) => write!(
+                f,
+                "Invalid byte range {}..{}: start must be <= end",
+                start_idx, end_idx
+            ),
+            Error::CharRangeInvalid(start_idx, end_idx) => write!(
+                f,
+                "Invalid char range {}..{}: start must be <= end",
+                start_idx, end_idx
+            ),
+            Error::CharRangeOutOfBounds(start_idx_opt, end_idx_opt, len) => {
+                write!(f, "Char range out of bounds: char range ")?;
+                write_range(f, start_idx_opt, end_idx_opt)?;
+                write!(f, ", Rope/RopeSlice char length {}", len)
+            }
+            Error::ByteRangeOutOfBounds(start_idx_opt, end_idx_opt, len) => {
+                write!(f, "Byte range out of bounds: byte range ")?;
+                write_range(f, start_idx_opt, end_idx_opt)?;
+                write!(f, ", Rope/RopeSlice byte length {}", len)
+            }
+            Error::Utf16RangeInvalid(start_idx, end_idx) => write!(
+                f,
+                "Invalid utf16 code-unit range {}..{}: start must be <= end",
+                start_idx, end_idx
+            ),
+            Error::Utf16RangeOutOfBounds(start_idx_opt, end_idx_opt, len) => {
+                write!(f, "Utf16 code-unit range out of bounds: utf16 range ")?;
+                write_range(f, start_idx_opt, end_idx_opt)?;
+                write!(f, ", Rope/RopeSlice utf16 length {}", len)
+            }
+            Error::RevisionNotFound(revision) => {
+                write!(f, "Revision not found in history: revision {}", revision)
+            }
+            Error::ChangeSetLengthMismatch(expected, len) => write!(
+                f,
+                "ChangeSet length mismatch: expected char length {}, actual char length {}",
+                expected, len
+            ),
+            Error::InvalidOpEncoding(offset) => {
+                write!(f, "Invalid encoded CRDT operation: byte offset {}", offset)
+            }
+            Error::OpIdOverflow(counter) => write!(
+                f,
+                "CRDT char ids out of range: first id counter {}",
+                counter
+            ),
+            Error::VisualRowOutOfBounds(index, len) => write!(
+                f,
+                "Visual row index out of bounds: row index {}, visual row count {}",
+                index, len
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // Just re-use the debug impl.
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+fn write_range(
+    f: &mut std::fmt::Formatter,
+    start_idx: Option<usize>,
+    end_idx: Option<usize>,
+) -> std::fmt::Result {
+    match (start_idx, end_idx) {
+        (None, None) => write!(f, ".."),
+        (Some(start), None) => write!(f, "{}..", start),
+        (None, Some(end)) => write!(f, "..{}", end),
+        (Some(start), Some(end)) => write!(f, "{}..{}", start, end),
+    }
+}
@@ -0,0 +1,706 @@
+//! Positions in a `Rope` that stay attached to the text around them as it's
+//! edited.
+
+use std::cmp::Ordering;
+use std::ops::{Bound, RangeBounds};
+
+use crate::change_set::{Assoc, ChangeSet, Operation};
+use crate::rope::Rope;
+use crate::slice::{end_bound_to_num, start_bound_to_num};
+use crate::str_utils::count_chars;
+use crate::Result;
+
+/// The maximum number of marks in a block before it's split in two.
+const MAX_BLOCK_LEN: usize = 128;
+
+/// The block index of removed marks in `Marks::slots`.
+const REMOVED: u32 = u32::MAX;
+
+/// A handle to a mark in a [`Marks`](struct.Marks.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MarkId(u32);
+
+/// A set of marks: char positions in a `Rope` that move along with the text
+/// as it's edited, such as bookmarks, cursors, or the ends of diagnostic
+/// ranges.
+///
+/// Each mark has a gravity, given as an [`Assoc`](enum.Assoc.html), which
+/// decides what happens when text is inserted exactly at its position:
+/// marks with `Assoc::Before` (left gravity) stay before the inserted text,
+/// while marks with `Assoc::After` (right gravity) move to after it.  Marks
+/// inside removed text move to the start of the removal.
+///
+/// Edits can be made through `insert()` and `remove()`, which edit the
+/// `Rope` and update the marks together, or reported after the fact with
+/// `adjust_for_insert()`, `adjust_for_remove()`, and `apply_changes()`.
+///
+/// Marks are stored sorted by position in small blocks, each with its own
+/// base position, and each mark records which block it's in.  Finding the
+/// marks in a range is O(log M) in the number of marks, looking up a
+/// single mark scans its block of at most 128 marks, and an edit only
+/// touches the marks in the blocks around it, plus one base position per
+/// later block.  Each mark takes about 20 bytes.
+///
+/// # Example
+///
+/// ```
+/// # use ropey::{Assoc, Marks, Rope};
+/// let mut rope = Rope::from_str("Hello world!");
+/// let mut marks = Marks::new();
+/// let before = marks.add_mark(6, Assoc::Before);
+/// let after = marks.add_mark(6, Assoc::After);
+///
+/// marks.insert(&mut rope, 6, "big ");
+/// assert_eq!(marks.position(before), Some(6));
+/// assert_eq!(marks.position(after), Some(10));
+///
+/// marks.remove(&mut rope, 2..8);
+/// assert_eq!(marks.position(before), Some(2));
+/// assert_eq!(marks.position(after), Some(4));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Marks {
+    blocks: Vec<Block>,
+    slots: Vec<u32>, // The block index of each mark, indexed by `MarkId`.
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Block {
+    base: usize, // Always the position of the first entry.
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    offset: usize, // Relative to the block's base.
+    id: u32,
+    assoc: Assoc,
+}
+
+impl Marks {
+    /// Creates an empty set of marks.
+    #[inline]
+    pub fn new() -> Marks {
+        Marks::default()
+    }
+
+    /// Returns the number of marks.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether there are no marks.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds a mark at char index `char_idx`, with the given gravity.
+    ///
+    /// Runs in O(log M + B) time, where M is the number of marks and B is
+    /// the block size (at most 128).  When the mark fills up its block, the
+    /// block is split, which takes O(M) time.
+    pub fn add_mark(&mut self, char_idx: usize, assoc: Assoc) -> MarkId {
+        assert!(self.slots.len() < REMOVED as usize, "Too many marks");
+        let id = self.slots.len() as u32;
+        let key = (char_idx, assoc_rank(assoc));
+
+        if self.blocks.is_empty() {
+            self.blocks.push(Block {
+                base: char_idx,
+                entries: vec![Entry {
+                    offset: 0,
+                    id: id,
+                    assoc: assoc,
+                }],
+            });
+            self.slots.push(0);
+            self.len += 1;
+            return MarkId(id);
+        }
+
+        // The last block starting at or before `key`, and the position
+        // after any entries with an equal key.
+        let block_idx = count_le(&self.blocks, |b| b.key(0), key).saturating_sub(1);
+        let block = &mut self.blocks[block_idx];
+        let entry_idx = count_le(
+            &block.entries,
+            |e| (block.base + e.offset, assoc_rank(e.assoc)),
+            key,
+        );
+        if char_idx < block.base {
+            for e in block.entries.iter_mut() {
+                e.offset += block.base - char_idx;
+            }
+            block.base = char_idx;
+        }
+        block.entries.insert(
+            entry_idx,
+            Entry {
+                offset: char_idx - block.base,
+                id: id,
+                assoc: assoc,
+            },
+        );
+        self.slots.push(block_idx as u32);
+        self.len += 1;
+
+        if block.entries.len() > MAX_BLOCK_LEN {
+            let mut entries = block.entries.split_off(MAX_BLOCK_LEN / 2);
+            let base = block.base + entries[0].offset;
+            for e in entries.iter_mut() {
+                e.offset -= base - block.base;
+            }
+            self.blocks.insert(block_idx + 1, Block { base, entries });
+            self.reindex_from(block_idx + 1);
+        }
+
+        MarkId(id)
+    }
+
+    /// Removes a mark, returning its last position.
+    ///
+    /// Returns `None` if the mark was already removed.
+    pub fn remove_mark(&mut self, mark: MarkId) -> Option<usize> {
+        let block_idx = *self.slots.get(mark.0 as usize)?;
+        if block_idx == REMOVED {
+            return None;
+        }
+        let block_idx = block_idx as usize;
+        self.slots[mark.0 as usize] = REMOVED;
+        self.len -= 1;
+
+        let block = &mut self.blocks[block_idx];
+        let entry_idx = block.find(mark.0);
+        let pos = block.pos(entry_idx);
+        block.entries.remove(entry_idx);
+        if block.entries.is_empty() {
+            self.blocks.remove(block_idx);
+            self.reindex_from(block_idx);
+        } else if entry_idx == 0 {
+            let shift = block.entries[0].offset;
+            for e in block.entries.iter_mut() {
+                e.offset -= shift;
+            }
+            block.base += shift;
+        }
+
+        Some(pos)
+    }
+
+    /// Returns the current char index of a mark, or `None` if it was
+    /// removed.
+    ///
+    /// Runs in O(B) time, where B is the block size (at most 128): the
+    /// mark's block is found directly, but then scanned for the mark.
+    pub fn position(&self, mark: MarkId) -> Option<usize> {
+        match self.slots.get(mark.0 as usize) {
+            Some(&block_idx) if block_idx != REMOVED => {
+                let block = &self.blocks[block_idx as usize];
+                Some(block.pos(block.find(mark.0)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the gravity of a mark, or `None` if it was removed.
+    ///
+    /// Runs in O(B) time, like [`position()`](#method.position).
+    pub fn assoc(&self, mark: MarkId) -> Option<Assoc> {
+        match self.slots.get(mark.0 as usize) {
+            Some(&block_idx) if block_idx != REMOVED => {
+                let block = &self.blocks[block_idx as usize];
+                Some(block.entries[block.find(mark.0)].assoc)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the marks whose positions are within the
+    /// given char range, in order of position.
+    ///
+    /// Yields `(MarkId, char_idx)` pairs.  Runs in O(log M) time, plus the
+    /// time to iterate.
+    pub fn marks_in<R>(&self, char_range: R) -> MarksIter<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = start_bound_to_num(char_range.start_bound()).unwrap_or(0);
+        let end = match char_range.end_bound() {
+            Bound::Included(&i) => Bound::Included(i),
+            Bound::Excluded(&i) => Bound::Excluded(i),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        // The first block whose last mark is at or after `start`.
+        let block_idx = count_le(&self.blocks, |b| b.key(b.entries.len() - 1).0 + 1, start);
+        let entry_idx = match self.blocks.get(block_idx) {
+            Some(block) => count_le(&block.entries, |e| block.base + e.offset + 1, start),
+            None => 0,
+        };
+
+        MarksIter {
+            marks: self,
+            block_idx: block_idx,
+            entry_idx: entry_idx,
+            end: end,
+        }
+    }
+
+    //-----------------------------------------------------------------------
+    // Editing
+
+    /// Inserts `text` into `rope` at char index `char_idx`, and updates the
+    /// marks accordingly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn insert(&mut self, rope: &mut Rope, char_idx: usize, text: &str) {
+        self.try_insert(rope, char_idx, text).unwrap()
+    }
+
+    /// Removes the text in the given char index range from `rope`, and
+    /// updates the marks accordingly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if the
+    /// end is out of bounds (i.e. `end > len_chars()`).
+    #[inline]
+    pub fn remove<R>(&mut self, rope: &mut Rope, char_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_remove(rope, char_range).unwrap()
+    }
+
+    /// Updates the marks for `len` chars having been inserted at char
+    /// index `char_idx`.
+    pub fn adjust_for_insert(&mut self, char_idx: usize, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let key = (char_idx, assoc_rank(Assoc::Before));
+        for block in self.blocks.iter_mut() {
+            if block.key(0) > key {
+                block.base += len;
+            } else if block.key(block.entries.len() - 1) > key {
+                block.map_positions(|pos, assoc| {
+                    if (pos, assoc_rank(assoc)) > key {
+                        pos + len
+                    } else {
+                        pos
+                    }
+                });
+            }
+        }
+    }
+
+    /// Updates the marks for the text in the given char index range having
+    /// been removed.
+    pub fn adjust_for_remove<R>(&mut self, char_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = start_bound_to_num(char_range.start_bound()).unwrap_or(0);
+        let end = end_bound_to_num(char_range.end_bound()).unwrap_or(usize::MAX);
+        if start >= end {
+            return;
+        }
+        let len = end - start;
+        for block in self.blocks.iter_mut() {
+            if block.base >= end {
+                block.base -= len;
+            } else if block.key(block.entries.len() - 1).0 > start {
+                block.map_positions(|pos, _| {
+                    if pos <= start {
+                        pos
+                    } else if pos < end {
+                        start
+                    } else {
+                        pos - len
+                    }
+                });
+            }
+        }
+
+        self.fix_ties(start);
+    }
+
+    /// Updates the marks for `changes` having been applied.
+    ///
+    /// Marks end up at the same positions as
+    /// [`ChangeSet::map_pos()`](struct.ChangeSet.html#method.map_pos) maps
+    /// them to, using their gravity.
+    pub fn apply_changes(&mut self, changes: &ChangeSet) {
+        let mut pos = 0;
+        for op in changes.ops() {
+            match *op {
+                Operation::Retain(n) => pos += n,
+                Operation::Delete(n) => self.adjust_for_remove(pos..(pos + n)),
+                Operation::Insert(ref text) => {
+                    let len = count_chars(text);
+                    self.adjust_for_insert(pos, len);
+                    pos += len;
+                }
+            }
+        }
+    }
+
+    //-----------------------------------------------------------------------
+    // Fallible versions of the above methods
+
+    /// Non-panicking version of [`insert()`](#method.insert).
+    pub fn try_insert(&mut self, rope: &mut Rope, char_idx: usize, text: &str) -> Result<()> {
+        rope.try_insert(char_idx, text)?;
+        self.adjust_for_insert(char_idx, count_chars(text));
+        Ok(())
+    }
+
+    /// Non-panicking version of [`remove()`](#method.remove).
+    pub fn try_remove<R>(&mut self, rope: &mut Rope, char_range: R) -> Result<()>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = rope.check_char_range(char_range)?;
+        rope.remove(start..end);
+        self.adjust_for_remove(start..end);
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------
+    // Internal utilities
+
+    /// Updates the block indices of the marks in `blocks[block_idx..]`.
+    fn reindex_from(&mut self, block_idx: usize) {
+        for (i, block) in self.blocks.iter().enumerate().skip(block_idx) {
+            for e in block.entries.iter() {
+                self.slots[e.id as usize] = i as u32;
+            }
+        }
+    }
+
+    /// Restores the ordering of marks at `char_idx`, where marks with
+    /// `Assoc::Before` must come before those with `Assoc::After`, after a
+    /// removal collapsed marks from different positions onto it.
+    fn fix_ties(&mut self, char_idx: usize) {
+        let mut locations = Vec::new();
+        let block_idx = count_le(&self.blocks, |b| b.key(b.entries.len() - 1).0 + 1, char_idx);
+        'outer: for (bi, block) in self.blocks.iter().enumerate().skip(block_idx) {
+            for (ei, _) in block.entries.iter().enumerate() {
+                match block.pos(ei).cmp(&char_idx) {
+                    Ordering::Less => {}
+                    Ordering::Equal => locations.push((bi, ei)),
+                    Ordering::Greater => break 'outer,
+                }
+            }
+        }
+
+        let mut entries: Vec<Entry> = locations
+            .iter()
+            .map(|&(bi, ei)| self.blocks[bi].entries[ei])
+            .collect();
+        if entries
+            .windows(2)
+            .all(|w| assoc_rank(w[0].assoc) <= assoc_rank(w[1].assoc))
+        {
+            return;
+        }
+        entries.sort_by_key(|e| assoc_rank(e.assoc)); // Stable.
+        for (&(bi, ei), e) in locations.iter().zip(entries.iter()) {
+            let entry = &mut self.blocks[bi].entries[ei];
+            entry.id = e.id;
+            entry.assoc = e.assoc;
+            self.slots[e.id as usize] = bi as u32;
+        }
+    }
+}
+
+impl Block {
+    #[inline]
+    fn pos(&self, entry_idx: usize) -> usize {
+        self.base + self.entries[entry_idx].offset
+    }
+
+    #[inline]
+    fn key(&self, entry_idx: usize) -> (usize, u8) {
+        (
+            self.pos(entry_idx),
+            assoc_rank(self.entries[entry_idx].assoc),
+        )
+    }
+
+    #[inline]
+    fn find(&self, id: u32) -> usize {
+        self.entries.iter().position(|e| e.id == id).unwrap()
+    }
+
+    /// Moves every mark in the block with `f`, which must preserve their
+    /// order.
+    fn map_positions<F: Fn(usize, Assoc) -> usize>(&mut self, f: F) {
+        for e in self.entries.iter_mut() {
+            e.offset = f(self.base + e.offset, e.assoc);
+        }
+        self.base = self.entries[0].offset;
+        for e in self.entries.iter_mut() {
+            e.offset -= self.base;
+        }
+    }
+}
+
+#[inline]
+fn assoc_rank(assoc: Assoc) -> u8 {
+    match assoc {
+        Assoc::Before => 0,
+        Assoc::After => 1,
+    }
+}
+
+/// Returns the number of items at the start of the sorted `items` whose key
+/// is less than or equal to `key`.
+fn count_le<T, K: Ord, F: Fn(&T) -> K>(items: &[T], f: F, key: K) -> usize {
+    match items.binary_search_by(|item| {
+        if f(item) <= key {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }) {
+        Ok(i) | Err(i) => i,
+    }
+}
+
+//==============================================================
+
+/// An iterator over the marks in a range of a
+/// [`Marks`](struct.Marks.html).
+///
+/// Created with [`Marks::marks_in()`](struct.Marks.html#method.marks_in).
+#[derive(Debug, Clone)]
+pub struct MarksIter<'a> {
+    marks: &'a Marks,
+    block_idx: usize,
+    entry_idx: usize,
+    end: Bound<usize>,
+}
+
+impl<'a> Iterator for MarksIter<'a> {
+    type Item = (MarkId, usize);
+
+    fn next(&mut self) -> Option<(MarkId, usize)> {
+        let block = self.marks.blocks.get(self.block_idx)?;
+        let pos = block.pos(self.entry_idx);
+        let in_range = match self.end {
+            Bound::Included(end) => pos <= end,
+            Bound::Excluded(end) => pos < end,
+            Bound::Unbounded => true,
+        };
+        if !in_range {
+            self.block_idx = self.marks.blocks.len();
+            return None;
+        }
+
+        let id = block.entries[self.entry_idx].id;
+        self.entry_idx += 1;
+        if self.entry_idx == block.entries.len() {
+            self.block_idx += 1;
+            self.entry_idx = 0;
+        }
+        Some((MarkId(id), pos))
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::{Assoc, ChangeSet, Marks, Rope};
+
+    const TEXT: &str = "Hello there!  How're you doing?\r\nIt's \
+                        a fine day, isn't it?\r\nAren't you glad \
+                        we're alive?\r\nこんにちは、みんなさん！";
+
+    #[test]
+    fn marks_01() {
+        let mut r = Rope::from_str(TEXT);
+        let mut m = Marks::new();
+        let a = m.add_mark(5, Assoc::Before);
+        let b = m.add_mark(5, Assoc::After);
+        let c = m.add_mark(20, Assoc::Before);
+        let d = m.add_mark(0, Assoc::After);
+        assert_eq!(m.len(), 4);
+
+        m.insert(&mut r, 5, "abc");
+        assert_eq!(m.position(a), Some(5));
+        assert_eq!(m.position(b), Some(8));
+        assert_eq!(m.position(c), Some(23));
+        assert_eq!(m.position(d), Some(0));
+
+        m.insert(&mut r, 0, "x");
+        assert_eq!(m.position(d), Some(1));
+
+        m.remove(&mut r, 3..22);
+        assert_eq!(m.position(a), Some(3));
+        assert_eq!(m.position(b), Some(3));
+        assert_eq!(m.position(c), Some(5));
+        assert_eq!(m.position(d), Some(1));
+        assert_eq!(m.assoc(b), Some(Assoc::After));
+    }
+
+    #[test]
+    fn marks_02() {
+        // Marks collapsed by a removal keep their gravity.
+        let mut r = Rope::from_str(TEXT);
+        let mut m = Marks::new();
+        let a = m.add_mark(3, Assoc::After);
+        let b = m.add_mark(5, Assoc::Before);
+
+        m.remove(&mut r, 2..6);
+        m.insert(&mut r, 2, "xy");
+        assert_eq!(m.position(a), Some(4));
+        assert_eq!(m.position(b), Some(2));
+
+        let found: Vec<_> = m.marks_in(..).collect();
+        assert_eq!(found, vec![(b, 2), (a, 4)]);
+    }
+
+    #[test]
+    fn marks_03() {
+        let mut m = Marks::new();
+        let ids: Vec<_> = (0..1000)
+            .map(|i| m.add_mark(i * 2, Assoc::Before))
+            .collect();
+
+        assert_eq!(
+            m.marks_in(10..20).map(|(_, pos)| pos).collect::<Vec<_>>(),
+            vec![10, 12, 14, 16, 18]
+        );
+        assert_eq!(
+            m.marks_in(11..=20).map(|(_, pos)| pos).collect::<Vec<_>>(),
+            vec![12, 14, 16, 18, 20]
+        );
+        assert_eq!(
+            m.marks_in(1995..).map(|(_, pos)| pos).collect::<Vec<_>>(),
+            vec![1996, 1998]
+        );
+        assert_eq!(m.marks_in(2000..).count(), 0);
+        assert_eq!(m.marks_in(..).count(), 1000);
+
+        for &id in ids.iter().step_by(2) {
+            assert!(m.remove_mark(id).is_some());
+            assert!(m.remove_mark(id).is_none());
+        }
+        assert_eq!(m.len(), 500);
+        assert_eq!(m.position(ids[0]), None);
+        assert_eq!(m.position(ids[1]), Some(2));
+        assert_eq!(
+            m.marks_in(..8).map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![ids[1], ids[3]]
+        );
+    }
+
+    #[test]
+    fn marks_04() {
+        // Marks follow the same rules as `ChangeSet::map_pos()`.
+        let mut m = Marks::new();
+        let mut expected = Vec::new();
+        for i in 0..200 {
+            let assoc = if i % 3 == 0 {
+                Assoc::After
+            } else {
+                Assoc::Before
+            };
+            expected.push((m.add_mark(i / 2, assoc), i / 2, assoc));
+        }
+
+        let cs = ChangeSet::from_changes(
+            100,
+            vec![
+                (0..0, Some("ab")),
+                (10..20, Some("xyz")),
+                (30..30, Some("q")),
+                (50..90, None),
+            ],
+        );
+        m.apply_changes(&cs);
+
+        for &(id, pos, assoc) in expected.iter() {
+            assert_eq!(m.position(id), Some(cs.map_pos(pos, assoc)));
+        }
+    }
+
+    #[test]
+    fn marks_05() {
+        // Lots of random edits and marks, checked against a simple model.
+        let mut rng = StdRng::seed_from_u64(14);
+        let mut r = Rope::from_str(TEXT);
+        let mut m = Marks::new();
+        let mut model: Vec<(crate::MarkId, usize, Assoc)> = Vec::new();
+
+        for _ in 0..2000 {
+            let len = r.len_chars();
+            match rng.gen_range(0, 5) {
+                0 | 1 => {
+                    let pos = rng.gen_range(0, len + 1);
+                    let assoc = if rng.gen_range(0, 2) == 0 {
+                        Assoc::Before
+                    } else {
+                        Assoc::After
+                    };
+                    model.push((m.add_mark(pos, assoc), pos, assoc));
+                }
+                2 => {
+                    let pos = rng.gen_range(0, len + 1);
+                    let text = ["a", "bc\r\n", "こんにちは"][rng.gen_range(0, 3)];
+                    m.insert(&mut r, pos, text);
+                    let n = text.chars().count();
+                    for &mut (_, ref mut p, assoc) in model.iter_mut() {
+                        if *p > pos || (*p == pos && assoc == Assoc::After) {
+                            *p += n;
+                        }
+                    }
+                }
+                3 => {
+                    let start = rng.gen_range(0, len + 1);
+                    let end = rng.gen_range(start, start + (len - start).min(10) + 1);
+                    m.remove(&mut r, start..end);
+                    for &mut (_, ref mut p, _) in model.iter_mut() {
+                        if *p > start {
+                            *p = if *p < end { start } else { *p - (end - start) };
+                        }
+                    }
+                }
+                _ => {
+                    if !model.is_empty() {
+                        let (id, pos, _) = model.swap_remove(rng.gen_range(0, model.len()));
+                        assert_eq!(m.remove_mark(id), Some(pos));
+                    }
+                }
+            }
+        }
+
+        assert_eq!(m.len(), model.len());
+        for &(id, pos, _) in model.iter() {
+            assert_eq!(m.position(id), Some(pos));
+        }
+
+        // Iteration is sorted by position, with left gravity first.
+        let all: Vec<_> = m.marks_in(..).collect();
+        assert_eq!(all.len(), model.len());
+        for w in all.windows(2) {
+            assert!(w[0].1 <= w[1].1);
+            if w[0].1 == w[1].1 {
+                assert!(
+                    m.assoc(w[0].0) == Some(Assoc::Before) || m.assoc(w[1].0) == Some(Assoc::After)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,689 @@
+//! User-defined summaries of a `Rope`'s text, cached in its tree nodes.
+
+use std::fmt;
+use std::ops::RangeBounds;
+
+use crate::rope::Rope;
+use crate::str_utils::{char_to_byte_idx, ends_with_line_break};
+use crate::tree::Node;
+use crate::{Error, Result};
+
+/// A user-defined summary of text, such as the number of occurrences of a
+/// particular byte, or the length of the longest line.
+///
+/// The summaries must form a monoid that `measure()` maps text into: for
+/// any two strings `a` and `b`, `combine(measure(a), measure(b))` must equal
+/// `measure(a + b)`, `combine()` must be associative, and `measure("")`
+/// (which must equal `Summary::default()`) must be its identity.
+/// Summaries that need to know about text on both sides of a boundary,
+/// like line lengths, can do so by tracking what's at their edges (e.g. the
+/// length of the first and last partial lines).
+///
+/// The methods take `&self` so that a metric can be parameterized, e.g. by
+/// a tab width.  A given metric value must always measure the same text the
+/// same way.
+///
+/// Summaries are cached in the rope's tree nodes, which can be shared
+/// between threads, and are told apart by the type and value of the metric
+/// that computed them.  Hence the `Clone + PartialEq + Send + Sync +
+/// 'static` bounds.
+pub trait Metric: Clone + PartialEq + Send + Sync + 'static {
+    /// The summary type.
+    type Summary: Copy + Default + Send + Sync + 'static;
+
+    /// Computes the summary of a piece of text.
+    fn measure(&self, text: &str) -> Self::Summary;
+
+    /// Combines the summaries of two adjacent pieces of text, `left` coming
+    /// before `right`.
+    fn combine(&self, left: Self::Summary, right: Self::Summary) -> Self::Summary;
+}
+
+/// Queries of a [`Metric`](trait.Metric.html)'s summaries of a `Rope`'s
+/// text, in O(log N) time.
+///
+/// The summaries are cached in the rope's tree nodes themselves, so they
+/// are shared with the rope's clones and freed along with the nodes.  An
+/// edit drops the cached summaries of the nodes it changes, which are the
+/// O(log N) nodes on the path from the root to the edited leaves, and the
+/// next query re-measures only those.  The first query of a rope measures
+/// all of its text, in O(N) time.
+///
+/// Summaries are told apart by the type and value of the metric, so
+/// `MetricIndex`es with equal metrics share them.  Each node caches the
+/// summaries of up to eight metrics, evicting the oldest one to make room
+/// for more.
+///
+/// # Example
+///
+/// ```
+/// # use ropey::{Metric, MetricIndex, Rope};
+/// // Counts tab characters.
+/// #[derive(Clone, Default, PartialEq)]
+/// struct Tabs;
+/// impl Metric for Tabs {
+///     type Summary = usize;
+///     fn measure(&self, text: &str) -> usize {
+///         text.bytes().filter(|&b| b == b'\t').count()
+///     }
+///     fn combine(&self, left: usize, right: usize) -> usize {
+///         left + right
+///     }
+/// }
+///
+/// let mut rope = Rope::from_str("a\tb\tc\t");
+/// let index = MetricIndex::<Tabs>::new();
+/// assert_eq!(index.summary(&rope), 3);
+///
+/// rope.insert(0, "\t\t");
+/// assert_eq!(index.summary(&rope), 5);
+/// assert_eq!(index.summary_to_char(&rope, 4), 3);
+///
+/// // The char index after the 4th tab.
+/// assert_eq!(index.search(&rope, |tabs| tabs >= 4), Some(6));
+/// ```
+#[derive(Clone)]
+pub struct MetricIndex<M: Metric> {
+    metric: M,
+}
+
+/// A summary cached in a tree node, along with the metric that computed
+/// it.
+struct CachedSummary<M: Metric> {
+    metric: M,
+    summary: M::Summary,
+}
+
+impl<M: Metric> MetricIndex<M> {
+    /// Creates an index for the default value of the metric.
+    #[inline]
+    pub fn new() -> MetricIndex<M>
+    where
+        M: Default,
+    {
+        MetricIndex::with_metric(M::default())
+    }
+
+    /// Creates an index for the given metric.
+    #[inline]
+    pub fn with_metric(metric: M) -> MetricIndex<M> {
+        MetricIndex { metric: metric }
+    }
+
+    /// Returns the metric the index summarizes text with.
+    #[inline]
+    pub fn metric(&self) -> &M {
+        &self.metric
+    }
+
+    /// Returns the summary of all of `rope`'s text.
+    ///
+    /// Runs in O(log N) time if the rope was only changed by a small edit
+    /// since its summary for this metric was last queried, and O(N) time
+    /// the first time.
+    #[inline]
+    pub fn summary(&self, rope: &Rope) -> M::Summary {
+        self.node_summary(&rope.root)
+    }
+
+    /// Returns the summary of `rope`'s text before char index `char_idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn summary_to_char(&self, rope: &Rope, char_idx: usize) -> M::Summary {
+        self.try_summary_to_char(rope, char_idx).unwrap()
+    }
+
+    /// Returns the summary of `rope`'s text in the given char range.
+    ///
+    /// Runs in O(log N) time, plus the length of two chunks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if the
+    /// end is out of bounds (i.e. `end > len_chars()`).
+    #[inline]
+    pub fn summary_in<R>(&self, rope: &Rope, char_range: R) -> M::Summary
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_summary_in(rope, char_range).unwrap()
+    }
+
+    /// Returns the smallest char index such that `pred` is true for the
+    /// summary of `rope`'s text before it, or `None` if there is none.
+    ///
+    /// `pred` must be monotonic: once it returns true for the summary of
+    /// some text, it must also return true for the summary of any longer
+    /// text starting the same way.
+    ///
+    /// Runs in O(log N) time, plus the length of one chunk.
+    pub fn search<F>(&self, rope: &Rope, mut pred: F) -> Option<usize>
+    where
+        F: FnMut(M::Summary) -> bool,
+    {
+        let mut acc = M::Summary::default();
+        if pred(acc) {
+            return Some(0);
+        }
+        if !pred(self.summary(rope)) {
+            return None;
+        }
+
+        let mut node = &rope.root;
+        let mut char_idx = 0;
+        loop {
+            match **node {
+                Node::Internal(ref children) => {
+                    let mut next = None;
+                    for (info, child) in children.iter() {
+                        let summary = self.metric.combine(acc, self.node_summary(child));
+                        if pred(summary) {
+                            next = Some(child);
+                            break;
+                        }
+                        acc = summary;
+                        char_idx += info.chars as usize;
+                    }
+                    // The whole node's summary satisfies `pred`, so one of
+                    // its children must too.
+                    node = next.unwrap();
+                }
+                Node::Leaf(ref text) => {
+                    for (i, c) in text.char_indices() {
+                        acc = self
+                            .metric
+                            .combine(acc, self.metric.measure(&text[i..(i + c.len_utf8())]));
+                        char_idx += 1;
+                        if pred(acc) {
+                            return Some(char_idx);
+                        }
+                    }
+                    unreachable!()
+                }
+            }
+        }
+    }
+
+    //-----------------------------------------------------------------------
+    // Fallible versions of the above methods
+
+    /// Non-panicking version of [`summary_to_char()`](#method.summary_to_char).
+    pub fn try_summary_to_char(&self, rope: &Rope, char_idx: usize) -> Result<M::Summary> {
+        // Bounds check
+        if char_idx > rope.len_chars() {
+            return Err(Error::CharIndexOutOfBounds(char_idx, rope.len_chars()));
+        }
+
+        let mut acc = M::Summary::default();
+        let mut node = &rope.root;
+        let mut char_idx = char_idx;
+        loop {
+            match **node {
+                Node::Internal(ref children) => {
+                    let mut next = None;
+                    for (info, child) in children.iter() {
+                        if char_idx < info.chars as usize {
+                            next = Some(child);
+                            break;
+                        }
+                        acc = self.metric.combine(acc, self.node_summary(child));
+                        char_idx -= info.chars as usize;
+                    }
+                    match next {
+                        Some(child) => node = child,
+                        None => break,
+                    }
+                }
+                Node::Leaf(ref text) => {
+                    let byte_idx = char_to_byte_idx(text, char_idx);
+                    acc = self
+                        .metric
+                        .combine(acc, self.metric.measure(&text[..byte_idx]));
+                    break;
+                }
+            }
+        }
+
+        Ok(acc)
+    }
+
+    /// Non-panicking version of [`summary_in()`](#method.summary_in).
+    pub fn try_summary_in<R>(&self, rope: &Rope, char_range: R) -> Result<M::Summary>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = rope.check_char_range(char_range)?;
+        Ok(self.range_summary(&rope.root, start, end))
+    }
+
+    //-----------------------------------------------------------------------
+    // Internal utilities
+
+    /// Returns the summary of the text in the char range `start..end` of
+    /// `node`, only descending into the children partially in the range.
+    pub(crate) fn range_summary(&self, node: &Node, start: usize, end: usize) -> M::Summary {
+        match *node {
+            Node::Leaf(ref text) => {
+                let byte_start = char_to_byte_idx(text, start);
+                let byte_end = byte_start + char_to_byte_idx(&text[byte_start..], end - start);
+                self.metric.measure(&text[byte_start..byte_end])
+            }
+            Node::Internal(ref children) => {
+                let mut acc = M::Summary::default();
+                let mut child_start = 0;
+                for (info, child) in children.iter() {
+                    let child_end = child_start + info.chars as usize;
+                    if child_start >= end {
+                        break;
+                    }
+                    if child_end > start {
+                        let summary = if start <= child_start && child_end <= end {
+                            self.node_summary(child)
+                        } else {
+                            self.range_summary(
+                                child,
+                                start.max(child_start) - child_start,
+                                end.min(child_end) - child_start,
+                            )
+                        };
+                        acc = self.metric.combine(acc, summary);
+                    }
+                    child_start = child_end;
+                }
+                acc
+            }
+        }
+    }
+
+    /// Returns the summary of all of `node`'s text, from its cache if
+    /// possible, and caches it otherwise.
+    fn node_summary(&self, node: &Node) -> M::Summary {
+        let cached = node.summaries().get(|cached: &CachedSummary<M>| {
+            if cached.metric == self.metric {
+                Some(cached.summary)
+            } else {
+                None
+            }
+        });
+        if let Some(summary) = cached {
+            return summary;
+        }
+
+        let summary = match *node {
+            Node::Leaf(ref text) => self.metric.measure(text),
+            Node::Internal(ref children) => children
+                .nodes()
+                .iter()
+                .fold(M::Summary::default(), |acc, child| {
+                    self.metric.combine(acc, self.node_summary(child))
+                }),
+        };
+        node.summaries().insert(
+            CachedSummary {
+                metric: self.metric.clone(),
+                summary: summary,
+            },
+            |cached| cached.metric == self.metric,
+        );
+        summary
+    }
+}
+
+impl<M: Metric + Default> Default for MetricIndex<M> {
+    #[inline]
+    fn default() -> MetricIndex<M> {
+        MetricIndex::new()
+    }
+}
+
+impl<M: Metric + fmt::Debug> fmt::Debug for MetricIndex<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MetricIndex")
+            .field("metric", &self.metric)
+            .finish()
+    }
+}
+
+//==============================================================
+
+/// The [`Metric`](trait.Metric.html) behind `Rope::longest_line()` and
+/// `RopeSlice::longest_line()`, which keeps track of the longest line.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) struct LongestLine;
+
+/// The `LongestLine` summary of some text.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) struct LineLengths {
+    line_breaks: usize,
+    first: usize,                    // Up to the first line break.
+    last: usize,                     // After the last line break.
+    between: Option<(usize, usize)>, // Longest of the lines in between.
+    starts_with_lf: bool,
+    ends_with_cr: bool,
+    non_empty: bool,
+}
+
+impl LineLengths {
+    /// Returns the index and length in chars of the longest line in the
+    /// text, as `(line_idx, len_chars)`.
+    ///
+    /// The length doesn't include the line break.  If several lines are
+    /// equally long, the first of them is returned.
+    pub fn longest_line(&self) -> (usize, usize) {
+        let mut longest = (0, self.first);
+        if let Some(between) = self.between {
+            longest = longer(longest, between);
+        }
+        if self.line_breaks > 0 {
+            longest = longer(longest, (self.line_breaks, self.last));
+        }
+        longest
+    }
+}
+
+impl Metric for LongestLine {
+    type Summary = LineLengths;
+
+    fn measure(&self, text: &str) -> LineLengths {
+        let mut summary = LineLengths {
+            starts_with_lf: text.starts_with('\n'),
+            ends_with_cr: cfg!(feature = "cr_lines") && text.ends_with('\r'),
+            non_empty: !text.is_empty(),
+            ..LineLengths::default()
+        };
+        let mut len = 0;
+        let mut prev_was_cr = false;
+        for c in text.chars() {
+            let mut buf = [0u8; 4];
+            if ends_with_line_break(c.encode_utf8(&mut buf)) {
+                // A CRLF pair is a single line break.
+                if !(prev_was_cr && c == '\n') {
+                    if summary.line_breaks == 0 {
+                        summary.first = len;
+                    } else {
+                        summary.between = longer_opt(summary.between, (summary.line_breaks, len));
+                    }
+                    summary.line_breaks += 1;
+                }
+                len = 0;
+            } else {
+                len += 1;
+            }
+            prev_was_cr = cfg!(feature = "cr_lines") && c == '\r';
+        }
+        if summary.line_breaks == 0 {
+            summary.first = len;
+        }
+        summary.last = len;
+        summary
+    }
+
+    fn combine(&self, left: LineLengths, right: LineLengths) -> LineLengths {
+        if !left.non_empty {
+            return right;
+        } else if !right.non_empty {
+            return left;
+        }
+
+        // A CR at the end of `left` and an LF at the start of `right` are a
+        // single line break, with no line between them.
+        let crlf = left.ends_with_cr && right.starts_with_lf;
+        let mut between = left.between;
+        if left.line_breaks > 0 && right.line_breaks > 0 && !crlf {
+            between = longer_opt(between, (left.line_breaks, left.last + right.first));
+        }
+        if let Some((line_idx, len)) = right.between {
+            let line_idx = line_idx + left.line_breaks - crlf as usize;
+            between = longer_opt(between, (line_idx, len));
+        }
+
+        LineLengths {
+            line_breaks: left.line_breaks + right.line_breaks - crlf as usize,
+            first: if left.line_breaks == 0 {
+                left.first + right.first
+            } else {
+                left.first
+            },
+            last: if right.line_breaks == 0 {
+                left.last + right.last
+            } else {
+                right.last
+            },
+            between: between,
+            starts_with_lf: left.starts_with_lf,
+            ends_with_cr: right.ends_with_cr,
+            non_empty: true,
+        }
+    }
+}
+
+/// Returns the longer of two `(line_idx, len)` pairs, preferring `a` on a
+/// tie.
+#[inline]
+fn longer(a: (usize, usize), b: (usize, usize)) -> (usize, usize) {
+    if b.1 > a.1 {
+        b
+    } else {
+        a
+    }
+}
+
+#[inline]
+fn longer_opt(a: Option<(usize, usize)>, b: (usize, usize)) -> Option<(usize, usize)> {
+    Some(a.map_or(b, |a| longer(a, b)))
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::LongestLine;
+    use crate::tree::MAX_SUMMARIES;
+    use crate::{Metric, MetricIndex, Rope};
+
+    const TEXT: &str = "Hello there!  How're you doing?\r\nIt's \
+                        a fine day, isn't it?\r\nAren't you glad \
+                        we're alive?\r\nこんにちは、みんなさん！";
+
+    #[derive(Clone, Default, PartialEq)]
+    struct Spaces;
+    impl Metric for Spaces {
+        type Summary = usize;
+        fn measure(&self, text: &str) -> usize {
+            text.bytes().filter(|&b| b == b' ').count()
+        }
+        fn combine(&self, left: usize, right: usize) -> usize {
+            left + right
+        }
+    }
+
+    /// Counts a given byte.
+    #[derive(Debug, Clone, PartialEq)]
+    struct Count(u8);
+    impl Metric for Count {
+        type Summary = usize;
+        fn measure(&self, text: &str) -> usize {
+            text.bytes().filter(|&b| b == self.0).count()
+        }
+        fn combine(&self, left: usize, right: usize) -> usize {
+            left + right
+        }
+    }
+
+    #[test]
+    fn metric_01() {
+        let mut r = Rope::new();
+        for _ in 0..20 {
+            r.insert(0, TEXT);
+        }
+        let index = MetricIndex::<Spaces>::new();
+        assert_eq!(index.summary(&r), Spaces.measure(&r.to_string()));
+
+        for i in 0..50 {
+            r.insert(i * 7, " x ");
+            r.remove((i * 13)..(i * 13 + 5));
+            let text = r.to_string();
+            assert_eq!(index.summary(&r), Spaces.measure(&text));
+
+            let char_idx = i * 31 % r.len_chars();
+            let byte_idx = r.char_to_byte(char_idx);
+            assert_eq!(
+                index.summary_to_char(&r, char_idx),
+                Spaces.measure(&text[..byte_idx])
+            );
+        }
+        assert!(index.try_summary_to_char(&r, r.len_chars() + 1).is_err());
+    }
+
+    #[test]
+    fn metric_02() {
+        let mut r = Rope::new();
+        for _ in 0..20 {
+            r.insert(0, TEXT);
+        }
+        let index = MetricIndex::<Spaces>::new();
+        let text = r.to_string();
+        let total = Spaces.measure(&text);
+
+        assert_eq!(index.search(&r, |_| true), Some(0));
+        assert_eq!(index.search(&r, |n| n > total), None);
+        for n in 1..=total {
+            let char_idx = index.search(&r, |s| s >= n).unwrap();
+            let byte_idx = r.char_to_byte(char_idx);
+            assert_eq!(Spaces.measure(&text[..byte_idx]), n);
+            assert_eq!(r.char(char_idx - 1), ' ');
+        }
+    }
+
+    #[test]
+    fn metric_03() {
+        let mut r = Rope::new();
+        let index = MetricIndex::<LongestLine>::new();
+        assert_eq!(index.summary(&r).longest_line(), (0, 0));
+
+        for i in 0..30 {
+            r.insert(r.len_chars() / 2, TEXT);
+            if i % 5 == 0 {
+                let pos = r.len_chars() / 3;
+                r.insert(pos, &"x".repeat(i * 10));
+            }
+            if i % 7 == 0 {
+                let len = r.len_chars();
+                r.remove((len / 4)..(len / 4 + 40));
+            }
+            assert_eq!(
+                index.summary(&r).longest_line(),
+                LongestLine.measure(&r.to_string()).longest_line()
+            );
+            r.assert_integrity();
+        }
+    }
+
+    #[test]
+    fn metric_04() {
+        // Queries don't hold on to the rope's nodes, so edits still happen
+        // in place, and only the edited nodes are re-measured.
+        let mut r = Rope::from_str(&TEXT.repeat(50));
+        let index = MetricIndex::<Spaces>::new();
+        index.summary(&r);
+        assert_eq!(Arc::strong_count(&r.root), 1);
+
+        for i in 0..1000 {
+            r.insert(i % 100, " ");
+            assert_eq!(Arc::strong_count(&r.root), 1);
+            assert_eq!(r.root.summaries().len(), 0);
+            index.summary(&r);
+            assert_eq!(r.root.summaries().len(), 1);
+        }
+        assert_eq!(index.summary(&r), Spaces.measure(&r.to_string()));
+        assert_eq!(
+            format!("{:?}", MetricIndex::with_metric(Count(b'x'))),
+            "MetricIndex { metric: Count(120) }"
+        );
+    }
+
+    #[test]
+    fn metric_05() {
+        // Combining the summaries of any split of the text gives the
+        // summary of the whole, including splits between CR and LF.
+        let text = "ab\r\ncde\rf\n\n\u{2028}ghij\r\n\r\nこんにちは\r";
+        let whole = LongestLine.measure(text);
+        for (i, _) in text.char_indices() {
+            let left = LongestLine.measure(&text[..i]);
+            let right = LongestLine.measure(&text[i..]);
+            assert_eq!(LongestLine.combine(left, right), whole);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn metric_06() {
+        let r = Rope::from_str(&TEXT.repeat(20));
+        let index = MetricIndex::<LongestLine>::new();
+        for &(start, end) in &[
+            (0, 0),
+            (34, 96),
+            (5, 300),
+            (100, 101),
+            (33, 1000),
+            (0, r.len_chars()),
+            (17, r.len_chars() - 3),
+        ] {
+            let text = r.slice(start..end).to_string();
+            assert_eq!(index.summary_in(&r, start..end), LongestLine.measure(&text));
+        }
+        assert!(index.try_summary_in(&r, 5..4).is_err());
+        assert!(index.try_summary_in(&r, ..(r.len_chars() + 1)).is_err());
+    }
+
+    #[test]
+    fn metric_07() {
+        // Indexes with equal metrics share their summaries, and nodes only
+        // keep so many of them.
+        let mut r = Rope::from_str(&TEXT.repeat(20));
+        let text = r.to_string();
+        for _ in 0..2 {
+            for b in 32..127 {
+                let index = MetricIndex::with_metric(Count(b));
+                assert_eq!(index.summary(&r), Count(b).measure(&text));
+                assert!(r.root.summaries().len() <= MAX_SUMMARIES);
+            }
+        }
+
+        let a = MetricIndex::with_metric(Count(b'e'));
+        let b = MetricIndex::with_metric(Count(b'e'));
+        r.insert(0, "e");
+        a.summary(&r);
+        let len = r.root.summaries().len();
+        assert_eq!(b.summary(&r), Count(b'e').measure(&text) + 1);
+        assert_eq!(r.root.summaries().len(), len);
+    }
+
+    #[test]
+    fn metric_08() {
+        // Clones of a rope can be queried from several threads at once.
+        let r = Rope::from_str(&TEXT.repeat(50));
+        let expected = Spaces.measure(&r.to_string());
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let mut r = r.clone();
+                std::thread::spawn(move || {
+                    let index = MetricIndex::<Spaces>::new();
+                    for j in 0..20 {
+                        assert_eq!(index.summary(&r), expected + j);
+                        r.insert((i * 101 + j * 37) % r.len_chars(), " ");
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(MetricIndex::<Spaces>::new().summary(&r), expected);
+    }
+}
@@ -0,0 +1,252 @@
+//! An `io::Read` adapter over a `Rope`'s text.
+
+use std::convert::TryFrom;
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+use crate::slice::RopeSlice;
+
+/// A reader over the utf8 bytes of a `Rope` or `RopeSlice`.
+///
+/// Implements `io::Read`, `io::BufRead`, and `io::Seek` (by byte offset),
+/// for handing text to APIs that expect a reader, such as compressors,
+/// hashers, or HTTP bodies.  `fill_buf()` returns the rest of the current
+/// chunk directly, so reading through `BufRead` doesn't copy the text at
+/// all.
+///
+/// The reader borrows the text, so to stream out a snapshot of a `Rope`
+/// while continuing to edit it, read from a clone of it: cloning a `Rope`
+/// is O(1).
+///
+/// Created with [`RopeSlice::reader()`](struct.RopeSlice.html#method.reader)
+/// or [`Rope::reader()`](struct.Rope.html#method.reader).
+///
+/// # Example
+///
+/// ```
+/// # use std::io::{BufRead, Read, Seek, SeekFrom};
+/// # use ropey::Rope;
+/// let rope = Rope::from_str("Hello world!\nHow are you?\n");
+/// let mut reader = rope.reader();
+///
+/// let mut line = String::new();
+/// reader.read_line(&mut line).unwrap();
+/// assert_eq!(line, "Hello world!\n");
+///
+/// reader.seek(SeekFrom::End(-4)).unwrap();
+/// let mut rest = String::new();
+/// reader.read_to_string(&mut rest).unwrap();
+/// assert_eq!(rest, "ou?\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RopeReader<'a> {
+    slice: RopeSlice<'a>,
+    chunk: &'a [u8],
+    chunk_start: usize, // Byte index of the start of `chunk`.
+    byte_idx: usize,    // May be past the end, after seeking there.
+}
+
+impl<'a> RopeReader<'a> {
+    pub(crate) fn new(slice: RopeSlice<'a>) -> RopeReader<'a> {
+        RopeReader {
+            slice: slice,
+            chunk: &[],
+            chunk_start: 0,
+            byte_idx: 0,
+        }
+    }
+
+    /// Returns the byte index of the reader's position.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.byte_idx
+    }
+}
+
+impl<'a> Read for RopeReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut len = 0;
+        while len < buf.len() {
+            let n = {
+                let bytes = self.fill_buf()?;
+                let n = bytes.len().min(buf.len() - len);
+                buf[len..(len + n)].copy_from_slice(&bytes[..n]);
+                n
+            };
+            if n == 0 {
+                break;
+            }
+            self.consume(n);
+            len += n;
+        }
+        Ok(len)
+    }
+}
+
+impl<'a> BufRead for RopeReader<'a> {
+    /// Returns the rest of the current chunk.
+    ///
+    /// Runs in O(1) time, or O(log N) when moving to a new chunk.
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.byte_idx >= self.slice.len_bytes() {
+            return Ok(&[]);
+        }
+        if self.byte_idx < self.chunk_start || self.byte_idx >= self.chunk_start + self.chunk.len()
+        {
+            let (chunk, chunk_start, _, _) = self.slice.chunk_at_byte(self.byte_idx);
+            self.chunk = chunk.as_bytes();
+            self.chunk_start = chunk_start;
+        }
+        Ok(&self.chunk[(self.byte_idx - self.chunk_start)..])
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.byte_idx += amt;
+    }
+}
+
+impl<'a> Seek for RopeReader<'a> {
+    /// Seeks to a byte offset.
+    ///
+    /// Seeking past the end is allowed, and reads from there return no
+    /// bytes.  Seeking to before the start is an error of kind
+    /// `InvalidInput`.
+    ///
+    /// Runs in O(1) time.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let byte_idx = match pos {
+            SeekFrom::Start(n) => usize::try_from(n).ok(),
+            SeekFrom::End(n) => offset(self.slice.len_bytes(), n),
+            SeekFrom::Current(n) => offset(self.byte_idx, n),
+        };
+        match byte_idx {
+            Some(byte_idx) => {
+                self.byte_idx = byte_idx;
+                Ok(byte_idx as u64)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+/// Returns `byte_idx` offset by `n`, or `None` if that's negative or
+/// overflows.
+#[inline]
+fn offset(byte_idx: usize, n: i64) -> Option<usize> {
+    if n >= 0 {
+        byte_idx.checked_add(usize::try_from(n).ok()?)
+    } else {
+        byte_idx.checked_sub(usize::try_from(n.unsigned_abs()).ok()?)
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, Read, Seek, SeekFrom};
+
+    use crate::Rope;
+
+    const TEXT: &str = "Hello there!  How're you doing?  It's \
+                        a fine day, isn't it?  Aren't you glad \
+                        we're alive?  こんにちは、みんなさん！";
+
+    #[test]
+    fn read_01() {
+        let r = Rope::from_str(&TEXT.repeat(20));
+        for s in &[r.slice(..), r.slice(13..500), r.slice(7..7)] {
+            let text = s.to_string();
+            for &buf_len in &[1, 3, 7, 64, 10000] {
+                let mut reader = s.reader();
+                let mut buf = vec![0u8; buf_len];
+                let mut bytes = Vec::new();
+                loop {
+                    let n = reader.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    assert!(n == buf_len || reader.position() == text.len());
+                    bytes.extend_from_slice(&buf[..n]);
+                }
+                assert_eq!(bytes, text.as_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn fill_buf_01() {
+        // `fill_buf()` hands out the chunks themselves.
+        let r = Rope::from_str(&TEXT.repeat(20));
+        let s = r.slice(13..500);
+        let mut reader = s.reader();
+        for chunk in s.chunks() {
+            let buf = reader.fill_buf().unwrap();
+            assert_eq!(buf.as_ptr(), chunk.as_ptr());
+            assert_eq!(buf.len(), chunk.len());
+            let len = buf.len();
+            reader.consume(len);
+        }
+        assert!(reader.fill_buf().unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_line_01() {
+        let r = Rope::from_str(&"Hello\nworld\r\n!".repeat(50));
+        let lines: Vec<_> = r.reader().lines().map(|l| l.unwrap()).collect();
+        let expected: Vec<_> = r.to_string().lines().map(|l| l.to_string()).collect();
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn seek_01() {
+        let r = Rope::from_str(&TEXT.repeat(20));
+        let text = r.to_string();
+        let mut reader = r.reader();
+        let mut buf = [0u8; 10];
+
+        assert_eq!(reader.seek(SeekFrom::Start(1000)).unwrap(), 1000);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, &text.as_bytes()[1000..1010]);
+
+        assert_eq!(reader.seek(SeekFrom::Current(-200)).unwrap(), 810);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, &text.as_bytes()[810..820]);
+
+        assert_eq!(
+            reader.seek(SeekFrom::End(-10)).unwrap() as usize,
+            text.len() - 10
+        );
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, &text.as_bytes()[(text.len() - 10)..]);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn seek_02() {
+        let r = Rope::from_str(TEXT);
+        let mut reader = r.reader();
+        let mut buf = [0u8; 10];
+
+        // Past the end.
+        assert_eq!(
+            reader.seek(SeekFrom::End(5)).unwrap() as usize,
+            TEXT.len() + 5
+        );
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        assert!(reader.fill_buf().unwrap().is_empty());
+
+        // Before the start.
+        assert!(reader.seek(SeekFrom::Current(-1000)).is_err());
+        assert!(reader.seek(SeekFrom::End(-1000)).is_err());
+        assert_eq!(reader.position(), TEXT.len() + 5);
+        assert_eq!(reader.seek(SeekFrom::Start(0)).unwrap(), 0);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, &TEXT.as_bytes()[..10]);
+    }
+}
@@ -0,0 +1,433 @@
+//! Regex search over a `RopeSlice`, using `regex-automata`'s lazy DFA.
+//!
+//! The DFAs consume the text a byte at a time, so they're fed directly from
+//! the rope's chunks and the text never needs to be copied into a `String`.
+
+use std::fmt;
+use std::ops::Range;
+
+use regex_automata::hybrid::dfa::{Cache, DFA};
+use regex_automata::hybrid::{BuildError, LazyStateID};
+use regex_automata::nfa::thompson;
+use regex_automata::util::start;
+use regex_automata::{Anchored, MatchKind};
+
+use crate::slice::RopeSlice;
+
+/// A compiled regular expression for searching `Rope`s and `RopeSlice`s.
+///
+/// The syntax is that of the `regex` crate.  Unicode word boundaries (`\b`
+/// in Unicode mode) aren't supported, since the lazy DFA can't handle them
+/// on non-ASCII text; use `(?-u:\b)` for ASCII word boundaries instead.
+///
+/// The DFA states are built lazily as the text is searched, in a cache
+/// that is created for each search or iterator.
+///
+/// # Example
+///
+/// ```
+/// # use ropey::{Regex, Rope};
+/// let rope = Rope::from_str("Hello 世界, hello world!");
+/// let regex = Regex::new(r"(?i)hello \w+").unwrap();
+///
+/// let m = rope.find_regex(&regex).unwrap();
+/// assert_eq!(m.char_range(), 0..8);
+/// assert_eq!(m.byte_range(), 0..12);
+///
+/// let m = rope.rfind_regex(&regex).unwrap();
+/// assert_eq!(rope.slice(m.char_range()), "hello world");
+/// ```
+pub struct Regex {
+    forward: DFA,
+    reverse: DFA,
+}
+
+impl Regex {
+    /// Compiles a regular expression.
+    pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+        let forward = DFA::new(pattern)?;
+        // Used anchored to find the start of a match from its end, and
+        // unanchored to find the last match start for reverse search.
+        let reverse = DFA::builder()
+            .configure(DFA::config().match_kind(MatchKind::All))
+            .thompson(thompson::Config::new().reverse(true))
+            .build(pattern)?;
+        Ok(Regex {
+            forward: forward,
+            reverse: reverse,
+        })
+    }
+
+    fn create_caches(&self) -> Caches {
+        Caches {
+            forward: self.forward.create_cache(),
+            reverse: self.reverse.create_cache(),
+        }
+    }
+
+    /// Returns the byte range of the leftmost-first match that starts at
+    /// or after `start`.
+    fn find_at(
+        &self,
+        caches: &mut Caches,
+        slice: &RopeSlice,
+        start: usize,
+    ) -> Option<Range<usize>> {
+        let end = self.search_forward(&mut caches.forward, slice, start, Anchored::No)?;
+        let start = self
+            .search_reverse(&mut caches.reverse, slice, start, end, Anchored::Yes)
+            .unwrap();
+        Some(start..end)
+    }
+
+    /// Returns the byte range of the match that starts last.
+    fn rfind(&self, caches: &mut Caches, slice: &RopeSlice) -> Option<Range<usize>> {
+        let len = slice.len_bytes();
+        let start = self.search_reverse(&mut caches.reverse, slice, 0, len, Anchored::No)?;
+        let end = self
+            .search_forward(&mut caches.forward, slice, start, Anchored::Yes)
+            .unwrap();
+        Some(start..end)
+    }
+
+    /// Runs the forward DFA from byte index `start`, and returns the end of
+    /// the match.
+    ///
+    /// Note: the lazy DFA can only fail if it's configured to give up on
+    /// poor cache usage or to quit on some bytes, and neither is the case
+    /// here.  Hence the unwraps in this and the reverse search.
+    fn search_forward(
+        &self,
+        cache: &mut Cache,
+        slice: &RopeSlice,
+        start: usize,
+        anchored: Anchored,
+    ) -> Option<usize> {
+        let dfa = &self.forward;
+        let look_behind = if start > 0 {
+            Some(slice.byte(start - 1))
+        } else {
+            None
+        };
+        let config = start::Config::new()
+            .anchored(anchored)
+            .look_behind(look_behind);
+        let mut sid = dfa.start_state(cache, &config).unwrap();
+
+        // Matches are reported one byte late by the DFA, so a match state
+        // after byte `i` means a match ending at `i`.
+        let mut last_match = None;
+        let mut byte_idx = start;
+        for byte in slice.bytes_at(start) {
+            sid = dfa.next_state(cache, sid, byte).unwrap();
+            if let Some(done) = check_state(sid, byte_idx, &mut last_match) {
+                return done;
+            }
+            byte_idx += 1;
+        }
+        sid = dfa.next_eoi_state(cache, sid).unwrap();
+        check_state(sid, byte_idx, &mut last_match);
+        last_match
+    }
+
+    /// Runs the reverse DFA backwards from byte index `end` to `min`, and
+    /// returns the start of the match.
+    ///
+    /// Anchored searches return the earliest start, and unanchored searches
+    /// the latest.
+    fn search_reverse(
+        &self,
+        cache: &mut Cache,
+        slice: &RopeSlice,
+        min: usize,
+        end: usize,
+        anchored: Anchored,
+    ) -> Option<usize> {
+        let dfa = &self.reverse;
+        let look_behind = if end < slice.len_bytes() {
+            Some(slice.byte(end))
+        } else {
+            None
+        };
+        let config = start::Config::new()
+            .anchored(anchored)
+            .look_behind(look_behind);
+        let mut sid = dfa.start_state(cache, &config).unwrap();
+
+        let stop_at_first = anchored == Anchored::No;
+        let mut last_match = None;
+        let mut bytes = slice.bytes_at(end);
+        let mut byte_idx = end;
+        while byte_idx > min {
+            let byte = bytes.prev().unwrap();
+            sid = dfa.next_state(cache, sid, byte).unwrap();
+            if let Some(done) = check_state(sid, byte_idx, &mut last_match) {
+                return done;
+            }
+            if stop_at_first && last_match.is_some() {
+                return last_match;
+            }
+            byte_idx -= 1;
+        }
+        // The text before `min` is still context for look-around.
+        sid = if min > 0 {
+            dfa.next_state(cache, sid, slice.byte(min - 1)).unwrap()
+        } else {
+            dfa.next_eoi_state(cache, sid).unwrap()
+        };
+        check_state(sid, byte_idx, &mut last_match);
+        last_match
+    }
+}
+
+/// Records a match in `last_match` if `sid` is a match state at `byte_idx`,
+/// and returns the search result if the search is over.
+#[inline(always)]
+fn check_state(
+    sid: LazyStateID,
+    byte_idx: usize,
+    last_match: &mut Option<usize>,
+) -> Option<Option<usize>> {
+    if sid.is_tagged() {
+        if sid.is_match() {
+            *last_match = Some(byte_idx);
+        } else if sid.is_dead() {
+            return Some(*last_match);
+        }
+    }
+    None
+}
+
+impl fmt::Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Regex").finish()
+    }
+}
+
+struct Caches {
+    forward: Cache,
+    reverse: Cache,
+}
+
+//==============================================================
+
+/// A match of a [`Regex`](struct.Regex.html) in a `Rope` or `RopeSlice`,
+/// with both its byte and char range.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegexMatch {
+    byte_range: Range<usize>,
+    char_range: Range<usize>,
+}
+
+impl RegexMatch {
+    fn new(slice: &RopeSlice, byte_range: Range<usize>) -> RegexMatch {
+        let char_start = slice.byte_to_char(byte_range.start);
+        let char_end = char_start
+            + slice
+                .byte_slice(byte_range.start..byte_range.end)
+                .len_chars();
+        RegexMatch {
+            byte_range: byte_range,
+            char_range: char_start..char_end,
+        }
+    }
+
+    /// The byte range of the match.
+    #[inline]
+    pub fn byte_range(&self) -> Range<usize> {
+        self.byte_range.clone()
+    }
+
+    /// The char range of the match.
+    #[inline]
+    pub fn char_range(&self) -> Range<usize> {
+        self.char_range.clone()
+    }
+
+    /// Whether the match is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.byte_range.start == self.byte_range.end
+    }
+}
+
+/// An iterator over the non-overlapping matches of a
+/// [`Regex`](struct.Regex.html) in a `Rope` or `RopeSlice`.
+///
+/// As with the `regex` crate, an empty match directly after the previous
+/// match is skipped.
+///
+/// Created with [`RopeSlice::find_regex_iter()`](struct.RopeSlice.html#method.find_regex_iter)
+/// or [`Rope::find_regex_iter()`](struct.Rope.html#method.find_regex_iter).
+pub struct FindRegexIter<'a> {
+    slice: RopeSlice<'a>,
+    regex: &'a Regex,
+    caches: Caches,
+    byte_idx: usize,
+    last_match_end: Option<usize>,
+    done: bool,
+}
+
+impl<'a> FindRegexIter<'a> {
+    pub(crate) fn new(slice: RopeSlice<'a>, regex: &'a Regex) -> FindRegexIter<'a> {
+        FindRegexIter {
+            slice: slice,
+            regex: regex,
+            caches: regex.create_caches(),
+            byte_idx: 0,
+            last_match_end: None,
+            done: false,
+        }
+    }
+}
+
+impl<'a> Iterator for FindRegexIter<'a> {
+    type Item = RegexMatch;
+
+    /// Advances the iterator forward and returns the next value.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    fn next(&mut self) -> Option<RegexMatch> {
+        while !self.done {
+            let range = match self
+                .regex
+                .find_at(&mut self.caches, &self.slice, self.byte_idx)
+            {
+                Some(range) => range,
+                None => break,
+            };
+
+            if range.start == range.end && Some(range.end) == self.last_match_end {
+                // Skip over the next char and try again.
+                if range.end == self.slice.len_bytes() {
+                    break;
+                }
+                let c = self.slice.char(self.slice.byte_to_char(range.end));
+                self.byte_idx = range.end + c.len_utf8();
+                continue;
+            }
+
+            self.byte_idx = range.end;
+            self.last_match_end = Some(range.end);
+            return Some(RegexMatch::new(&self.slice, range));
+        }
+
+        self.done = true;
+        None
+    }
+}
+
+impl<'a> fmt::Debug for FindRegexIter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FindRegexIter")
+            .field("byte_idx", &self.byte_idx)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+/// Returns the first match of `regex` in `slice`.
+pub(crate) fn find_regex(slice: &RopeSlice, regex: &Regex) -> Option<RegexMatch> {
+    let mut caches = regex.create_caches();
+    regex
+        .find_at(&mut caches, slice, 0)
+        .map(|range| RegexMatch::new(slice, range))
+}
+
+/// Returns the match of `regex` in `slice` that starts last.
+pub(crate) fn rfind_regex(slice: &RopeSlice, regex: &Regex) -> Option<RegexMatch> {
+    let mut caches = regex.create_caches();
+    regex
+        .rfind(&mut caches, slice)
+        .map(|range| RegexMatch::new(slice, range))
+}
+
+//==============================================================
+
+/// An error from compiling a [`Regex`](struct.Regex.html).
+#[derive(Debug, Clone)]
+pub struct RegexError(Box<BuildError>);
+
+impl From<BuildError> for RegexError {
+    fn from(err: BuildError) -> RegexError {
+        RegexError(Box::new(err))
+    }
+}
+
+impl std::error::Error for RegexError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.0)
+    }
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid regex: {}", self.0)
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::{Regex, Rope};
+
+    const TEXT: &str = "Hello there!  How're you doing?  It's \
+                        a fine day, isn't it?  Aren't you glad \
+                        we're alive?  こんにちは、みんなさん！";
+
+    #[test]
+    fn find_regex_01() {
+        let r = Rope::from_str(&TEXT.repeat(3));
+        let re = Regex::new(r"you \w+").unwrap();
+
+        let m = r.find_regex(&re).unwrap();
+        assert_eq!(r.slice(m.char_range()), "you doing");
+        assert_eq!(r.byte_slice(m.byte_range()), "you doing");
+
+        let m = r.rfind_regex(&re).unwrap();
+        assert_eq!(r.slice(m.char_range()), "you glad");
+
+        let re = Regex::new("zzz").unwrap();
+        assert_eq!(r.find_regex(&re), None);
+        assert_eq!(r.rfind_regex(&re), None);
+    }
+
+    #[test]
+    fn find_regex_02() {
+        // Matches across chunk boundaries, and anchors in slices.
+        let r = Rope::from_str(&TEXT.repeat(3));
+        let s = r.slice(7..);
+        let re = Regex::new(r"^\w+").unwrap();
+        assert_eq!(s.slice(s.find_regex(&re).unwrap().char_range()), "here");
+        let re = Regex::new(r"\p{Hiragana}+！$").unwrap();
+        let m = r.find_regex(&re).unwrap();
+        assert_eq!(r.slice(m.char_range()), "みんなさん！");
+        assert_eq!(m.char_range().end, r.len_chars());
+        let re = Regex::new(r"(?-u:\b)[a-z]{4}(?-u:\b)").unwrap();
+        let m = r.rfind_regex(&re).unwrap();
+        assert_eq!(r.slice(m.char_range()), "glad");
+    }
+
+    #[test]
+    fn find_regex_iter_01() {
+        let r = Rope::from_str("aaa bb\ncc");
+        let re = Regex::new(r"\w+").unwrap();
+        let words: Vec<_> = r
+            .find_regex_iter(&re)
+            .map(|m| r.slice(m.char_range()).to_string())
+            .collect();
+        assert_eq!(words, vec!["aaa", "bb", "cc"]);
+
+        let re = Regex::new("").unwrap();
+        let empties: Vec<_> = r.find_regex_iter(&re).map(|m| m.char_range()).collect();
+        assert_eq!(empties.len(), r.len_chars() + 1);
+    }
+
+    #[test]
+    fn regex_error_01() {
+        assert!(Regex::new("(").is_err());
+        assert!(Regex::new(r"\b").is_err());
+        assert!(Regex::new(r"(?-u:\b)").is_ok());
+    }
+}
@@ -1,18 +1,33 @@
 use std::io;
 use std::iter::FromIterator;
+#[cfg(feature = "unicode-segmentation")]
+use std::ops::Range;
 use std::ops::RangeBounds;
 use std::ptr;
 use std::sync::Arc;
 
 use crate::crlf;
+use crate::cursor::Cursor;
+#[cfg(feature = "unicode-segmentation")]
+use crate::graphemes::Graphemes;
 use crate::iter::{Bytes, Chars, Chunks, Lines};
+use crate::metric::{LongestLine, MetricIndex};
+use crate::reader::RopeReader;
+#[cfg(feature = "regex")]
+use crate::regex::{find_regex, rfind_regex, FindRegexIter, Regex, RegexMatch};
 use crate::rope_builder::RopeBuilder;
+use crate::search::{FindIter, Matches};
+#[cfg(feature = "unicode-segmentation")]
+use crate::segmentation::{Sentences, Words};
 use crate::slice::{end_bound_to_num, start_bound_to_num, RopeSlice};
 use crate::str_utils::{
     byte_to_char_idx, byte_to_line_idx, byte_to_utf16_surrogate_idx, char_to_byte_idx,
     char_to_line_idx, line_to_byte_idx, line_to_char_idx, utf16_code_unit_to_char_idx,
 };
-use crate::tree::{Count, Node, NodeChildren, TextInfo, MAX_BYTES};
+use crate::tree::{Count, Node, NodeChildren, NodeText, TextInfo, MAX_BYTES, MIN_BYTES};
+#[cfg(feature = "unicode-width")]
+use crate::visual::ColumnRounding;
+use crate::{Error, Position, Result};
 
 /// A utf8 text rope.
 ///
@@ -221,6 +236,18 @@ impl Rope {
         Ok(())
     }
 
+    /// Creates a [`RopeReader`](struct.RopeReader.html) over the `Rope`'s
+    /// utf8 bytes, implementing `io::Read`, `io::BufRead`, and `io::Seek`.
+    ///
+    /// This is the counterpart to `write_to()` for APIs that want to pull
+    /// the text from a reader.  The text isn't copied.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn reader(&self) -> RopeReader<'_> {
+        RopeReader::new(self.slice(..))
+    }
+
     //-----------------------------------------------------------------------
     // Informational methods
 
@@ -263,6 +290,23 @@ impl Rope {
         (info.chars + info.utf16_surrogates) as usize
     }
 
+    /// Returns the index and length in chars of the longest line in the
+    /// `Rope`, as `(line_idx, len_chars)`.
+    ///
+    /// The length doesn't include the line break.  If several lines are
+    /// equally long, the first of them is returned.
+    ///
+    /// The line lengths of the `Rope`'s tree nodes are cached in the nodes,
+    /// and an edit only drops those of the nodes it changes.  So this runs
+    /// in O(log N) time if the `Rope` was only changed by small edits since
+    /// the last call, and O(N) time the first time.
+    #[inline]
+    pub fn longest_line(&self) -> (usize, usize) {
+        MetricIndex::with_metric(LongestLine)
+            .summary(self)
+            .longest_line()
+    }
+
     //-----------------------------------------------------------------------
     // Memory management methods
 
@@ -318,7 +362,7 @@ impl Rope {
             } else if node_stack.last().unwrap().child_count() == 0 {
                 node_stack.pop();
             } else {
-                let (_, next_node) = Arc::make_mut(node_stack.last_mut().unwrap())
+                let (_, next_node) = Node::make_mut(node_stack.last_mut().unwrap())
                     .children_mut()
                     .remove(0);
                 node_stack.push(next_node);
@@ -341,14 +385,13 @@ impl Rope {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn insert(&mut self, char_idx: usize, text: &str) {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to insert past end of Rope: insertion point {}, Rope length {}",
-            char_idx,
-            self.len_chars()
-        );
+        self.try_insert(char_idx, text).unwrap()
+    }
 
+    /// Shared implementation of `insert()` and `try_insert()`.
+    ///
+    /// Assumes that `char_idx` has already been bounds-checked.
+    fn insert_unchecked(&mut self, char_idx: usize, text: &str) {
         // We have three cases here:
         // 1. The insertion text is very large, in which case building a new
         //    Rope out of it and splicing it into the existing Rope is most
@@ -404,16 +447,68 @@ impl Rope {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn insert_char(&mut self, char_idx: usize, ch: char) {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to insert past end of Rope: insertion point {}, Rope length {}",
-            char_idx,
-            self.len_chars()
-        );
+        self.try_insert_char(char_idx, ch).unwrap()
+    }
+
+    /// Inserts `text` at byte index `byte_idx`.
+    ///
+    /// Runs in O(M + log N) time, where N is the length of the `Rope` and M
+    /// is the length of `text`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`)
+    /// or isn't on a char boundary.
+    #[inline]
+    pub fn insert_at_byte(&mut self, byte_idx: usize, text: &str) {
+        self.try_insert_at_byte(byte_idx, text).unwrap()
+    }
+
+    /// Shared implementation of `insert_at_byte()` and
+    /// `try_insert_at_byte()`.
+    ///
+    /// Assumes that `byte_idx` has already been bounds-checked and is on a
+    /// char boundary.
+    fn insert_at_byte_unchecked(&mut self, byte_idx: usize, text: &str) {
+        // See `insert_unchecked()` for an explanation of the cases here.
+        if text.len() > MAX_BYTES * 6 {
+            // Case #1: very large text, build rope and splice it in.
+            let char_idx = self.byte_to_char(byte_idx);
+            let text_rope = Rope::from_str(text);
+            let right = self.split_off(char_idx);
+            self.append(text_rope);
+            self.append(right);
+        } else {
+            // Cases #2 and #3: split into chunks and repeatedly insert.
+            let mut text = text;
+            while !text.is_empty() {
+                let split_idx = crlf::find_good_split(
+                    text.len() - (MAX_BYTES - 4).min(text.len()),
+                    text.as_bytes(),
+                    false,
+                );
+                let ins_text = &text[split_idx..];
+                text = &text[..split_idx];
 
-        let mut buf = [0u8; 4];
-        self.insert_internal(char_idx, ch.encode_utf8(&mut buf));
+                // Do the insertion.
+                self.insert_internal_at_byte(byte_idx, ins_text);
+            }
+        }
+    }
+
+    /// Inserts `text` at utf16 code unit index `utf16_cu_idx`.
+    ///
+    /// Runs in O(M + log N) time, where N is the length of the `Rope` and M
+    /// is the length of `text`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `utf16_cu_idx` is out of bounds
+    /// (i.e. `utf16_cu_idx > len_utf16_cu()`) or is in the middle of a
+    /// surrogate pair.
+    #[inline]
+    pub fn insert_at_utf16_cu(&mut self, utf16_cu_idx: usize, text: &str) {
+        self.try_insert_at_utf16_cu(utf16_cu_idx, text).unwrap()
     }
 
     /// Private internal-only method that does a single insertion of
@@ -430,7 +525,7 @@ impl Rope {
         let mut left_seam = false;
         let root_info = self.root.text_info();
 
-        let (l_info, residual) = Arc::make_mut(&mut self.root).edit_chunk_at_char(
+        let (l_info, residual) = Node::make_mut(&mut self.root).edit_chunk_at_char(
             char_idx,
             root_info,
             |idx, cur_info, leaf_text| {
@@ -447,123 +542,88 @@ impl Rope {
                 // Find our byte index
                 let byte_idx = char_to_byte_idx(leaf_text, idx);
 
-                // No node splitting
-                if (leaf_text.len() + ins_text.len()) <= MAX_BYTES {
-                    // Calculate new info without doing a full re-scan of cur_text
-                    let new_info = {
-                        // Get summed info of current text and to-be-inserted text
-                        let mut info = cur_info + TextInfo::from_str(ins_text);
-                        // Check for CRLF pairs on the insertion seams, and
-                        // adjust line break counts accordingly
-                        if byte_idx > 0 {
-                            if leaf_text.as_bytes()[byte_idx - 1] == 0x0D
-                                && ins_text.as_bytes()[0] == 0x0A
-                            {
-                                info.line_breaks -= 1;
-                            }
-                            if byte_idx < leaf_text.len()
-                                && leaf_text.as_bytes()[byte_idx - 1] == 0x0D
-                                && leaf_text.as_bytes()[byte_idx] == 0x0A
-                            {
-                                info.line_breaks += 1;
-                            }
-                        }
-                        if byte_idx < leaf_text.len()
-                            && *ins_text.as_bytes().last().unwrap() == 0x0D
-                            && leaf_text.as_bytes()[byte_idx] == 0x0A
-                        {
-                            info.line_breaks -= 1;
-                        }
-                        info
-                    };
-                    // Insert the text and return the new info
-                    leaf_text.insert_str(byte_idx, ins_text);
-                    (new_info, None)
-                }
-                // We're splitting the node
-                else {
-                    let r_text = leaf_text.insert_str_split(byte_idx, ins_text);
-                    let l_text_info = TextInfo::from_str(&leaf_text);
-                    if r_text.len() > 0 {
-                        let r_text_info = TextInfo::from_str(&r_text);
-                        (
-                            l_text_info,
-                            Some((r_text_info, Arc::new(Node::Leaf(r_text)))),
-                        )
-                    } else {
-                        // Leaf couldn't be validly split, so leave it oversized
-                        (l_text_info, None)
-                    }
-                }
+                insert_into_leaf(leaf_text, cur_info, byte_idx, ins_text)
             },
         );
 
         // Handle root splitting, if any.
-        if let Some((r_info, r_node)) = residual {
-            let mut l_node = Arc::new(Node::new());
-            std::mem::swap(&mut l_node, &mut self.root);
-
-            let mut children = NodeChildren::new();
-            children.push((l_info, l_node));
-            children.push((r_info, r_node));
-
-            *Arc::make_mut(&mut self.root) = Node::Internal(children);
-        }
+        self.handle_root_split(l_info, residual);
 
         // Insert the LF to the left.
-        // TODO: this code feels fairly redundant with above.  Can we DRY this
-        // better?
         if left_seam {
-            // Do the insertion
             let root_info = self.root.text_info();
-            let (l_info, residual) = Arc::make_mut(&mut self.root).edit_chunk_at_char(
+            let (l_info, residual) = Node::make_mut(&mut self.root).edit_chunk_at_char(
                 char_idx - 1,
                 root_info,
                 |_, cur_info, leaf_text| {
                     let byte_idx = leaf_text.len();
+                    insert_into_leaf(leaf_text, cur_info, byte_idx, "\n")
+                },
+            );
+            self.handle_root_split(l_info, residual);
+        }
+    }
 
-                    // No node splitting
-                    if (leaf_text.len() + ins_text.len()) <= MAX_BYTES {
-                        // Calculate new info without doing a full re-scan of cur_text
-                        let mut new_info = cur_info;
-                        new_info.bytes += 1;
-                        new_info.chars += 1;
-                        if *leaf_text.as_bytes().last().unwrap() != 0x0D {
-                            new_info.line_breaks += 1;
-                        }
-                        // Insert the text and return the new info
-                        leaf_text.insert_str(byte_idx, "\n");
-                        (new_info, None)
-                    }
-                    // We're splitting the node
-                    else {
-                        let r_text = leaf_text.insert_str_split(byte_idx, "\n");
-                        let l_text_info = TextInfo::from_str(&leaf_text);
-                        if r_text.len() > 0 {
-                            let r_text_info = TextInfo::from_str(&r_text);
-                            (
-                                l_text_info,
-                                Some((r_text_info, Arc::new(Node::Leaf(r_text)))),
-                            )
-                        } else {
-                            // Leaf couldn't be validly split, so leave it oversized
-                            (l_text_info, None)
-                        }
+    /// Same as `insert_internal()` above, except that the insertion point
+    /// is given as a byte index, and the tree is walked by byte index
+    /// directly.
+    ///
+    /// `byte_idx` must be on a char boundary.
+    fn insert_internal_at_byte(&mut self, byte_idx: usize, ins_text: &str) {
+        let mut ins_text = ins_text;
+        let mut left_seam = false;
+        let root_info = self.root.text_info();
+
+        let (l_info, residual) = Node::make_mut(&mut self.root).edit_chunk_at_byte(
+            byte_idx,
+            root_info,
+            |idx, cur_info, leaf_text| {
+                debug_assert!(leaf_text.is_char_boundary(idx));
+
+                // First check if we have a left seam.
+                if idx == 0 && byte_idx > 0 && ins_text.as_bytes()[0] == 0x0A {
+                    left_seam = true;
+                    ins_text = &ins_text[1..];
+                    // Early out if it was only an LF.
+                    if ins_text.is_empty() {
+                        return (cur_info, None);
                     }
+                }
+
+                insert_into_leaf(leaf_text, cur_info, idx, ins_text)
+            },
+        );
+
+        // Handle root splitting, if any.
+        self.handle_root_split(l_info, residual);
+
+        // Insert the LF to the left.
+        if left_seam {
+            let root_info = self.root.text_info();
+            let (l_info, residual) = Node::make_mut(&mut self.root).edit_chunk_at_byte(
+                byte_idx - 1,
+                root_info,
+                |_, cur_info, leaf_text| {
+                    let byte_idx = leaf_text.len();
+                    insert_into_leaf(leaf_text, cur_info, byte_idx, "\n")
                 },
             );
+            self.handle_root_split(l_info, residual);
+        }
+    }
 
-            // Handle root splitting, if any.
-            if let Some((r_info, r_node)) = residual {
-                let mut l_node = Arc::new(Node::new());
-                std::mem::swap(&mut l_node, &mut self.root);
+    /// Puts the root and `residual` under a new root if an edit split the
+    /// root node.
+    fn handle_root_split(&mut self, l_info: TextInfo, residual: Option<(TextInfo, Arc<Node>)>) {
+        if let Some((r_info, r_node)) = residual {
+            let mut l_node = Arc::new(Node::new());
+            std::mem::swap(&mut l_node, &mut self.root);
 
-                let mut children = NodeChildren::new();
-                children.push((l_info, l_node));
-                children.push((r_info, r_node));
+            let mut children = NodeChildren::new();
+            children.push((l_info, l_node));
+            children.push((r_info, r_node));
 
-                *Arc::make_mut(&mut self.root) = Node::Internal(children);
-            }
+            *Node::make_mut(&mut self.root) = Node::Internal(children);
         }
     }
 
@@ -593,18 +653,160 @@ impl Rope {
     where
         R: RangeBounds<usize>,
     {
-        let start = start_bound_to_num(char_range.start_bound()).unwrap_or(0);
-        let end = end_bound_to_num(char_range.end_bound()).unwrap_or_else(|| self.len_chars());
+        self.try_remove(char_range).unwrap()
+    }
 
-        // Bounds check
-        assert!(start <= end);
-        assert!(
-            end <= self.len_chars(),
-            "Attempt to remove past end of Rope: removal end {}, Rope length {}",
-            end,
-            self.len_chars()
-        );
+    /// Removes the text in the given byte index range.
+    ///
+    /// Uses range syntax, e.g. `2..7`, `2..`, etc.  The range is in `byte`
+    /// indices.
+    ///
+    /// Runs in O(M + log N) time, where N is the length of the `Rope` and M
+    /// is the length of the range being removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ropey::Rope;
+    /// let mut rope = Rope::from_str("Hello wörld!");
+    /// rope.remove_byte_range(5..);
+    ///
+    /// assert_eq!("Hello", rope);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, if the
+    /// end is out of bounds (i.e. `end > len_bytes()`), or if either end of
+    /// the range isn't on a char boundary.
+    pub fn remove_byte_range<R>(&mut self, byte_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_remove_byte_range(byte_range).unwrap()
+    }
+
+    /// Removes the text in the given utf16 code unit index range.
+    ///
+    /// Uses range syntax, e.g. `2..7`, `2..`, etc.  The range is in utf16
+    /// code unit indices.
+    ///
+    /// Runs in O(M + log N) time, where N is the length of the `Rope` and M
+    /// is the length of the range being removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ropey::Rope;
+    /// let mut rope = Rope::from_str("Hello 🐸 world!");
+    /// rope.remove_utf16_cu_range(5..8);
+    ///
+    /// assert_eq!("Hello world!", rope);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, if the
+    /// end is out of bounds (i.e. `end > len_utf16_cu()`), or if either end
+    /// of the range is in the middle of a surrogate pair.
+    pub fn remove_utf16_cu_range<R>(&mut self, utf16_cu_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_remove_utf16_cu_range(utf16_cu_range).unwrap()
+    }
+
+    /// Replaces the text in the given char index range with `text`.
+    ///
+    /// Uses range syntax, e.g. `2..7`, `2..`, etc.  The range is in `char`
+    /// indices.
+    ///
+    /// This is equivalent to a `remove()` followed by an `insert()`, but
+    /// when the range and the replacement fit within a single chunk it's
+    /// done as a single edit of that chunk.
+    ///
+    /// Runs in O(M + log N) time, where N is the length of the `Rope` and M
+    /// is the length of the range being removed plus the length of `text`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ropey::Rope;
+    /// let mut rope = Rope::from_str("Hello world!");
+    /// rope.replace(6..11, "there");
+    ///
+    /// assert_eq!("Hello there!", rope);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if the
+    /// end is out of bounds (i.e. `end > len_chars()`).
+    pub fn replace<R>(&mut self, char_range: R, text: &str)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_replace(char_range, text).unwrap()
+    }
+
+    /// Replaces all non-overlapping matches of `pattern` with
+    /// `replacement`, and returns the number of matches replaced.
+    ///
+    /// As with `str::replace()`, the matches are found in the original
+    /// text, and an empty pattern matches at every char boundary.
+    ///
+    /// When there are only a few matches they are replaced in place.  When
+    /// there are many, the `Rope` is instead rebuilt in a single pass over
+    /// its text.
+    ///
+    /// Runs in O(N + M log N) time, where M is the number of matches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ropey::Rope;
+    /// let mut rope = Rope::from_str("one fish, two fish");
+    /// assert_eq!(rope.replace_all("fish", "cat"), 2);
+    ///
+    /// assert_eq!("one cat, two cat", rope);
+    /// ```
+    pub fn replace_all(&mut self, pattern: &str, replacement: &str) -> usize {
+        // Above this many matches, rebuilding is cheaper than editing.
+        let rebuild_threshold = 4 + self.len_bytes() / MAX_BYTES;
+
+        let matches: Vec<_> = self.matches(pattern).take(rebuild_threshold + 1).collect();
+        if matches.len() <= rebuild_threshold {
+            // Replace back to front, so the earlier indices stay valid.
+            for range in matches.iter().rev() {
+                let start = self.byte_to_char(range.start);
+                let end = self.byte_to_char(range.end);
+                self.replace_unchecked(start, end, replacement);
+            }
+            return matches.len();
+        }
+
+        let mut builder = RopeBuilder::new();
+        let mut count = 0;
+        let mut last_end = 0;
+        for range in self.matches(pattern) {
+            for chunk in self.byte_slice(last_end..range.start).chunks() {
+                builder.append(chunk);
+            }
+            builder.append(replacement);
+            last_end = range.end;
+            count += 1;
+        }
+        for chunk in self.byte_slice(last_end..).chunks() {
+            builder.append(chunk);
+        }
+        *self = builder.finish();
+        count
+    }
 
+    /// Shared implementation of `remove()` and `try_remove()`.
+    ///
+    /// Assumes that the range has already been validated.
+    fn remove_unchecked(&mut self, start: usize, end: usize) {
         // A special case that the rest of the logic doesn't handle
         // correctly.
         if start == 0 && end == self.len_chars() {
@@ -614,7 +816,7 @@ impl Rope {
 
         // Scope to contain borrow of root
         {
-            let root = Arc::make_mut(&mut self.root);
+            let root = Node::make_mut(&mut self.root);
 
             let root_info = root.text_info();
             let (_, crlf_seam, needs_fix) = root.remove_char_range(start, end, root_info);
@@ -632,6 +834,52 @@ impl Rope {
         self.pull_up_singular_nodes();
     }
 
+    /// Shared implementation of `replace()` and `try_replace()`.
+    ///
+    /// Assumes that the range has already been validated.
+    fn replace_unchecked(&mut self, start: usize, end: usize, text: &str) {
+        if start == end {
+            self.insert_unchecked(start, text);
+            return;
+        } else if text.is_empty() {
+            self.remove_unchecked(start, end);
+            return;
+        }
+
+        // If the range is strictly inside a single chunk, and the chunk
+        // stays within its size bounds, edit the chunk directly.  Since the
+        // edges of the chunk are untouched, no CRLF seams can be created or
+        // broken.
+        let mut replaced = false;
+        let root_info = self.root.text_info();
+        let (l_info, residual) = Node::make_mut(&mut self.root).edit_chunk_at_char(
+            start,
+            root_info,
+            |idx, cur_info, leaf_text| {
+                if idx == 0 || idx + (end - start) >= cur_info.chars as usize {
+                    return (cur_info, None);
+                }
+                let byte_start = char_to_byte_idx(leaf_text, idx);
+                let byte_end = byte_start + char_to_byte_idx(&leaf_text[byte_start..], end - start);
+                let new_len = leaf_text.len() - (byte_end - byte_start) + text.len();
+                if new_len > MAX_BYTES || (new_len < MIN_BYTES && new_len < leaf_text.len()) {
+                    return (cur_info, None);
+                }
+
+                leaf_text.remove_range(byte_start, byte_end);
+                leaf_text.insert_str(byte_start, text);
+                replaced = true;
+                (TextInfo::from_str(leaf_text), None)
+            },
+        );
+        self.handle_root_split(l_info, residual);
+
+        if !replaced {
+            self.remove_unchecked(start, end);
+            self.insert_unchecked(start, text);
+        }
+    }
+
     /// Splits the `Rope` at `char_idx`, returning the right part of
     /// the split.
     ///
@@ -641,14 +889,13 @@ impl Rope {
     ///
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     pub fn split_off(&mut self, char_idx: usize) -> Self {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to split past end of Rope: split point {}, Rope length {}",
-            char_idx,
-            self.len_chars()
-        );
+        self.try_split_off(char_idx).unwrap()
+    }
 
+    /// Shared implementation of `split_off()` and `try_split_off()`.
+    ///
+    /// Assumes that `char_idx` has already been bounds-checked.
+    fn split_off_unchecked(&mut self, char_idx: usize) -> Self {
         if char_idx == 0 {
             // Special case 1
             let mut new_rope = Rope::new();
@@ -660,12 +907,12 @@ impl Rope {
         } else {
             // Do the split
             let mut new_rope = Rope {
-                root: Arc::new(Arc::make_mut(&mut self.root).split(char_idx)),
+                root: Arc::new(Node::make_mut(&mut self.root).split(char_idx)),
             };
 
             // Fix up the edges
-            Arc::make_mut(&mut self.root).zip_fix_right();
-            Arc::make_mut(&mut new_rope.root).zip_fix_left();
+            Node::make_mut(&mut self.root).zip_fix_right();
+            Node::make_mut(&mut new_rope.root).zip_fix_left();
             self.pull_up_singular_nodes();
             new_rope.pull_up_singular_nodes();
 
@@ -693,7 +940,7 @@ impl Rope {
 
             if l_depth > r_depth {
                 let extra =
-                    Arc::make_mut(&mut self.root).append_at_depth(other.root, l_depth - r_depth);
+                    Node::make_mut(&mut self.root).append_at_depth(other.root, l_depth - r_depth);
                 if let Some(node) = extra {
                     let mut children = NodeChildren::new();
                     children.push((self.root.text_info(), Arc::clone(&self.root)));
@@ -702,7 +949,7 @@ impl Rope {
                 }
             } else {
                 let mut other = other;
-                let extra = Arc::make_mut(&mut other.root)
+                let extra = Node::make_mut(&mut other.root)
                     .prepend_at_depth(Arc::clone(&self.root), r_depth - l_depth);
                 if let Some(node) = extra {
                     let mut children = NodeChildren::new();
@@ -714,7 +961,7 @@ impl Rope {
             };
 
             if let Some(i) = seam_byte_i {
-                Arc::make_mut(&mut self.root).fix_crlf_seam(i, true);
+                Node::make_mut(&mut self.root).fix_crlf_seam(i, true);
             }
         }
     }
@@ -738,16 +985,7 @@ impl Rope {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
     #[inline]
     pub fn byte_to_char(&self, byte_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            byte_idx <= self.len_bytes(),
-            "Attempt to index past end of Rope: byte index {}, Rope byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
-
-        let (chunk, b, c, _) = self.chunk_at_byte(byte_idx);
-        c + byte_to_char_idx(chunk, byte_idx - b)
+        self.try_byte_to_char(byte_idx).unwrap()
     }
 
     /// Returns the line index of the given byte.
@@ -766,16 +1004,27 @@ impl Rope {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
     #[inline]
     pub fn byte_to_line(&self, byte_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            byte_idx <= self.len_bytes(),
-            "Attempt to index past end of Rope: byte index {}, Rope byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
+        self.try_byte_to_line(byte_idx).unwrap()
+    }
 
-        let (chunk, b, _, l) = self.chunk_at_byte(byte_idx);
-        l + byte_to_line_idx(chunk, byte_idx - b)
+    /// Returns the utf16 code unit index of the given byte.
+    ///
+    /// Ropey stores text internally as utf8, but sometimes it is necessary
+    /// to interact with external APIs that still use utf16.  This function is
+    /// primarily intended for such situations, and is otherwise not very
+    /// useful.
+    ///
+    /// Note: if the byte is in the middle of a multi-byte char, returns the
+    /// utf16 code unit index of the char that the byte belongs to.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
+    #[inline]
+    pub fn byte_to_utf16_cu(&self, byte_idx: usize) -> usize {
+        self.try_byte_to_utf16_cu(byte_idx).unwrap()
     }
 
     /// Returns the byte index of the given char.
@@ -792,16 +1041,7 @@ impl Rope {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn char_to_byte(&self, char_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of Rope: char index {}, Rope char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        let (chunk, b, c, _) = self.chunk_at_char(char_idx);
-        b + char_to_byte_idx(chunk, char_idx - c)
+        self.try_char_to_byte(char_idx).unwrap()
     }
 
     /// Returns the line index of the given char.
@@ -820,16 +1060,7 @@ impl Rope {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn char_to_line(&self, char_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of Rope: char index {}, Rope char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        let (chunk, _, c, l) = self.chunk_at_char(char_idx);
-        l + char_to_line_idx(chunk, char_idx - c)
+        self.try_char_to_line(char_idx).unwrap()
     }
 
     /// Returns the utf16 code unit index of the given char.
@@ -846,19 +1077,7 @@ impl Rope {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn char_to_utf16_cu(&self, char_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of Rope: char index {}, Rope char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        let (chunk, chunk_start_info) = self.root.get_chunk_at_char(char_idx);
-        let chunk_byte_idx = char_to_byte_idx(chunk, char_idx - chunk_start_info.chars as usize);
-        let surrogate_count = byte_to_utf16_surrogate_idx(chunk, chunk_byte_idx);
-
-        char_idx + chunk_start_info.utf16_surrogates as usize + surrogate_count
+        self.try_char_to_utf16_cu(char_idx).unwrap()
     }
 
     /// Returns the char index of the given utf16 code unit.
@@ -879,20 +1098,48 @@ impl Rope {
     /// (i.e. `utf16_cu_idx > len_utf16_cu()`).
     #[inline]
     pub fn utf16_cu_to_char(&self, utf16_cu_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            utf16_cu_idx <= self.len_utf16_cu(),
-            "Attempt to index past end of Rope: utf16 code unit index {}, Rope utf16 code unit length {}",
-            utf16_cu_idx,
-            self.len_utf16_cu()
-        );
+        self.try_utf16_cu_to_char(utf16_cu_idx).unwrap()
+    }
 
-        let (chunk, chunk_start_info) = self.root.get_chunk_at_utf16_code_unit(utf16_cu_idx);
-        let chunk_utf16_cu_idx =
-            utf16_cu_idx - (chunk_start_info.chars + chunk_start_info.utf16_surrogates) as usize;
-        let chunk_char_idx = utf16_code_unit_to_char_idx(chunk, chunk_utf16_cu_idx);
+    /// Returns the byte index of the given utf16 code unit.
+    ///
+    /// Ropey stores text internally as utf8, but sometimes it is necessary
+    /// to interact with external APIs that still use utf16.  This function is
+    /// primarily intended for such situations, and is otherwise not very
+    /// useful.
+    ///
+    /// Note: if the utf16 code unit is in the middle of a char, returns the
+    /// byte index of the char that it belongs to.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `utf16_cu_idx` is out of bounds
+    /// (i.e. `utf16_cu_idx > len_utf16_cu()`).
+    #[inline]
+    pub fn utf16_cu_to_byte(&self, utf16_cu_idx: usize) -> usize {
+        self.try_utf16_cu_to_byte(utf16_cu_idx).unwrap()
+    }
 
-        chunk_start_info.chars as usize + chunk_char_idx
+    /// Returns the line index of the given utf16 code unit.
+    ///
+    /// Notes:
+    ///
+    /// - Lines are zero-indexed.  This is functionally equivalent to
+    ///   counting the line endings before the specified utf16 code unit.
+    /// - `utf16_cu_idx` can be one-past-the-end, which will return the
+    ///   last line index.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `utf16_cu_idx` is out of bounds
+    /// (i.e. `utf16_cu_idx > len_utf16_cu()`).
+    #[inline]
+    pub fn utf16_cu_to_line(&self, utf16_cu_idx: usize) -> usize {
+        self.try_utf16_cu_to_line(utf16_cu_idx).unwrap()
     }
 
     /// Returns the byte index of the start of the given line.
@@ -910,20 +1157,7 @@ impl Rope {
     /// Panics if `line_idx` is out of bounds (i.e. `line_idx > len_lines()`).
     #[inline]
     pub fn line_to_byte(&self, line_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            line_idx <= self.len_lines(),
-            "Attempt to index past end of Rope: line index {}, Rope line length {}",
-            line_idx,
-            self.len_lines()
-        );
-
-        if line_idx == self.len_lines() {
-            self.len_bytes()
-        } else {
-            let (chunk, b, _, l) = self.chunk_at_line_break(line_idx);
-            b + line_to_byte_idx(chunk, line_idx - l)
-        }
+        self.try_line_to_byte(line_idx).unwrap()
     }
 
     /// Returns the char index of the start of the given line.
@@ -941,20 +1175,159 @@ impl Rope {
     /// Panics if `line_idx` is out of bounds (i.e. `line_idx > len_lines()`).
     #[inline]
     pub fn line_to_char(&self, line_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            line_idx <= self.len_lines(),
-            "Attempt to index past end of Rope: line index {}, Rope line length {}",
-            line_idx,
-            self.len_lines()
-        );
+        self.try_line_to_char(line_idx).unwrap()
+    }
 
-        if line_idx == self.len_lines() {
-            self.len_chars()
-        } else {
-            let (chunk, _, c, l) = self.chunk_at_line_break(line_idx);
-            c + line_to_char_idx(chunk, line_idx - l)
-        }
+    /// Returns the utf16 code unit index of the start of the given line.
+    ///
+    /// Combined with [`utf16_cu_to_line()`](#method.utf16_cu_to_line), this
+    /// can be used to convert to and from the (line, utf16 column) positions
+    /// used by some external APIs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ropey::Rope;
+    /// let rope = Rope::from_str("Hello\n🐸 world!");
+    ///
+    /// // (line, utf16 column) to char index.
+    /// let utf16_cu_idx = rope.line_to_utf16_cu(1) + 3;
+    /// assert_eq!(rope.utf16_cu_to_char(utf16_cu_idx), 8);
+    ///
+    /// // Char index to (line, utf16 column).
+    /// let utf16_cu_idx = rope.char_to_utf16_cu(8);
+    /// let line_idx = rope.utf16_cu_to_line(utf16_cu_idx);
+    /// assert_eq!(line_idx, 1);
+    /// assert_eq!(utf16_cu_idx - rope.line_to_utf16_cu(line_idx), 3);
+    /// ```
+    ///
+    /// Notes:
+    ///
+    /// - Lines are zero-indexed.
+    /// - `line_idx` can be one-past-the-end, which will return
+    ///   one-past-the-end utf16 code unit index.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line_idx` is out of bounds (i.e. `line_idx > len_lines()`).
+    #[inline]
+    pub fn line_to_utf16_cu(&self, line_idx: usize) -> usize {
+        self.try_line_to_utf16_cu(line_idx).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Position conversion methods
+
+    /// Returns the line/column position of the given char, with the column
+    /// measured in chars.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ropey::{Position, Rope};
+    /// let rope = Rope::from_str("Hello\nwörld!");
+    ///
+    /// assert_eq!(rope.char_to_position(8), Position::new(1, 2));
+    /// assert_eq!(rope.position_to_char(Position::new(1, 2)), 8);
+    /// ```
+    ///
+    /// Notes:
+    ///
+    /// - Lines and columns are zero-indexed.
+    /// - `char_idx` can be one-past-the-end, which will return the
+    ///   position at the end of the last line.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn char_to_position(&self, char_idx: usize) -> Position {
+        self.try_char_to_position(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the given line/column position, with the
+    /// column measured in chars.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line is out of bounds (i.e. `line >= len_lines()`), or
+    /// if the column is past the end of the line.
+    #[inline]
+    pub fn position_to_char(&self, position: Position) -> usize {
+        self.try_position_to_char(position).unwrap()
+    }
+
+    /// Returns the line/column position of the given byte, with the column
+    /// measured in bytes.
+    ///
+    /// Notes:
+    ///
+    /// - Lines and columns are zero-indexed.
+    /// - `byte_idx` can be one-past-the-end, which will return the
+    ///   position at the end of the last line.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
+    #[inline]
+    pub fn byte_to_position(&self, byte_idx: usize) -> Position {
+        self.try_byte_to_position(byte_idx).unwrap()
+    }
+
+    /// Returns the byte index of the given line/column position, with the
+    /// column measured in bytes.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line is out of bounds (i.e. `line >= len_lines()`), or
+    /// if the column is past the end of the line.
+    #[inline]
+    pub fn position_to_byte(&self, position: Position) -> usize {
+        self.try_position_to_byte(position).unwrap()
+    }
+
+    /// Returns the line/column position of the given utf16 code unit, with
+    /// the column measured in utf16 code units.
+    ///
+    /// Notes:
+    ///
+    /// - Lines and columns are zero-indexed.
+    /// - `utf16_cu_idx` can be one-past-the-end, which will return the
+    ///   position at the end of the last line.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `utf16_cu_idx` is out of bounds
+    /// (i.e. `utf16_cu_idx > len_utf16_cu()`).
+    #[inline]
+    pub fn utf16_cu_to_position(&self, utf16_cu_idx: usize) -> Position {
+        self.try_utf16_cu_to_position(utf16_cu_idx).unwrap()
+    }
+
+    /// Returns the utf16 code unit index of the given line/column position,
+    /// with the column measured in utf16 code units.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line is out of bounds (i.e. `line >= len_lines()`), or
+    /// if the column is past the end of the line.
+    #[inline]
+    pub fn position_to_utf16_cu(&self, position: Position) -> usize {
+        self.try_position_to_utf16_cu(position).unwrap()
     }
 
     //-----------------------------------------------------------------------
@@ -969,17 +1342,7 @@ impl Rope {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx >= len_bytes()`).
     #[inline]
     pub fn byte(&self, byte_idx: usize) -> u8 {
-        // Bounds check
-        assert!(
-            byte_idx < self.len_bytes(),
-            "Attempt to index past end of Rope: byte index {}, Rope byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
-
-        let (chunk, chunk_byte_idx, _, _) = self.chunk_at_byte(byte_idx);
-        let chunk_rel_byte_idx = byte_idx - chunk_byte_idx;
-        chunk.as_bytes()[chunk_rel_byte_idx]
+        self.try_byte(byte_idx).unwrap()
     }
 
     /// Returns the char at `char_idx`.
@@ -991,17 +1354,7 @@ impl Rope {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx >= len_chars()`).
     #[inline]
     pub fn char(&self, char_idx: usize) -> char {
-        // Bounds check
-        assert!(
-            char_idx < self.len_chars(),
-            "Attempt to index past end of Rope: char index {}, Rope char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        let (chunk, _, chunk_char_idx, _) = self.chunk_at_char(char_idx);
-        let byte_idx = char_to_byte_idx(chunk, char_idx - chunk_char_idx);
-        chunk[byte_idx..].chars().next().unwrap()
+        self.try_char(char_idx).unwrap()
     }
 
     /// Returns the line at `line_idx`.
@@ -1015,35 +1368,7 @@ impl Rope {
     /// Panics if `line_idx` is out of bounds (i.e. `line_idx >= len_lines()`).
     #[inline]
     pub fn line(&self, line_idx: usize) -> RopeSlice {
-        use crate::slice::RSEnum;
-        use crate::str_utils::{count_chars, count_utf16_surrogates};
-
-        let len_lines = self.len_lines();
-
-        // Bounds check
-        assert!(
-            line_idx < len_lines,
-            "Attempt to index past end of Rope: line index {}, Rope line length {}",
-            line_idx,
-            len_lines
-        );
-
-        let (chunk_1, _, c1, l1) = self.chunk_at_line_break(line_idx);
-        let (chunk_2, _, c2, l2) = self.chunk_at_line_break(line_idx + 1);
-        if c1 == c2 {
-            let text1 = &chunk_1[line_to_byte_idx(chunk_1, line_idx - l1)..];
-            let text2 = &text1[..line_to_byte_idx(text1, 1)];
-            RopeSlice(RSEnum::Light {
-                text: text2,
-                char_count: count_chars(text2) as Count,
-                utf16_surrogate_count: count_utf16_surrogates(text2) as Count,
-                line_break_count: if line_idx == (len_lines - 1) { 0 } else { 1 },
-            })
-        } else {
-            let start = c1 + line_to_char_idx(chunk_1, line_idx - l1);
-            let end = c2 + line_to_char_idx(chunk_2, line_idx + 1 - l2);
-            self.slice(start..end)
-        }
+        self.try_line(line_idx).unwrap()
     }
 
     /// Returns the chunk containing the given byte index.
@@ -1064,21 +1389,7 @@ impl Rope {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
     #[inline]
     pub fn chunk_at_byte(&self, byte_idx: usize) -> (&str, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            byte_idx <= self.len_bytes(),
-            "Attempt to index past end of Rope: byte index {}, Rope byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
-
-        let (chunk, info) = self.root.get_chunk_at_byte(byte_idx);
-        (
-            chunk,
-            info.bytes as usize,
-            info.chars as usize,
-            info.line_breaks as usize,
-        )
+        self.try_chunk_at_byte(byte_idx).unwrap()
     }
 
     /// Returns the chunk containing the given char index.
@@ -1099,21 +1410,7 @@ impl Rope {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn chunk_at_char(&self, char_idx: usize) -> (&str, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of Rope: char index {}, Rope char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        let (chunk, info) = self.root.get_chunk_at_char(char_idx);
-        (
-            chunk,
-            info.bytes as usize,
-            info.chars as usize,
-            info.line_breaks as usize,
-        )
+        self.try_chunk_at_char(char_idx).unwrap()
     }
 
     /// Returns the chunk containing the given line break.
@@ -1137,21 +1434,7 @@ impl Rope {
     /// Panics if `line_break_idx` is out of bounds (i.e. `line_break_idx > len_lines()`).
     #[inline]
     pub fn chunk_at_line_break(&self, line_break_idx: usize) -> (&str, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            line_break_idx <= self.len_lines(),
-            "Attempt to index past end of Rope: line break index {}, max index {}",
-            line_break_idx,
-            self.len_lines()
-        );
-
-        let (chunk, info) = self.root.get_chunk_at_line_break(line_break_idx);
-        (
-            chunk,
-            info.bytes as usize,
-            info.chars as usize,
-            info.line_breaks as usize,
-        )
+        self.try_chunk_at_line_break(line_break_idx).unwrap()
     }
 
     //-----------------------------------------------------------------------
@@ -1182,19 +1465,65 @@ impl Rope {
     where
         R: RangeBounds<usize>,
     {
-        let start = start_bound_to_num(char_range.start_bound()).unwrap_or(0);
-        let end = end_bound_to_num(char_range.end_bound()).unwrap_or_else(|| self.len_chars());
+        self.try_slice(char_range).unwrap()
+    }
 
-        // Bounds check
-        assert!(start <= end);
-        assert!(
-            end <= self.len_chars(),
-            "Attempt to slice past end of Rope: slice end {}, Rope length {}",
-            end,
-            self.len_chars()
-        );
+    /// Gets an immutable slice of the `Rope`, using byte indices.
+    ///
+    /// Uses range syntax, e.g. `2..7`, `2..`, etc.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ropey::Rope;
+    /// let rope = Rope::from_str("Hello wörld!");
+    /// let slice = rope.byte_slice(6..12);
+    ///
+    /// assert_eq!("wörld", slice);
+    /// ```
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, if the end
+    /// is out of bounds (i.e. `end > len_bytes()`), or if either end of the
+    /// range isn't on a char boundary.
+    #[inline]
+    pub fn byte_slice<R>(&self, byte_range: R) -> RopeSlice<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_byte_slice(byte_range).unwrap()
+    }
 
-        RopeSlice::new_with_range(&self.root, start, end)
+    /// Gets an immutable slice of the `Rope`, using utf16 code unit indices.
+    ///
+    /// Uses range syntax, e.g. `2..7`, `2..`, etc.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ropey::Rope;
+    /// let rope = Rope::from_str("Hello 🐸 world!");
+    /// let slice = rope.utf16_cu_slice(6..14);
+    ///
+    /// assert_eq!("🐸 world", slice);
+    /// ```
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, if the end
+    /// is out of bounds (i.e. `end > len_utf16_cu()`), or if either end of
+    /// the range is in the middle of a surrogate pair.
+    #[inline]
+    pub fn utf16_cu_slice<R>(&self, utf16_cu_range: R) -> RopeSlice<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_utf16_cu_slice(utf16_cu_range).unwrap()
     }
 
     //-----------------------------------------------------------------------
@@ -1221,22 +1550,7 @@ impl Rope {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
     #[inline]
     pub fn bytes_at(&self, byte_idx: usize) -> Bytes {
-        // Bounds check
-        assert!(
-            byte_idx <= self.len_bytes(),
-            "Attempt to index past end of Rope: byte index {}, Rope byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
-
-        let info = self.root.text_info();
-        Bytes::new_with_range_at(
-            &self.root,
-            byte_idx,
-            (0, info.bytes as usize),
-            (0, info.chars as usize),
-            (0, info.line_breaks as usize + 1),
-        )
+        self.try_bytes_at(byte_idx).unwrap()
     }
 
     /// Creates an iterator over the chars of the `Rope`.
@@ -1260,22 +1574,7 @@ impl Rope {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn chars_at(&self, char_idx: usize) -> Chars {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of Rope: char index {}, Rope char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        let info = self.root.text_info();
-        Chars::new_with_range_at(
-            &self.root,
-            char_idx,
-            (0, info.bytes as usize),
-            (0, info.chars as usize),
-            (0, info.line_breaks as usize + 1),
-        )
+        self.try_chars_at(char_idx).unwrap()
     }
 
     /// Creates an iterator over the lines of the `Rope`.
@@ -1299,20 +1598,7 @@ impl Rope {
     /// Panics if `line_idx` is out of bounds (i.e. `line_idx > len_lines()`).
     #[inline]
     pub fn lines_at(&self, line_idx: usize) -> Lines {
-        // Bounds check
-        assert!(
-            line_idx <= self.len_lines(),
-            "Attempt to index past end of Rope: line index {}, Rope line length {}",
-            line_idx,
-            self.len_lines()
-        );
-
-        Lines::new_with_range_at(
-            &self.root,
-            line_idx,
-            (0, self.len_bytes()),
-            (0, self.len_lines()),
-        )
+        self.try_lines_at(line_idx).unwrap()
     }
 
     /// Creates an iterator over the chunks of the `Rope`.
@@ -1342,21 +1628,7 @@ impl Rope {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
     #[inline]
     pub fn chunks_at_byte(&self, byte_idx: usize) -> (Chunks, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            byte_idx <= self.len_bytes(),
-            "Attempt to index past end of Rope: byte index {}, Rope byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
-
-        Chunks::new_with_range_at_byte(
-            &self.root,
-            byte_idx,
-            (0, self.len_bytes()),
-            (0, self.len_chars()),
-            (0, self.len_lines()),
-        )
+        self.try_chunks_at_byte(byte_idx).unwrap()
     }
 
     /// Creates an iterator over the chunks of the `Rope`, with the
@@ -1378,21 +1650,7 @@ impl Rope {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn chunks_at_char(&self, char_idx: usize) -> (Chunks, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of Rope: char index {}, Rope char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        Chunks::new_with_range_at_char(
-            &self.root,
-            char_idx,
-            (0, self.len_bytes()),
-            (0, self.len_chars()),
-            (0, self.len_lines()),
-        )
+        self.try_chunks_at_char(char_idx).unwrap()
     }
 
     /// Creates an iterator over the chunks of the `Rope`, with the
@@ -1418,43 +1676,1064 @@ impl Rope {
     /// Panics if `line_break_idx` is out of bounds (i.e. `line_break_idx > len_lines()`).
     #[inline]
     pub fn chunks_at_line_break(&self, line_break_idx: usize) -> (Chunks, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            line_break_idx <= self.len_lines(),
-            "Attempt to index past end of Rope: line break index {}, max index {}",
-            line_break_idx,
-            self.len_lines()
-        );
-
-        Chunks::new_with_range_at_line_break(
-            &self.root,
-            line_break_idx,
-            (0, self.len_bytes()),
-            (0, self.len_chars()),
-            (0, self.len_lines()),
-        )
+        self.try_chunks_at_line_break(line_break_idx).unwrap()
     }
 
-    //-----------------------------------------------------------------------
-    // Debugging
-
-    /// NOT PART OF THE PUBLIC API (hidden from docs for a reason!)
+    /// Creates a [`Cursor`](struct.Cursor.html) at the start of the `Rope`.
     ///
-    /// Debugging tool to make sure that all of the meta-data of the
-    /// tree is consistent with the actual data.
-    #[doc(hidden)]
-    pub fn assert_integrity(&self) {
-        self.root.assert_integrity();
+    /// Runs in O(log N) time.
+    #[inline]
+    pub fn cursor(&self) -> Cursor<'_> {
+        Cursor::new(self.slice(..), 0)
     }
 
-    /// NOT PART OF THE PUBLIC API (hidden from docs for a reason!)
+    /// Creates a [`Cursor`](struct.Cursor.html) at char index `char_idx`.
     ///
-    /// Debugging tool to make sure that all of the following invariants
-    /// hold true throughout the tree:
+    /// If `char_idx == len_chars()` the cursor is created at the end of the
+    /// `Rope`.
     ///
-    /// - The tree is the same height everywhere.
-    /// - All internal nodes have the minimum number of children.
-    /// - All leaf nodes are non-empty.
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn cursor_at(&self, char_idx: usize) -> Cursor<'_> {
+        self.try_cursor_at(char_idx).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Search methods
+
+    /// Returns the char index of the first match of `pattern` in the
+    /// `Rope`, or `None` if there is none.
+    ///
+    /// Matches that span chunk boundaries are found too.  An empty pattern
+    /// matches at the start of the `Rope`.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[inline]
+    pub fn find(&self, pattern: &str) -> Option<usize> {
+        self.slice(..).find(pattern)
+    }
+
+    /// Returns the char index of the start of the last match of `pattern`
+    /// in the `Rope`, or `None` if there is none.
+    ///
+    /// The search runs backwards from the end of the `Rope`.  An empty
+    /// pattern matches at the end of the `Rope`.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[inline]
+    pub fn rfind(&self, pattern: &str) -> Option<usize> {
+        self.slice(..).rfind(pattern)
+    }
+
+    /// Returns whether `pattern` occurs in the `Rope`.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[inline]
+    pub fn contains(&self, pattern: &str) -> bool {
+        self.slice(..).contains(pattern)
+    }
+
+    /// Creates an iterator over the char ranges of the non-overlapping
+    /// matches of `pattern` in the `Rope`, from front to back.
+    ///
+    /// As with `str::match_indices()`, an empty pattern matches at every
+    /// char boundary.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn find_iter<'a>(&'a self, pattern: &'a str) -> FindIter<'a> {
+        FindIter::new(self.slice(..), pattern)
+    }
+
+    /// Creates an iterator over the byte ranges of the non-overlapping
+    /// matches of `pattern` in the `Rope`, from front to back.
+    ///
+    /// This is the same as [`find_iter()`](#method.find_iter), but cheaper
+    /// since byte indices don't need to be converted to char indices.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn matches<'a>(&'a self, pattern: &'a str) -> Matches<'a> {
+        Matches::new(self.slice(..), pattern)
+    }
+
+    /// Returns the leftmost-first match of `regex` in the `Rope`, or `None`
+    /// if there is none.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[cfg(feature = "regex")]
+    #[inline]
+    pub fn find_regex(&self, regex: &Regex) -> Option<RegexMatch> {
+        find_regex(&self.slice(..), regex)
+    }
+
+    /// Returns the match of `regex` in the `Rope` that starts last, or
+    /// `None` if there is none.
+    ///
+    /// The search runs backwards from the end of the `Rope`, so this is
+    /// efficient for finding a match near the end.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[cfg(feature = "regex")]
+    #[inline]
+    pub fn rfind_regex(&self, regex: &Regex) -> Option<RegexMatch> {
+        rfind_regex(&self.slice(..), regex)
+    }
+
+    /// Creates an iterator over the non-overlapping matches of `regex` in
+    /// the `Rope`, from front to back.
+    ///
+    /// Runs in O(1) time.
+    #[cfg(feature = "regex")]
+    #[inline]
+    pub fn find_regex_iter<'a>(&'a self, regex: &'a Regex) -> FindRegexIter<'a> {
+        FindRegexIter::new(self.slice(..), regex)
+    }
+
+    //-----------------------------------------------------------------------
+    // Grapheme methods
+
+    /// Creates an iterator over the extended grapheme clusters of the
+    /// `Rope`.
+    ///
+    /// Runs in O(log N) time.
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn graphemes(&self) -> Graphemes<'_> {
+        self.slice(..).graphemes()
+    }
+
+    /// Creates an iterator over the extended grapheme clusters of the
+    /// `Rope`, starting at the grapheme boundary `char_idx`.
+    ///
+    /// If `char_idx` isn't a grapheme boundary, the iterator starts at the
+    /// boundary before it, so that the first call to `next()` returns the
+    /// grapheme containing `char_idx`.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn graphemes_at(&self, char_idx: usize) -> Graphemes<'_> {
+        self.try_graphemes_at(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the grapheme boundary before `char_idx`,
+    /// or 0 if there is none.
+    ///
+    /// Runs in O(log N) time, for graphemes of bounded length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn prev_grapheme_boundary(&self, char_idx: usize) -> usize {
+        self.try_prev_grapheme_boundary(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the grapheme boundary after `char_idx`,
+    /// or `len_chars()` if there is none.
+    ///
+    /// Runs in O(log N) time, for graphemes of bounded length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn next_grapheme_boundary(&self, char_idx: usize) -> usize {
+        self.try_next_grapheme_boundary(char_idx).unwrap()
+    }
+
+    /// Returns whether `char_idx` is a grapheme boundary.
+    ///
+    /// The start and end of the `Rope` are always grapheme boundaries.
+    ///
+    /// Runs in O(log N) time, for graphemes of bounded length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn is_grapheme_boundary(&self, char_idx: usize) -> bool {
+        self.try_is_grapheme_boundary(char_idx).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Word and sentence methods
+
+    /// Creates an iterator over the char ranges of the words of the
+    /// `Rope`, as determined by Unicode Standard Annex #29.
+    ///
+    /// Only the words themselves are yielded, skipping the whitespace and
+    /// punctuation between them.
+    ///
+    /// Runs in O(1) time.
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn words(&self) -> Words<'_> {
+        Words::new(self.slice(..))
+    }
+
+    /// Returns the char range of the word boundary segment containing the
+    /// char at `char_idx`: a word, or a run of whitespace, or a punctuation
+    /// char.  This is what e.g. double-clicking a word should select.
+    ///
+    /// Returns an empty range at the end if `char_idx == len_chars()`.
+    ///
+    /// Runs in O(log N) time plus the length of the segment, along with a
+    /// few hundred chars around it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn word_bounds_at(&self, char_idx: usize) -> Range<usize> {
+        self.try_word_bounds_at(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the start of the first word that starts
+    /// after `char_idx`, or `len_chars()` if there is none.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn next_word_start(&self, char_idx: usize) -> usize {
+        self.try_next_word_start(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the end of the last word that ends before
+    /// `char_idx`, or 0 if there is none.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn prev_word_end(&self, char_idx: usize) -> usize {
+        self.try_prev_word_end(char_idx).unwrap()
+    }
+
+    /// Creates an iterator over the char ranges of the sentences of the
+    /// `Rope`, as determined by Unicode Standard Annex #29.
+    ///
+    /// Runs in O(1) time.
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn sentences(&self) -> Sentences<'_> {
+        Sentences::new(self.slice(..))
+    }
+
+    /// Returns the char range of the sentence boundary segment containing
+    /// the char at `char_idx`.
+    ///
+    /// Returns an empty range at the end if `char_idx == len_chars()`.
+    ///
+    /// Runs in O(log N) time plus the length of the segment, along with a
+    /// few hundred chars around it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn sentence_bounds_at(&self, char_idx: usize) -> Range<usize> {
+        self.try_sentence_bounds_at(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the start of the first sentence that
+    /// starts after `char_idx`, or `len_chars()` if there is none.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn next_sentence_start(&self, char_idx: usize) -> usize {
+        self.try_next_sentence_start(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the end of the last sentence that ends
+    /// before `char_idx`, or 0 if there is none.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn prev_sentence_end(&self, char_idx: usize) -> usize {
+        self.try_prev_sentence_end(char_idx).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Visual column methods
+
+    /// Returns the display width of the `Rope`'s text, in columns, as
+    /// if it were shown on a single line.
+    ///
+    /// East Asian wide chars count as two columns and combining marks and
+    /// control chars as zero, as determined by `unicode-width`.  Tabs
+    /// advance to the next multiple of `tab_width`, and line breaks go back
+    /// to column zero.  For text without line breaks this is just its width.
+    ///
+    /// Runs in O(N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tab_width` is zero.
+    #[cfg(feature = "unicode-width")]
+    #[inline]
+    pub fn visual_width(&self, tab_width: usize) -> usize {
+        self.slice(..).visual_width(tab_width)
+    }
+
+    /// Returns the visual column of `char_idx` within its line, with tabs
+    /// expanded to multiples of `tab_width`.
+    ///
+    /// See [`visual_width()`](#method.visual_width) for how chars are
+    /// measured, and [`VisualColumns`](struct.VisualColumns.html) for a
+    /// version that doesn't need to scan from the start of the line.
+    ///
+    /// Runs in O(log N) time plus the length of the line up to `char_idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`),
+    /// or if `tab_width` is zero.
+    #[cfg(feature = "unicode-width")]
+    #[inline]
+    pub fn char_to_visual_col(&self, char_idx: usize, tab_width: usize) -> usize {
+        self.try_char_to_visual_col(char_idx, tab_width).unwrap()
+    }
+
+    /// Returns the char index at visual column `col` of line `line_idx`,
+    /// with tabs expanded to multiples of `tab_width`.
+    ///
+    /// If `col` is past the end of the line, the index of the end of the
+    /// line (before its line break) is returned.  If `col` falls in the
+    /// middle of a char that is more than one column wide, `rounding`
+    /// decides which side of it to return.  Zero-width chars at `col` are
+    /// skipped over.
+    ///
+    /// Runs in O(log N) time plus the length of the line up to `col`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line_idx` is out of bounds (i.e. `line_idx >= len_lines()`),
+    /// or if `tab_width` is zero.
+    #[cfg(feature = "unicode-width")]
+    #[inline]
+    pub fn visual_col_to_char(
+        &self,
+        line_idx: usize,
+        col: usize,
+        tab_width: usize,
+        rounding: ColumnRounding,
+    ) -> usize {
+        self.try_visual_col_to_char(line_idx, col, tab_width, rounding)
+            .unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Fallible versions of the above methods
+
+    /// Non-panicking version of [`insert()`](#method.insert).
+    #[inline]
+    pub fn try_insert(&mut self, char_idx: usize, text: &str) -> Result<()> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            self.insert_unchecked(char_idx, text);
+            Ok(())
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`insert_char()`](#method.insert_char).
+    #[inline]
+    pub fn try_insert_char(&mut self, char_idx: usize, ch: char) -> Result<()> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            let mut buf = [0u8; 4];
+            self.insert_internal(char_idx, ch.encode_utf8(&mut buf));
+            Ok(())
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`insert_at_byte()`](#method.insert_at_byte).
+    #[inline]
+    pub fn try_insert_at_byte(&mut self, byte_idx: usize, text: &str) -> Result<()> {
+        self.check_byte_idx(byte_idx)?;
+        self.insert_at_byte_unchecked(byte_idx, text);
+        Ok(())
+    }
+
+    /// Non-panicking version of [`insert_at_utf16_cu()`](#method.insert_at_utf16_cu).
+    #[inline]
+    pub fn try_insert_at_utf16_cu(&mut self, utf16_cu_idx: usize, text: &str) -> Result<()> {
+        let char_idx = self.check_utf16_cu_idx(utf16_cu_idx)?;
+        self.insert_unchecked(char_idx, text);
+        Ok(())
+    }
+
+    /// Non-panicking version of [`remove()`](#method.remove).
+    pub fn try_remove<R>(&mut self, char_range: R) -> Result<()>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = self.check_char_range(char_range)?;
+        self.remove_unchecked(start, end);
+        Ok(())
+    }
+
+    /// Non-panicking version of [`remove_byte_range()`](#method.remove_byte_range).
+    pub fn try_remove_byte_range<R>(&mut self, byte_range: R) -> Result<()>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = self.check_byte_range(byte_range)?;
+        let start_char = self.byte_to_char(start);
+        let end_char = self.byte_to_char(end);
+        self.remove_unchecked(start_char, end_char);
+        Ok(())
+    }
+
+    /// Non-panicking version of [`remove_utf16_cu_range()`](#method.remove_utf16_cu_range).
+    pub fn try_remove_utf16_cu_range<R>(&mut self, utf16_cu_range: R) -> Result<()>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = self.check_utf16_cu_range(utf16_cu_range)?;
+        self.remove_unchecked(start, end);
+        Ok(())
+    }
+
+    /// Non-panicking version of [`replace()`](#method.replace).
+    pub fn try_replace<R>(&mut self, char_range: R, text: &str) -> Result<()>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = self.check_char_range(char_range)?;
+        self.replace_unchecked(start, end, text);
+        Ok(())
+    }
+
+    /// Non-panicking version of [`split_off()`](#method.split_off).
+    pub fn try_split_off(&mut self, char_idx: usize) -> Result<Self> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(self.split_off_unchecked(char_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`byte_to_char()`](#method.byte_to_char).
+    #[inline]
+    pub fn try_byte_to_char(&self, byte_idx: usize) -> Result<usize> {
+        // Bounds check
+        if byte_idx <= self.len_bytes() {
+            let (chunk, b, c, _) = self.chunk_at_byte(byte_idx);
+            Ok(c + byte_to_char_idx(chunk, byte_idx - b))
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`byte_to_line()`](#method.byte_to_line).
+    #[inline]
+    pub fn try_byte_to_line(&self, byte_idx: usize) -> Result<usize> {
+        // Bounds check
+        if byte_idx <= self.len_bytes() {
+            let (chunk, b, _, l) = self.chunk_at_byte(byte_idx);
+            Ok(l + byte_to_line_idx(chunk, byte_idx - b))
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`byte_to_utf16_cu()`](#method.byte_to_utf16_cu).
+    #[inline]
+    pub fn try_byte_to_utf16_cu(&self, byte_idx: usize) -> Result<usize> {
+        let char_idx = self.try_byte_to_char(byte_idx)?;
+        Ok(self.char_to_utf16_cu(char_idx))
+    }
+
+    /// Non-panicking version of [`char_to_byte()`](#method.char_to_byte).
+    #[inline]
+    pub fn try_char_to_byte(&self, char_idx: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            let (chunk, b, c, _) = self.chunk_at_char(char_idx);
+            Ok(b + char_to_byte_idx(chunk, char_idx - c))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`char_to_line()`](#method.char_to_line).
+    #[inline]
+    pub fn try_char_to_line(&self, char_idx: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            let (chunk, _, c, l) = self.chunk_at_char(char_idx);
+            Ok(l + char_to_line_idx(chunk, char_idx - c))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`char_to_utf16_cu()`](#method.char_to_utf16_cu).
+    #[inline]
+    pub fn try_char_to_utf16_cu(&self, char_idx: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            let (chunk, chunk_start_info) = self.root.get_chunk_at_char(char_idx);
+            let chunk_byte_idx =
+                char_to_byte_idx(chunk, char_idx - chunk_start_info.chars as usize);
+            let surrogate_count = byte_to_utf16_surrogate_idx(chunk, chunk_byte_idx);
+
+            Ok(char_idx + chunk_start_info.utf16_surrogates as usize + surrogate_count)
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`utf16_cu_to_char()`](#method.utf16_cu_to_char).
+    #[inline]
+    pub fn try_utf16_cu_to_char(&self, utf16_cu_idx: usize) -> Result<usize> {
+        // Bounds check
+        if utf16_cu_idx <= self.len_utf16_cu() {
+            let (chunk, chunk_start_info) = self.root.get_chunk_at_utf16_code_unit(utf16_cu_idx);
+            let chunk_utf16_cu_idx = utf16_cu_idx
+                - (chunk_start_info.chars + chunk_start_info.utf16_surrogates) as usize;
+            let chunk_char_idx = utf16_code_unit_to_char_idx(chunk, chunk_utf16_cu_idx);
+
+            Ok(chunk_start_info.chars as usize + chunk_char_idx)
+        } else {
+            Err(Error::Utf16IndexOutOfBounds(
+                utf16_cu_idx,
+                self.len_utf16_cu(),
+            ))
+        }
+    }
+
+    /// Non-panicking version of [`utf16_cu_to_byte()`](#method.utf16_cu_to_byte).
+    #[inline]
+    pub fn try_utf16_cu_to_byte(&self, utf16_cu_idx: usize) -> Result<usize> {
+        let char_idx = self.try_utf16_cu_to_char(utf16_cu_idx)?;
+        Ok(self.char_to_byte(char_idx))
+    }
+
+    /// Non-panicking version of [`utf16_cu_to_line()`](#method.utf16_cu_to_line).
+    #[inline]
+    pub fn try_utf16_cu_to_line(&self, utf16_cu_idx: usize) -> Result<usize> {
+        let char_idx = self.try_utf16_cu_to_char(utf16_cu_idx)?;
+        Ok(self.char_to_line(char_idx))
+    }
+
+    /// Non-panicking version of [`line_to_byte()`](#method.line_to_byte).
+    #[inline]
+    pub fn try_line_to_byte(&self, line_idx: usize) -> Result<usize> {
+        // Bounds check
+        if line_idx <= self.len_lines() {
+            if line_idx == self.len_lines() {
+                Ok(self.len_bytes())
+            } else {
+                let (chunk, b, _, l) = self.chunk_at_line_break(line_idx);
+                Ok(b + line_to_byte_idx(chunk, line_idx - l))
+            }
+        } else {
+            Err(Error::LineIndexOutOfBounds(line_idx, self.len_lines()))
+        }
+    }
+
+    /// Non-panicking version of [`line_to_char()`](#method.line_to_char).
+    #[inline]
+    pub fn try_line_to_char(&self, line_idx: usize) -> Result<usize> {
+        // Bounds check
+        if line_idx <= self.len_lines() {
+            if line_idx == self.len_lines() {
+                Ok(self.len_chars())
+            } else {
+                let (chunk, _, c, l) = self.chunk_at_line_break(line_idx);
+                Ok(c + line_to_char_idx(chunk, line_idx - l))
+            }
+        } else {
+            Err(Error::LineIndexOutOfBounds(line_idx, self.len_lines()))
+        }
+    }
+
+    /// Non-panicking version of [`line_to_utf16_cu()`](#method.line_to_utf16_cu).
+    #[inline]
+    pub fn try_line_to_utf16_cu(&self, line_idx: usize) -> Result<usize> {
+        let char_idx = self.try_line_to_char(line_idx)?;
+        Ok(self.char_to_utf16_cu(char_idx))
+    }
+
+    /// Non-panicking version of [`char_to_position()`](#method.char_to_position).
+    #[inline]
+    pub fn try_char_to_position(&self, char_idx: usize) -> Result<Position> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(self.idx_to_position(char_idx, |info| info.chars as usize, char_to_byte_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`position_to_char()`](#method.position_to_char).
+    #[inline]
+    pub fn try_position_to_char(&self, position: Position) -> Result<usize> {
+        let (line_start, line_end) = self.line_bounds(position.line, |info| info.chars as usize)?;
+        check_column(position, line_start, line_end, self.len_lines())
+    }
+
+    /// Non-panicking version of [`byte_to_position()`](#method.byte_to_position).
+    #[inline]
+    pub fn try_byte_to_position(&self, byte_idx: usize) -> Result<Position> {
+        // Bounds check
+        if byte_idx <= self.len_bytes() {
+            Ok(self.idx_to_position(byte_idx, |info| info.bytes as usize, |_, idx| idx))
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`position_to_byte()`](#method.position_to_byte).
+    #[inline]
+    pub fn try_position_to_byte(&self, position: Position) -> Result<usize> {
+        let (line_start, line_end) = self.line_bounds(position.line, |info| info.bytes as usize)?;
+        check_column(position, line_start, line_end, self.len_lines())
+    }
+
+    /// Non-panicking version of [`utf16_cu_to_position()`](#method.utf16_cu_to_position).
+    #[inline]
+    pub fn try_utf16_cu_to_position(&self, utf16_cu_idx: usize) -> Result<Position> {
+        // Bounds check
+        if utf16_cu_idx <= self.len_utf16_cu() {
+            Ok(self.idx_to_position(
+                utf16_cu_idx,
+                |info| (info.chars + info.utf16_surrogates) as usize,
+                |text, idx| char_to_byte_idx(text, utf16_code_unit_to_char_idx(text, idx)),
+            ))
+        } else {
+            Err(Error::Utf16IndexOutOfBounds(
+                utf16_cu_idx,
+                self.len_utf16_cu(),
+            ))
+        }
+    }
+
+    /// Non-panicking version of [`position_to_utf16_cu()`](#method.position_to_utf16_cu).
+    #[inline]
+    pub fn try_position_to_utf16_cu(&self, position: Position) -> Result<usize> {
+        let (line_start, line_end) = self.line_bounds(position.line, |info| {
+            (info.chars + info.utf16_surrogates) as usize
+        })?;
+        check_column(position, line_start, line_end, self.len_lines())
+    }
+
+    /// Non-panicking version of [`byte()`](#method.byte).
+    #[inline]
+    pub fn try_byte(&self, byte_idx: usize) -> Result<u8> {
+        // Bounds check
+        if byte_idx < self.len_bytes() {
+            let (chunk, chunk_byte_idx, _, _) = self.chunk_at_byte(byte_idx);
+            let chunk_rel_byte_idx = byte_idx - chunk_byte_idx;
+            Ok(chunk.as_bytes()[chunk_rel_byte_idx])
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`char()`](#method.char).
+    #[inline]
+    pub fn try_char(&self, char_idx: usize) -> Result<char> {
+        // Bounds check
+        if char_idx < self.len_chars() {
+            let (chunk, _, chunk_char_idx, _) = self.chunk_at_char(char_idx);
+            let byte_idx = char_to_byte_idx(chunk, char_idx - chunk_char_idx);
+            Ok(chunk[byte_idx..].chars().next().unwrap())
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`line()`](#method.line).
+    #[inline]
+    pub fn try_line(&self, line_idx: usize) -> Result<RopeSlice<'_>> {
+        use crate::slice::RSEnum;
+        use crate::str_utils::{count_chars, count_utf16_surrogates};
+
+        let len_lines = self.len_lines();
+
+        // Bounds check
+        if line_idx < len_lines {
+            let (chunk_1, _, c1, l1) = self.chunk_at_line_break(line_idx);
+            let (chunk_2, _, c2, l2) = self.chunk_at_line_break(line_idx + 1);
+            if c1 == c2 {
+                let text1 = &chunk_1[line_to_byte_idx(chunk_1, line_idx - l1)..];
+                let text2 = &text1[..line_to_byte_idx(text1, 1)];
+                Ok(RopeSlice(RSEnum::Light {
+                    text: text2,
+                    char_count: count_chars(text2) as Count,
+                    utf16_surrogate_count: count_utf16_surrogates(text2) as Count,
+                    line_break_count: if line_idx == (len_lines - 1) { 0 } else { 1 },
+                }))
+            } else {
+                let start = c1 + line_to_char_idx(chunk_1, line_idx - l1);
+                let end = c2 + line_to_char_idx(chunk_2, line_idx + 1 - l2);
+                Ok(self.slice(start..end))
+            }
+        } else {
+            Err(Error::LineIndexOutOfBounds(line_idx, len_lines))
+        }
+    }
+
+    /// Non-panicking version of [`chunk_at_byte()`](#method.chunk_at_byte).
+    #[inline]
+    pub fn try_chunk_at_byte(&self, byte_idx: usize) -> Result<(&str, usize, usize, usize)> {
+        // Bounds check
+        if byte_idx <= self.len_bytes() {
+            let (chunk, info) = self.root.get_chunk_at_byte(byte_idx);
+            Ok((
+                chunk,
+                info.bytes as usize,
+                info.chars as usize,
+                info.line_breaks as usize,
+            ))
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`chunk_at_char()`](#method.chunk_at_char).
+    #[inline]
+    pub fn try_chunk_at_char(&self, char_idx: usize) -> Result<(&str, usize, usize, usize)> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            let (chunk, info) = self.root.get_chunk_at_char(char_idx);
+            Ok((
+                chunk,
+                info.bytes as usize,
+                info.chars as usize,
+                info.line_breaks as usize,
+            ))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of
+    /// [`chunk_at_line_break()`](#method.chunk_at_line_break).
+    #[inline]
+    pub fn try_chunk_at_line_break(
+        &self,
+        line_break_idx: usize,
+    ) -> Result<(&str, usize, usize, usize)> {
+        // Bounds check
+        if line_break_idx <= self.len_lines() {
+            let (chunk, info) = self.root.get_chunk_at_line_break(line_break_idx);
+            Ok((
+                chunk,
+                info.bytes as usize,
+                info.chars as usize,
+                info.line_breaks as usize,
+            ))
+        } else {
+            Err(Error::LineIndexOutOfBounds(
+                line_break_idx,
+                self.len_lines(),
+            ))
+        }
+    }
+
+    /// Non-panicking version of [`slice()`](#method.slice).
+    #[inline]
+    pub fn try_slice<R>(&self, char_range: R) -> Result<RopeSlice<'_>>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = self.check_char_range(char_range)?;
+        Ok(RopeSlice::new_with_range(&self.root, start, end))
+    }
+
+    /// Non-panicking version of [`byte_slice()`](#method.byte_slice).
+    #[inline]
+    pub fn try_byte_slice<R>(&self, byte_range: R) -> Result<RopeSlice<'_>>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = self.check_byte_range(byte_range)?;
+        Ok(RopeSlice::new_with_byte_range(&self.root, start, end))
+    }
+
+    /// Non-panicking version of [`utf16_cu_slice()`](#method.utf16_cu_slice).
+    #[inline]
+    pub fn try_utf16_cu_slice<R>(&self, utf16_cu_range: R) -> Result<RopeSlice<'_>>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = self.check_utf16_cu_range(utf16_cu_range)?;
+        Ok(RopeSlice::new_with_range(&self.root, start, end))
+    }
+
+    /// Non-panicking version of [`bytes_at()`](#method.bytes_at).
+    #[inline]
+    pub fn try_bytes_at(&self, byte_idx: usize) -> Result<Bytes<'_>> {
+        // Bounds check
+        if byte_idx <= self.len_bytes() {
+            let info = self.root.text_info();
+            Ok(Bytes::new_with_range_at(
+                &self.root,
+                byte_idx,
+                (0, info.bytes as usize),
+                (0, info.chars as usize),
+                (0, info.line_breaks as usize + 1),
+            ))
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`chars_at()`](#method.chars_at).
+    #[inline]
+    pub fn try_chars_at(&self, char_idx: usize) -> Result<Chars<'_>> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            let info = self.root.text_info();
+            Ok(Chars::new_with_range_at(
+                &self.root,
+                char_idx,
+                (0, info.bytes as usize),
+                (0, info.chars as usize),
+                (0, info.line_breaks as usize + 1),
+            ))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`lines_at()`](#method.lines_at).
+    #[inline]
+    pub fn try_lines_at(&self, line_idx: usize) -> Result<Lines<'_>> {
+        // Bounds check
+        if line_idx <= self.len_lines() {
+            Ok(Lines::new_with_range_at(
+                &self.root,
+                line_idx,
+                (0, self.len_bytes()),
+                (0, self.len_chars()),
+                (0, self.len_lines()),
+            ))
+        } else {
+            Err(Error::LineIndexOutOfBounds(line_idx, self.len_lines()))
+        }
+    }
+
+    /// Non-panicking version of [`chunks_at_byte()`](#method.chunks_at_byte).
+    #[inline]
+    pub fn try_chunks_at_byte(&self, byte_idx: usize) -> Result<(Chunks<'_>, usize, usize, usize)> {
+        // Bounds check
+        if byte_idx <= self.len_bytes() {
+            Ok(Chunks::new_with_range_at_byte(
+                &self.root,
+                byte_idx,
+                (0, self.len_bytes()),
+                (0, self.len_chars()),
+                (0, self.len_lines()),
+            ))
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`chunks_at_char()`](#method.chunks_at_char).
+    #[inline]
+    pub fn try_chunks_at_char(&self, char_idx: usize) -> Result<(Chunks<'_>, usize, usize, usize)> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(Chunks::new_with_range_at_char(
+                &self.root,
+                char_idx,
+                (0, self.len_bytes()),
+                (0, self.len_chars()),
+                (0, self.len_lines()),
+            ))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of
+    /// [`chunks_at_line_break()`](#method.chunks_at_line_break).
+    #[inline]
+    pub fn try_chunks_at_line_break(
+        &self,
+        line_break_idx: usize,
+    ) -> Result<(Chunks<'_>, usize, usize, usize)> {
+        // Bounds check
+        if line_break_idx <= self.len_lines() {
+            Ok(Chunks::new_with_range_at_line_break(
+                &self.root,
+                line_break_idx,
+                (0, self.len_bytes()),
+                (0, self.len_chars()),
+                (0, self.len_lines()),
+            ))
+        } else {
+            Err(Error::LineIndexOutOfBounds(
+                line_break_idx,
+                self.len_lines(),
+            ))
+        }
+    }
+
+    /// Non-panicking version of [`cursor_at()`](#method.cursor_at).
+    #[inline]
+    pub fn try_cursor_at(&self, char_idx: usize) -> Result<Cursor<'_>> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(Cursor::new(self.slice(..), char_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`graphemes_at()`](#method.graphemes_at).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn try_graphemes_at(&self, char_idx: usize) -> Result<Graphemes<'_>> {
+        self.slice(..).try_graphemes_at(char_idx)
+    }
+
+    /// Non-panicking version of [`prev_grapheme_boundary()`](#method.prev_grapheme_boundary).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn try_prev_grapheme_boundary(&self, char_idx: usize) -> Result<usize> {
+        self.slice(..).try_prev_grapheme_boundary(char_idx)
+    }
+
+    /// Non-panicking version of [`next_grapheme_boundary()`](#method.next_grapheme_boundary).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn try_next_grapheme_boundary(&self, char_idx: usize) -> Result<usize> {
+        self.slice(..).try_next_grapheme_boundary(char_idx)
+    }
+
+    /// Non-panicking version of [`is_grapheme_boundary()`](#method.is_grapheme_boundary).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn try_is_grapheme_boundary(&self, char_idx: usize) -> Result<bool> {
+        self.slice(..).try_is_grapheme_boundary(char_idx)
+    }
+
+    /// Non-panicking version of [`word_bounds_at()`](#method.word_bounds_at).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn try_word_bounds_at(&self, char_idx: usize) -> Result<Range<usize>> {
+        self.slice(..).try_word_bounds_at(char_idx)
+    }
+
+    /// Non-panicking version of [`next_word_start()`](#method.next_word_start).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn try_next_word_start(&self, char_idx: usize) -> Result<usize> {
+        self.slice(..).try_next_word_start(char_idx)
+    }
+
+    /// Non-panicking version of [`prev_word_end()`](#method.prev_word_end).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn try_prev_word_end(&self, char_idx: usize) -> Result<usize> {
+        self.slice(..).try_prev_word_end(char_idx)
+    }
+
+    /// Non-panicking version of [`sentence_bounds_at()`](#method.sentence_bounds_at).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn try_sentence_bounds_at(&self, char_idx: usize) -> Result<Range<usize>> {
+        self.slice(..).try_sentence_bounds_at(char_idx)
+    }
+
+    /// Non-panicking version of [`next_sentence_start()`](#method.next_sentence_start).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn try_next_sentence_start(&self, char_idx: usize) -> Result<usize> {
+        self.slice(..).try_next_sentence_start(char_idx)
+    }
+
+    /// Non-panicking version of [`prev_sentence_end()`](#method.prev_sentence_end).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn try_prev_sentence_end(&self, char_idx: usize) -> Result<usize> {
+        self.slice(..).try_prev_sentence_end(char_idx)
+    }
+
+    /// Non-panicking version of [`char_to_visual_col()`](#method.char_to_visual_col).
+    #[cfg(feature = "unicode-width")]
+    #[inline]
+    pub fn try_char_to_visual_col(&self, char_idx: usize, tab_width: usize) -> Result<usize> {
+        self.slice(..).try_char_to_visual_col(char_idx, tab_width)
+    }
+
+    /// Non-panicking version of [`visual_col_to_char()`](#method.visual_col_to_char).
+    #[cfg(feature = "unicode-width")]
+    #[inline]
+    pub fn try_visual_col_to_char(
+        &self,
+        line_idx: usize,
+        col: usize,
+        tab_width: usize,
+        rounding: ColumnRounding,
+    ) -> Result<usize> {
+        self.slice(..)
+            .try_visual_col_to_char(line_idx, col, tab_width, rounding)
+    }
+
+    //-----------------------------------------------------------------------
+    // Debugging
+
+    /// NOT PART OF THE PUBLIC API (hidden from docs for a reason!)
+    ///
+    /// Debugging tool to make sure that all of the meta-data of the
+    /// tree is consistent with the actual data.
+    #[doc(hidden)]
+    pub fn assert_integrity(&self) {
+        self.root.assert_integrity();
+    }
+
+    /// NOT PART OF THE PUBLIC API (hidden from docs for a reason!)
+    ///
+    /// Debugging tool to make sure that all of the following invariants
+    /// hold true throughout the tree:
+    ///
+    /// - The tree is the same height everywhere.
+    /// - All internal nodes have the minimum number of children.
+    /// - All leaf nodes are non-empty.
     /// - CRLF pairs are never split over chunk boundaries.
     #[doc(hidden)]
     pub fn assert_invariants(&self) {
@@ -1482,6 +2761,140 @@ impl Rope {
     //-----------------------------------------------------------------------
     // Internal utilities
 
+    /// Converts a char range to `(start, end)` indices, checking that it is
+    /// valid and in bounds.
+    pub(crate) fn check_char_range<R>(&self, char_range: R) -> Result<(usize, usize)>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start_opt = start_bound_to_num(char_range.start_bound());
+        let end_opt = end_bound_to_num(char_range.end_bound());
+        let start = start_opt.unwrap_or(0);
+        let end = end_opt.unwrap_or_else(|| self.len_chars());
+
+        if start > end {
+            Err(Error::CharRangeInvalid(start, end))
+        } else if end > self.len_chars() {
+            Err(Error::CharRangeOutOfBounds(
+                start_opt,
+                end_opt,
+                self.len_chars(),
+            ))
+        } else {
+            Ok((start, end))
+        }
+    }
+
+    /// Checks that a byte index is in bounds and on a char boundary.
+    fn check_byte_idx(&self, byte_idx: usize) -> Result<()> {
+        if byte_idx > self.len_bytes() {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        } else if !self.root.is_char_boundary(byte_idx) {
+            Err(Error::ByteIndexNotCharBoundary(byte_idx))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Converts a byte range to `(start, end)` indices, checking that it is
+    /// valid, in bounds, and on char boundaries.
+    fn check_byte_range<R>(&self, byte_range: R) -> Result<(usize, usize)>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start_opt = start_bound_to_num(byte_range.start_bound());
+        let end_opt = end_bound_to_num(byte_range.end_bound());
+        let start = start_opt.unwrap_or(0);
+        let end = end_opt.unwrap_or_else(|| self.len_bytes());
+
+        if start > end {
+            Err(Error::ByteRangeInvalid(start, end))
+        } else if end > self.len_bytes() {
+            Err(Error::ByteRangeOutOfBounds(
+                start_opt,
+                end_opt,
+                self.len_bytes(),
+            ))
+        } else if !self.root.is_char_boundary(start) {
+            Err(Error::ByteIndexNotCharBoundary(start))
+        } else if !self.root.is_char_boundary(end) {
+            Err(Error::ByteIndexNotCharBoundary(end))
+        } else {
+            Ok((start, end))
+        }
+    }
+
+    /// Checks that a utf16 code unit index is in bounds and not in the
+    /// middle of a surrogate pair, and returns the corresponding char index.
+    fn check_utf16_cu_idx(&self, utf16_cu_idx: usize) -> Result<usize> {
+        let char_idx = self.try_utf16_cu_to_char(utf16_cu_idx)?;
+        if self.char_to_utf16_cu(char_idx) == utf16_cu_idx {
+            Ok(char_idx)
+        } else {
+            Err(Error::Utf16IndexNotCharBoundary(utf16_cu_idx))
+        }
+    }
+
+    /// Converts a utf16 code unit range to `(start, end)` char indices,
+    /// checking that it is valid, in bounds, and not splitting any
+    /// surrogate pairs.
+    fn check_utf16_cu_range<R>(&self, utf16_cu_range: R) -> Result<(usize, usize)>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start_opt = start_bound_to_num(utf16_cu_range.start_bound());
+        let end_opt = end_bound_to_num(utf16_cu_range.end_bound());
+        let start = start_opt.unwrap_or(0);
+        let end = end_opt.unwrap_or_else(|| self.len_utf16_cu());
+
+        if start > end {
+            Err(Error::Utf16RangeInvalid(start, end))
+        } else if end > self.len_utf16_cu() {
+            Err(Error::Utf16RangeOutOfBounds(
+                start_opt,
+                end_opt,
+                self.len_utf16_cu(),
+            ))
+        } else {
+            Ok((
+                self.check_utf16_cu_idx(start)?,
+                self.check_utf16_cu_idx(end)?,
+            ))
+        }
+    }
+
+    /// Shared implementation of the `try_*_to_position()` methods.  See
+    /// `Node::line_start_info_at()` for the meaning of the parameters.
+    ///
+    /// Assumes that `idx` has already been bounds-checked.
+    fn idx_to_position(
+        &self,
+        idx: usize,
+        metric: fn(&TextInfo) -> usize,
+        to_byte_idx: fn(&str, usize) -> usize,
+    ) -> Position {
+        let line_start = self.root.line_start_info_at(idx, metric, to_byte_idx);
+        Position {
+            line: line_start.line_breaks as usize,
+            column: idx - metric(&line_start),
+        }
+    }
+
+    /// Returns the start and end of the given line, as indices in the given
+    /// metric.  The end includes the line break.
+    fn line_bounds(
+        &self,
+        line_idx: usize,
+        metric: fn(&TextInfo) -> usize,
+    ) -> Result<(usize, usize)> {
+        if line_idx < self.len_lines() {
+            let (start_info, end_info) = self.root.line_bounds_info(line_idx);
+            Ok((metric(&start_info), metric(&end_info)))
+        } else {
+            Err(Error::LineIndexOutOfBounds(line_idx, self.len_lines()))
+        }
+    }
+
     /// Iteratively replaces the root node with its child if it only has
     /// one child.
     pub(crate) fn pull_up_singular_nodes(&mut self) {
@@ -1497,6 +2910,96 @@ impl Rope {
     }
 }
 
+//==============================================================
+// Internal helpers
+
+/// Converts a position to an index, given the start and end indices of
+/// its line, checking that the column is within the line.
+///
+/// Only the last line may be indexed at its end, since the end of every
+/// other line is the start of the next one.
+pub(crate) fn check_column(
+    position: Position,
+    line_start: usize,
+    line_end: usize,
+    line_count: usize,
+) -> Result<usize> {
+    let line_len = line_end - line_start;
+    let is_last_line = position.line + 1 == line_count;
+    if position.column < line_len || (is_last_line && position.column == line_len) {
+        Ok(line_start + position.column)
+    } else {
+        Err(Error::ColumnIndexOutOfBounds(position.column, line_len))
+    }
+}
+
+/// Inserts `ins_text` into `leaf_text` at `byte_idx`, splitting the leaf
+/// if necessary.
+///
+/// Takes the current text info of the leaf, and returns the updated text
+/// info and the right-hand leaf if there was a split, in the form expected
+/// by the `Node::edit_chunk_at_*()` closures.
+///
+/// This only works correctly for insertion texts smaller than or equal to
+/// `MAX_BYTES - 4`.
+fn insert_into_leaf(
+    leaf_text: &mut NodeText,
+    cur_info: TextInfo,
+    byte_idx: usize,
+    ins_text: &str,
+) -> (TextInfo, Option<(TextInfo, Arc<Node>)>) {
+    // No node splitting
+    if (leaf_text.len() + ins_text.len()) <= MAX_BYTES {
+        // Calculate new info without doing a full re-scan of cur_text
+        let new_info = {
+            // Get summed info of current text and to-be-inserted text
+            let mut info = cur_info + TextInfo::from_str(ins_text);
+            // Check for CRLF pairs on the insertion seams, and
+            // adjust line break counts accordingly (only
+            // relevant when CR is a line break on its own)
+            if cfg!(feature = "cr_lines") {
+                if byte_idx > 0 {
+                    if leaf_text.as_bytes()[byte_idx - 1] == 0x0D && ins_text.as_bytes()[0] == 0x0A
+                    {
+                        info.line_breaks -= 1;
+                    }
+                    if byte_idx < leaf_text.len()
+                        && leaf_text.as_bytes()[byte_idx - 1] == 0x0D
+                        && leaf_text.as_bytes()[byte_idx] == 0x0A
+                    {
+                        info.line_breaks += 1;
+                    }
+                }
+                if byte_idx < leaf_text.len()
+                    && *ins_text.as_bytes().last().unwrap() == 0x0D
+                    && leaf_text.as_bytes()[byte_idx] == 0x0A
+                {
+                    info.line_breaks -= 1;
+                }
+            }
+            info
+        };
+        // Insert the text and return the new info
+        leaf_text.insert_str(byte_idx, ins_text);
+        (new_info, None)
+    }
+    // We're splitting the node
+    else {
+        let r_text = leaf_text.insert_str_split(byte_idx, ins_text);
+        let l_text_info = TextInfo::from_str(&leaf_text);
+        if r_text.len() > 0 {
+            let r_text_info = TextInfo::from_str(&r_text);
+            (
+                l_text_info,
+                Some((r_text_info, Arc::new(Node::Leaf(r_text)))),
+            )
+        } else {
+            // Leaf couldn't be validly split, so leave it oversized
+            (l_text_info, None)
+        }
+    }
+}
+
 //==============================================================
 // Conversion impls
 
@@ -1540,7 +3043,7 @@ impl<'a> From<RopeSlice<'a>> for Rope {
                 // Chop off right end if needed
                 if end_info.chars < node.text_info().chars {
                     {
-                        let root = Arc::make_mut(&mut rope.root);
+                        let root = Node::make_mut(&mut rope.root);
                         root.split(end_info.chars as usize);
                         root.zip_fix_right();
                     }
@@ -1550,7 +3053,7 @@ impl<'a> From<RopeSlice<'a>> for Rope {
                 // Chop off left end if needed
                 if start_info.chars > 0 {
                     {
-                        let root = Arc::make_mut(&mut rope.root);
+                        let root = Node::make_mut(&mut rope.root);
                         *root = root.split(start_info.chars as usize);
                         root.zip_fix_left();
                     }
@@ -1747,6 +3250,13 @@ impl std::cmp::PartialOrd<Rope> for Rope {
     }
 }
 
+impl std::hash::Hash for Rope {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.slice(..).hash(state)
+    }
+}
+
 //==============================================================
 
 #[cfg(test)]
@@ -1810,15 +3320,82 @@ mod tests {
     }
 
     #[test]
-    fn len_lines_01() {
-        let r = Rope::from_str(TEXT_LINES);
+    fn len_lines_01() {
+        let r = Rope::from_str(TEXT_LINES);
+        assert_eq!(r.len_lines(), 4);
+    }
+
+    #[test]
+    fn len_lines_02() {
+        let r = Rope::from_str("");
+        assert_eq!(r.len_lines(), 1);
+    }
+
+    #[test]
+    fn len_lines_03() {
+        let r = Rope::from_str("a\nb\r\nc\rd\u{000B}e\u{0085}f\u{2028}g");
+
+        #[cfg(feature = "unicode_lines")]
+        assert_eq!(r.len_lines(), 7);
+        #[cfg(all(feature = "cr_lines", not(feature = "unicode_lines")))]
+        assert_eq!(r.len_lines(), 4);
+        #[cfg(not(feature = "cr_lines"))]
+        assert_eq!(r.len_lines(), 3);
+    }
+
+    #[test]
+    #[cfg(all(feature = "cr_lines", not(feature = "unicode_lines")))]
+    fn len_lines_04() {
+        let r = Rope::from_str("a\nb\r\nc\rd\u{000B}e\u{0085}f\u{2028}g");
+
+        assert_eq!(r.char_to_line(2), 1);
+        assert_eq!(r.char_to_line(6), 2);
+        assert_eq!(r.char_to_line(7), 3);
+        assert_eq!(r.line(1), "b\r\n");
+        assert_eq!(r.line(2), "c\r");
+        assert_eq!(r.line(3), "d\u{000B}e\u{0085}f\u{2028}g");
+        let lines: Vec<_> = r.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[2], "c\r");
+    }
+
+    #[test]
+    #[cfg(not(feature = "cr_lines"))]
+    fn len_lines_05() {
+        let r = Rope::from_str("a\nb\r\nc\rd\u{000B}e\u{0085}f\u{2028}g");
+
+        assert_eq!(r.char_to_line(2), 1);
+        assert_eq!(r.char_to_line(6), 2);
+        assert_eq!(r.char_to_line(7), 2);
+        assert_eq!(r.line(1), "b\r\n");
+        assert_eq!(r.line(2), "c\rd\u{000B}e\u{0085}f\u{2028}g");
+        let lines: Vec<_> = r.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "b\r\n");
+    }
+
+    #[test]
+    #[cfg(not(feature = "cr_lines"))]
+    fn len_lines_06() {
+        // Edits next to lone CRs and CRLF pairs.
+        let mut r = Rope::from_str("Hello\r world!\n");
+        r.insert(6, "\nthere\r");
+        assert_eq!("Hello\r\nthere\r world!\n", r);
+        assert_eq!(r.len_lines(), 3);
+
+        r.insert(13, "\n");
         assert_eq!(r.len_lines(), 4);
-    }
 
-    #[test]
-    fn len_lines_02() {
-        let r = Rope::from_str("");
-        assert_eq!(r.len_lines(), 1);
+        r.remove(5..6);
+        assert_eq!("Hello\nthere\r\n world!\n", r);
+        assert_eq!(r.len_lines(), 4);
+
+        r.remove(11..13);
+        assert_eq!("Hello\nthere world!\n", r);
+        assert_eq!(r.len_lines(), 3);
+
+        r.assert_integrity();
+        r.assert_invariants();
     }
 
     #[test]
@@ -1978,6 +3555,105 @@ mod tests {
         r.assert_invariants();
     }
 
+    #[test]
+    fn insert_at_byte_01() {
+        let mut r = Rope::from_str(TEXT);
+        r.insert_at_byte(3, "AA");
+        r.insert_at_byte(96, "zopter");
+
+        assert_eq!(
+            r,
+            "HelAAlo there!  How're you doing?  It's \
+             a fine day, isn't it?  Aren't you glad \
+             we're alive?  こzopterんにちは、みんなさん！"
+        );
+
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    fn insert_at_byte_02() {
+        let mut r = Rope::new();
+        r.insert_at_byte(0, "こんいちは、みんなさん！");
+        r.insert_at_byte(21, "zopter");
+        r.insert_at_byte(0, "AA");
+        r.insert_at_byte(44, "BB");
+        assert_eq!("AAこんいちは、みzopterんなさん！BB", r);
+
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    #[cfg(feature = "cr_lines")]
+    fn insert_at_byte_03() {
+        // CRLF seams.
+        let mut r = Rope::from_str("Hello\r world!\n");
+        r.insert_at_byte(6, "\nthere\r");
+        assert_eq!("Hello\r\nthere\r world!\n", r);
+        assert_eq!(r.len_lines(), 4);
+
+        r.insert_at_byte(13, "\n");
+        assert_eq!("Hello\r\nthere\r\n world!\n", r);
+        assert_eq!(r.len_lines(), 4);
+
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    fn insert_at_byte_04() {
+        // Large insertion text.
+        let mut r = Rope::from_str(TEXT);
+        r.insert_at_byte(94, TEXT_LINES);
+
+        let mut s = String::from(TEXT);
+        s.insert_str(94, TEXT_LINES);
+        assert_eq!(r, s);
+
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_at_byte_05() {
+        let mut r = Rope::from_str(TEXT);
+        r.insert_at_byte(92, "AA"); // Not a char boundary
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_at_byte_06() {
+        let mut r = Rope::from_str(TEXT);
+        r.insert_at_byte(128, "AA"); // Past the end
+    }
+
+    #[test]
+    fn insert_at_utf16_cu_01() {
+        let mut r = Rope::from_str(TEXT_EMOJI);
+        r.insert_at_utf16_cu(111, "!");
+        r.insert_at_utf16_cu(14, "AA");
+        r.insert_at_utf16_cu(12, "BB");
+
+        let mut s = String::from(TEXT_EMOJI);
+        s.push('!');
+        s.insert_str(16, "AA");
+        s.insert_str(12, "BB");
+        assert_eq!(r, s);
+
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_at_utf16_cu_02() {
+        let mut r = Rope::from_str(TEXT_EMOJI);
+        r.insert_at_utf16_cu(13, "AA"); // Middle of a surrogate pair
+    }
+
     #[test]
     fn remove_01() {
         let mut r = Rope::from_str(TEXT);
@@ -2081,6 +3757,86 @@ mod tests {
         r.remove(104..105); // Removing past the end
     }
 
+    #[test]
+    fn remove_byte_range_01() {
+        let mut r = Rope::from_str(TEXT);
+
+        r.remove_byte_range(5..11);
+        r.remove_byte_range(85..94);
+        r.remove_byte_range(..2);
+        assert_eq!(
+            r,
+            "llo!  How're you doing?  It's \
+             a fine day, isn't it?  Aren't you glad \
+             we're alive?  ちは、みんなさん！"
+        );
+
+        r.remove_byte_range(89..);
+        assert_eq!(
+            r,
+            "llo!  How're you doing?  It's \
+             a fine day, isn't it?  Aren't you glad \
+             we're alive?  ちは"
+        );
+
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    fn remove_byte_range_02() {
+        let mut r = Rope::from_str("Hello\r\r\n\nworld!");
+
+        r.remove_byte_range(6..8);
+        assert_eq!("Hello\r\nworld!", r);
+        assert_eq!(r.len_lines(), 2);
+
+        r.remove_byte_range(..);
+        assert_eq!("", r);
+
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_byte_range_03() {
+        let mut r = Rope::from_str(TEXT);
+        r.remove_byte_range(90..92); // Not a char boundary
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_byte_range_04() {
+        let mut r = Rope::from_str(TEXT);
+        r.remove_byte_range(126..128); // Removing past the end
+    }
+
+    #[test]
+    fn remove_utf16_cu_range_01() {
+        let mut r = Rope::from_str(TEXT_EMOJI);
+
+        r.remove_utf16_cu_range(33..35);
+        r.remove_utf16_cu_range(12..14);
+        r.remove_utf16_cu_range(..5);
+
+        let mut s = String::from(TEXT_EMOJI);
+        s.replace_range(35..39, "");
+        s.replace_range(12..16, "");
+        s.replace_range(..5, "");
+        assert_eq!(r, s);
+
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_utf16_cu_range_02() {
+        let mut r = Rope::from_str(TEXT_EMOJI);
+        r.remove_utf16_cu_range(12..13); // Middle of a surrogate pair
+    }
+
     #[test]
     fn split_off_01() {
         let mut r = Rope::from_str(TEXT);
@@ -2277,6 +4033,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "cr_lines")]
     fn append_07() {
         let mut r = Rope::from_str("\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r");
         let r2 = Rope::from_str("\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r");
@@ -2577,6 +4334,201 @@ mod tests {
         r.utf16_cu_to_char(112);
     }
 
+    #[test]
+    fn byte_to_utf16_cu_01() {
+        let r = Rope::from_str(TEXT_EMOJI);
+
+        assert_eq!(0, r.byte_to_utf16_cu(0));
+        assert_eq!(12, r.byte_to_utf16_cu(12));
+        assert_eq!(12, r.byte_to_utf16_cu(13));
+        assert_eq!(12, r.byte_to_utf16_cu(15));
+        assert_eq!(14, r.byte_to_utf16_cu(16));
+        assert_eq!(33, r.byte_to_utf16_cu(35));
+        assert_eq!(35, r.byte_to_utf16_cu(39));
+        assert_eq!(111, r.byte_to_utf16_cu(143));
+    }
+
+    #[test]
+    #[should_panic]
+    fn byte_to_utf16_cu_02() {
+        let r = Rope::from_str(TEXT_EMOJI);
+        r.byte_to_utf16_cu(144);
+    }
+
+    #[test]
+    fn utf16_cu_to_byte_01() {
+        let r = Rope::from_str(TEXT_EMOJI);
+
+        assert_eq!(0, r.utf16_cu_to_byte(0));
+        assert_eq!(12, r.utf16_cu_to_byte(12));
+        assert_eq!(12, r.utf16_cu_to_byte(13));
+        assert_eq!(16, r.utf16_cu_to_byte(14));
+        assert_eq!(35, r.utf16_cu_to_byte(33));
+        assert_eq!(35, r.utf16_cu_to_byte(34));
+        assert_eq!(39, r.utf16_cu_to_byte(35));
+        assert_eq!(143, r.utf16_cu_to_byte(111));
+    }
+
+    #[test]
+    #[should_panic]
+    fn utf16_cu_to_byte_02() {
+        let r = Rope::from_str(TEXT_EMOJI);
+        r.utf16_cu_to_byte(112);
+    }
+
+    #[test]
+    fn line_to_utf16_cu_01() {
+        let r = Rope::from_str("Hello 🐸\nworld 🐸\r\n🐸!");
+
+        assert_eq!(0, r.line_to_utf16_cu(0));
+        assert_eq!(9, r.line_to_utf16_cu(1));
+        assert_eq!(19, r.line_to_utf16_cu(2));
+        assert_eq!(22, r.line_to_utf16_cu(3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn line_to_utf16_cu_02() {
+        let r = Rope::from_str("Hello 🐸\nworld 🐸\r\n🐸!");
+        r.line_to_utf16_cu(4);
+    }
+
+    #[test]
+    fn utf16_cu_to_line_01() {
+        let r = Rope::from_str("Hello 🐸\nworld 🐸\r\n🐸!");
+
+        assert_eq!(0, r.utf16_cu_to_line(0));
+        assert_eq!(0, r.utf16_cu_to_line(8));
+        assert_eq!(1, r.utf16_cu_to_line(9));
+        assert_eq!(1, r.utf16_cu_to_line(18));
+        assert_eq!(2, r.utf16_cu_to_line(19));
+        assert_eq!(2, r.utf16_cu_to_line(22));
+    }
+
+    #[test]
+    #[should_panic]
+    fn utf16_cu_to_line_02() {
+        let r = Rope::from_str("Hello 🐸\nworld 🐸\r\n🐸!");
+        r.utf16_cu_to_line(23);
+    }
+
+    #[test]
+    fn char_to_position_01() {
+        let r = Rope::from_str(TEXT_LINES);
+
+        assert_eq!(Position::new(0, 0), r.char_to_position(0));
+        assert_eq!(Position::new(0, 31), r.char_to_position(31));
+        assert_eq!(Position::new(1, 0), r.char_to_position(32));
+        assert_eq!(Position::new(1, 5), r.char_to_position(37));
+        assert_eq!(Position::new(3, 0), r.char_to_position(88));
+        assert_eq!(Position::new(3, 12), r.char_to_position(100));
+    }
+
+    #[test]
+    fn char_to_position_02() {
+        let r = Rope::from_str(TEXT_LINES);
+        for i in 0..=r.len_chars() {
+            let line_idx = r.char_to_line(i);
+            let pos = Position::new(line_idx, i - r.line_to_char(line_idx));
+            assert_eq!(pos, r.char_to_position(i));
+        }
+    }
+
+    #[test]
+    fn char_to_position_03() {
+        let r = Rope::from_str("Hello\r\n\r\n🐸 there!\n\u{2028}How's it\r\ngoing?\r\n");
+        for i in 0..=r.len_chars() {
+            let line_idx = r.char_to_line(i);
+            let pos = Position::new(line_idx, i - r.line_to_char(line_idx));
+            assert_eq!(pos, r.char_to_position(i));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn char_to_position_04() {
+        let r = Rope::from_str(TEXT_LINES);
+        r.char_to_position(101);
+    }
+
+    #[test]
+    fn position_to_char_01() {
+        let r = Rope::from_str(TEXT_LINES);
+        for i in 0..=r.len_chars() {
+            assert_eq!(i, r.position_to_char(r.char_to_position(i)));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn position_to_char_02() {
+        let r = Rope::from_str(TEXT_LINES);
+        r.position_to_char(Position::new(0, 32)); // Past the end of the line
+    }
+
+    #[test]
+    #[should_panic]
+    fn position_to_char_03() {
+        let r = Rope::from_str(TEXT_LINES);
+        r.position_to_char(Position::new(4, 0));
+    }
+
+    #[test]
+    fn byte_to_position_01() {
+        let r = Rope::from_str("Hello\r\n\r\n🐸 there!\n\u{2028}How's it\r\ngoing?\r\n");
+        for i in 0..=r.len_bytes() {
+            let line_idx = r.byte_to_line(i);
+            let pos = Position::new(line_idx, i - r.line_to_byte(line_idx));
+            assert_eq!(pos, r.byte_to_position(i));
+            assert_eq!(i, r.position_to_byte(pos));
+        }
+    }
+
+    #[test]
+    fn byte_to_position_02() {
+        let r = Rope::from_str(TEXT_LINES);
+        for i in 0..=r.len_bytes() {
+            let line_idx = r.byte_to_line(i);
+            let pos = Position::new(line_idx, i - r.line_to_byte(line_idx));
+            assert_eq!(pos, r.byte_to_position(i));
+            assert_eq!(i, r.position_to_byte(pos));
+        }
+    }
+
+    #[test]
+    fn utf16_cu_to_position_01() {
+        let r = Rope::from_str("Hello\r\n\r\n🐸 there!\n\u{2028}How's it\r\ngoing?\r\n");
+        for i in 0..=r.len_utf16_cu() {
+            let line_idx = r.utf16_cu_to_line(i);
+            let pos = Position::new(line_idx, i - r.line_to_utf16_cu(line_idx));
+            assert_eq!(pos, r.utf16_cu_to_position(i));
+            assert_eq!(i, r.position_to_utf16_cu(pos));
+        }
+    }
+
+    #[test]
+    fn utf16_cu_to_position_02() {
+        let r = Rope::from_str("Hello 🐸\nworld 🐸\r\n🐸!");
+
+        assert_eq!(Position::new(0, 8), r.utf16_cu_to_position(8));
+        assert_eq!(Position::new(1, 0), r.utf16_cu_to_position(9));
+        assert_eq!(Position::new(1, 8), r.utf16_cu_to_position(17));
+        assert_eq!(Position::new(2, 3), r.utf16_cu_to_position(22));
+        assert_eq!(22, r.position_to_utf16_cu(Position::new(2, 3)));
+    }
+
+    #[test]
+    fn position_01() {
+        // Empty rope and trailing line break.
+        let r = Rope::from_str("");
+        assert_eq!(Position::new(0, 0), r.char_to_position(0));
+        assert_eq!(0, r.position_to_char(Position::new(0, 0)));
+
+        let r = Rope::from_str("Hi\n");
+        assert_eq!(Position::new(1, 0), r.char_to_position(3));
+        assert_eq!(3, r.position_to_char(Position::new(1, 0)));
+    }
+
     #[test]
     fn byte_01() {
         let r = Rope::from_str(TEXT);
@@ -2825,23 +4777,92 @@ mod tests {
     fn slice_04() {
         let r = Rope::from_str(TEXT);
 
-        let s = r.slice(53..53);
+        let s = r.slice(53..53);
+
+        assert_eq!("", s);
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_05() {
+        let r = Rope::from_str(TEXT);
+        r.slice(53..52);
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_06() {
+        let r = Rope::from_str(TEXT);
+        r.slice(102..104);
+    }
+
+    #[test]
+    fn byte_slice_01() {
+        let r = Rope::from_str(TEXT);
+
+        let s = r.byte_slice(..);
+
+        assert_eq!(TEXT, s);
+    }
+
+    #[test]
+    fn byte_slice_02() {
+        let r = Rope::from_str(TEXT);
+
+        let s = r.byte_slice(5..21);
+
+        assert_eq!(&TEXT[5..21], s);
+    }
+
+    #[test]
+    fn byte_slice_03() {
+        let r = Rope::from_str(TEXT);
+
+        let s = r.byte_slice(31..109);
+
+        assert_eq!(&TEXT[31..109], s);
+        assert_eq!(s.len_chars(), 66);
+        assert_eq!(s, r.slice(31..97));
+    }
+
+    #[test]
+    fn byte_slice_04() {
+        let r = Rope::from_str(TEXT);
+
+        let s = r.byte_slice(94..94);
 
         assert_eq!("", s);
     }
 
     #[test]
     #[should_panic]
-    fn slice_05() {
+    fn byte_slice_05() {
         let r = Rope::from_str(TEXT);
-        r.slice(53..52);
+        r.byte_slice(31..110); // Not a char boundary
     }
 
     #[test]
     #[should_panic]
-    fn slice_06() {
+    fn byte_slice_06() {
         let r = Rope::from_str(TEXT);
-        r.slice(102..104);
+        r.byte_slice(126..128);
+    }
+
+    #[test]
+    fn utf16_cu_slice_01() {
+        let r = Rope::from_str(TEXT_EMOJI);
+
+        let s = r.utf16_cu_slice(12..35);
+
+        assert_eq!(&TEXT_EMOJI[12..39], s);
+        assert_eq!(s, r.slice(12..33));
+    }
+
+    #[test]
+    #[should_panic]
+    fn utf16_cu_slice_02() {
+        let r = Rope::from_str(TEXT_EMOJI);
+        r.utf16_cu_slice(13..20); // Middle of a surrogate pair
     }
 
     #[test]
@@ -2903,6 +4924,40 @@ mod tests {
         assert_eq!(s, r);
     }
 
+    #[test]
+    fn hash_rope_01() {
+        use std::collections::HashMap;
+
+        let mut r1 = Rope::from_str(TEXT);
+        let mut r2 = Rope::new();
+        for c in TEXT.chars().rev() {
+            r2.insert_char(0, c);
+        }
+
+        let mut map = HashMap::new();
+        map.insert(r1.clone(), 1);
+        assert_eq!(map.get(&r2), Some(&1));
+
+        r1.insert(0, "a");
+        assert_eq!(map.get(&r1), None);
+    }
+
+    #[test]
+    fn hash_rope_02() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        fn hash<T: Hash + ?Sized>(x: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            x.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        assert_eq!(hash(&Rope::from_str(TEXT)), hash(TEXT));
+        assert_eq!(hash(&Rope::from_str("")), hash(""));
+        assert_ne!(hash(&Rope::from_str("a")), hash(&Rope::from_str("ab")));
+    }
+
     #[test]
     fn to_string_01() {
         let r = Rope::from_str(TEXT);
@@ -2988,5 +5043,511 @@ mod tests {
         assert_eq!(r1, r2);
     }
 
+    #[test]
+    fn try_insert_01() {
+        let mut r = Rope::from_str(TEXT);
+        assert_eq!(r.try_insert(103, "!"), Ok(()));
+        assert_eq!(
+            r.try_insert(105, "!"),
+            Err(Error::CharIndexOutOfBounds(105, 104))
+        );
+
+        assert_eq!(r.len_chars(), 104);
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    fn try_insert_char_01() {
+        let mut r = Rope::from_str("Hello");
+        assert_eq!(
+            r.try_insert_char(6, '!'),
+            Err(Error::CharIndexOutOfBounds(6, 5))
+        );
+        assert_eq!(r.try_insert_char(5, '!'), Ok(()));
+        assert_eq!(r, "Hello!");
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_remove_01() {
+        let mut r = Rope::from_str(TEXT);
+
+        assert_eq!(r.try_remove(5..4), Err(Error::CharRangeInvalid(5, 4)));
+        assert_eq!(
+            r.try_remove(5..104),
+            Err(Error::CharRangeOutOfBounds(Some(5), Some(104), 103))
+        );
+        assert_eq!(r.try_remove(104..), Err(Error::CharRangeInvalid(104, 103)));
+        assert_eq!(r, TEXT);
+
+        assert_eq!(r.try_remove(5..), Ok(()));
+        assert_eq!(r, "Hello");
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    fn try_split_off_01() {
+        let mut r = Rope::from_str(TEXT);
+
+        assert_eq!(
+            r.try_split_off(104).unwrap_err(),
+            Error::CharIndexOutOfBounds(104, 103)
+        );
+        let r2 = r.try_split_off(5).unwrap();
+        assert_eq!(r, "Hello");
+        assert_eq!(r2, &TEXT[5..]);
+    }
+
+    #[test]
+    fn try_index_conversion_01() {
+        let r = Rope::from_str(TEXT_LINES);
+
+        assert_eq!(r.try_byte_to_char(124), Ok(100));
+        assert_eq!(r.try_byte_to_line(124), Ok(3));
+        assert_eq!(r.try_char_to_byte(100), Ok(124));
+        assert_eq!(r.try_char_to_line(100), Ok(3));
+        assert_eq!(r.try_line_to_char(4), Ok(100));
+        assert_eq!(r.try_line_to_byte(4), Ok(124));
+        assert_eq!(r.try_char_to_utf16_cu(100), Ok(100));
+        assert_eq!(r.try_utf16_cu_to_char(100), Ok(100));
+
+        assert_eq!(
+            r.try_byte_to_char(125),
+            Err(Error::ByteIndexOutOfBounds(125, 124))
+        );
+        assert_eq!(
+            r.try_byte_to_line(125),
+            Err(Error::ByteIndexOutOfBounds(125, 124))
+        );
+        assert_eq!(
+            r.try_char_to_byte(101),
+            Err(Error::CharIndexOutOfBounds(101, 100))
+        );
+        assert_eq!(
+            r.try_char_to_line(101),
+            Err(Error::CharIndexOutOfBounds(101, 100))
+        );
+        assert_eq!(
+            r.try_line_to_char(5),
+            Err(Error::LineIndexOutOfBounds(5, 4))
+        );
+        assert_eq!(
+            r.try_line_to_byte(5),
+            Err(Error::LineIndexOutOfBounds(5, 4))
+        );
+        assert_eq!(
+            r.try_char_to_utf16_cu(101),
+            Err(Error::CharIndexOutOfBounds(101, 100))
+        );
+        assert_eq!(
+            r.try_utf16_cu_to_char(101),
+            Err(Error::Utf16IndexOutOfBounds(101, 100))
+        );
+    }
+
+    #[test]
+    fn try_fetch_01() {
+        let r = Rope::from_str(TEXT_LINES);
+
+        assert_eq!(r.try_byte(0), Ok(b'H'));
+        assert_eq!(r.try_char(99), Ok('！'));
+        assert_eq!(r.try_line(3).unwrap(), "こんにちは、みんなさん！");
+        assert_eq!(r.try_chunk_at_byte(124).unwrap(), r.chunk_at_byte(124));
+        assert_eq!(r.try_chunk_at_char(100).unwrap(), r.chunk_at_char(100));
+        assert_eq!(
+            r.try_chunk_at_line_break(4).unwrap(),
+            r.chunk_at_line_break(4)
+        );
+
+        assert_eq!(r.try_byte(124), Err(Error::ByteIndexOutOfBounds(124, 124)));
+        assert_eq!(r.try_char(100), Err(Error::CharIndexOutOfBounds(100, 100)));
+        assert_eq!(
+            r.try_line(4).unwrap_err(),
+            Error::LineIndexOutOfBounds(4, 4)
+        );
+        assert_eq!(
+            r.try_chunk_at_byte(125).unwrap_err(),
+            Error::ByteIndexOutOfBounds(125, 124)
+        );
+        assert_eq!(
+            r.try_chunk_at_char(101).unwrap_err(),
+            Error::CharIndexOutOfBounds(101, 100)
+        );
+        assert_eq!(
+            r.try_chunk_at_line_break(5).unwrap_err(),
+            Error::LineIndexOutOfBounds(5, 4)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_slice_01() {
+        let r = Rope::from_str(TEXT);
+
+        assert_eq!(r.try_slice(5..21).unwrap(), &TEXT[5..21]);
+        assert_eq!(
+            r.try_slice(21..5).unwrap_err(),
+            Error::CharRangeInvalid(21, 5)
+        );
+        assert_eq!(
+            r.try_slice(..104).unwrap_err(),
+            Error::CharRangeOutOfBounds(None, Some(104), 103)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_insert_at_byte_01() {
+        let mut r = Rope::from_str(TEXT);
+        assert_eq!(
+            r.try_insert_at_byte(128, "!"),
+            Err(Error::ByteIndexOutOfBounds(128, 127))
+        );
+        assert_eq!(
+            r.try_insert_at_byte(92, "!"),
+            Err(Error::ByteIndexNotCharBoundary(92))
+        );
+        assert_eq!(r, TEXT);
+        assert_eq!(r.try_insert_at_byte(127, "!"), Ok(()));
+
+        assert_eq!(r.len_bytes(), 128);
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_remove_byte_range_01() {
+        let mut r = Rope::from_str(TEXT);
+
+        assert_eq!(
+            r.try_remove_byte_range(5..4),
+            Err(Error::ByteRangeInvalid(5, 4))
+        );
+        assert_eq!(
+            r.try_remove_byte_range(120..128),
+            Err(Error::ByteRangeOutOfBounds(Some(120), Some(128), 127))
+        );
+        assert_eq!(
+            r.try_remove_byte_range(92..),
+            Err(Error::ByteIndexNotCharBoundary(92))
+        );
+        assert_eq!(
+            r.try_remove_byte_range(..93),
+            Err(Error::ByteIndexNotCharBoundary(93))
+        );
+        assert_eq!(r, TEXT);
+
+        assert_eq!(r.try_remove_byte_range(91..), Ok(()));
+        assert_eq!(r, &TEXT[..91]);
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_byte_slice_01() {
+        let r = Rope::from_str(TEXT);
+
+        assert_eq!(r.try_byte_slice(5..21).unwrap(), &TEXT[5..21]);
+        assert_eq!(
+            r.try_byte_slice(21..5).unwrap_err(),
+            Error::ByteRangeInvalid(21, 5)
+        );
+        assert_eq!(
+            r.try_byte_slice(..128).unwrap_err(),
+            Error::ByteRangeOutOfBounds(None, Some(128), 127)
+        );
+        assert_eq!(
+            r.try_byte_slice(95..).unwrap_err(),
+            Error::ByteIndexNotCharBoundary(95)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_utf16_cu_01() {
+        let mut r = Rope::from_str(TEXT_EMOJI);
+
+        assert_eq!(r.try_byte_to_utf16_cu(143), Ok(111));
+        assert_eq!(
+            r.try_byte_to_utf16_cu(144),
+            Err(Error::ByteIndexOutOfBounds(144, 143))
+        );
+        assert_eq!(r.try_utf16_cu_to_byte(111), Ok(143));
+        assert_eq!(
+            r.try_utf16_cu_to_byte(112),
+            Err(Error::Utf16IndexOutOfBounds(112, 111))
+        );
+        assert_eq!(r.try_utf16_cu_to_line(111), Ok(0));
+        assert_eq!(
+            r.try_utf16_cu_to_line(112),
+            Err(Error::Utf16IndexOutOfBounds(112, 111))
+        );
+        assert_eq!(r.try_line_to_utf16_cu(1), Ok(111));
+        assert_eq!(
+            r.try_line_to_utf16_cu(2),
+            Err(Error::LineIndexOutOfBounds(2, 1))
+        );
+
+        assert_eq!(
+            r.try_utf16_cu_slice(14..12).unwrap_err(),
+            Error::Utf16RangeInvalid(14, 12)
+        );
+        assert_eq!(r.try_utf16_cu_slice(12..).unwrap(), r.slice(12..));
+        assert_eq!(
+            r.try_utf16_cu_slice(13..).unwrap_err(),
+            Error::Utf16IndexNotCharBoundary(13)
+        );
+        assert_eq!(
+            r.try_remove_utf16_cu_range(..112),
+            Err(Error::Utf16RangeOutOfBounds(None, Some(112), 111))
+        );
+        assert_eq!(
+            r.try_remove_utf16_cu_range(..13),
+            Err(Error::Utf16IndexNotCharBoundary(13))
+        );
+        assert_eq!(
+            r.try_insert_at_utf16_cu(112, "!"),
+            Err(Error::Utf16IndexOutOfBounds(112, 111))
+        );
+        assert_eq!(
+            r.try_insert_at_utf16_cu(13, "!"),
+            Err(Error::Utf16IndexNotCharBoundary(13))
+        );
+        assert_eq!(r, TEXT_EMOJI);
+
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    fn try_position_01() {
+        let r = Rope::from_str(TEXT_LINES);
+
+        assert_eq!(r.try_char_to_position(100), Ok(Position::new(3, 12)));
+        assert_eq!(
+            r.try_char_to_position(101),
+            Err(Error::CharIndexOutOfBounds(101, 100))
+        );
+        assert_eq!(r.try_position_to_char(Position::new(3, 12)), Ok(100));
+        assert_eq!(
+            r.try_position_to_char(Position::new(0, 32)),
+            Err(Error::ColumnIndexOutOfBounds(32, 32))
+        );
+        assert_eq!(
+            r.try_position_to_char(Position::new(3, 13)),
+            Err(Error::ColumnIndexOutOfBounds(13, 12))
+        );
+        assert_eq!(
+            r.try_position_to_char(Position::new(4, 0)),
+            Err(Error::LineIndexOutOfBounds(4, 4))
+        );
+        assert_eq!(
+            r.try_byte_to_position(125),
+            Err(Error::ByteIndexOutOfBounds(125, 124))
+        );
+        assert_eq!(
+            r.try_position_to_byte(Position::new(3, 37)),
+            Err(Error::ColumnIndexOutOfBounds(37, 36))
+        );
+        assert_eq!(
+            r.try_utf16_cu_to_position(101),
+            Err(Error::Utf16IndexOutOfBounds(101, 100))
+        );
+        assert_eq!(
+            r.try_position_to_utf16_cu(Position::new(1, 30)),
+            Err(Error::ColumnIndexOutOfBounds(30, 27))
+        );
+    }
+
+    #[test]
+    fn try_iter_at_01() {
+        let r = Rope::from_str(TEXT_LINES);
+
+        assert!(r.try_bytes_at(124).is_ok());
+        assert!(r.try_chars_at(100).is_ok());
+        assert!(r.try_lines_at(4).is_ok());
+        assert!(r.try_chunks_at_byte(124).is_ok());
+        assert!(r.try_chunks_at_char(100).is_ok());
+        assert!(r.try_chunks_at_line_break(4).is_ok());
+
+        assert!(r.try_bytes_at(125).is_err());
+        assert!(r.try_chars_at(101).is_err());
+        assert!(r.try_lines_at(5).is_err());
+        assert!(r.try_chunks_at_byte(125).is_err());
+        assert!(r.try_chunks_at_char(101).is_err());
+        assert!(r.try_chunks_at_line_break(5).is_err());
+    }
+
+    /// Naive longest line, for checking `longest_line()` against.
+    fn longest_line_slow(text: &str) -> (usize, usize) {
+        let mut longest = (0, 0);
+        for (i, line) in Rope::from_str(text).lines().enumerate() {
+            let line = line.to_string();
+            let mut len = line.chars().count();
+            if line.ends_with("\r\n") && cfg!(feature = "cr_lines") {
+                len -= 2;
+            } else if line.ends_with(|c| {
+                c == '\n'
+                    || (cfg!(feature = "cr_lines") && c == '\r')
+                    || (cfg!(feature = "unicode_lines")
+                        && ['\u{000B}', '\u{000C}', '\u{0085}', '\u{2028}', '\u{2029}']
+                            .contains(&c))
+            }) {
+                len -= 1;
+            }
+            if len > longest.1 {
+                longest = (i, len);
+            }
+        }
+        longest
+    }
+
+    #[test]
+    fn longest_line_01() {
+        assert_eq!(Rope::from_str("").longest_line(), (0, 0));
+        assert_eq!(Rope::from_str("\n\n").longest_line(), (0, 0));
+        assert_eq!(Rope::from_str(TEXT).longest_line(), (0, 103));
+        assert_eq!(Rope::from_str(TEXT_LINES).longest_line(), (0, 31));
+        assert_eq!(
+            Rope::from_str("ab\r\nabc\r\nab").longest_line(),
+            longest_line_slow("ab\r\nabc\r\nab")
+        );
+        assert_eq!(Rope::from_str("a\nb\nc").longest_line(), (0, 1));
+        assert_eq!(Rope::from_str("a\nb\ncd").longest_line(), (2, 2));
+        assert_eq!(
+            Rope::from_str(&TEXT_LINES.repeat(10)).longest_line(),
+            longest_line_slow(&TEXT_LINES.repeat(10))
+        );
+    }
+
+    #[test]
+    fn longest_line_02() {
+        let mut r = Rope::new();
+        let pieces = [
+            "Hello\r\n",
+            "world, how are you?",
+            "\n",
+            "\r",
+            "\u{2028}a much longer line than the others\u{0085}",
+            "こんにちは",
+            "\r\n\r\n",
+        ];
+        for i in 0..200 {
+            let len = r.len_chars();
+            r.insert((i * 37) % (len + 1), pieces[i % pieces.len()]);
+            if i % 3 == 0 {
+                let len = r.len_chars();
+                let start = (i * 13) % (len + 1);
+                r.remove(start..(start + 7).min(len));
+            }
+            r.assert_integrity();
+            assert_eq!(r.longest_line(), longest_line_slow(&r.to_string()));
+
+            // Slices use the same cached line lengths.
+            let len = r.len_chars();
+            let s = r.slice((len / 3)..(len - len / 5));
+            assert_eq!(s.longest_line(), longest_line_slow(&s.to_string()));
+        }
+    }
+
+    #[test]
+    fn replace_01() {
+        let mut r = Rope::from_str(TEXT);
+        r.replace(6..11, "everyone");
+        assert_eq!(r, TEXT.replacen("there", "everyone", 1));
+        r.replace(0..5, "");
+        r.replace(0..0, "Oh,");
+        r.replace(r.len_chars().., "!");
+        assert_eq!(
+            r,
+            "Oh, everyone!  How're you doing?  It's a fine day, \
+             isn't it?  Aren't you glad we're alive?  \
+             こんにちは、みんなさん！!"
+        );
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    fn replace_02() {
+        // Small replacements inside chunks, and large ones across them.
+        let mut r = Rope::from_str(TEXT);
+        let mut s = TEXT.to_string();
+        let pieces = [
+            "a",
+            "\r\n",
+            "こん",
+            "",
+            "a longer piece of text than a chunk\r",
+        ];
+        for i in 0..100 {
+            let len = r.len_chars();
+            let start = (i * 37) % (len + 1);
+            let end = (start + i % 9).min(len);
+            let piece = pieces[i % pieces.len()];
+            r.replace(start..end, piece);
+
+            let byte_start = s
+                .char_indices()
+                .map(|(i, _)| i)
+                .nth(start)
+                .unwrap_or(s.len());
+            let byte_end = s.char_indices().map(|(i, _)| i).nth(end).unwrap_or(s.len());
+            s.replace_range(byte_start..byte_end, piece);
+
+            assert_eq!(r, s.as_str());
+            r.assert_integrity();
+            r.assert_invariants();
+        }
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_replace_01() {
+        let mut r = Rope::from_str(TEXT);
+
+        assert_eq!(r.try_replace(5..4, "a"), Err(Error::CharRangeInvalid(5, 4)));
+        assert_eq!(
+            r.try_replace(5..104, "a"),
+            Err(Error::CharRangeOutOfBounds(Some(5), Some(104), 103))
+        );
+        assert_eq!(r, TEXT);
+
+        assert_eq!(r.try_replace(5.., "!"), Ok(()));
+        assert_eq!(r, "Hello!");
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    fn replace_all_01() {
+        // Few matches, replaced in place.
+        let mut r = Rope::from_str(TEXT);
+        assert_eq!(r.replace_all("you", "y'all"), 2);
+        assert_eq!(r, TEXT.replace("you", "y'all"));
+        assert_eq!(r.replace_all("zzz", "a"), 0);
+        assert_eq!(r, TEXT.replace("you", "y'all"));
+        r.assert_integrity();
+        r.assert_invariants();
+    }
+
+    #[test]
+    fn replace_all_02() {
+        // Many matches, rebuilt.
+        let text = TEXT.repeat(10);
+        for &(pattern, replacement) in &[("o", "0"), ("'", ""), ("ん", "\r\n"), ("", "-")] {
+            let mut r = Rope::from_str(&text);
+            let count = text.matches(pattern).count();
+            assert_eq!(r.replace_all(pattern, replacement), count);
+            assert_eq!(r, text.replace(pattern, replacement));
+            r.assert_integrity();
+            r.assert_invariants();
+        }
+    }
+
     // Iterator tests are in the iter module
 }
@@ -124,7 +124,8 @@ impl RopeBuilder {
         let mut stack_idx = self.stack.len() - 1;
         while stack_idx >= 1 {
             let node = self.stack.pop().unwrap();
-            if let Node::Internal(ref mut children) = *Arc::make_mut(&mut self.stack[stack_idx - 1])
+            if let Node::Internal(ref mut children) =
+                *Node::make_mut(&mut self.stack[stack_idx - 1])
             {
                 children.push((node.text_info(), node));
             } else {
@@ -135,7 +136,7 @@ impl RopeBuilder {
 
         // Get root and fix any right-side nodes with too few children.
         let mut root = self.stack.pop().unwrap();
-        Arc::make_mut(&mut root).zip_fix_right();
+        Node::make_mut(&mut root).zip_fix_right();
 
         // Create the rope, make sure it's well-formed, and return it.
         let mut rope = Rope { root: root };
@@ -228,14 +229,14 @@ impl RopeBuilder {
                         break;
                     } else if self.stack[stack_idx as usize].child_count() < (MAX_CHILDREN - 1) {
                         // There's room to add a child, so do that.
-                        Arc::make_mut(&mut self.stack[stack_idx as usize])
+                        Node::make_mut(&mut self.stack[stack_idx as usize])
                             .children_mut()
                             .push((left.text_info(), left));
                         break;
                     } else {
                         // Not enough room to fit a child, so split.
                         left = Arc::new(Node::Internal(
-                            Arc::make_mut(&mut self.stack[stack_idx as usize])
+                            Node::make_mut(&mut self.stack[stack_idx as usize])
                                 .children_mut()
                                 .push_split((left.text_info(), left)),
                         ));
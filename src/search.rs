@@ -0,0 +1,381 @@
+//! Substring search over a `RopeSlice`'s chunks.
+//!
+//! Matches within a chunk are found with the standard library's substring
+//! search (the two-way algorithm) directly on the chunk, and only the few
+//! bytes around each chunk seam are copied to find matches that straddle
+//! it.  So searching takes no more memory than the length of the pattern,
+//! regardless of how the text is split into chunks.
+
+use std::ops::Range;
+
+use crate::iter::Chunks;
+use crate::slice::RopeSlice;
+
+/// Returns the smallest char boundary in `text` that is `>= byte_idx`.
+#[inline]
+fn ceil_char_boundary(text: &str, byte_idx: usize) -> usize {
+    let mut i = byte_idx.min(text.len());
+    while !text.is_char_boundary(i) {
+        i += 1;
+    }
+    i
+}
+
+/// Returns the largest char boundary in `text` that is `<= byte_idx`.
+#[inline]
+fn floor_char_boundary(text: &str, byte_idx: usize) -> usize {
+    let mut i = byte_idx.min(text.len());
+    while !text.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+//==============================================================
+
+/// Finds the non-overlapping matches of a pattern, front to back, yielding
+/// their byte ranges.
+#[derive(Debug, Clone)]
+struct Searcher<'a> {
+    chunks: Chunks<'a>,
+    pattern: &'a str,
+    chunk: &'a str,
+    chunk_start: usize, // Byte index of the start of `chunk`.
+    pos: usize,         // Byte index the next match may start at.
+    carry: String,      // Text just before `chunk` that a match may start in.
+    seam: String,       // Scratch space for searching across the seam.
+    seam_done: bool,    // Whether all matches starting in `carry` were found.
+    done: bool,
+}
+
+impl<'a> Searcher<'a> {
+    fn new(slice: &RopeSlice<'a>, pattern: &'a str) -> Searcher<'a> {
+        let mut chunks = slice.chunks();
+        let chunk = chunks.next().unwrap_or("");
+        Searcher {
+            chunks: chunks,
+            pattern: pattern,
+            chunk: chunk,
+            chunk_start: 0,
+            pos: 0,
+            carry: String::new(),
+            seam: String::new(),
+            seam_done: true,
+            done: false,
+        }
+    }
+
+    fn next_match(&mut self) -> Option<Range<usize>> {
+        if self.done {
+            return None;
+        }
+
+        let pat_len = self.pattern.len();
+        if pat_len == 0 {
+            return self.next_empty_match();
+        }
+
+        loop {
+            // Matches starting in the carried-over text, and ending in the
+            // current chunk.
+            if !self.seam_done {
+                let carry_start = self.chunk_start - self.carry.len();
+                let prefix_len = ceil_char_boundary(self.chunk, pat_len - 1);
+                self.seam.clear();
+                self.seam.push_str(&self.carry);
+                self.seam.push_str(&self.chunk[..prefix_len]);
+
+                let from = self.pos.max(carry_start) - carry_start;
+                match self.seam[from..].find(self.pattern) {
+                    Some(i) if from + i < self.carry.len() => {
+                        let start = carry_start + from + i;
+                        self.pos = start + pat_len;
+                        return Some(start..self.pos);
+                    }
+                    _ => self.seam_done = true,
+                }
+            }
+
+            // Matches within the current chunk.
+            let from = self.pos.max(self.chunk_start) - self.chunk_start;
+            if let Some(i) = self.chunk[from..].find(self.pattern) {
+                let start = self.chunk_start + from + i;
+                self.pos = start + pat_len;
+                return Some(start..self.pos);
+            }
+
+            // Move on to the next chunk, carrying over the end of the text
+            // so far that a match could still start in.
+            let next_chunk = match self.chunks.next() {
+                Some(chunk) => chunk,
+                None => {
+                    self.done = true;
+                    return None;
+                }
+            };
+            let chunk_end = self.chunk_start + self.chunk.len();
+            let carry_from = chunk_end.saturating_sub(pat_len - 1).max(self.pos);
+            if carry_from >= self.chunk_start {
+                let i = floor_char_boundary(self.chunk, carry_from - self.chunk_start);
+                self.carry.clear();
+                self.carry.push_str(&self.chunk[i..]);
+            } else {
+                let carry_start = self.chunk_start - self.carry.len();
+                let i = floor_char_boundary(&self.carry, carry_from - carry_start);
+                self.carry.drain(..i);
+                self.carry.push_str(self.chunk);
+            }
+            self.chunk = next_chunk;
+            self.chunk_start = chunk_end;
+            self.seam_done = self.carry.is_empty();
+        }
+    }
+
+    /// The empty pattern matches at every char boundary.
+    fn next_empty_match(&mut self) -> Option<Range<usize>> {
+        while self.pos - self.chunk_start >= self.chunk.len() {
+            match self.chunks.next() {
+                Some(chunk) => {
+                    self.chunk_start += self.chunk.len();
+                    self.chunk = chunk;
+                }
+                None => {
+                    self.done = true;
+                    return Some(self.pos..self.pos);
+                }
+            }
+        }
+
+        let start = self.pos;
+        let c = self.chunk[(start - self.chunk_start)..]
+            .chars()
+            .next()
+            .unwrap();
+        self.pos += c.len_utf8();
+        Some(start..start)
+    }
+}
+
+/// Returns the byte index of the start of the last match of `pattern` in
+/// `slice`.
+pub(crate) fn rfind_byte(slice: &RopeSlice, pattern: &str) -> Option<usize> {
+    let pat_len = pattern.len();
+    if pat_len == 0 {
+        return Some(slice.len_bytes());
+    }
+
+    let mut chunks = slice.chunks_at_byte(slice.len_bytes()).0;
+    let mut chunk_end = slice.len_bytes();
+    let mut carry = String::new(); // Text just after the current chunk.
+    let mut seam = String::new();
+    while let Some(chunk) = chunks.prev() {
+        let chunk_start = chunk_end - chunk.len();
+
+        // Matches starting in the current chunk and ending in the
+        // carried-over text come after those within the chunk.
+        if !carry.is_empty() {
+            let suffix_start = floor_char_boundary(chunk, chunk.len().saturating_sub(pat_len - 1));
+            seam.clear();
+            seam.push_str(&chunk[suffix_start..]);
+            seam.push_str(&carry);
+            if let Some(i) = seam.rfind(pattern) {
+                return Some(chunk_start + suffix_start + i);
+            }
+        }
+
+        if let Some(i) = chunk.rfind(pattern) {
+            return Some(chunk_start + i);
+        }
+
+        // Carry over the start of the text so far that a match could still
+        // end in.
+        let carry_len = pat_len - 1;
+        if chunk.len() >= carry_len {
+            let i = ceil_char_boundary(chunk, carry_len);
+            carry.clear();
+            carry.push_str(&chunk[..i]);
+        } else {
+            let i = ceil_char_boundary(&carry, carry_len - chunk.len());
+            carry.truncate(i);
+            carry.insert_str(0, chunk);
+        }
+        chunk_end = chunk_start;
+    }
+
+    None
+}
+
+//==============================================================
+
+/// An iterator over the char ranges of the non-overlapping matches of a
+/// pattern in a `Rope` or `RopeSlice`.
+///
+/// Created with [`RopeSlice::find_iter()`](struct.RopeSlice.html#method.find_iter)
+/// or [`Rope::find_iter()`](struct.Rope.html#method.find_iter).
+#[derive(Debug, Clone)]
+pub struct FindIter<'a> {
+    slice: RopeSlice<'a>,
+    searcher: Searcher<'a>,
+    pattern_chars: usize,
+}
+
+impl<'a> FindIter<'a> {
+    pub(crate) fn new(slice: RopeSlice<'a>, pattern: &'a str) -> FindIter<'a> {
+        FindIter {
+            slice: slice,
+            searcher: Searcher::new(&slice, pattern),
+            pattern_chars: pattern.chars().count(),
+        }
+    }
+}
+
+impl<'a> Iterator for FindIter<'a> {
+    type Item = Range<usize>;
+
+    /// Advances the iterator forward and returns the next value.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    fn next(&mut self) -> Option<Range<usize>> {
+        self.searcher.next_match().map(|range| {
+            let start = self.slice.byte_to_char(range.start);
+            start..(start + self.pattern_chars)
+        })
+    }
+}
+
+/// An iterator over the byte ranges of the non-overlapping matches of a
+/// pattern in a `Rope` or `RopeSlice`.
+///
+/// Created with [`RopeSlice::matches()`](struct.RopeSlice.html#method.matches)
+/// or [`Rope::matches()`](struct.Rope.html#method.matches).
+#[derive(Debug, Clone)]
+pub struct Matches<'a> {
+    searcher: Searcher<'a>,
+}
+
+impl<'a> Matches<'a> {
+    pub(crate) fn new(slice: RopeSlice<'a>, pattern: &'a str) -> Matches<'a> {
+        Matches {
+            searcher: Searcher::new(&slice, pattern),
+        }
+    }
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = Range<usize>;
+
+    /// Advances the iterator forward and returns the next value.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[inline]
+    fn next(&mut self) -> Option<Range<usize>> {
+        self.searcher.next_match()
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use crate::Rope;
+
+    const TEXT: &str = "Hello there!  How're you doing?  It's \
+                        a fine day, isn't it?  Aren't you glad \
+                        we're alive?  こんにちは、みんなさん！";
+
+    /// Naive (overlapping) match byte indices.
+    fn naive_matches(text: &str, pattern: &str) -> Vec<usize> {
+        (0..=text.len())
+            .filter(|&i| text.is_char_boundary(i) && text[i..].starts_with(pattern))
+            .collect()
+    }
+
+    #[test]
+    fn find_01() {
+        let r = Rope::from_str(&TEXT.repeat(5));
+        let text = r.to_string();
+        for pattern in &[
+            "Hello",
+            "o",
+            "you",
+            "!  ",
+            "ん",
+            "さん！Hello",
+            "alive?  こん",
+            "zzz",
+            "",
+        ] {
+            let expected = text.find(pattern).map(|i| r.byte_to_char(i));
+            assert_eq!(r.slice(..).find(pattern), expected);
+            let expected = text.rfind(pattern).map(|i| r.byte_to_char(i));
+            assert_eq!(r.slice(..).rfind(pattern), expected);
+            assert_eq!(r.slice(..).contains(pattern), text.contains(pattern));
+        }
+    }
+
+    #[test]
+    fn find_02() {
+        // Patterns longer than the chunks, straddling several of them.
+        let r = Rope::from_str(&TEXT.repeat(5));
+        let text = r.to_string();
+        for len in 1..60 {
+            for start in (0..(text.len() - len)).step_by(7) {
+                if !text.is_char_boundary(start) || !text.is_char_boundary(start + len) {
+                    continue;
+                }
+                let pattern = &text[start..(start + len)];
+                let naive = naive_matches(&text, pattern);
+                assert_eq!(r.slice(..).find(pattern), Some(r.byte_to_char(naive[0])));
+                assert_eq!(
+                    r.slice(..).rfind(pattern),
+                    Some(r.byte_to_char(*naive.last().unwrap()))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn matches_01() {
+        let r = Rope::from_str(&"aaabaaab".repeat(20));
+        let text = r.to_string();
+        for pattern in &["a", "aa", "aaa", "ab", "baaa", "aaabaaabaaab", "c"] {
+            let expected: Vec<_> = text
+                .match_indices(pattern)
+                .map(|(i, m)| i..(i + m.len()))
+                .collect();
+            let found: Vec<_> = r.slice(..).matches(pattern).collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn matches_02() {
+        let r = Rope::from_str("こんにちは");
+        let found: Vec<_> = r.slice(..).matches("").collect();
+        let expected: Vec<_> = "こんにちは".match_indices("").map(|(i, _)| i..i).collect();
+        assert_eq!(found, expected);
+        assert_eq!(Rope::from_str("").slice(..).matches("").count(), 1);
+        assert_eq!(Rope::from_str("").slice(..).matches("a").count(), 0);
+    }
+
+    #[test]
+    fn find_iter_01() {
+        let r = Rope::from_str(&TEXT.repeat(5));
+        let s = r.slice(20..400);
+        let text = s.to_string();
+        for pattern in &["you", "ん", "こんにちは、", "re", ""] {
+            let expected: Vec<_> = text
+                .match_indices(pattern)
+                .map(|(i, _)| {
+                    let start = s.byte_to_char(i);
+                    start..(start + pattern.chars().count())
+                })
+                .collect();
+            let found: Vec<_> = s.find_iter(pattern).collect();
+            assert_eq!(found, expected);
+            for range in found {
+                assert_eq!(s.slice(range), *pattern);
+            }
+        }
+    }
+}
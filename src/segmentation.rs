@@ -0,0 +1,485 @@
+//! Word and sentence segmentation of a `RopeSlice`, following Unicode
+//! Standard Annex #29.
+//!
+//! `unicode-segmentation` only segments contiguous `&str`s into words and
+//! sentences.  But UAX #29 always breaks words at line breaks and sentences
+//! at paragraph separators (LF, CR, NEL, LS and PS), so the text between two
+//! such "hard" breaks can be segmented on its own.  Each query therefore
+//! copies just the text around the position it's interested in, which
+//! handles words and sentences spanning chunk boundaries correctly.
+//!
+//! Since lines can be arbitrarily long, that text is further limited to a
+//! window of a few hundred chars around the position at first.  The
+//! segments next to a window edge that isn't a hard break can be wrong, for
+//! lack of the context beyond it, so the window is widened when the segment
+//! a query needs is one of those.
+
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::slice::RopeSlice;
+
+/// The number of chars on each side of a position that are segmented at
+/// first, before widening the window.
+const WINDOW_RADIUS: usize = 256;
+
+/// The number of segments next to a window edge that isn't a hard break
+/// that aren't relied on.  UAX #29 decides most boundaries from the chars
+/// right next to them, and the exceptions (regional indicator pairs and
+/// runs of extending chars) don't cross grapheme boundaries, which is where
+/// the windows are cut.  So only the segment touching the edge and the one
+/// before it can be affected.
+const EDGE_SEGMENTS: usize = 2;
+
+/// The kind of text segment to find the boundaries of.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Segment {
+    Word,
+    Sentence,
+}
+
+impl Segment {
+    /// Returns whether there's always a boundary after `c`.
+    #[inline]
+    fn is_hard_break(self, c: char) -> bool {
+        match c {
+            '\u{000A}' | '\u{000D}' | '\u{0085}' | '\u{2028}' | '\u{2029}' => true,
+            // Vertical tab and form feed only end words.
+            '\u{000B}' | '\u{000C}' => self == Segment::Word,
+            _ => false,
+        }
+    }
+
+    /// Returns the char ranges of all segments of `text`, offset by
+    /// `char_offset`, along with whether each one is a word or sentence
+    /// (i.e. contains alphanumeric chars) rather than e.g. punctuation or
+    /// whitespace.
+    fn split(self, text: &str, char_offset: usize) -> Vec<(Range<usize>, bool)> {
+        let segments: Box<dyn Iterator<Item = &str>> = match self {
+            Segment::Word => Box::new(text.split_word_bounds()),
+            Segment::Sentence => Box::new(text.split_sentence_bounds()),
+        };
+        let mut char_idx = char_offset;
+        segments
+            .map(|segment| {
+                let start = char_idx;
+                char_idx += segment.chars().count();
+                (start..char_idx, segment.chars().any(char::is_alphanumeric))
+            })
+            .collect()
+    }
+
+    /// Returns the char range of the text around `char_idx` that is
+    /// delimited by hard breaks (or the ends of `slice`), and can thus be
+    /// segmented on its own.
+    fn window(self, slice: &RopeSlice, char_idx: usize) -> Range<usize> {
+        // Every line break ends a word, and every line break except vertical
+        // tab and form feed ends a sentence, so this rarely needs more than
+        // the one line.
+        let line_idx = slice.char_to_line(char_idx);
+        let mut start_line = line_idx;
+        let mut start = slice.line_to_char(start_line);
+        while start > 0 && !self.is_hard_break(slice.char(start - 1)) {
+            start_line -= 1;
+            start = slice.line_to_char(start_line);
+        }
+
+        let mut end_line = line_idx + 1;
+        let mut end = slice.line_to_char(end_line.min(slice.len_lines()));
+        while end < slice.len_chars() && !self.is_hard_break(slice.char(end - 1)) {
+            end_line += 1;
+            end = slice.line_to_char(end_line.min(slice.len_lines()));
+        }
+
+        start..end
+    }
+
+    /// Returns a run of consecutive segments around the char at
+    /// `char_idx`, including the one containing it.
+    ///
+    /// The text segmented starts out at `WINDOW_RADIUS` chars on each side
+    /// of `char_idx`, and is doubled until the segments that might be wrong
+    /// near its edges don't include the one containing `char_idx`.
+    fn window_segments(self, slice: &RopeSlice, char_idx: usize) -> Vec<(Range<usize>, bool)> {
+        let hard_window = self.window(slice, char_idx);
+        let mut radius = WINDOW_RADIUS;
+        loop {
+            let mut start = char_idx.saturating_sub(radius).max(hard_window.start);
+            if !slice.is_grapheme_boundary(start) {
+                start = slice.prev_grapheme_boundary(start);
+            }
+            let mut end = char_idx.saturating_add(radius).min(hard_window.end);
+            if !slice.is_grapheme_boundary(end) {
+                end = slice.next_grapheme_boundary(end);
+            }
+
+            let text = slice.slice(start..end).to_string();
+            let mut segments = self.split(&text, start);
+            let first = if start > hard_window.start {
+                EDGE_SEGMENTS
+            } else {
+                0
+            };
+            let last = if end < hard_window.end {
+                segments.len().saturating_sub(EDGE_SEGMENTS)
+            } else {
+                segments.len()
+            };
+            if first < last
+                && segments[first].0.start <= char_idx
+                && char_idx < segments[last - 1].0.end
+            {
+                segments.truncate(last);
+                segments.drain(..first);
+                return segments;
+            }
+            radius = radius.saturating_mul(2);
+        }
+    }
+}
+
+/// Returns the char range of the segment containing the char at `char_idx`,
+/// or an empty range at the end of `slice` if `char_idx == len_chars()`.
+pub(crate) fn bounds_at(slice: &RopeSlice, segment: Segment, char_idx: usize) -> Range<usize> {
+    if char_idx == slice.len_chars() {
+        return char_idx..char_idx;
+    }
+    segment
+        .window_segments(slice, char_idx)
+        .into_iter()
+        .map(|(range, _)| range)
+        .find(|range| range.end > char_idx)
+        .unwrap()
+}
+
+/// Returns the start of the first word or sentence that starts after
+/// `char_idx`, or the end of `slice` if there is none.
+pub(crate) fn next_start(slice: &RopeSlice, segment: Segment, char_idx: usize) -> usize {
+    let mut window_idx = char_idx;
+    while window_idx < slice.len_chars() {
+        let segments = segment.window_segments(slice, window_idx);
+        let found = segments
+            .iter()
+            .find(|(range, is_word)| *is_word && range.start > char_idx);
+        if let Some((range, _)) = found {
+            return range.start;
+        }
+        window_idx = segments.last().unwrap().0.end;
+    }
+    slice.len_chars()
+}
+
+/// Returns the end of the last word or sentence that ends before
+/// `char_idx`, or 0 if there is none.
+pub(crate) fn prev_end(slice: &RopeSlice, segment: Segment, char_idx: usize) -> usize {
+    let mut window_idx = char_idx;
+    while window_idx > 0 {
+        // The window containing the char before the position.
+        let segments = segment.window_segments(slice, window_idx - 1);
+        let found = segments
+            .iter()
+            .rev()
+            .find(|(range, is_word)| *is_word && range.end < char_idx);
+        if let Some((range, _)) = found {
+            return range.end;
+        }
+        window_idx = segments[0].0.start;
+    }
+    0
+}
+
+//==============================================================
+
+/// Yields the char ranges of the words or sentences of a `RopeSlice`, one
+/// window at a time.
+#[derive(Debug, Clone)]
+struct SegmentIter<'a> {
+    slice: RopeSlice<'a>,
+    segment: Segment,
+    segments: std::vec::IntoIter<(Range<usize>, bool)>,
+    next_window: usize, // Char index of the start of the next window.
+}
+
+impl<'a> SegmentIter<'a> {
+    fn new(slice: RopeSlice<'a>, segment: Segment) -> SegmentIter<'a> {
+        SegmentIter {
+            slice: slice,
+            segment: segment,
+            segments: Vec::new().into_iter(),
+            next_window: 0,
+        }
+    }
+
+    fn next_segment(&mut self) -> Option<Range<usize>> {
+        loop {
+            if let Some((range, _)) = self.segments.by_ref().find(|&(_, is_word)| is_word) {
+                return Some(range);
+            }
+            if self.next_window >= self.slice.len_chars() {
+                return None;
+            }
+            let mut segments = self.segment.window_segments(&self.slice, self.next_window);
+            // The window can start before the segments already yielded.
+            let next_window = self.next_window;
+            segments.retain(|(range, _)| range.start >= next_window);
+            self.next_window = segments.last().unwrap().0.end;
+            self.segments = segments.into_iter();
+        }
+    }
+}
+
+/// An iterator over the char ranges of the words of a `Rope` or
+/// `RopeSlice`, as determined by Unicode Standard Annex #29.
+///
+/// Like `str::unicode_words()`, only the segments that contain alphanumeric
+/// chars are yielded, skipping whitespace and punctuation.
+///
+/// Created with [`RopeSlice::words()`](struct.RopeSlice.html#method.words)
+/// or [`Rope::words()`](struct.Rope.html#method.words).
+#[derive(Debug, Clone)]
+pub struct Words<'a>(SegmentIter<'a>);
+
+impl<'a> Words<'a> {
+    pub(crate) fn new(slice: RopeSlice<'a>) -> Words<'a> {
+        Words(SegmentIter::new(slice, Segment::Word))
+    }
+}
+
+impl<'a> Iterator for Words<'a> {
+    type Item = Range<usize>;
+
+    /// Advances the iterator forward and returns the next value.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    #[inline]
+    fn next(&mut self) -> Option<Range<usize>> {
+        self.0.next_segment()
+    }
+}
+
+/// An iterator over the char ranges of the sentences of a `Rope` or
+/// `RopeSlice`, as determined by Unicode Standard Annex #29.
+///
+/// Like `str::unicode_sentences()`, only the segments that contain
+/// alphanumeric chars are yielded.
+///
+/// Created with [`RopeSlice::sentences()`](struct.RopeSlice.html#method.sentences)
+/// or [`Rope::sentences()`](struct.Rope.html#method.sentences).
+#[derive(Debug, Clone)]
+pub struct Sentences<'a>(SegmentIter<'a>);
+
+impl<'a> Sentences<'a> {
+    pub(crate) fn new(slice: RopeSlice<'a>) -> Sentences<'a> {
+        Sentences(SegmentIter::new(slice, Segment::Sentence))
+    }
+}
+
+impl<'a> Iterator for Sentences<'a> {
+    type Item = Range<usize>;
+
+    /// Advances the iterator forward and returns the next value.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    #[inline]
+    fn next(&mut self) -> Option<Range<usize>> {
+        self.0.next_segment()
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use unicode_segmentation::UnicodeSegmentation;
+
+    use crate::Rope;
+
+    const TEXT: &str = "Hello there!  How're you doing?  It's a fine day, \
+                        isn't it?\r\nAren't you glad we're alive?  \
+                        こんにちは、みんなさん！ Mr. Smith paid $3.50\u{000C}for the \
+                        U.S.A. flag\u{000B}yesterday.\u{2029}Ünïcödé wörds, \
+                        e\u{301}tc.\n\nThe end";
+
+    fn char_ranges<'a, I>(text: &str, segments: I) -> Vec<Range<usize>>
+    where
+        I: Iterator<Item = (usize, &'a str)>,
+    {
+        segments
+            .map(|(i, segment)| {
+                let start = text[..i].chars().count();
+                start..(start + segment.chars().count())
+            })
+            .collect()
+    }
+
+    fn is_word(text: &str, range: &Range<usize>) -> bool {
+        text.chars()
+            .skip(range.start)
+            .take(range.end - range.start)
+            .any(char::is_alphanumeric)
+    }
+
+    #[test]
+    fn words_01() {
+        let r = Rope::from_str(&TEXT.repeat(3));
+        for s in &[r.slice(..), r.slice(7..250), r.slice(3..3)] {
+            let text = s.to_string();
+            let expected: Vec<_> = char_ranges(
+                &text,
+                text.split_word_bound_indices()
+                    .filter(|&(_, w)| w.chars().any(char::is_alphanumeric)),
+            );
+            assert_eq!(s.words().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn sentences_01() {
+        let r = Rope::from_str(&TEXT.repeat(3));
+        for s in &[r.slice(..), r.slice(7..250), r.slice(3..3)] {
+            let text = s.to_string();
+            let expected: Vec<_> = char_ranges(
+                &text,
+                text.split_sentence_bound_indices()
+                    .filter(|&(_, w)| w.chars().any(char::is_alphanumeric)),
+            );
+            assert_eq!(s.sentences().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn bounds_01() {
+        let r = Rope::from_str(&TEXT.repeat(3));
+        let s = r.slice(7..250);
+        let text = s.to_string();
+        let words = char_ranges(&text, text.split_word_bound_indices());
+        let sentences = char_ranges(&text, text.split_sentence_bound_indices());
+
+        for char_idx in 0..s.len_chars() {
+            let word = words.iter().find(|w| w.end > char_idx).unwrap();
+            assert_eq!(s.word_bounds_at(char_idx), *word);
+            let sentence = sentences.iter().find(|w| w.end > char_idx).unwrap();
+            assert_eq!(s.sentence_bounds_at(char_idx), *sentence);
+        }
+        let len = s.len_chars();
+        assert_eq!(s.word_bounds_at(len), len..len);
+        assert_eq!(s.sentence_bounds_at(len), len..len);
+    }
+
+    #[test]
+    fn next_prev_01() {
+        let r = Rope::from_str(&TEXT.repeat(3));
+        let s = r.slice(7..250);
+        let text = s.to_string();
+        let len = s.len_chars();
+        for (segments, is_sentences) in [
+            (char_ranges(&text, text.split_word_bound_indices()), false),
+            (
+                char_ranges(&text, text.split_sentence_bound_indices()),
+                true,
+            ),
+        ] {
+            let segments: Vec<_> = segments
+                .into_iter()
+                .filter(|range| is_word(&text, range))
+                .collect();
+            for char_idx in 0..=len {
+                let next = segments
+                    .iter()
+                    .find(|range| range.start > char_idx)
+                    .map_or(len, |range| range.start);
+                let prev = segments
+                    .iter()
+                    .rev()
+                    .find(|range| range.end < char_idx)
+                    .map_or(0, |range| range.end);
+                if is_sentences {
+                    assert_eq!(s.next_sentence_start(char_idx), next);
+                    assert_eq!(s.prev_sentence_end(char_idx), prev);
+                } else {
+                    assert_eq!(s.next_word_start(char_idx), next);
+                    assert_eq!(s.prev_word_end(char_idx), prev);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_lines_01() {
+        // Lines much longer than the initial window, with segments whose
+        // boundaries depend on context: regional indicator pairs, a long run
+        // of combining marks, abbreviations, and a sentence break decided by
+        // a lowercase letter far ahead.
+        let mut text = TEXT.replace(|c: char| c.is_whitespace(), " ");
+        text.push_str(" a.b 3.50 ");
+        text.push_str(&"\u{1F1EB}".repeat(301));
+        text.push_str(" e");
+        text.push_str(&"\u{301}".repeat(600));
+        text.push_str(" etc. (");
+        text.push_str(&"1, ".repeat(300));
+        text.push_str(") and so on.  The end.\nNext ");
+        let text = text.repeat(2);
+        let r = Rope::from_str(&text);
+        let s = r.slice(5..);
+        let text = s.to_string();
+        let len = s.len_chars();
+
+        let words = char_ranges(&text, text.split_word_bound_indices());
+        let sentences = char_ranges(&text, text.split_sentence_bound_indices());
+        for (segments, is_sentences) in [(words, false), (sentences, true)] {
+            for char_idx in (0..len).step_by(3) {
+                let segment = segments.iter().find(|w| w.end > char_idx).unwrap();
+                if is_sentences {
+                    assert_eq!(s.sentence_bounds_at(char_idx), *segment);
+                } else {
+                    assert_eq!(s.word_bounds_at(char_idx), *segment);
+                }
+            }
+
+            let segments: Vec<_> = segments
+                .into_iter()
+                .filter(|range| is_word(&text, range))
+                .collect();
+            if is_sentences {
+                assert_eq!(s.sentences().collect::<Vec<_>>(), segments);
+            } else {
+                assert_eq!(s.words().collect::<Vec<_>>(), segments);
+            }
+            for char_idx in (0..=len).step_by(7) {
+                let next = segments
+                    .iter()
+                    .find(|range| range.start > char_idx)
+                    .map_or(len, |range| range.start);
+                let prev = segments
+                    .iter()
+                    .rev()
+                    .find(|range| range.end < char_idx)
+                    .map_or(0, |range| range.end);
+                if is_sentences {
+                    assert_eq!(s.next_sentence_start(char_idx), next);
+                    assert_eq!(s.prev_sentence_end(char_idx), prev);
+                } else {
+                    assert_eq!(s.next_word_start(char_idx), next);
+                    assert_eq!(s.prev_word_end(char_idx), prev);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn errors_01() {
+        let r = Rope::from_str("Hello world");
+        let s = r.slice(..);
+        assert!(s.try_word_bounds_at(12).is_err());
+        assert!(s.try_next_word_start(12).is_err());
+        assert!(s.try_prev_word_end(12).is_err());
+        assert!(s.try_sentence_bounds_at(12).is_err());
+        assert!(s.try_next_sentence_start(12).is_err());
+        assert!(s.try_prev_sentence_end(12).is_err());
+        assert_eq!(s.try_next_word_start(0), Ok(6));
+        assert_eq!(s.try_prev_word_end(11), Ok(5));
+    }
+}
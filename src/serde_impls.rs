@@ -0,0 +1,60 @@
+//! Serde support, enabled with the `serde` feature.
+//!
+//! `Rope` and `RopeSlice` serialize as a single string, and `Rope`
+//! deserializes from one.  When the deserializer can hand out the string
+//! without copying it (borrowed or transient), the rope is built directly
+//! from it via `RopeBuilder`.
+
+use std::fmt;
+
+use serde::de::{Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::rope::Rope;
+use crate::rope_builder::RopeBuilder;
+use crate::slice::RopeSlice;
+
+impl Serialize for Rope {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.slice(..).serialize(serializer)
+    }
+}
+
+impl<'a> Serialize for RopeSlice<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut chunks = self.chunks();
+        let first_chunk = chunks.next().unwrap_or("");
+        if chunks.next().is_none() {
+            serializer.serialize_str(first_chunk)
+        } else {
+            // Streams the chunks via `Display`, for serializers that support
+            // it.  Others fall back to collecting them into a `String`.
+            serializer.collect_str(self)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Rope {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Rope, D::Error> {
+        deserializer.deserialize_str(RopeVisitor)
+    }
+}
+
+struct RopeVisitor;
+
+impl<'de> Visitor<'de> for RopeVisitor {
+    type Value = Rope;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    #[inline]
+    fn visit_str<E: serde::de::Error>(self, text: &str) -> Result<Rope, E> {
+        let mut builder = RopeBuilder::new();
+        builder.append(text);
+        Ok(builder.finish())
+    }
+}
@@ -1,14 +1,29 @@
+#[cfg(feature = "unicode-segmentation")]
+use std::ops::Range;
 use std::ops::{Bound, RangeBounds};
 use std::sync::Arc;
 
+use crate::cursor::Cursor;
+#[cfg(feature = "unicode-segmentation")]
+use crate::graphemes::{self, Graphemes};
 use crate::iter::{Bytes, Chars, Chunks, Lines};
-use crate::rope::Rope;
+use crate::metric::{LongestLine, Metric, MetricIndex};
+use crate::reader::RopeReader;
+#[cfg(feature = "regex")]
+use crate::regex::{find_regex, rfind_regex, FindRegexIter, Regex, RegexMatch};
+use crate::rope::{check_column, Rope};
+use crate::search::{rfind_byte, FindIter, Matches};
+#[cfg(feature = "unicode-segmentation")]
+use crate::segmentation::{self, Segment, Sentences, Words};
 use crate::str_utils::{
     byte_to_char_idx, byte_to_line_idx, byte_to_utf16_surrogate_idx, char_to_byte_idx,
     char_to_line_idx, count_chars, count_line_breaks, count_utf16_surrogates, line_to_byte_idx,
     line_to_char_idx, utf16_code_unit_to_char_idx,
 };
 use crate::tree::{Count, Node, TextInfo};
+#[cfg(feature = "unicode-width")]
+use crate::visual::{self, ColumnRounding};
+use crate::{Error, Position, Result};
 
 /// An immutable view into part of a `Rope`.
 ///
@@ -117,6 +132,61 @@ impl<'a> RopeSlice<'a> {
         })
     }
 
+    /// Same as `new_with_range()` above, except that `start` and `end` are
+    /// byte indices.
+    ///
+    /// Both indices must be on char boundaries.
+    pub(crate) fn new_with_byte_range(node: &'a Arc<Node>, start: usize, end: usize) -> Self {
+        assert!(start <= end);
+        assert!(end <= node.text_info().bytes as usize);
+
+        // Early-out shortcut for taking a slice of the full thing.
+        if start == 0 && end == node.byte_count() {
+            return RopeSlice::new_with_range(node, 0, node.char_count());
+        }
+
+        // Find the deepest node that still contains the full range given.
+        let mut n_start = start;
+        let mut n_end = end;
+        let mut node = node;
+        'outer: loop {
+            match *(node as &Node) {
+                // Early out if we reach a leaf, because we can do the
+                // simpler lightweight slice then.
+                Node::Leaf(ref text) => {
+                    let text = &text[n_start..n_end];
+                    return RopeSlice(RSEnum::Light {
+                        text: text,
+                        char_count: count_chars(text) as Count,
+                        utf16_surrogate_count: count_utf16_surrogates(text) as Count,
+                        line_break_count: count_line_breaks(text) as Count,
+                    });
+                }
+
+                Node::Internal(ref children) => {
+                    let mut start_byte = 0;
+                    for (i, inf) in children.info().iter().enumerate() {
+                        if n_start >= start_byte && n_end < (start_byte + inf.bytes as usize) {
+                            n_start -= start_byte;
+                            n_end -= start_byte;
+                            node = &children.nodes()[i];
+                            continue 'outer;
+                        }
+                        start_byte += inf.bytes as usize;
+                    }
+                    break;
+                }
+            }
+        }
+
+        // Create the slice
+        RopeSlice(RSEnum::Full {
+            node: node,
+            start_info: node.byte_to_text_info(n_start),
+            end_info: node.byte_to_text_info(n_end),
+        })
+    }
+
     //-----------------------------------------------------------------------
     // Informational methods
 
@@ -195,6 +265,32 @@ impl<'a> RopeSlice<'a> {
         }
     }
 
+    /// Returns the index and length in chars of the longest line in the
+    /// `RopeSlice`, as `(line_idx, len_chars)`.
+    ///
+    /// The length doesn't include the line break.  If several lines are
+    /// equally long, the first of them is returned.
+    ///
+    /// Runs in O(log N) time, plus the length of two chunks, using the line
+    /// lengths cached in the tree's nodes by
+    /// [`Rope::longest_line()`](struct.Rope.html#method.longest_line).
+    /// Measuring the nodes that aren't cached yet takes O(N) time.
+    pub fn longest_line(&self) -> (usize, usize) {
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
+            }) => MetricIndex::with_metric(LongestLine).range_summary(
+                node,
+                start_info.chars as usize,
+                end_info.chars as usize,
+            ),
+            RopeSlice(RSEnum::Light { text, .. }) => LongestLine.measure(text),
+        }
+        .longest_line()
+    }
+
     //-----------------------------------------------------------------------
     // Index conversion methods
 
@@ -214,16 +310,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
     #[inline]
     pub fn byte_to_char(&self, byte_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            byte_idx <= self.len_bytes(),
-            "Attempt to index past end of slice: byte index {}, slice byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
-
-        let (chunk, b, c, _) = self.chunk_at_byte(byte_idx);
-        c + byte_to_char_idx(chunk, byte_idx - b)
+        self.try_byte_to_char(byte_idx).unwrap()
     }
 
     /// Returns the line index of the given byte.
@@ -242,16 +329,27 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
     #[inline]
     pub fn byte_to_line(&self, byte_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            byte_idx <= self.len_bytes(),
-            "Attempt to index past end of slice: byte index {}, slice byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
+        self.try_byte_to_line(byte_idx).unwrap()
+    }
 
-        let (chunk, b, _, l) = self.chunk_at_byte(byte_idx);
-        l + byte_to_line_idx(chunk, byte_idx - b)
+    /// Returns the utf16 code unit index of the given byte.
+    ///
+    /// Ropey stores text internally as utf8, but sometimes it is necessary
+    /// to interact with external APIs that still use utf16.  This function is
+    /// primarily intended for such situations, and is otherwise not very
+    /// useful.
+    ///
+    /// Note: if the byte is in the middle of a multi-byte char, returns the
+    /// utf16 code unit index of the char that the byte belongs to.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
+    #[inline]
+    pub fn byte_to_utf16_cu(&self, byte_idx: usize) -> usize {
+        self.try_byte_to_utf16_cu(byte_idx).unwrap()
     }
 
     /// Returns the byte index of the given char.
@@ -268,16 +366,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn char_to_byte(&self, char_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of slice: char index {}, slice char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        let (chunk, b, c, _) = self.chunk_at_char(char_idx);
-        b + char_to_byte_idx(chunk, char_idx - c)
+        self.try_char_to_byte(char_idx).unwrap()
     }
 
     /// Returns the line index of the given char.
@@ -296,16 +385,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn char_to_line(&self, char_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of slice: char index {}, slice char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        let (chunk, _, c, l) = self.chunk_at_char(char_idx);
-        l + char_to_line_idx(chunk, char_idx - c)
+        self.try_char_to_line(char_idx).unwrap()
     }
 
     /// Returns the utf16 code unit index of the given char.
@@ -322,39 +402,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn char_to_utf16_cu(&self, char_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of slice: char index {}, slice char \
-             length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        match *self {
-            RopeSlice(RSEnum::Full {
-                ref node,
-                start_info,
-                ..
-            }) => {
-                let char_idx = char_idx + start_info.chars as usize;
-
-                let (chunk, chunk_start_info) = node.get_chunk_at_char(char_idx);
-                let chunk_byte_idx =
-                    char_to_byte_idx(chunk, char_idx - chunk_start_info.chars as usize);
-                let surrogate_count = byte_to_utf16_surrogate_idx(chunk, chunk_byte_idx);
-
-                char_idx + chunk_start_info.utf16_surrogates as usize + surrogate_count
-                    - start_info.chars as usize
-                    - start_info.utf16_surrogates as usize
-            }
-
-            RopeSlice(RSEnum::Light { text, .. }) => {
-                let byte_idx = char_to_byte_idx(text, char_idx);
-                let surrogate_count = byte_to_utf16_surrogate_idx(text, byte_idx);
-                char_idx + surrogate_count
-            }
-        }
+        self.try_char_to_utf16_cu(char_idx).unwrap()
     }
 
     /// Returns the char index of the given utf16 code unit.
@@ -375,36 +423,48 @@ impl<'a> RopeSlice<'a> {
     /// (i.e. `utf16_cu_idx > len_utf16_cu()`).
     #[inline]
     pub fn utf16_cu_to_char(&self, utf16_cu_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            utf16_cu_idx <= self.len_utf16_cu(),
-            "Attempt to index past end of slice: utf16 code unit index {}, \
-             slice utf16 code unit length {}",
-            utf16_cu_idx,
-            self.len_utf16_cu()
-        );
-
-        match *self {
-            RopeSlice(RSEnum::Full {
-                ref node,
-                start_info,
-                ..
-            }) => {
-                let utf16_cu_idx =
-                    utf16_cu_idx + (start_info.chars + start_info.utf16_surrogates) as usize;
-
-                let (chunk, chunk_start_info) = node.get_chunk_at_utf16_code_unit(utf16_cu_idx);
-                let chunk_utf16_cu_idx = utf16_cu_idx
-                    - (chunk_start_info.chars + chunk_start_info.utf16_surrogates) as usize;
-                let chunk_char_idx = utf16_code_unit_to_char_idx(chunk, chunk_utf16_cu_idx);
+        self.try_utf16_cu_to_char(utf16_cu_idx).unwrap()
+    }
 
-                chunk_start_info.chars as usize + chunk_char_idx - start_info.chars as usize
-            }
+    /// Returns the byte index of the given utf16 code unit.
+    ///
+    /// Ropey stores text internally as utf8, but sometimes it is necessary
+    /// to interact with external APIs that still use utf16.  This function is
+    /// primarily intended for such situations, and is otherwise not very
+    /// useful.
+    ///
+    /// Note: if the utf16 code unit is in the middle of a char, returns the
+    /// byte index of the char that it belongs to.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `utf16_cu_idx` is out of bounds
+    /// (i.e. `utf16_cu_idx > len_utf16_cu()`).
+    #[inline]
+    pub fn utf16_cu_to_byte(&self, utf16_cu_idx: usize) -> usize {
+        self.try_utf16_cu_to_byte(utf16_cu_idx).unwrap()
+    }
 
-            RopeSlice(RSEnum::Light { text, .. }) => {
-                utf16_code_unit_to_char_idx(text, utf16_cu_idx)
-            }
-        }
+    /// Returns the line index of the given utf16 code unit.
+    ///
+    /// Notes:
+    ///
+    /// - Lines are zero-indexed.  This is functionally equivalent to
+    ///   counting the line endings before the specified utf16 code unit.
+    /// - `utf16_cu_idx` can be one-past-the-end, which will return the
+    ///   last line index.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `utf16_cu_idx` is out of bounds
+    /// (i.e. `utf16_cu_idx > len_utf16_cu()`).
+    #[inline]
+    pub fn utf16_cu_to_line(&self, utf16_cu_idx: usize) -> usize {
+        self.try_utf16_cu_to_line(utf16_cu_idx).unwrap()
     }
 
     /// Returns the byte index of the start of the given line.
@@ -422,20 +482,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `line_idx` is out of bounds (i.e. `line_idx > len_lines()`).
     #[inline]
     pub fn line_to_byte(&self, line_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            line_idx <= self.len_lines(),
-            "Attempt to index past end of slice: line index {}, slice line length {}",
-            line_idx,
-            self.len_lines()
-        );
-
-        if line_idx == self.len_lines() {
-            self.len_bytes()
-        } else {
-            let (chunk, b, _, l) = self.chunk_at_line_break(line_idx);
-            b + line_to_byte_idx(chunk, line_idx - l)
-        }
+        self.try_line_to_byte(line_idx).unwrap()
     }
 
     /// Returns the char index of the start of the given line.
@@ -453,20 +500,132 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `line_idx` is out of bounds (i.e. `line_idx > len_lines()`).
     #[inline]
     pub fn line_to_char(&self, line_idx: usize) -> usize {
-        // Bounds check
-        assert!(
-            line_idx <= self.len_lines(),
-            "Attempt to index past end of slice: line index {}, slice line length {}",
-            line_idx,
-            self.len_lines()
-        );
+        self.try_line_to_char(line_idx).unwrap()
+    }
 
-        if line_idx == self.len_lines() {
-            self.len_chars()
-        } else {
-            let (chunk, _, c, l) = self.chunk_at_line_break(line_idx);
-            c + line_to_char_idx(chunk, line_idx - l)
-        }
+    /// Returns the utf16 code unit index of the start of the given line.
+    ///
+    /// Combined with [`utf16_cu_to_line()`](#method.utf16_cu_to_line), this
+    /// can be used to convert to and from the (line, utf16 column) positions
+    /// used by some external APIs.
+    ///
+    /// Notes:
+    ///
+    /// - Lines are zero-indexed.
+    /// - `line_idx` can be one-past-the-end, which will return
+    ///   one-past-the-end utf16 code unit index.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line_idx` is out of bounds (i.e. `line_idx > len_lines()`).
+    #[inline]
+    pub fn line_to_utf16_cu(&self, line_idx: usize) -> usize {
+        self.try_line_to_utf16_cu(line_idx).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Position conversion methods
+
+    /// Returns the line/column position of the given char, with the column
+    /// measured in chars.
+    ///
+    /// Notes:
+    ///
+    /// - Lines and columns are zero-indexed.
+    /// - `char_idx` can be one-past-the-end, which will return the
+    ///   position at the end of the last line.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn char_to_position(&self, char_idx: usize) -> Position {
+        self.try_char_to_position(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the given line/column position, with the
+    /// column measured in chars.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line is out of bounds (i.e. `line >= len_lines()`), or
+    /// if the column is past the end of the line.
+    #[inline]
+    pub fn position_to_char(&self, position: Position) -> usize {
+        self.try_position_to_char(position).unwrap()
+    }
+
+    /// Returns the line/column position of the given byte, with the column
+    /// measured in bytes.
+    ///
+    /// Notes:
+    ///
+    /// - Lines and columns are zero-indexed.
+    /// - `byte_idx` can be one-past-the-end, which will return the
+    ///   position at the end of the last line.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
+    #[inline]
+    pub fn byte_to_position(&self, byte_idx: usize) -> Position {
+        self.try_byte_to_position(byte_idx).unwrap()
+    }
+
+    /// Returns the byte index of the given line/column position, with the
+    /// column measured in bytes.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line is out of bounds (i.e. `line >= len_lines()`), or
+    /// if the column is past the end of the line.
+    #[inline]
+    pub fn position_to_byte(&self, position: Position) -> usize {
+        self.try_position_to_byte(position).unwrap()
+    }
+
+    /// Returns the line/column position of the given utf16 code unit, with
+    /// the column measured in utf16 code units.
+    ///
+    /// Notes:
+    ///
+    /// - Lines and columns are zero-indexed.
+    /// - `utf16_cu_idx` can be one-past-the-end, which will return the
+    ///   position at the end of the last line.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `utf16_cu_idx` is out of bounds
+    /// (i.e. `utf16_cu_idx > len_utf16_cu()`).
+    #[inline]
+    pub fn utf16_cu_to_position(&self, utf16_cu_idx: usize) -> Position {
+        self.try_utf16_cu_to_position(utf16_cu_idx).unwrap()
+    }
+
+    /// Returns the utf16 code unit index of the given line/column position,
+    /// with the column measured in utf16 code units.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line is out of bounds (i.e. `line >= len_lines()`), or
+    /// if the column is past the end of the line.
+    #[inline]
+    pub fn position_to_utf16_cu(&self, position: Position) -> usize {
+        self.try_position_to_utf16_cu(position).unwrap()
     }
 
     //-----------------------------------------------------------------------
@@ -481,17 +640,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx >= len_bytes()`).
     #[inline]
     pub fn byte(&self, byte_idx: usize) -> u8 {
-        // Bounds check
-        assert!(
-            byte_idx < self.len_bytes(),
-            "Attempt to index past end of slice: byte index {}, slice byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
-
-        let (chunk, chunk_byte_idx, _, _) = self.chunk_at_byte(byte_idx);
-        let chunk_rel_byte_idx = byte_idx - chunk_byte_idx;
-        chunk.as_bytes()[chunk_rel_byte_idx]
+        self.try_byte(byte_idx).unwrap()
     }
 
     /// Returns the char at `char_idx`.
@@ -503,17 +652,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx >= len_chars()`).
     #[inline]
     pub fn char(&self, char_idx: usize) -> char {
-        // Bounds check
-        assert!(
-            char_idx < self.len_chars(),
-            "Attempt to index past end of slice: char index {}, slice char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        let (chunk, _, chunk_char_idx, _) = self.chunk_at_char(char_idx);
-        let byte_idx = char_to_byte_idx(chunk, char_idx - chunk_char_idx);
-        chunk[byte_idx..].chars().next().unwrap()
+        self.try_char(char_idx).unwrap()
     }
 
     /// Returns the line at `line_idx`.
@@ -527,32 +666,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `line_idx` is out of bounds (i.e. `line_idx >= len_lines()`).
     #[inline]
     pub fn line(&self, line_idx: usize) -> RopeSlice<'a> {
-        let len_lines = self.len_lines();
-
-        // Bounds check
-        assert!(
-            line_idx < len_lines,
-            "Attempt to index past end of slice: line index {}, slice line length {}",
-            line_idx,
-            len_lines
-        );
-
-        let (chunk_1, _, c1, l1) = self.chunk_at_line_break(line_idx);
-        let (chunk_2, _, c2, l2) = self.chunk_at_line_break(line_idx + 1);
-        if c1 == c2 {
-            let text1 = &chunk_1[line_to_byte_idx(chunk_1, line_idx - l1)..];
-            let text2 = &text1[..line_to_byte_idx(text1, 1)];
-            RopeSlice(RSEnum::Light {
-                text: text2,
-                char_count: count_chars(text2) as Count,
-                utf16_surrogate_count: count_utf16_surrogates(text2) as Count,
-                line_break_count: if line_idx == (len_lines - 1) { 0 } else { 1 },
-            })
-        } else {
-            let start = c1 + line_to_char_idx(chunk_1, line_idx - l1);
-            let end = c2 + line_to_char_idx(chunk_2, line_idx + 1 - l2);
-            self.slice(start..end)
-        }
+        self.try_line(line_idx).unwrap()
     }
 
     /// Returns the chunk containing the given byte index.
@@ -572,42 +686,8 @@ impl<'a> RopeSlice<'a> {
     ///
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
     pub fn chunk_at_byte(&self, byte_idx: usize) -> (&'a str, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            byte_idx <= self.len_bytes(),
-            "Attempt to index past end of slice: byte index {}, slice byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
-
-        match *self {
-            RopeSlice(RSEnum::Full {
-                node,
-                start_info,
-                end_info,
-            }) => {
-                // Get the chunk.
-                let (chunk, chunk_start_info) =
-                    node.get_chunk_at_byte(byte_idx + start_info.bytes as usize);
-
-                // Calculate clipped start/end byte indices within the chunk.
-                let chunk_start_byte_idx = start_info.bytes.saturating_sub(chunk_start_info.bytes);
-                let chunk_end_byte_idx =
-                    (chunk.len() as Count).min(end_info.bytes - chunk_start_info.bytes);
-
-                // Return the clipped chunk and byte offset.
-                (
-                    &chunk[chunk_start_byte_idx as usize..chunk_end_byte_idx as usize],
-                    chunk_start_info.bytes.saturating_sub(start_info.bytes) as usize,
-                    chunk_start_info.chars.saturating_sub(start_info.chars) as usize,
-                    chunk_start_info
-                        .line_breaks
-                        .saturating_sub(start_info.line_breaks) as usize,
-                )
-            }
-            RopeSlice(RSEnum::Light { text, .. }) => (text, 0, 0, 0),
-        }
-    }
+        self.try_chunk_at_byte(byte_idx).unwrap()
+    }
 
     /// Returns the chunk containing the given char index.
     ///
@@ -626,41 +706,7 @@ impl<'a> RopeSlice<'a> {
     ///
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     pub fn chunk_at_char(&self, char_idx: usize) -> (&'a str, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of slice: char index {}, slice char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        match *self {
-            RopeSlice(RSEnum::Full {
-                node,
-                start_info,
-                end_info,
-            }) => {
-                // Get the chunk.
-                let (chunk, chunk_start_info) =
-                    node.get_chunk_at_char(char_idx + start_info.chars as usize);
-
-                // Calculate clipped start/end byte indices within the chunk.
-                let chunk_start_byte_idx = start_info.bytes.saturating_sub(chunk_start_info.bytes);
-                let chunk_end_byte_idx =
-                    (chunk.len() as Count).min(end_info.bytes - chunk_start_info.bytes);
-
-                // Return the clipped chunk and byte offset.
-                (
-                    &chunk[chunk_start_byte_idx as usize..chunk_end_byte_idx as usize],
-                    chunk_start_info.bytes.saturating_sub(start_info.bytes) as usize,
-                    chunk_start_info.chars.saturating_sub(start_info.chars) as usize,
-                    chunk_start_info
-                        .line_breaks
-                        .saturating_sub(start_info.line_breaks) as usize,
-                )
-            }
-            RopeSlice(RSEnum::Light { text, .. }) => (text, 0, 0, 0),
-        }
+        self.try_chunk_at_char(char_idx).unwrap()
     }
 
     /// Returns the chunk containing the given line break.
@@ -683,46 +729,7 @@ impl<'a> RopeSlice<'a> {
     ///
     /// Panics if `line_break_idx` is out of bounds (i.e. `line_break_idx > len_lines()`).
     pub fn chunk_at_line_break(&self, line_break_idx: usize) -> (&'a str, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            line_break_idx <= self.len_lines(),
-            "Attempt to index past end of Rope: line break index {}, max index {}",
-            line_break_idx,
-            self.len_lines()
-        );
-
-        match *self {
-            RopeSlice(RSEnum::Full {
-                node,
-                start_info,
-                end_info,
-            }) => {
-                // Get the chunk.
-                let (chunk, chunk_start_info) = if line_break_idx == 0 {
-                    node.get_chunk_at_byte(start_info.bytes as usize)
-                } else if line_break_idx == self.len_lines() {
-                    node.get_chunk_at_byte(end_info.bytes as usize)
-                } else {
-                    node.get_chunk_at_line_break(line_break_idx + start_info.line_breaks as usize)
-                };
-
-                // Calculate clipped start/end byte indices within the chunk.
-                let chunk_start_byte_idx = start_info.bytes.saturating_sub(chunk_start_info.bytes);
-                let chunk_end_byte_idx =
-                    (chunk.len() as Count).min(end_info.bytes - chunk_start_info.bytes);
-
-                // Return the clipped chunk and byte offset.
-                (
-                    &chunk[chunk_start_byte_idx as usize..chunk_end_byte_idx as usize],
-                    chunk_start_info.bytes.saturating_sub(start_info.bytes) as usize,
-                    chunk_start_info.chars.saturating_sub(start_info.chars) as usize,
-                    chunk_start_info
-                        .line_breaks
-                        .saturating_sub(start_info.line_breaks) as usize,
-                )
-            }
-            RopeSlice(RSEnum::Light { text, .. }) => (text, 0, 0, 0),
-        }
+        self.try_chunk_at_line_break(line_break_idx).unwrap()
     }
 
     /// Returns the entire contents of the `RopeSlice` as a `&str` if
@@ -763,50 +770,44 @@ impl<'a> RopeSlice<'a> {
     where
         R: RangeBounds<usize>,
     {
-        let (start, end) = {
-            let start_range = start_bound_to_num(char_range.start_bound());
-            let end_range = end_bound_to_num(char_range.end_bound());
-
-            // Early-out shortcut for taking a slice of the full thing.
-            if start_range == None && end_range == None {
-                return *self;
-            }
-
-            (
-                start_range.unwrap_or(0),
-                end_range.unwrap_or_else(|| self.len_chars()),
-            )
-        };
+        self.try_slice(char_range).unwrap()
+    }
 
-        // Bounds check
-        assert!(start <= end);
-        assert!(
-            end <= self.len_chars(),
-            "Attempt to slice past end of RopeSlice: slice end {}, RopeSlice length {}",
-            end,
-            self.len_chars()
-        );
+    /// Returns a sub-slice of the `RopeSlice` in the given byte index range.
+    ///
+    /// Uses range syntax, e.g. `2..7`, `2..`, etc.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, if the end
+    /// is out of bounds (i.e. `end > len_bytes()`), or if either end of the
+    /// range isn't on a char boundary.
+    pub fn byte_slice<R>(&self, byte_range: R) -> Self
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_byte_slice(byte_range).unwrap()
+    }
 
-        match *self {
-            RopeSlice(RSEnum::Full {
-                node, start_info, ..
-            }) => RopeSlice::new_with_range(
-                node,
-                start_info.chars as usize + start,
-                start_info.chars as usize + end,
-            ),
-            RopeSlice(RSEnum::Light { text, .. }) => {
-                let start_byte = char_to_byte_idx(text, start);
-                let end_byte = char_to_byte_idx(text, end);
-                let new_text = &text[start_byte..end_byte];
-                RopeSlice(RSEnum::Light {
-                    text: new_text,
-                    char_count: (end - start) as Count,
-                    utf16_surrogate_count: count_utf16_surrogates(new_text) as Count,
-                    line_break_count: count_line_breaks(new_text) as Count,
-                })
-            }
-        }
+    /// Returns a sub-slice of the `RopeSlice` in the given utf16 code unit
+    /// index range.
+    ///
+    /// Uses range syntax, e.g. `2..7`, `2..`, etc.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, if the end
+    /// is out of bounds (i.e. `end > len_utf16_cu()`), or if either end of
+    /// the range is in the middle of a surrogate pair.
+    pub fn utf16_cu_slice<R>(&self, utf16_cu_range: R) -> Self
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_utf16_cu_slice(utf16_cu_range).unwrap()
     }
 
     //-----------------------------------------------------------------------
@@ -848,32 +849,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
     #[inline]
     pub fn bytes_at(&self, byte_idx: usize) -> Bytes {
-        // Bounds check
-        assert!(
-            byte_idx <= self.len_bytes(),
-            "Attempt to index past end of RopeSlice: byte index {}, RopeSlice byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
-
-        match *self {
-            RopeSlice(RSEnum::Full {
-                node,
-                start_info,
-                end_info,
-            }) => Bytes::new_with_range_at(
-                node,
-                start_info.bytes as usize + byte_idx,
-                (start_info.bytes as usize, end_info.bytes as usize),
-                (start_info.chars as usize, end_info.chars as usize),
-                (
-                    start_info.line_breaks as usize,
-                    end_info.line_breaks as usize + 1,
-                ),
-            ),
-
-            RopeSlice(RSEnum::Light { text, .. }) => Bytes::from_str_at(text, byte_idx),
-        }
+        self.try_bytes_at(byte_idx).unwrap()
     }
 
     /// Creates an iterator over the chars of the `RopeSlice`.
@@ -912,32 +888,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn chars_at(&self, char_idx: usize) -> Chars {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of RopeSlice: char index {}, RopeSlice char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        match *self {
-            RopeSlice(RSEnum::Full {
-                node,
-                start_info,
-                end_info,
-            }) => Chars::new_with_range_at(
-                node,
-                start_info.chars as usize + char_idx,
-                (start_info.bytes as usize, end_info.bytes as usize),
-                (start_info.chars as usize, end_info.chars as usize),
-                (
-                    start_info.line_breaks as usize,
-                    end_info.line_breaks as usize + 1,
-                ),
-            ),
-
-            RopeSlice(RSEnum::Light { text, .. }) => Chars::from_str_at(text, char_idx),
-        }
+        self.try_chars_at(char_idx).unwrap()
     }
 
     /// Creates an iterator over the lines of the `RopeSlice`.
@@ -952,6 +903,7 @@ impl<'a> RopeSlice<'a> {
                 end_info,
             }) => Lines::new_with_range(
                 node,
+                (start_info.bytes as usize, end_info.bytes as usize),
                 (start_info.chars as usize, end_info.chars as usize),
                 (
                     start_info.line_breaks as usize,
@@ -975,30 +927,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `line_idx` is out of bounds (i.e. `line_idx > len_lines()`).
     #[inline]
     pub fn lines_at(&self, line_idx: usize) -> Lines {
-        // Bounds check
-        assert!(
-            line_idx <= self.len_lines(),
-            "Attempt to index past end of RopeSlice: line index {}, RopeSlice line length {}",
-            line_idx,
-            self.len_lines()
-        );
-
-        match *self {
-            RopeSlice(RSEnum::Full {
-                node,
-                start_info,
-                end_info,
-            }) => Lines::new_with_range_at(
-                node,
-                start_info.line_breaks as usize + line_idx,
-                (start_info.chars as usize, end_info.chars as usize),
-                (
-                    start_info.line_breaks as usize,
-                    end_info.line_breaks as usize + 1,
-                ),
-            ),
-            RopeSlice(RSEnum::Light { text, .. }) => Lines::from_str_at(text, line_idx),
-        }
+        self.try_lines_at(line_idx).unwrap()
     }
 
     /// Creates an iterator over the chunks of the `RopeSlice`.
@@ -1043,60 +972,7 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `byte_idx` is out of bounds (i.e. `byte_idx > len_bytes()`).
     #[inline]
     pub fn chunks_at_byte(&self, byte_idx: usize) -> (Chunks<'a>, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            byte_idx <= self.len_bytes(),
-            "Attempt to index past end of RopeSlice: byte index {}, RopeSlice byte length {}",
-            byte_idx,
-            self.len_bytes()
-        );
-
-        match *self {
-            RopeSlice(RSEnum::Full {
-                node,
-                start_info,
-                end_info,
-            }) => {
-                let (chunks, chunk_byte_idx, chunk_char_idx, chunk_line_idx) =
-                    Chunks::new_with_range_at_byte(
-                        node,
-                        byte_idx + start_info.bytes as usize,
-                        (start_info.bytes as usize, end_info.bytes as usize),
-                        (start_info.chars as usize, end_info.chars as usize),
-                        (
-                            start_info.line_breaks as usize,
-                            end_info.line_breaks as usize + 1,
-                        ),
-                    );
-
-                (
-                    chunks,
-                    chunk_byte_idx.saturating_sub(start_info.bytes as usize),
-                    chunk_char_idx.saturating_sub(start_info.chars as usize),
-                    chunk_line_idx.saturating_sub(start_info.line_breaks as usize),
-                )
-            }
-
-            RopeSlice(RSEnum::Light {
-                text,
-                char_count,
-                line_break_count,
-                ..
-            }) => {
-                let chunks = Chunks::from_str(text, byte_idx == text.len());
-
-                if byte_idx == text.len() {
-                    (
-                        chunks,
-                        text.len(),
-                        char_count as usize,
-                        line_break_count as usize,
-                    )
-                } else {
-                    (chunks, 0, 0, 0)
-                }
-            }
-        }
+        self.try_chunks_at_byte(byte_idx).unwrap()
     }
 
     /// Creates an iterator over the chunks of the `RopeSlice`, with the
@@ -1118,38 +994,1174 @@ impl<'a> RopeSlice<'a> {
     /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
     #[inline]
     pub fn chunks_at_char(&self, char_idx: usize) -> (Chunks<'a>, usize, usize, usize) {
-        // Bounds check
-        assert!(
-            char_idx <= self.len_chars(),
-            "Attempt to index past end of RopeSlice: char index {}, RopeSlice char length {}",
-            char_idx,
-            self.len_chars()
-        );
-
-        match *self {
-            RopeSlice(RSEnum::Full {
-                node,
-                start_info,
-                end_info,
-            }) => {
-                let (chunks, chunk_byte_idx, chunk_char_idx, chunk_line_idx) =
-                    Chunks::new_with_range_at_char(
-                        node,
-                        char_idx + start_info.chars as usize,
-                        (start_info.bytes as usize, end_info.bytes as usize),
-                        (start_info.chars as usize, end_info.chars as usize),
-                        (
-                            start_info.line_breaks as usize,
-                            end_info.line_breaks as usize + 1,
-                        ),
-                    );
+        self.try_chunks_at_char(char_idx).unwrap()
+    }
 
-                (
-                    chunks,
+    /// Creates an iterator over the chunks of the `RopeSlice`, with the
+    /// iterator starting at the chunk containing `line_break_idx`.
+    ///
+    /// Also returns the byte and char indices of the beginning of the first
+    /// chunk to be yielded, and the index of the line that chunk starts on.
+    ///
+    /// Note: for convenience, both the beginning and end of the `RopeSlice` are
+    /// considered line breaks for the purposes of indexing.  For example, in
+    /// the string `"Hello \n world!"` 0 would create an iterator starting on
+    /// the first chunk, 1 would create an iterator starting on the chunk
+    /// containing the newline character, and 2 would create an iterator at
+    /// the end of the `RopeSlice` (yielding `None` on a call to `next()`).
+    ///
+    /// The return value is organized as
+    /// `(iterator, chunk_byte_idx, chunk_char_idx, chunk_line_idx)`.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line_break_idx` is out of bounds (i.e. `line_break_idx > len_lines()`).
+    #[inline]
+    pub fn chunks_at_line_break(&self, line_break_idx: usize) -> (Chunks, usize, usize, usize) {
+        self.try_chunks_at_line_break(line_break_idx).unwrap()
+    }
+
+    /// Creates a [`Cursor`](struct.Cursor.html) at the start of the `RopeSlice`.
+    ///
+    /// Runs in O(log N) time.
+    #[inline]
+    pub fn cursor(&self) -> Cursor<'a> {
+        Cursor::new(*self, 0)
+    }
+
+    /// Creates a [`Cursor`](struct.Cursor.html) at char index `char_idx`.
+    ///
+    /// If `char_idx == len_chars()` the cursor is created at the end of the
+    /// `RopeSlice`.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn cursor_at(&self, char_idx: usize) -> Cursor<'a> {
+        self.try_cursor_at(char_idx).unwrap()
+    }
+
+    /// Creates a [`RopeReader`](struct.RopeReader.html) over the
+    /// `RopeSlice`'s utf8 bytes, implementing `io::Read`, `io::BufRead`, and
+    /// `io::Seek`.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn reader(&self) -> RopeReader<'a> {
+        RopeReader::new(*self)
+    }
+
+    //-----------------------------------------------------------------------
+    // Search methods
+
+    /// Returns the char index of the first match of `pattern` in the
+    /// `RopeSlice`, or `None` if there is none.
+    ///
+    /// Matches that span chunk boundaries are found too.  An empty pattern
+    /// matches at the start of the `RopeSlice`.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[inline]
+    pub fn find(&self, pattern: &str) -> Option<usize> {
+        self.matches(pattern)
+            .next()
+            .map(|range| self.byte_to_char(range.start))
+    }
+
+    /// Returns the char index of the start of the last match of `pattern`
+    /// in the `RopeSlice`, or `None` if there is none.
+    ///
+    /// The search runs backwards from the end of the `RopeSlice`.  An empty
+    /// pattern matches at the end of the `RopeSlice`.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[inline]
+    pub fn rfind(&self, pattern: &str) -> Option<usize> {
+        rfind_byte(self, pattern).map(|byte_idx| self.byte_to_char(byte_idx))
+    }
+
+    /// Returns whether `pattern` occurs in the `RopeSlice`.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[inline]
+    pub fn contains(&self, pattern: &str) -> bool {
+        self.matches(pattern).next().is_some()
+    }
+
+    /// Creates an iterator over the char ranges of the non-overlapping
+    /// matches of `pattern` in the `RopeSlice`, from front to back.
+    ///
+    /// As with `str::match_indices()`, an empty pattern matches at every
+    /// char boundary.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn find_iter<'b>(&self, pattern: &'b str) -> FindIter<'b>
+    where
+        'a: 'b,
+    {
+        FindIter::new(*self, pattern)
+    }
+
+    /// Creates an iterator over the byte ranges of the non-overlapping
+    /// matches of `pattern` in the `RopeSlice`, from front to back.
+    ///
+    /// This is the same as [`find_iter()`](#method.find_iter), but cheaper
+    /// since byte indices don't need to be converted to char indices.
+    ///
+    /// Runs in O(1) time.
+    #[inline]
+    pub fn matches<'b>(&self, pattern: &'b str) -> Matches<'b>
+    where
+        'a: 'b,
+    {
+        Matches::new(*self, pattern)
+    }
+
+    /// Returns the leftmost-first match of `regex` in the `RopeSlice`, or `None`
+    /// if there is none.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[cfg(feature = "regex")]
+    #[inline]
+    pub fn find_regex(&self, regex: &Regex) -> Option<RegexMatch> {
+        find_regex(self, regex)
+    }
+
+    /// Returns the match of `regex` in the `RopeSlice` that starts last, or
+    /// `None` if there is none.
+    ///
+    /// The search runs backwards from the end of the `RopeSlice`, so this is
+    /// efficient for finding a match near the end.
+    ///
+    /// Runs in time proportional to the length of the text searched.
+    #[cfg(feature = "regex")]
+    #[inline]
+    pub fn rfind_regex(&self, regex: &Regex) -> Option<RegexMatch> {
+        rfind_regex(self, regex)
+    }
+
+    /// Creates an iterator over the non-overlapping matches of `regex` in
+    /// the `RopeSlice`, from front to back.
+    ///
+    /// Runs in O(1) time.
+    #[cfg(feature = "regex")]
+    #[inline]
+    pub fn find_regex_iter<'b>(&self, regex: &'b Regex) -> FindRegexIter<'b>
+    where
+        'a: 'b,
+    {
+        FindRegexIter::new(*self, regex)
+    }
+
+    //-----------------------------------------------------------------------
+    // Grapheme methods
+
+    /// Creates an iterator over the extended grapheme clusters of the
+    /// `RopeSlice`.
+    ///
+    /// Runs in O(log N) time.
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn graphemes(&self) -> Graphemes<'a> {
+        Graphemes::new(*self, 0)
+    }
+
+    /// Creates an iterator over the extended grapheme clusters of the
+    /// `RopeSlice`, starting at the grapheme boundary `char_idx`.
+    ///
+    /// If `char_idx` isn't a grapheme boundary, the iterator starts at the
+    /// boundary before it, so that the first call to `next()` returns the
+    /// grapheme containing `char_idx`.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn graphemes_at(&self, char_idx: usize) -> Graphemes<'a> {
+        self.try_graphemes_at(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the grapheme boundary before `char_idx`,
+    /// or 0 if there is none.
+    ///
+    /// Runs in O(log N) time, for graphemes of bounded length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn prev_grapheme_boundary(&self, char_idx: usize) -> usize {
+        self.try_prev_grapheme_boundary(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the grapheme boundary after `char_idx`,
+    /// or `len_chars()` if there is none.
+    ///
+    /// Runs in O(log N) time, for graphemes of bounded length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn next_grapheme_boundary(&self, char_idx: usize) -> usize {
+        self.try_next_grapheme_boundary(char_idx).unwrap()
+    }
+
+    /// Returns whether `char_idx` is a grapheme boundary.
+    ///
+    /// The start and end of the `RopeSlice` are always grapheme boundaries.
+    ///
+    /// Runs in O(log N) time, for graphemes of bounded length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn is_grapheme_boundary(&self, char_idx: usize) -> bool {
+        self.try_is_grapheme_boundary(char_idx).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Word and sentence methods
+
+    /// Creates an iterator over the char ranges of the words of the
+    /// `RopeSlice`, as determined by Unicode Standard Annex #29.
+    ///
+    /// Only the words themselves are yielded, skipping the whitespace and
+    /// punctuation between them.
+    ///
+    /// Runs in O(1) time.
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn words(&self) -> Words<'a> {
+        Words::new(*self)
+    }
+
+    /// Returns the char range of the word boundary segment containing the
+    /// char at `char_idx`: a word, or a run of whitespace, or a punctuation
+    /// char.  This is what e.g. double-clicking a word should select.
+    ///
+    /// Returns an empty range at the end if `char_idx == len_chars()`.
+    ///
+    /// Runs in O(log N) time plus the length of the segment, along with a
+    /// few hundred chars around it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn word_bounds_at(&self, char_idx: usize) -> Range<usize> {
+        self.try_word_bounds_at(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the start of the first word that starts
+    /// after `char_idx`, or `len_chars()` if there is none.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn next_word_start(&self, char_idx: usize) -> usize {
+        self.try_next_word_start(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the end of the last word that ends before
+    /// `char_idx`, or 0 if there is none.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn prev_word_end(&self, char_idx: usize) -> usize {
+        self.try_prev_word_end(char_idx).unwrap()
+    }
+
+    /// Creates an iterator over the char ranges of the sentences of the
+    /// `RopeSlice`, as determined by Unicode Standard Annex #29.
+    ///
+    /// Runs in O(1) time.
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn sentences(&self) -> Sentences<'a> {
+        Sentences::new(*self)
+    }
+
+    /// Returns the char range of the sentence boundary segment containing
+    /// the char at `char_idx`.
+    ///
+    /// Returns an empty range at the end if `char_idx == len_chars()`.
+    ///
+    /// Runs in O(log N) time plus the length of the segment, along with a
+    /// few hundred chars around it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn sentence_bounds_at(&self, char_idx: usize) -> Range<usize> {
+        self.try_sentence_bounds_at(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the start of the first sentence that
+    /// starts after `char_idx`, or `len_chars()` if there is none.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn next_sentence_start(&self, char_idx: usize) -> usize {
+        self.try_next_sentence_start(char_idx).unwrap()
+    }
+
+    /// Returns the char index of the end of the last sentence that ends
+    /// before `char_idx`, or 0 if there is none.
+    ///
+    /// Runs in O(log N) time plus the length of the text searched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[cfg(feature = "unicode-segmentation")]
+    #[inline]
+    pub fn prev_sentence_end(&self, char_idx: usize) -> usize {
+        self.try_prev_sentence_end(char_idx).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Visual column methods
+
+    /// Returns the display width of the `RopeSlice`'s text, in columns, as
+    /// if it were shown on a single line.
+    ///
+    /// East Asian wide chars count as two columns and combining marks and
+    /// control chars as zero, as determined by `unicode-width`.  Tabs
+    /// advance to the next multiple of `tab_width`, and line breaks go back
+    /// to column zero.  For text without line breaks this is just its width.
+    ///
+    /// Runs in O(N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tab_width` is zero.
+    #[cfg(feature = "unicode-width")]
+    #[inline]
+    pub fn visual_width(&self, tab_width: usize) -> usize {
+        visual::width(self, tab_width)
+    }
+
+    /// Returns the visual column of `char_idx` within its line, with tabs
+    /// expanded to multiples of `tab_width`.
+    ///
+    /// See [`visual_width()`](#method.visual_width) for how chars are
+    /// measured, and [`VisualColumns`](struct.VisualColumns.html) for a
+    /// version that doesn't need to scan from the start of the line.
+    ///
+    /// Runs in O(log N) time plus the length of the line up to `char_idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`),
+    /// or if `tab_width` is zero.
+    #[cfg(feature = "unicode-width")]
+    #[inline]
+    pub fn char_to_visual_col(&self, char_idx: usize, tab_width: usize) -> usize {
+        self.try_char_to_visual_col(char_idx, tab_width).unwrap()
+    }
+
+    /// Returns the char index at visual column `col` of line `line_idx`,
+    /// with tabs expanded to multiples of `tab_width`.
+    ///
+    /// If `col` is past the end of the line, the index of the end of the
+    /// line (before its line break) is returned.  If `col` falls in the
+    /// middle of a char that is more than one column wide, `rounding`
+    /// decides which side of it to return.  Zero-width chars at `col` are
+    /// skipped over.
+    ///
+    /// Runs in O(log N) time plus the length of the line up to `col`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line_idx` is out of bounds (i.e. `line_idx >= len_lines()`),
+    /// or if `tab_width` is zero.
+    #[cfg(feature = "unicode-width")]
+    #[inline]
+    pub fn visual_col_to_char(
+        &self,
+        line_idx: usize,
+        col: usize,
+        tab_width: usize,
+        rounding: ColumnRounding,
+    ) -> usize {
+        self.try_visual_col_to_char(line_idx, col, tab_width, rounding)
+            .unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Fallible versions of the above methods
+
+    /// Non-panicking version of [`byte_to_char()`](#method.byte_to_char).
+    #[inline]
+    pub fn try_byte_to_char(&self, byte_idx: usize) -> Result<usize> {
+        // Bounds check
+        if byte_idx <= self.len_bytes() {
+            let (chunk, b, c, _) = self.chunk_at_byte(byte_idx);
+            Ok(c + byte_to_char_idx(chunk, byte_idx - b))
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`byte_to_line()`](#method.byte_to_line).
+    #[inline]
+    pub fn try_byte_to_line(&self, byte_idx: usize) -> Result<usize> {
+        // Bounds check
+        if byte_idx <= self.len_bytes() {
+            let (chunk, b, _, l) = self.chunk_at_byte(byte_idx);
+            Ok(l + byte_to_line_idx(chunk, byte_idx - b))
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`byte_to_utf16_cu()`](#method.byte_to_utf16_cu).
+    #[inline]
+    pub fn try_byte_to_utf16_cu(&self, byte_idx: usize) -> Result<usize> {
+        let char_idx = self.try_byte_to_char(byte_idx)?;
+        Ok(self.char_to_utf16_cu(char_idx))
+    }
+
+    /// Non-panicking version of [`char_to_byte()`](#method.char_to_byte).
+    #[inline]
+    pub fn try_char_to_byte(&self, char_idx: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            let (chunk, b, c, _) = self.chunk_at_char(char_idx);
+            Ok(b + char_to_byte_idx(chunk, char_idx - c))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`char_to_line()`](#method.char_to_line).
+    #[inline]
+    pub fn try_char_to_line(&self, char_idx: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            let (chunk, _, c, l) = self.chunk_at_char(char_idx);
+            Ok(l + char_to_line_idx(chunk, char_idx - c))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`char_to_utf16_cu()`](#method.char_to_utf16_cu).
+    #[inline]
+    pub fn try_char_to_utf16_cu(&self, char_idx: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx > self.len_chars() {
+            return Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                ref node,
+                start_info,
+                ..
+            }) => {
+                let char_idx = char_idx + start_info.chars as usize;
+
+                let (chunk, chunk_start_info) = node.get_chunk_at_char(char_idx);
+                let chunk_byte_idx =
+                    char_to_byte_idx(chunk, char_idx - chunk_start_info.chars as usize);
+                let surrogate_count = byte_to_utf16_surrogate_idx(chunk, chunk_byte_idx);
+
+                Ok(
+                    char_idx + chunk_start_info.utf16_surrogates as usize + surrogate_count
+                        - start_info.chars as usize
+                        - start_info.utf16_surrogates as usize,
+                )
+            }
+
+            RopeSlice(RSEnum::Light { text, .. }) => {
+                let byte_idx = char_to_byte_idx(text, char_idx);
+                let surrogate_count = byte_to_utf16_surrogate_idx(text, byte_idx);
+                Ok(char_idx + surrogate_count)
+            }
+        }
+    }
+
+    /// Non-panicking version of [`utf16_cu_to_char()`](#method.utf16_cu_to_char).
+    #[inline]
+    pub fn try_utf16_cu_to_char(&self, utf16_cu_idx: usize) -> Result<usize> {
+        // Bounds check
+        if utf16_cu_idx > self.len_utf16_cu() {
+            return Err(Error::Utf16IndexOutOfBounds(
+                utf16_cu_idx,
+                self.len_utf16_cu(),
+            ));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                ref node,
+                start_info,
+                ..
+            }) => {
+                let utf16_cu_idx =
+                    utf16_cu_idx + (start_info.chars + start_info.utf16_surrogates) as usize;
+
+                let (chunk, chunk_start_info) = node.get_chunk_at_utf16_code_unit(utf16_cu_idx);
+                let chunk_utf16_cu_idx = utf16_cu_idx
+                    - (chunk_start_info.chars + chunk_start_info.utf16_surrogates) as usize;
+                let chunk_char_idx = utf16_code_unit_to_char_idx(chunk, chunk_utf16_cu_idx);
+
+                Ok(chunk_start_info.chars as usize + chunk_char_idx - start_info.chars as usize)
+            }
+
+            RopeSlice(RSEnum::Light { text, .. }) => {
+                Ok(utf16_code_unit_to_char_idx(text, utf16_cu_idx))
+            }
+        }
+    }
+
+    /// Non-panicking version of [`utf16_cu_to_byte()`](#method.utf16_cu_to_byte).
+    #[inline]
+    pub fn try_utf16_cu_to_byte(&self, utf16_cu_idx: usize) -> Result<usize> {
+        let char_idx = self.try_utf16_cu_to_char(utf16_cu_idx)?;
+        Ok(self.char_to_byte(char_idx))
+    }
+
+    /// Non-panicking version of [`utf16_cu_to_line()`](#method.utf16_cu_to_line).
+    #[inline]
+    pub fn try_utf16_cu_to_line(&self, utf16_cu_idx: usize) -> Result<usize> {
+        let char_idx = self.try_utf16_cu_to_char(utf16_cu_idx)?;
+        Ok(self.char_to_line(char_idx))
+    }
+
+    /// Non-panicking version of [`line_to_byte()`](#method.line_to_byte).
+    #[inline]
+    pub fn try_line_to_byte(&self, line_idx: usize) -> Result<usize> {
+        // Bounds check
+        if line_idx <= self.len_lines() {
+            if line_idx == self.len_lines() {
+                Ok(self.len_bytes())
+            } else {
+                let (chunk, b, _, l) = self.chunk_at_line_break(line_idx);
+                Ok(b + line_to_byte_idx(chunk, line_idx - l))
+            }
+        } else {
+            Err(Error::LineIndexOutOfBounds(line_idx, self.len_lines()))
+        }
+    }
+
+    /// Non-panicking version of [`line_to_char()`](#method.line_to_char).
+    #[inline]
+    pub fn try_line_to_char(&self, line_idx: usize) -> Result<usize> {
+        // Bounds check
+        if line_idx <= self.len_lines() {
+            if line_idx == self.len_lines() {
+                Ok(self.len_chars())
+            } else {
+                let (chunk, _, c, l) = self.chunk_at_line_break(line_idx);
+                Ok(c + line_to_char_idx(chunk, line_idx - l))
+            }
+        } else {
+            Err(Error::LineIndexOutOfBounds(line_idx, self.len_lines()))
+        }
+    }
+
+    /// Non-panicking version of [`line_to_utf16_cu()`](#method.line_to_utf16_cu).
+    #[inline]
+    pub fn try_line_to_utf16_cu(&self, line_idx: usize) -> Result<usize> {
+        let char_idx = self.try_line_to_char(line_idx)?;
+        Ok(self.char_to_utf16_cu(char_idx))
+    }
+
+    /// Non-panicking version of [`char_to_position()`](#method.char_to_position).
+    #[inline]
+    pub fn try_char_to_position(&self, char_idx: usize) -> Result<Position> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(self.idx_to_position(char_idx, |info| info.chars as usize, char_to_byte_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`position_to_char()`](#method.position_to_char).
+    #[inline]
+    pub fn try_position_to_char(&self, position: Position) -> Result<usize> {
+        let (line_start, line_end) = self.line_bounds(position.line, |info| info.chars as usize)?;
+        check_column(position, line_start, line_end, self.len_lines())
+    }
+
+    /// Non-panicking version of [`byte_to_position()`](#method.byte_to_position).
+    #[inline]
+    pub fn try_byte_to_position(&self, byte_idx: usize) -> Result<Position> {
+        // Bounds check
+        if byte_idx <= self.len_bytes() {
+            Ok(self.idx_to_position(byte_idx, |info| info.bytes as usize, |_, idx| idx))
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`position_to_byte()`](#method.position_to_byte).
+    #[inline]
+    pub fn try_position_to_byte(&self, position: Position) -> Result<usize> {
+        let (line_start, line_end) = self.line_bounds(position.line, |info| info.bytes as usize)?;
+        check_column(position, line_start, line_end, self.len_lines())
+    }
+
+    /// Non-panicking version of [`utf16_cu_to_position()`](#method.utf16_cu_to_position).
+    #[inline]
+    pub fn try_utf16_cu_to_position(&self, utf16_cu_idx: usize) -> Result<Position> {
+        // Bounds check
+        if utf16_cu_idx <= self.len_utf16_cu() {
+            Ok(self.idx_to_position(
+                utf16_cu_idx,
+                |info| (info.chars + info.utf16_surrogates) as usize,
+                |text, idx| char_to_byte_idx(text, utf16_code_unit_to_char_idx(text, idx)),
+            ))
+        } else {
+            Err(Error::Utf16IndexOutOfBounds(
+                utf16_cu_idx,
+                self.len_utf16_cu(),
+            ))
+        }
+    }
+
+    /// Non-panicking version of [`position_to_utf16_cu()`](#method.position_to_utf16_cu).
+    #[inline]
+    pub fn try_position_to_utf16_cu(&self, position: Position) -> Result<usize> {
+        let (line_start, line_end) = self.line_bounds(position.line, |info| {
+            (info.chars + info.utf16_surrogates) as usize
+        })?;
+        check_column(position, line_start, line_end, self.len_lines())
+    }
+
+    /// Non-panicking version of [`byte()`](#method.byte).
+    #[inline]
+    pub fn try_byte(&self, byte_idx: usize) -> Result<u8> {
+        // Bounds check
+        if byte_idx < self.len_bytes() {
+            let (chunk, chunk_byte_idx, _, _) = self.chunk_at_byte(byte_idx);
+            let chunk_rel_byte_idx = byte_idx - chunk_byte_idx;
+            Ok(chunk.as_bytes()[chunk_rel_byte_idx])
+        } else {
+            Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()))
+        }
+    }
+
+    /// Non-panicking version of [`char()`](#method.char).
+    #[inline]
+    pub fn try_char(&self, char_idx: usize) -> Result<char> {
+        // Bounds check
+        if char_idx < self.len_chars() {
+            let (chunk, _, chunk_char_idx, _) = self.chunk_at_char(char_idx);
+            let byte_idx = char_to_byte_idx(chunk, char_idx - chunk_char_idx);
+            Ok(chunk[byte_idx..].chars().next().unwrap())
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`line()`](#method.line).
+    #[inline]
+    pub fn try_line(&self, line_idx: usize) -> Result<RopeSlice<'a>> {
+        let len_lines = self.len_lines();
+
+        // Bounds check
+        if line_idx < len_lines {
+            let (chunk_1, _, c1, l1) = self.chunk_at_line_break(line_idx);
+            let (chunk_2, _, c2, l2) = self.chunk_at_line_break(line_idx + 1);
+            if c1 == c2 {
+                let text1 = &chunk_1[line_to_byte_idx(chunk_1, line_idx - l1)..];
+                let text2 = &text1[..line_to_byte_idx(text1, 1)];
+                Ok(RopeSlice(RSEnum::Light {
+                    text: text2,
+                    char_count: count_chars(text2) as Count,
+                    utf16_surrogate_count: count_utf16_surrogates(text2) as Count,
+                    line_break_count: if line_idx == (len_lines - 1) { 0 } else { 1 },
+                }))
+            } else {
+                let start = c1 + line_to_char_idx(chunk_1, line_idx - l1);
+                let end = c2 + line_to_char_idx(chunk_2, line_idx + 1 - l2);
+                Ok(self.slice(start..end))
+            }
+        } else {
+            Err(Error::LineIndexOutOfBounds(line_idx, len_lines))
+        }
+    }
+
+    /// Non-panicking version of [`chunk_at_byte()`](#method.chunk_at_byte).
+    pub fn try_chunk_at_byte(&self, byte_idx: usize) -> Result<(&'a str, usize, usize, usize)> {
+        // Bounds check
+        if byte_idx > self.len_bytes() {
+            return Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
+            }) => {
+                // Get the chunk.
+                let (chunk, chunk_start_info) =
+                    node.get_chunk_at_byte(byte_idx + start_info.bytes as usize);
+
+                // Calculate clipped start/end byte indices within the chunk.
+                let chunk_start_byte_idx = start_info.bytes.saturating_sub(chunk_start_info.bytes);
+                let chunk_end_byte_idx =
+                    (chunk.len() as Count).min(end_info.bytes - chunk_start_info.bytes);
+
+                // Return the clipped chunk and byte offset.
+                Ok((
+                    &chunk[chunk_start_byte_idx as usize..chunk_end_byte_idx as usize],
+                    chunk_start_info.bytes.saturating_sub(start_info.bytes) as usize,
+                    chunk_start_info.chars.saturating_sub(start_info.chars) as usize,
+                    chunk_start_info
+                        .line_breaks
+                        .saturating_sub(start_info.line_breaks) as usize,
+                ))
+            }
+            RopeSlice(RSEnum::Light { text, .. }) => Ok((text, 0, 0, 0)),
+        }
+    }
+
+    /// Non-panicking version of [`chunk_at_char()`](#method.chunk_at_char).
+    pub fn try_chunk_at_char(&self, char_idx: usize) -> Result<(&'a str, usize, usize, usize)> {
+        // Bounds check
+        if char_idx > self.len_chars() {
+            return Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
+            }) => {
+                // Get the chunk.
+                let (chunk, chunk_start_info) =
+                    node.get_chunk_at_char(char_idx + start_info.chars as usize);
+
+                // Calculate clipped start/end byte indices within the chunk.
+                let chunk_start_byte_idx = start_info.bytes.saturating_sub(chunk_start_info.bytes);
+                let chunk_end_byte_idx =
+                    (chunk.len() as Count).min(end_info.bytes - chunk_start_info.bytes);
+
+                // Return the clipped chunk and byte offset.
+                Ok((
+                    &chunk[chunk_start_byte_idx as usize..chunk_end_byte_idx as usize],
+                    chunk_start_info.bytes.saturating_sub(start_info.bytes) as usize,
+                    chunk_start_info.chars.saturating_sub(start_info.chars) as usize,
+                    chunk_start_info
+                        .line_breaks
+                        .saturating_sub(start_info.line_breaks) as usize,
+                ))
+            }
+            RopeSlice(RSEnum::Light { text, .. }) => Ok((text, 0, 0, 0)),
+        }
+    }
+
+    /// Non-panicking version of
+    /// [`chunk_at_line_break()`](#method.chunk_at_line_break).
+    pub fn try_chunk_at_line_break(
+        &self,
+        line_break_idx: usize,
+    ) -> Result<(&'a str, usize, usize, usize)> {
+        // Bounds check
+        if line_break_idx > self.len_lines() {
+            return Err(Error::LineIndexOutOfBounds(
+                line_break_idx,
+                self.len_lines(),
+            ));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
+            }) => {
+                // Get the chunk.
+                let (chunk, chunk_start_info) = if line_break_idx == 0 {
+                    node.get_chunk_at_byte(start_info.bytes as usize)
+                } else if line_break_idx == self.len_lines() {
+                    node.get_chunk_at_byte(end_info.bytes as usize)
+                } else {
+                    node.get_chunk_at_line_break(line_break_idx + start_info.line_breaks as usize)
+                };
+
+                // Calculate clipped start/end byte indices within the chunk.
+                let chunk_start_byte_idx = start_info.bytes.saturating_sub(chunk_start_info.bytes);
+                let chunk_end_byte_idx =
+                    (chunk.len() as Count).min(end_info.bytes - chunk_start_info.bytes);
+
+                // Return the clipped chunk and byte offset.
+                Ok((
+                    &chunk[chunk_start_byte_idx as usize..chunk_end_byte_idx as usize],
+                    chunk_start_info.bytes.saturating_sub(start_info.bytes) as usize,
+                    chunk_start_info.chars.saturating_sub(start_info.chars) as usize,
+                    chunk_start_info
+                        .line_breaks
+                        .saturating_sub(start_info.line_breaks) as usize,
+                ))
+            }
+            RopeSlice(RSEnum::Light { text, .. }) => Ok((text, 0, 0, 0)),
+        }
+    }
+
+    /// Non-panicking version of [`slice()`](#method.slice).
+    pub fn try_slice<R>(&self, char_range: R) -> Result<RopeSlice<'a>>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start_range = start_bound_to_num(char_range.start_bound());
+        let end_range = end_bound_to_num(char_range.end_bound());
+
+        // Early-out shortcut for taking a slice of the full thing.
+        if start_range == None && end_range == None {
+            return Ok(*self);
+        }
+
+        let start = start_range.unwrap_or(0);
+        let end = end_range.unwrap_or_else(|| self.len_chars());
+
+        // Bounds check
+        if start > end {
+            return Err(Error::CharRangeInvalid(start, end));
+        } else if end > self.len_chars() {
+            return Err(Error::CharRangeOutOfBounds(
+                start_range,
+                end_range,
+                self.len_chars(),
+            ));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node, start_info, ..
+            }) => Ok(RopeSlice::new_with_range(
+                node,
+                start_info.chars as usize + start,
+                start_info.chars as usize + end,
+            )),
+            RopeSlice(RSEnum::Light { text, .. }) => {
+                let start_byte = char_to_byte_idx(text, start);
+                let end_byte = char_to_byte_idx(text, end);
+                let new_text = &text[start_byte..end_byte];
+                Ok(RopeSlice(RSEnum::Light {
+                    text: new_text,
+                    char_count: (end - start) as Count,
+                    utf16_surrogate_count: count_utf16_surrogates(new_text) as Count,
+                    line_break_count: count_line_breaks(new_text) as Count,
+                }))
+            }
+        }
+    }
+
+    /// Non-panicking version of [`byte_slice()`](#method.byte_slice).
+    pub fn try_byte_slice<R>(&self, byte_range: R) -> Result<RopeSlice<'a>>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start_range = start_bound_to_num(byte_range.start_bound());
+        let end_range = end_bound_to_num(byte_range.end_bound());
+
+        // Early-out shortcut for taking a slice of the full thing.
+        if start_range.is_none() && end_range.is_none() {
+            return Ok(*self);
+        }
+
+        let start = start_range.unwrap_or(0);
+        let end = end_range.unwrap_or_else(|| self.len_bytes());
+
+        // Bounds check
+        if start > end {
+            return Err(Error::ByteRangeInvalid(start, end));
+        } else if end > self.len_bytes() {
+            return Err(Error::ByteRangeOutOfBounds(
+                start_range,
+                end_range,
+                self.len_bytes(),
+            ));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node, start_info, ..
+            }) => {
+                let start = start_info.bytes as usize + start;
+                let end = start_info.bytes as usize + end;
+
+                // Char boundary check
+                if !node.is_char_boundary(start) {
+                    return Err(Error::ByteIndexNotCharBoundary(
+                        start - start_info.bytes as usize,
+                    ));
+                } else if !node.is_char_boundary(end) {
+                    return Err(Error::ByteIndexNotCharBoundary(
+                        end - start_info.bytes as usize,
+                    ));
+                }
+
+                Ok(RopeSlice::new_with_byte_range(node, start, end))
+            }
+            RopeSlice(RSEnum::Light { text, .. }) => {
+                // Char boundary check
+                if !text.is_char_boundary(start) {
+                    return Err(Error::ByteIndexNotCharBoundary(start));
+                } else if !text.is_char_boundary(end) {
+                    return Err(Error::ByteIndexNotCharBoundary(end));
+                }
+
+                let new_text = &text[start..end];
+                Ok(RopeSlice(RSEnum::Light {
+                    text: new_text,
+                    char_count: count_chars(new_text) as Count,
+                    utf16_surrogate_count: count_utf16_surrogates(new_text) as Count,
+                    line_break_count: count_line_breaks(new_text) as Count,
+                }))
+            }
+        }
+    }
+
+    /// Non-panicking version of [`utf16_cu_slice()`](#method.utf16_cu_slice).
+    pub fn try_utf16_cu_slice<R>(&self, utf16_cu_range: R) -> Result<RopeSlice<'a>>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start_range = start_bound_to_num(utf16_cu_range.start_bound());
+        let end_range = end_bound_to_num(utf16_cu_range.end_bound());
+
+        // Early-out shortcut for taking a slice of the full thing.
+        if start_range.is_none() && end_range.is_none() {
+            return Ok(*self);
+        }
+
+        let start = start_range.unwrap_or(0);
+        let end = end_range.unwrap_or_else(|| self.len_utf16_cu());
+
+        // Bounds check
+        if start > end {
+            return Err(Error::Utf16RangeInvalid(start, end));
+        } else if end > self.len_utf16_cu() {
+            return Err(Error::Utf16RangeOutOfBounds(
+                start_range,
+                end_range,
+                self.len_utf16_cu(),
+            ));
+        }
+
+        // Surrogate pair check
+        let start_char = self.utf16_cu_to_char(start);
+        let end_char = self.utf16_cu_to_char(end);
+        if self.char_to_utf16_cu(start_char) != start {
+            return Err(Error::Utf16IndexNotCharBoundary(start));
+        } else if self.char_to_utf16_cu(end_char) != end {
+            return Err(Error::Utf16IndexNotCharBoundary(end));
+        }
+
+        self.try_slice(start_char..end_char)
+    }
+
+    /// Non-panicking version of [`bytes_at()`](#method.bytes_at).
+    #[inline]
+    pub fn try_bytes_at(&self, byte_idx: usize) -> Result<Bytes<'a>> {
+        // Bounds check
+        if byte_idx > self.len_bytes() {
+            return Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
+            }) => Ok(Bytes::new_with_range_at(
+                node,
+                start_info.bytes as usize + byte_idx,
+                (start_info.bytes as usize, end_info.bytes as usize),
+                (start_info.chars as usize, end_info.chars as usize),
+                (
+                    start_info.line_breaks as usize,
+                    end_info.line_breaks as usize + 1,
+                ),
+            )),
+
+            RopeSlice(RSEnum::Light { text, .. }) => Ok(Bytes::from_str_at(text, byte_idx)),
+        }
+    }
+
+    /// Non-panicking version of [`chars_at()`](#method.chars_at).
+    #[inline]
+    pub fn try_chars_at(&self, char_idx: usize) -> Result<Chars<'a>> {
+        // Bounds check
+        if char_idx > self.len_chars() {
+            return Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
+            }) => Ok(Chars::new_with_range_at(
+                node,
+                start_info.chars as usize + char_idx,
+                (start_info.bytes as usize, end_info.bytes as usize),
+                (start_info.chars as usize, end_info.chars as usize),
+                (
+                    start_info.line_breaks as usize,
+                    end_info.line_breaks as usize + 1,
+                ),
+            )),
+
+            RopeSlice(RSEnum::Light { text, .. }) => Ok(Chars::from_str_at(text, char_idx)),
+        }
+    }
+
+    /// Non-panicking version of [`lines_at()`](#method.lines_at).
+    #[inline]
+    pub fn try_lines_at(&self, line_idx: usize) -> Result<Lines<'a>> {
+        // Bounds check
+        if line_idx > self.len_lines() {
+            return Err(Error::LineIndexOutOfBounds(line_idx, self.len_lines()));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
+            }) => Ok(Lines::new_with_range_at(
+                node,
+                start_info.line_breaks as usize + line_idx,
+                (start_info.bytes as usize, end_info.bytes as usize),
+                (start_info.chars as usize, end_info.chars as usize),
+                (
+                    start_info.line_breaks as usize,
+                    end_info.line_breaks as usize + 1,
+                ),
+            )),
+            RopeSlice(RSEnum::Light { text, .. }) => Ok(Lines::from_str_at(text, line_idx)),
+        }
+    }
+
+    /// Non-panicking version of [`chunks_at_byte()`](#method.chunks_at_byte).
+    #[inline]
+    pub fn try_chunks_at_byte(&self, byte_idx: usize) -> Result<(Chunks<'a>, usize, usize, usize)> {
+        // Bounds check
+        if byte_idx > self.len_bytes() {
+            return Err(Error::ByteIndexOutOfBounds(byte_idx, self.len_bytes()));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
+            }) => {
+                let (chunks, chunk_byte_idx, chunk_char_idx, chunk_line_idx) =
+                    Chunks::new_with_range_at_byte(
+                        node,
+                        byte_idx + start_info.bytes as usize,
+                        (start_info.bytes as usize, end_info.bytes as usize),
+                        (start_info.chars as usize, end_info.chars as usize),
+                        (
+                            start_info.line_breaks as usize,
+                            end_info.line_breaks as usize + 1,
+                        ),
+                    );
+
+                Ok((
+                    chunks,
                     chunk_byte_idx.saturating_sub(start_info.bytes as usize),
                     chunk_char_idx.saturating_sub(start_info.chars as usize),
                     chunk_line_idx.saturating_sub(start_info.line_breaks as usize),
-                )
+                ))
+            }
+
+            RopeSlice(RSEnum::Light {
+                text,
+                char_count,
+                line_break_count,
+                ..
+            }) => {
+                let chunks = Chunks::from_str(text, byte_idx == text.len());
+
+                if byte_idx == text.len() {
+                    Ok((
+                        chunks,
+                        text.len(),
+                        char_count as usize,
+                        line_break_count as usize,
+                    ))
+                } else {
+                    Ok((chunks, 0, 0, 0))
+                }
+            }
+        }
+    }
+
+    /// Non-panicking version of [`chunks_at_char()`](#method.chunks_at_char).
+    #[inline]
+    pub fn try_chunks_at_char(&self, char_idx: usize) -> Result<(Chunks<'a>, usize, usize, usize)> {
+        // Bounds check
+        if char_idx > self.len_chars() {
+            return Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
+            }) => {
+                let (chunks, chunk_byte_idx, chunk_char_idx, chunk_line_idx) =
+                    Chunks::new_with_range_at_char(
+                        node,
+                        char_idx + start_info.chars as usize,
+                        (start_info.bytes as usize, end_info.bytes as usize),
+                        (start_info.chars as usize, end_info.chars as usize),
+                        (
+                            start_info.line_breaks as usize,
+                            end_info.line_breaks as usize + 1,
+                        ),
+                    );
+
+                Ok((
+                    chunks,
+                    chunk_byte_idx.saturating_sub(start_info.bytes as usize),
+                    chunk_char_idx.saturating_sub(start_info.chars as usize),
+                    chunk_line_idx.saturating_sub(start_info.line_breaks as usize),
+                ))
             }
 
             RopeSlice(RSEnum::Light {
@@ -1161,49 +2173,33 @@ impl<'a> RopeSlice<'a> {
                 let chunks = Chunks::from_str(text, char_idx == char_count as usize);
 
                 if char_idx == char_count as usize {
-                    (
+                    Ok((
                         chunks,
                         text.len(),
                         char_count as usize,
                         line_break_count as usize,
-                    )
+                    ))
                 } else {
-                    (chunks, 0, 0, 0)
+                    Ok((chunks, 0, 0, 0))
                 }
             }
         }
     }
 
-    /// Creates an iterator over the chunks of the `RopeSlice`, with the
-    /// iterator starting at the chunk containing `line_break_idx`.
-    ///
-    /// Also returns the byte and char indices of the beginning of the first
-    /// chunk to be yielded, and the index of the line that chunk starts on.
-    ///
-    /// Note: for convenience, both the beginning and end of the `RopeSlice` are
-    /// considered line breaks for the purposes of indexing.  For example, in
-    /// the string `"Hello \n world!"` 0 would create an iterator starting on
-    /// the first chunk, 1 would create an iterator starting on the chunk
-    /// containing the newline character, and 2 would create an iterator at
-    /// the end of the `RopeSlice` (yielding `None` on a call to `next()`).
-    ///
-    /// The return value is organized as
-    /// `(iterator, chunk_byte_idx, chunk_char_idx, chunk_line_idx)`.
-    ///
-    /// Runs in O(log N) time.
-    ///
-    /// # Panics
-    ///
-    /// Panics if `line_break_idx` is out of bounds (i.e. `line_break_idx > len_lines()`).
+    /// Non-panicking version of
+    /// [`chunks_at_line_break()`](#method.chunks_at_line_break).
     #[inline]
-    pub fn chunks_at_line_break(&self, line_break_idx: usize) -> (Chunks, usize, usize, usize) {
+    pub fn try_chunks_at_line_break(
+        &self,
+        line_break_idx: usize,
+    ) -> Result<(Chunks<'a>, usize, usize, usize)> {
         // Bounds check
-        assert!(
-            line_break_idx <= self.len_lines(),
-            "Attempt to index past end of RopeSlice: line break index {}, RopeSlice line break max index {}",
-            line_break_idx,
-            self.len_lines()
-        );
+        if line_break_idx > self.len_lines() {
+            return Err(Error::LineIndexOutOfBounds(
+                line_break_idx,
+                self.len_lines(),
+            ));
+        }
 
         match *self {
             RopeSlice(RSEnum::Full {
@@ -1248,35 +2244,273 @@ impl<'a> RopeSlice<'a> {
                         )
                     };
 
-                (
-                    chunks,
-                    chunk_byte_idx.saturating_sub(start_info.bytes as usize),
-                    chunk_char_idx.saturating_sub(start_info.chars as usize),
-                    chunk_line_idx.saturating_sub(start_info.line_breaks as usize),
-                )
-            }
+                Ok((
+                    chunks,
+                    chunk_byte_idx.saturating_sub(start_info.bytes as usize),
+                    chunk_char_idx.saturating_sub(start_info.chars as usize),
+                    chunk_line_idx.saturating_sub(start_info.line_breaks as usize),
+                ))
+            }
+
+            RopeSlice(RSEnum::Light {
+                text,
+                char_count,
+                line_break_count,
+                ..
+            }) => {
+                let chunks = Chunks::from_str(text, line_break_idx == line_break_count as usize);
+
+                if line_break_idx == line_break_count as usize {
+                    Ok((
+                        chunks,
+                        text.len(),
+                        char_count as usize,
+                        line_break_count as usize,
+                    ))
+                } else {
+                    Ok((chunks, 0, 0, 0))
+                }
+            }
+        }
+    }
+
+    /// Non-panicking version of [`cursor_at()`](#method.cursor_at).
+    #[inline]
+    pub fn try_cursor_at(&self, char_idx: usize) -> Result<Cursor<'a>> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(Cursor::new(*self, char_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`graphemes_at()`](#method.graphemes_at).
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn try_graphemes_at(&self, char_idx: usize) -> Result<Graphemes<'a>> {
+        let byte_idx = self.try_char_to_byte(char_idx)?;
+        if graphemes::is_boundary(self, byte_idx) {
+            Ok(Graphemes::new(*self, byte_idx))
+        } else {
+            Ok(Graphemes::new(
+                *self,
+                graphemes::prev_boundary(self, byte_idx),
+            ))
+        }
+    }
+
+    /// Non-panicking version of [`prev_grapheme_boundary()`](#method.prev_grapheme_boundary).
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn try_prev_grapheme_boundary(&self, char_idx: usize) -> Result<usize> {
+        let byte_idx = self.try_char_to_byte(char_idx)?;
+        Ok(self.byte_to_char(graphemes::prev_boundary(self, byte_idx)))
+    }
+
+    /// Non-panicking version of [`next_grapheme_boundary()`](#method.next_grapheme_boundary).
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn try_next_grapheme_boundary(&self, char_idx: usize) -> Result<usize> {
+        let byte_idx = self.try_char_to_byte(char_idx)?;
+        Ok(self.byte_to_char(graphemes::next_boundary(self, byte_idx)))
+    }
+
+    /// Non-panicking version of [`is_grapheme_boundary()`](#method.is_grapheme_boundary).
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn try_is_grapheme_boundary(&self, char_idx: usize) -> Result<bool> {
+        let byte_idx = self.try_char_to_byte(char_idx)?;
+        Ok(graphemes::is_boundary(self, byte_idx))
+    }
+
+    /// Non-panicking version of [`word_bounds_at()`](#method.word_bounds_at).
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn try_word_bounds_at(&self, char_idx: usize) -> Result<Range<usize>> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(segmentation::bounds_at(self, Segment::Word, char_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`next_word_start()`](#method.next_word_start).
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn try_next_word_start(&self, char_idx: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(segmentation::next_start(self, Segment::Word, char_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`prev_word_end()`](#method.prev_word_end).
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn try_prev_word_end(&self, char_idx: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(segmentation::prev_end(self, Segment::Word, char_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`sentence_bounds_at()`](#method.sentence_bounds_at).
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn try_sentence_bounds_at(&self, char_idx: usize) -> Result<Range<usize>> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(segmentation::bounds_at(self, Segment::Sentence, char_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`next_sentence_start()`](#method.next_sentence_start).
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn try_next_sentence_start(&self, char_idx: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(segmentation::next_start(self, Segment::Sentence, char_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`prev_sentence_end()`](#method.prev_sentence_end).
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn try_prev_sentence_end(&self, char_idx: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(segmentation::prev_end(self, Segment::Sentence, char_idx))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`char_to_visual_col()`](#method.char_to_visual_col).
+    #[cfg(feature = "unicode-width")]
+    pub fn try_char_to_visual_col(&self, char_idx: usize, tab_width: usize) -> Result<usize> {
+        // Bounds check
+        if char_idx <= self.len_chars() {
+            Ok(visual::char_to_col(self, char_idx, tab_width))
+        } else {
+            Err(Error::CharIndexOutOfBounds(char_idx, self.len_chars()))
+        }
+    }
+
+    /// Non-panicking version of [`visual_col_to_char()`](#method.visual_col_to_char).
+    #[cfg(feature = "unicode-width")]
+    pub fn try_visual_col_to_char(
+        &self,
+        line_idx: usize,
+        col: usize,
+        tab_width: usize,
+        rounding: ColumnRounding,
+    ) -> Result<usize> {
+        // Bounds check
+        if line_idx < self.len_lines() {
+            Ok(visual::col_to_char(
+                self, line_idx, col, tab_width, rounding,
+            ))
+        } else {
+            Err(Error::LineIndexOutOfBounds(line_idx, self.len_lines()))
+        }
+    }
 
-            RopeSlice(RSEnum::Light {
-                text,
-                char_count,
-                line_break_count,
-                ..
+    //-----------------------------------------------------------------------
+    // Internal utilities
+
+    /// Shared implementation of the `try_*_to_position()` methods.  See
+    /// `Node::line_start_info_at()` for the meaning of the parameters.
+    ///
+    /// Assumes that `idx` has already been bounds-checked.
+    fn idx_to_position(
+        &self,
+        idx: usize,
+        metric: fn(&TextInfo) -> usize,
+        to_byte_idx: fn(&str, usize) -> usize,
+    ) -> Position {
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
             }) => {
-                let chunks = Chunks::from_str(text, line_break_idx == line_break_count as usize);
+                let start_idx = metric(&start_info);
+
+                // A CR at the very end of the slice is a line break of its
+                // own, even if it's followed by an LF outside of the slice.
+                if cfg!(feature = "cr_lines")
+                    && idx + start_idx == metric(&end_info)
+                    && end_info.bytes > start_info.bytes
+                    && self.byte(self.len_bytes() - 1) == 0x0D
+                {
+                    return Position {
+                        line: self.byte_to_line(self.len_bytes()),
+                        column: 0,
+                    };
+                }
 
-                if line_break_idx == line_break_count as usize {
-                    (
-                        chunks,
-                        text.len(),
-                        char_count as usize,
-                        line_break_count as usize,
-                    )
+                let line_start = node.line_start_info_at(idx + start_idx, metric, to_byte_idx);
+                if line_start.bytes <= start_info.bytes {
+                    // The line starts at or before the start of the slice.
+                    Position {
+                        line: 0,
+                        column: idx,
+                    }
                 } else {
-                    (chunks, 0, 0, 0)
+                    Position {
+                        line: (line_start.line_breaks - start_info.line_breaks) as usize,
+                        column: idx + start_idx - metric(&line_start),
+                    }
+                }
+            }
+            RopeSlice(RSEnum::Light { text, .. }) => {
+                let line_idx = byte_to_line_idx(text, to_byte_idx(text, idx));
+                let line_start = TextInfo::from_str(&text[..line_to_byte_idx(text, line_idx)]);
+                Position {
+                    line: line_idx,
+                    column: idx - metric(&line_start),
                 }
             }
         }
     }
+
+    /// Returns the start and end of the given line, as indices in the given
+    /// metric.  The end includes the line break.
+    fn line_bounds(
+        &self,
+        line_idx: usize,
+        metric: fn(&TextInfo) -> usize,
+    ) -> Result<(usize, usize)> {
+        if line_idx >= self.len_lines() {
+            return Err(Error::LineIndexOutOfBounds(line_idx, self.len_lines()));
+        }
+
+        match *self {
+            RopeSlice(RSEnum::Full {
+                node,
+                start_info,
+                end_info,
+            }) => {
+                // Clip the line's bounds in the whole tree to the slice.
+                let (line_start, line_end) =
+                    node.line_bounds_info(line_idx + start_info.line_breaks as usize);
+                let (start, end) = (metric(&start_info), metric(&end_info));
+                Ok((
+                    metric(&line_start).max(start).min(end) - start,
+                    metric(&line_end).max(start).min(end) - start,
+                ))
+            }
+            RopeSlice(RSEnum::Light { text, .. }) => {
+                let line_start = line_to_byte_idx(text, line_idx);
+                let line_end = line_to_byte_idx(text, line_idx + 1);
+                Ok((
+                    metric(&TextInfo::from_str(&text[..line_start])),
+                    metric(&TextInfo::from_str(&text[..line_end])),
+                ))
+            }
+        }
+    }
 }
 
 //==============================================================
@@ -1564,6 +2798,47 @@ impl<'a, 'b> std::cmp::PartialOrd<RopeSlice<'b>> for RopeSlice<'a> {
     }
 }
 
+impl<'a> std::hash::Hash for RopeSlice<'a> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        // `Hasher` only guarantees the same output for the exact same
+        // sequence of `write()` calls, so to be independent of where the
+        // chunk boundaries happen to fall we always feed the text in
+        // fixed-size blocks.  Texts that fit in a single block therefore
+        // hash exactly like `str`, and with streaming hashers (such as the
+        // std library's default one) so do longer texts.
+        const BLOCK_SIZE: usize = 256;
+
+        let mut buffer = [0u8; BLOCK_SIZE];
+        let mut buffer_len = 0;
+
+        for chunk in self.chunks() {
+            let mut data = chunk.as_bytes();
+            while !data.is_empty() {
+                if buffer_len == 0 && data.len() >= BLOCK_SIZE {
+                    // Whole blocks can skip the buffer.
+                    state.write(&data[..BLOCK_SIZE]);
+                    data = &data[BLOCK_SIZE..];
+                } else {
+                    let n = data.len().min(BLOCK_SIZE - buffer_len);
+                    buffer[buffer_len..(buffer_len + n)].copy_from_slice(&data[..n]);
+                    buffer_len += n;
+                    data = &data[n..];
+                    if buffer_len == BLOCK_SIZE {
+                        state.write(&buffer[..]);
+                        buffer_len = 0;
+                    }
+                }
+            }
+        }
+        if buffer_len > 0 || self.len_bytes() == 0 {
+            state.write(&buffer[..buffer_len]);
+        }
+
+        // Same terminator as `str`'s `Hash` impl.
+        state.write_u8(0xff);
+    }
+}
+
 //===========================================================
 
 #[cfg(test)]
@@ -1571,7 +2846,7 @@ mod tests {
     use crate::str_utils::{
         byte_to_char_idx, byte_to_line_idx, char_to_byte_idx, char_to_line_idx,
     };
-    use crate::Rope;
+    use crate::{Error, Position, Rope};
 
     // 127 bytes, 103 chars, 1 line
     const TEXT: &str = "Hello there!  How're you doing?  It's \
@@ -2051,6 +3326,193 @@ mod tests {
         s.utf16_cu_to_char(110);
     }
 
+    #[test]
+    fn byte_to_utf16_cu_01() {
+        let r = Rope::from_str(TEXT_EMOJI);
+        let s = r.slice(10..62);
+
+        assert_eq!(0, s.byte_to_utf16_cu(0));
+        assert_eq!(2, s.byte_to_utf16_cu(2));
+        assert_eq!(2, s.byte_to_utf16_cu(3));
+        assert_eq!(4, s.byte_to_utf16_cu(6));
+        assert_eq!(23, s.byte_to_utf16_cu(25));
+        assert_eq!(55, s.byte_to_utf16_cu(61));
+    }
+
+    #[test]
+    #[should_panic]
+    fn byte_to_utf16_cu_02() {
+        let r = Rope::from_str(TEXT_EMOJI);
+        let s = r.slice(10..62);
+        s.byte_to_utf16_cu(62);
+    }
+
+    #[test]
+    fn utf16_cu_to_byte_01() {
+        let r = Rope::from_str(TEXT_EMOJI);
+        let s = r.slice(10..62);
+
+        assert_eq!(0, s.utf16_cu_to_byte(0));
+        assert_eq!(2, s.utf16_cu_to_byte(2));
+        assert_eq!(2, s.utf16_cu_to_byte(3));
+        assert_eq!(6, s.utf16_cu_to_byte(4));
+        assert_eq!(61, s.utf16_cu_to_byte(55));
+    }
+
+    #[test]
+    #[should_panic]
+    fn utf16_cu_to_byte_02() {
+        let r = Rope::from_str(TEXT_EMOJI);
+        let s = r.slice(10..62);
+        s.utf16_cu_to_byte(56);
+    }
+
+    #[test]
+    fn line_to_utf16_cu_01() {
+        let r = Rope::from_str("Hello 🐸\nworld 🐸\r\n🐸!");
+        let s = r.slice(2..);
+
+        assert_eq!(0, s.line_to_utf16_cu(0));
+        assert_eq!(7, s.line_to_utf16_cu(1));
+        assert_eq!(17, s.line_to_utf16_cu(2));
+        assert_eq!(20, s.line_to_utf16_cu(3));
+    }
+
+    #[test]
+    fn utf16_cu_to_line_01() {
+        let r = Rope::from_str("Hello 🐸\nworld 🐸\r\n🐸!");
+        let s = r.slice(2..);
+
+        assert_eq!(0, s.utf16_cu_to_line(0));
+        assert_eq!(0, s.utf16_cu_to_line(6));
+        assert_eq!(1, s.utf16_cu_to_line(7));
+        assert_eq!(2, s.utf16_cu_to_line(17));
+        assert_eq!(2, s.utf16_cu_to_line(20));
+    }
+
+    #[test]
+    fn char_to_position_01() {
+        let r = Rope::from_str(TEXT_LINES);
+        let s = r.slice(34..96);
+
+        assert_eq!(Position::new(0, 0), s.char_to_position(0));
+        assert_eq!(Position::new(0, 4), s.char_to_position(4));
+        assert_eq!(Position::new(1, 0), s.char_to_position(25));
+        assert_eq!(Position::new(2, 0), s.char_to_position(54));
+        assert_eq!(Position::new(2, 8), s.char_to_position(62));
+    }
+
+    #[test]
+    fn char_to_position_02() {
+        let text = "Hello\r\n🐸 there!\nHow's it\r\ngoing?\r\n";
+        let r = Rope::from_str(text);
+        for start in 0..=r.len_chars() {
+            for end in start..=r.len_chars() {
+                let s = r.slice(start..end);
+                for i in 0..=s.len_chars() {
+                    let line_idx = s.char_to_line(i);
+                    let pos = Position::new(line_idx, i - s.line_to_char(line_idx));
+                    assert_eq!(pos, s.char_to_position(i));
+                    // `len_lines()` doesn't count a CR at the end of a
+                    // slice that splits a CRLF pair, so those positions
+                    // can't be converted back.
+                    if pos.line < s.len_lines() {
+                        assert_eq!(i, s.position_to_char(pos));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn byte_to_position_01() {
+        let text = "Hello\r\n🐸 there!\nHow's it\r\ngoing?\r\n";
+        let r = Rope::from_str(text);
+        for start in 0..=r.len_chars() {
+            for end in start..=r.len_chars() {
+                let s = r.slice(start..end);
+                for i in 0..=s.len_bytes() {
+                    let line_idx = s.byte_to_line(i);
+                    let pos = Position::new(line_idx, i - s.line_to_byte(line_idx));
+                    assert_eq!(pos, s.byte_to_position(i));
+                    // `len_lines()` doesn't count a CR at the end of a
+                    // slice that splits a CRLF pair, so those positions
+                    // can't be converted back.
+                    if pos.line < s.len_lines() {
+                        assert_eq!(i, s.position_to_byte(pos));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn utf16_cu_to_position_01() {
+        let text = "Hello\r\n🐸 there!\nHow's it\r\ngoing?\r\n";
+        let r = Rope::from_str(text);
+        for start in 0..=r.len_chars() {
+            for end in start..=r.len_chars() {
+                let s = r.slice(start..end);
+                for i in 0..=s.len_utf16_cu() {
+                    let line_idx = s.utf16_cu_to_line(i);
+                    let pos = Position::new(line_idx, i - s.line_to_utf16_cu(line_idx));
+                    assert_eq!(pos, s.utf16_cu_to_position(i));
+                    // `len_lines()` doesn't count a CR at the end of a
+                    // slice that splits a CRLF pair, so those positions
+                    // can't be converted back.
+                    if pos.line < s.len_lines() {
+                        assert_eq!(i, s.position_to_utf16_cu(pos));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn position_to_char_01() {
+        let r = Rope::from_str(TEXT_LINES);
+        let s = r.slice(34..96);
+
+        s.position_to_char(Position::new(0, 25));
+    }
+
+    #[test]
+    fn position_to_char_02() {
+        // Lines spanning chunks, and slices starting and ending inside
+        // CRLF pairs.
+        let text = "Hello\r\n\r\n🐸 there!\n\u{2028}How's it\r\ngoing?\r\n".repeat(8);
+        let r = Rope::from_str(&text);
+        let len = r.len_chars();
+        for &(start, end) in &[(0, len), (6, len), (0, 6), (6, 7), (3, len - 1), (7, 7)] {
+            let s = r.slice(start..end);
+            for line_idx in 0..s.len_lines() {
+                let line_start = s.line_to_char(line_idx);
+                let line_end = s.line_to_char(line_idx + 1);
+                for char_idx in line_start..line_end {
+                    let pos = Position::new(line_idx, char_idx - line_start);
+                    assert_eq!(s.position_to_char(pos), char_idx);
+
+                    let byte_idx = s.char_to_byte(char_idx);
+                    let line_byte = s.line_to_byte(line_idx);
+                    let pos = Position::new(line_idx, byte_idx - line_byte);
+                    assert_eq!(s.position_to_byte(pos), byte_idx);
+
+                    let utf16_idx = s.char_to_utf16_cu(char_idx);
+                    let line_utf16 = s.line_to_utf16_cu(line_idx);
+                    let pos = Position::new(line_idx, utf16_idx - line_utf16);
+                    assert_eq!(s.position_to_utf16_cu(pos), utf16_idx);
+                }
+            }
+            let last_line = s.len_lines() - 1;
+            let last_col = s.len_chars() - s.line_to_char(last_line);
+            assert_eq!(
+                s.position_to_char(Position::new(last_line, last_col)),
+                s.len_chars()
+            );
+        }
+    }
+
     #[test]
     fn byte_01() {
         let r = Rope::from_str(TEXT);
@@ -2351,6 +3813,57 @@ mod tests {
         s.slice(37..39);
     }
 
+    #[test]
+    fn byte_slice_01() {
+        let r = Rope::from_str(TEXT);
+        let s1 = r.slice(5..97);
+
+        let s2 = s1.byte_slice(3..101);
+
+        assert_eq!(&TEXT[8..106], s2);
+        assert_eq!(s2, s1.slice(3..91));
+    }
+
+    #[test]
+    fn byte_slice_02() {
+        let r = Rope::from_str(TEXT);
+        let s1 = r.slice(5..97);
+
+        let s2 = s1.byte_slice(86..92);
+
+        assert_eq!(&TEXT[91..97], s2);
+        assert_eq!(s2.len_chars(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn byte_slice_03() {
+        let r = Rope::from_str(TEXT);
+        let s = r.slice(5..97);
+
+        s.byte_slice(85..88);
+    }
+
+    #[test]
+    fn utf16_cu_slice_01() {
+        let r = Rope::from_str(TEXT_EMOJI);
+        let s1 = r.slice(10..62);
+
+        let s2 = s1.utf16_cu_slice(4..23);
+
+        assert_eq!(&TEXT_EMOJI[16..35], s2);
+        assert_eq!(s2, s1.slice(3..22));
+    }
+
+    #[test]
+    #[should_panic]
+    fn utf16_cu_slice_02() {
+        let r = Rope::from_str(TEXT_EMOJI);
+        let s = r.slice(10..62);
+
+        s.utf16_cu_slice(3..10);
+    }
+
     #[test]
     fn eq_str_01() {
         let r = Rope::from_str(TEXT);
@@ -2456,6 +3969,66 @@ mod tests {
         assert_eq!(s2.cmp(&s1), std::cmp::Ordering::Less);
     }
 
+    #[test]
+    fn hash_rope_slice_01() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        fn hash<T: Hash + ?Sized>(x: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            x.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let r = Rope::from_str(&TEXT.repeat(20));
+        for &(start, end) in &[(0, 0), (5, 17), (0, 300), (31, 1000), (0, r.len_chars())] {
+            let slc = r.slice(start..end);
+            assert_eq!(hash(&slc), hash(&slc.to_string()[..]));
+        }
+    }
+
+    #[test]
+    fn hash_rope_slice_02() {
+        use std::hash::{Hash, Hasher};
+
+        // A hasher that records the exact sequence of writes, which is
+        // stricter than what a streaming hasher would notice.
+        #[derive(Default)]
+        struct Recorder(Vec<Vec<u8>>);
+        impl Hasher for Recorder {
+            fn finish(&self) -> u64 {
+                0
+            }
+            fn write(&mut self, bytes: &[u8]) {
+                self.0.push(bytes.to_vec());
+            }
+        }
+
+        fn writes<T: Hash + ?Sized>(x: &T) -> Vec<Vec<u8>> {
+            let mut hasher = Recorder::default();
+            x.hash(&mut hasher);
+            hasher.0
+        }
+
+        // Same text, built with very different chunk boundaries.
+        let text = TEXT.repeat(20);
+        let r1 = Rope::from_str(&text);
+        let mut r2 = Rope::new();
+        for c in text.chars().rev() {
+            r2.insert_char(0, c);
+        }
+        let r3 = Rope::from_str(&(String::from("x") + &text));
+        let s3 = r3.slice(1..);
+
+        assert_eq!(writes(&r1.slice(..)), writes(&r2.slice(..)));
+        assert_eq!(writes(&r1.slice(..)), writes(&s3));
+        assert_eq!(
+            writes(&r1.slice(0..100)),
+            writes(&r1.slice(0..100).to_string()[..])
+        );
+        assert_eq!(writes(&r1.slice(0..0)), writes(""));
+    }
+
     #[test]
     fn to_string_01() {
         let r = Rope::from_str(TEXT);
@@ -2518,5 +4091,239 @@ mod tests {
         assert_eq!(s, cow);
     }
 
+    #[test]
+    fn try_index_conversion_01() {
+        let r = Rope::from_str(TEXT_LINES);
+        let s = r.slice(34..96);
+
+        assert_eq!(s.try_byte_to_char(72), Ok(60));
+        assert_eq!(s.try_byte_to_line(72), Ok(2));
+        assert_eq!(s.try_char_to_byte(60), Ok(72));
+        assert_eq!(s.try_char_to_line(60), Ok(2));
+        assert_eq!(s.try_line_to_char(3), Ok(62));
+        assert_eq!(s.try_line_to_byte(3), Ok(78));
+        assert_eq!(s.try_char_to_utf16_cu(62), Ok(62));
+        assert_eq!(s.try_utf16_cu_to_char(62), Ok(62));
+
+        assert_eq!(
+            s.try_byte_to_char(79),
+            Err(Error::ByteIndexOutOfBounds(79, 78))
+        );
+        assert_eq!(
+            s.try_byte_to_line(79),
+            Err(Error::ByteIndexOutOfBounds(79, 78))
+        );
+        assert_eq!(
+            s.try_char_to_byte(63),
+            Err(Error::CharIndexOutOfBounds(63, 62))
+        );
+        assert_eq!(
+            s.try_char_to_line(63),
+            Err(Error::CharIndexOutOfBounds(63, 62))
+        );
+        assert_eq!(
+            s.try_line_to_char(4),
+            Err(Error::LineIndexOutOfBounds(4, 3))
+        );
+        assert_eq!(
+            s.try_line_to_byte(4),
+            Err(Error::LineIndexOutOfBounds(4, 3))
+        );
+        assert_eq!(
+            s.try_char_to_utf16_cu(63),
+            Err(Error::CharIndexOutOfBounds(63, 62))
+        );
+        assert_eq!(
+            s.try_utf16_cu_to_char(63),
+            Err(Error::Utf16IndexOutOfBounds(63, 62))
+        );
+    }
+
+    #[test]
+    fn try_fetch_01() {
+        let r = Rope::from_str(TEXT_LINES);
+        let s = r.slice(34..96);
+
+        assert_eq!(s.try_byte(0), Ok(b'\''));
+        assert_eq!(s.try_char(61), Ok('ん'));
+        assert_eq!(s.try_line(2).unwrap(), "こんにちは、みん");
+        assert_eq!(s.try_chunk_at_byte(78).unwrap(), s.chunk_at_byte(78));
+        assert_eq!(s.try_chunk_at_char(62).unwrap(), s.chunk_at_char(62));
+        assert_eq!(
+            s.try_chunk_at_line_break(3).unwrap(),
+            s.chunk_at_line_break(3)
+        );
+
+        assert_eq!(s.try_byte(78), Err(Error::ByteIndexOutOfBounds(78, 78)));
+        assert_eq!(s.try_char(62), Err(Error::CharIndexOutOfBounds(62, 62)));
+        assert_eq!(
+            s.try_line(3).unwrap_err(),
+            Error::LineIndexOutOfBounds(3, 3)
+        );
+        assert_eq!(
+            s.try_chunk_at_byte(79).unwrap_err(),
+            Error::ByteIndexOutOfBounds(79, 78)
+        );
+        assert_eq!(
+            s.try_chunk_at_char(63).unwrap_err(),
+            Error::CharIndexOutOfBounds(63, 62)
+        );
+        assert_eq!(
+            s.try_chunk_at_line_break(4).unwrap_err(),
+            Error::LineIndexOutOfBounds(4, 3)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_slice_01() {
+        let r = Rope::from_str(TEXT);
+        let s = r.slice(5..21);
+
+        assert_eq!(s.try_slice(3..10).unwrap(), &TEXT[8..15]);
+        assert_eq!(
+            s.try_slice(10..3).unwrap_err(),
+            Error::CharRangeInvalid(10, 3)
+        );
+        assert_eq!(s.try_slice(3..).unwrap(), &TEXT[8..21]);
+        assert_eq!(
+            s.try_slice(3..17).unwrap_err(),
+            Error::CharRangeOutOfBounds(Some(3), Some(17), 16)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_byte_slice_01() {
+        let r = Rope::from_str(TEXT);
+        let s = r.slice(5..97);
+
+        assert_eq!(s.try_byte_slice(3..10).unwrap(), &TEXT[8..15]);
+        assert_eq!(s.try_byte_slice(86..).unwrap(), &TEXT[91..109]);
+        assert_eq!(
+            s.try_byte_slice(10..3).unwrap_err(),
+            Error::ByteRangeInvalid(10, 3)
+        );
+        assert_eq!(
+            s.try_byte_slice(3..105).unwrap_err(),
+            Error::ByteRangeOutOfBounds(Some(3), Some(105), 104)
+        );
+        assert_eq!(
+            s.try_byte_slice(87..).unwrap_err(),
+            Error::ByteIndexNotCharBoundary(87)
+        );
+
+        // Light slices.
+        let r2 = Rope::from_str("こんに");
+        let s2 = r2.slice(..);
+        assert_eq!(s2.try_byte_slice(3..6).unwrap(), "ん");
+        assert_eq!(
+            s2.try_byte_slice(4..).unwrap_err(),
+            Error::ByteIndexNotCharBoundary(4)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn try_utf16_cu_slice_01() {
+        let r = Rope::from_str(TEXT_EMOJI);
+        let s = r.slice(10..62);
+
+        assert_eq!(s.try_utf16_cu_slice(2..).unwrap(), &TEXT_EMOJI[12..71]);
+        assert_eq!(
+            s.try_utf16_cu_slice(10..3).unwrap_err(),
+            Error::Utf16RangeInvalid(10, 3)
+        );
+        assert_eq!(
+            s.try_utf16_cu_slice(3..56).unwrap_err(),
+            Error::Utf16RangeOutOfBounds(Some(3), Some(56), 55)
+        );
+        assert_eq!(
+            s.try_utf16_cu_slice(3..).unwrap_err(),
+            Error::Utf16IndexNotCharBoundary(3)
+        );
+        assert_eq!(
+            s.try_utf16_cu_slice(..54).unwrap_err(),
+            Error::Utf16IndexNotCharBoundary(54)
+        );
+    }
+
+    #[test]
+    fn try_position_01() {
+        let r = Rope::from_str(TEXT_LINES);
+        let s = r.slice(34..96);
+
+        assert_eq!(s.try_char_to_position(62), Ok(Position::new(2, 8)));
+        assert_eq!(
+            s.try_char_to_position(63),
+            Err(Error::CharIndexOutOfBounds(63, 62))
+        );
+        assert_eq!(s.try_position_to_char(Position::new(2, 8)), Ok(62));
+        assert_eq!(
+            s.try_position_to_char(Position::new(1, 29)),
+            Err(Error::ColumnIndexOutOfBounds(29, 29))
+        );
+        assert_eq!(
+            s.try_position_to_char(Position::new(3, 0)),
+            Err(Error::LineIndexOutOfBounds(3, 3))
+        );
+        assert_eq!(
+            s.try_byte_to_position(79),
+            Err(Error::ByteIndexOutOfBounds(79, 78))
+        );
+        assert_eq!(
+            s.try_position_to_byte(Position::new(2, 25)),
+            Err(Error::ColumnIndexOutOfBounds(25, 24))
+        );
+        assert_eq!(
+            s.try_utf16_cu_to_position(63),
+            Err(Error::Utf16IndexOutOfBounds(63, 62))
+        );
+        assert_eq!(
+            s.try_position_to_utf16_cu(Position::new(2, 9)),
+            Err(Error::ColumnIndexOutOfBounds(9, 8))
+        );
+    }
+
+    #[test]
+    fn try_iter_at_01() {
+        let r = Rope::from_str(TEXT_LINES);
+        let s = r.slice(34..96);
+
+        assert!(s.try_bytes_at(78).is_ok());
+        assert!(s.try_chars_at(62).is_ok());
+        assert!(s.try_lines_at(3).is_ok());
+        assert!(s.try_chunks_at_byte(78).is_ok());
+        assert!(s.try_chunks_at_char(62).is_ok());
+        assert!(s.try_chunks_at_line_break(3).is_ok());
+
+        assert!(s.try_bytes_at(79).is_err());
+        assert!(s.try_chars_at(63).is_err());
+        assert!(s.try_lines_at(4).is_err());
+        assert!(s.try_chunks_at_byte(79).is_err());
+        assert!(s.try_chunks_at_char(63).is_err());
+        assert!(s.try_chunks_at_line_break(4).is_err());
+    }
+
+    #[test]
+    fn longest_line_01() {
+        let r = Rope::from_str(&TEXT_LINES.repeat(10));
+        let text = r.to_string();
+        for &(start, end) in &[
+            (0, 0),
+            (34, 96),
+            (5, 300),
+            (100, 101),
+            (33, 1000),
+            (0, 1000),
+        ] {
+            let s = r.slice(start..end);
+            let expected = Rope::from_str(&text[r.char_to_byte(start)..r.char_to_byte(end)]);
+            assert_eq!(s.longest_line(), expected.longest_line());
+        }
+        assert_eq!(r.slice(34..96).longest_line(), (1, 28));
+        assert_eq!(r.slice(0..50).longest_line(), (0, 31));
+    }
+
     // Iterator tests are in the iter module
 }
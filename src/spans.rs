@@ -0,0 +1,832 @@
+//! Attributed char ranges over a `Rope`, kept up to date as it's edited.
+
+use std::cmp::Ordering;
+use std::ops::{Range, RangeBounds};
+
+use crate::change_set::{ChangeSet, Operation};
+use crate::rope::Rope;
+use crate::slice::{end_bound_to_num, start_bound_to_num};
+use crate::str_utils::count_chars;
+use crate::{Error, Result};
+
+/// The maximum number of spans in a block before it's split in two.
+const MAX_BLOCK_LEN: usize = 64;
+
+/// A set of char ranges ("spans") over a `Rope`, each with a value of type
+/// `T` attached, such as syntax highlighting styles or diagnostics.
+///
+/// The spans are updated as the text is edited:
+///
+/// - Spans after an edit are shifted.
+/// - Text inserted strictly inside a span extends it, or, if
+///   `set_split_on_insert(true)` was called, splits it in two around the
+///   inserted text.  Text inserted at either end of a span isn't included in
+///   it.
+/// - Spans overlapping removed text are trimmed, and spans that end up
+///   empty are dropped.  (Spans that were empty to begin with are kept.)
+///
+/// Edits can be made through `insert()` and `remove()`, which edit the
+/// `Rope` and update the spans together, or reported after the fact with
+/// `adjust_for_insert()`, `adjust_for_remove()`, and `apply_changes()`.
+///
+/// Spans may overlap each other.  They're stored sorted by start position
+/// in blocks of up to 64, each with its own base position and the maximum
+/// end of its spans, along with a max segment tree over the blocks' ends.
+/// Finding the spans overlapping a range (for example a line being
+/// rendered) uses the segment tree to jump from one block with a span
+/// ending in or after the range to the next, so a long span elsewhere
+/// doesn't cause the blocks in between to be scanned.  It takes O(log M)
+/// time in the number of spans for each block with an overlapping span,
+/// plus the time to scan those blocks.  Edits update every block after
+/// them, taking O(M / 64) time plus the time to adjust the spans they
+/// touch.
+///
+/// # Example
+///
+/// ```
+/// # use ropey::{Rope, Spans};
+/// let mut rope = Rope::from_str("let x = 1;\nlet y = 2;\n");
+/// let mut spans = Spans::new();
+/// spans.add(0..3, "keyword");
+/// spans.add(11..14, "keyword");
+///
+/// spans.insert(&mut rope, 0, "// Hi!\n");
+/// let on_line_2: Vec<_> = spans.overlapping_line(&rope, 2).collect();
+/// assert_eq!(on_line_2, vec![(18..21, &"keyword")]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Spans<T> {
+    blocks: Vec<Block<T>>,
+    // A max segment tree over the absolute ends of the blocks: node 1 is the
+    // root, node `i` has children `2 * i` and `2 * i + 1`, and the leaves
+    // start at half the length (a power of two), padded with zeros.
+    block_ends: Vec<usize>,
+    len: usize,
+    split_on_insert: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Block<T> {
+    base: usize,    // Always the start of the first entry.
+    max_end: usize, // The maximum end of the entries, relative to `base`.
+    entries: Vec<Entry<T>>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    start: usize, // Relative to the block's base.
+    end: usize,   // Relative to the block's base.
+    value: T,
+}
+
+impl<T> Default for Spans<T> {
+    fn default() -> Spans<T> {
+        Spans {
+            blocks: Vec::new(),
+            block_ends: Vec::new(),
+            len: 0,
+            split_on_insert: false,
+        }
+    }
+}
+
+impl<T: Clone> Spans<T> {
+    /// Creates an empty set of spans.
+    #[inline]
+    pub fn new() -> Spans<T> {
+        Spans::default()
+    }
+
+    /// Sets whether text inserted strictly inside a span splits it in two
+    /// (`true`) or extends it (`false`, the default).
+    #[inline]
+    pub fn set_split_on_insert(&mut self, split: bool) {
+        self.split_on_insert = split;
+    }
+
+    /// Returns the number of spans.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether there are no spans.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all spans.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.block_ends.clear();
+        self.len = 0;
+    }
+
+    /// Adds a span covering the given char range, with `value` attached.
+    ///
+    /// Runs in O(log M + B) time, where M is the number of spans and B is
+    /// the block size (at most 64).  When the span fills up its block, the
+    /// block is split, which takes O(M / B) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end.
+    #[inline]
+    pub fn add(&mut self, char_range: Range<usize>, value: T) {
+        self.try_add(char_range, value).unwrap()
+    }
+
+    /// Keeps only the spans for which `f` returns `true`.
+    pub fn retain<F: FnMut(Range<usize>, &T) -> bool>(&mut self, mut f: F) {
+        for block in self.blocks.iter_mut() {
+            let base = block.base;
+            block
+                .entries
+                .retain(|e| f((base + e.start)..(base + e.end), &e.value));
+            block.normalize();
+        }
+        self.blocks.retain(|b| !b.entries.is_empty());
+        self.len = self.blocks.iter().map(|b| b.entries.len()).sum();
+        self.update_block_ends();
+    }
+
+    /// Returns an iterator over all spans, in order of their start.
+    ///
+    /// Yields `(char_range, &value)` pairs.
+    #[inline]
+    pub fn iter(&self) -> SpansIter<'_, T> {
+        self.overlapping(..)
+    }
+
+    /// Returns an iterator over the spans overlapping the given char range,
+    /// in order of their start.
+    ///
+    /// A span overlaps the range if they share at least one char, or if the
+    /// span is empty and its position is within the range.  Yields
+    /// `(char_range, &value)` pairs.
+    pub fn overlapping<R>(&self, char_range: R) -> SpansIter<'_, T>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = start_bound_to_num(char_range.start_bound()).unwrap_or(0);
+        let end = end_bound_to_num(char_range.end_bound()).unwrap_or(usize::MAX);
+
+        SpansIter {
+            spans: self,
+            block_idx: self.next_block(0, start),
+            entry_idx: 0,
+            start: start,
+            end: end,
+        }
+    }
+
+    /// Returns an iterator over the spans overlapping line `line_idx` of
+    /// `rope`, including its line break.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line_idx` is out of bounds (i.e. `line_idx >= len_lines()`).
+    #[inline]
+    pub fn overlapping_line(&self, rope: &Rope, line_idx: usize) -> SpansIter<'_, T> {
+        self.try_overlapping_line(rope, line_idx).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Editing
+
+    /// Inserts `text` into `rope` at char index `char_idx`, and updates the
+    /// spans accordingly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn insert(&mut self, rope: &mut Rope, char_idx: usize, text: &str) {
+        self.try_insert(rope, char_idx, text).unwrap()
+    }
+
+    /// Removes the text in the given char index range from `rope`, and
+    /// updates the spans accordingly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if the
+    /// end is out of bounds (i.e. `end > len_chars()`).
+    #[inline]
+    pub fn remove<R>(&mut self, rope: &mut Rope, char_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_remove(rope, char_range).unwrap()
+    }
+
+    /// Updates the spans for `len` chars having been inserted at char index
+    /// `char_idx`.
+    pub fn adjust_for_insert(&mut self, char_idx: usize, len: usize) {
+        if len == 0 {
+            return;
+        }
+
+        let mut split_off = Vec::new();
+        for block in self.blocks.iter_mut() {
+            if block.base >= char_idx {
+                block.base += len;
+            } else if block.base + block.max_end >= char_idx {
+                let base = block.base;
+                for e in block.entries.iter_mut() {
+                    let (start, end) = (base + e.start, base + e.end);
+                    if start >= char_idx {
+                        e.start += len;
+                        e.end += len;
+                    } else if end > char_idx {
+                        if self.split_on_insert {
+                            split_off.push(((char_idx + len)..(end + len), e.value.clone()));
+                            e.end = char_idx - base;
+                        } else {
+                            e.end += len;
+                        }
+                    }
+                }
+                block.normalize();
+            }
+        }
+        self.update_block_ends();
+
+        for (range, value) in split_off {
+            self.add(range, value);
+        }
+    }
+
+    /// Updates the spans for the text in the given char index range having
+    /// been removed.
+    pub fn adjust_for_remove<R>(&mut self, char_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = start_bound_to_num(char_range.start_bound()).unwrap_or(0);
+        let end = end_bound_to_num(char_range.end_bound()).unwrap_or(usize::MAX);
+        if start >= end {
+            return;
+        }
+        let len = end - start;
+        let map = |pos: usize| {
+            if pos <= start {
+                pos
+            } else if pos < end {
+                start
+            } else {
+                pos - len
+            }
+        };
+
+        for block in self.blocks.iter_mut() {
+            if block.base >= end {
+                block.base -= len;
+            } else if block.base + block.max_end > start {
+                let base = block.base;
+                block
+                    .entries
+                    .retain(|e| e.start == e.end || map(base + e.start) != map(base + e.end));
+                for e in block.entries.iter_mut() {
+                    // Temporarily absolute.
+                    e.start = map(base + e.start);
+                    e.end = map(base + e.end);
+                }
+                block.base = 0;
+                block.normalize();
+            }
+        }
+        self.blocks.retain(|b| !b.entries.is_empty());
+        self.len = self.blocks.iter().map(|b| b.entries.len()).sum();
+        self.update_block_ends();
+    }
+
+    /// Updates the spans for `changes` having been applied.
+    pub fn apply_changes(&mut self, changes: &ChangeSet) {
+        let mut pos = 0;
+        for op in changes.ops() {
+            match *op {
+                Operation::Retain(n) => pos += n,
+                Operation::Delete(n) => self.adjust_for_remove(pos..(pos + n)),
+                Operation::Insert(ref text) => {
+                    let len = count_chars(text);
+                    self.adjust_for_insert(pos, len);
+                    pos += len;
+                }
+            }
+        }
+    }
+
+    //-----------------------------------------------------------------------
+    // Fallible versions of the above methods
+
+    /// Non-panicking version of [`add()`](#method.add).
+    pub fn try_add(&mut self, char_range: Range<usize>, value: T) -> Result<()> {
+        let (start, end) = (char_range.start, char_range.end);
+        if start > end {
+            return Err(Error::CharRangeInvalid(start, end));
+        }
+
+        // The last block starting at or before `start`.
+        let block_idx = count_le(&self.blocks, |b| b.base, start).saturating_sub(1);
+        if self.blocks.is_empty() {
+            self.blocks.push(Block {
+                base: start,
+                max_end: 0,
+                entries: Vec::new(),
+            });
+        }
+        let block = &mut self.blocks[block_idx];
+        if start < block.base {
+            block.rebase(start);
+        }
+        let entry_idx = count_le(&block.entries, |e| e.start, start - block.base);
+        block.entries.insert(
+            entry_idx,
+            Entry {
+                start: start - block.base,
+                end: end - block.base,
+                value: value,
+            },
+        );
+        block.max_end = block.max_end.max(end - block.base);
+        self.len += 1;
+
+        if block.entries.len() > MAX_BLOCK_LEN {
+            let entries = block.entries.split_off(MAX_BLOCK_LEN / 2);
+            let base = block.base;
+            block.normalize();
+            let mut new_block = Block {
+                base: base,
+                max_end: 0,
+                entries: entries,
+            };
+            new_block.normalize();
+            self.blocks.insert(block_idx + 1, new_block);
+            self.update_block_ends();
+        } else if self.block_ends.is_empty() {
+            // The first block.
+            self.update_block_ends();
+        } else {
+            self.update_block_end(block_idx);
+        }
+
+        Ok(())
+    }
+
+    /// Non-panicking version of [`overlapping_line()`](#method.overlapping_line).
+    pub fn try_overlapping_line(&self, rope: &Rope, line_idx: usize) -> Result<SpansIter<'_, T>> {
+        // Bounds check
+        if line_idx >= rope.len_lines() {
+            return Err(Error::LineIndexOutOfBounds(line_idx, rope.len_lines()));
+        }
+
+        let start = rope.line_to_char(line_idx);
+        let end = rope.line_to_char(line_idx + 1);
+        Ok(self.overlapping(start..end))
+    }
+
+    /// Non-panicking version of [`insert()`](#method.insert).
+    pub fn try_insert(&mut self, rope: &mut Rope, char_idx: usize, text: &str) -> Result<()> {
+        rope.try_insert(char_idx, text)?;
+        self.adjust_for_insert(char_idx, count_chars(text));
+        Ok(())
+    }
+
+    /// Non-panicking version of [`remove()`](#method.remove).
+    pub fn try_remove<R>(&mut self, rope: &mut Rope, char_range: R) -> Result<()>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = rope.check_char_range(char_range)?;
+        rope.remove(start..end);
+        self.adjust_for_remove(start..end);
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------
+    // Internal utilities
+}
+
+impl<T> Spans<T> {
+    /// Rebuilds the segment tree of block ends from scratch.
+    fn update_block_ends(&mut self) {
+        let leaves = self.blocks.len().next_power_of_two();
+        self.block_ends.clear();
+        self.block_ends.resize(leaves * 2, 0);
+        for (i, block) in self.blocks.iter().enumerate() {
+            self.block_ends[leaves + i] = block.end();
+        }
+        for i in (1..leaves).rev() {
+            self.block_ends[i] = self.block_ends[i * 2].max(self.block_ends[i * 2 + 1]);
+        }
+    }
+
+    /// Updates the segment tree of block ends for a change to the end of
+    /// block `block_idx`.
+    fn update_block_end(&mut self, block_idx: usize) {
+        let mut i = self.block_ends.len() / 2 + block_idx;
+        self.block_ends[i] = self.blocks[block_idx].end();
+        while i > 1 {
+            i /= 2;
+            self.block_ends[i] = self.block_ends[i * 2].max(self.block_ends[i * 2 + 1]);
+        }
+    }
+
+    /// Returns the index of the first block at or after `block_idx` that
+    /// has a span ending at or after `char_idx`, or the number of blocks if
+    /// there's none.
+    fn next_block(&self, block_idx: usize, char_idx: usize) -> usize {
+        if block_idx >= self.blocks.len() {
+            return self.blocks.len();
+        }
+
+        // Go up until a node at or after `block_idx` has a late enough end...
+        let leaves = self.block_ends.len() / 2;
+        let mut i = leaves + block_idx;
+        while self.block_ends[i] < char_idx {
+            while i % 2 == 1 {
+                i /= 2;
+            }
+            if i == 0 {
+                return self.blocks.len();
+            }
+            i += 1;
+        }
+
+        // ...and then down to its first leaf that does.
+        while i < leaves {
+            i *= 2;
+            if self.block_ends[i] < char_idx {
+                i += 1;
+            }
+        }
+        (i - leaves).min(self.blocks.len())
+    }
+}
+
+impl<T> Block<T> {
+    /// The absolute end of the block's spans.
+    #[inline]
+    fn end(&self) -> usize {
+        self.base + self.max_end
+    }
+
+    /// Changes the base to `base`, which must not be after any span start.
+    fn rebase(&mut self, base: usize) {
+        let shift = self.base - base;
+        for e in self.entries.iter_mut() {
+            e.start += shift;
+            e.end += shift;
+        }
+        self.max_end += shift;
+        self.base = base;
+    }
+
+    /// Restores the invariants on `base` and `max_end` after the entries
+    /// were modified, keeping their sorted order.
+    fn normalize(&mut self) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let shift = self.entries[0].start;
+        let mut max_end = 0;
+        for e in self.entries.iter_mut() {
+            e.start -= shift;
+            e.end -= shift;
+            max_end = max_end.max(e.end);
+        }
+        self.base += shift;
+        self.max_end = max_end;
+    }
+}
+
+/// Returns the number of items at the start of the sorted `items` whose key
+/// is less than or equal to `key`.
+fn count_le<T, F: Fn(&T) -> usize>(items: &[T], f: F, key: usize) -> usize {
+    match items.binary_search_by(|item| {
+        if f(item) <= key {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }) {
+        Ok(i) | Err(i) => i,
+    }
+}
+
+//==============================================================
+
+/// An iterator over the spans in a [`Spans`](struct.Spans.html).
+///
+/// Created with [`Spans::iter()`](struct.Spans.html#method.iter) and
+/// [`Spans::overlapping()`](struct.Spans.html#method.overlapping).
+#[derive(Debug)]
+pub struct SpansIter<'a, T> {
+    spans: &'a Spans<T>,
+    block_idx: usize,
+    entry_idx: usize,
+    start: usize,
+    end: usize,
+}
+
+impl<'a, T> Clone for SpansIter<'a, T> {
+    fn clone(&self) -> SpansIter<'a, T> {
+        SpansIter { ..*self }
+    }
+}
+
+impl<'a, T> SpansIter<'a, T> {
+    /// Returns whether spans starting at `start` or later can't overlap the
+    /// range.
+    #[inline]
+    fn is_past(&self, start: usize) -> bool {
+        start > self.end || (start == self.end && self.start < self.end)
+    }
+}
+
+impl<'a, T> Iterator for SpansIter<'a, T> {
+    type Item = (Range<usize>, &'a T);
+
+    fn next(&mut self) -> Option<(Range<usize>, &'a T)> {
+        let spans = self.spans;
+        while let Some(block) = spans.blocks.get(self.block_idx) {
+            // Spans are sorted by start, so nothing further can overlap.
+            if self.is_past(block.base) {
+                break;
+            }
+
+            while let Some(e) = block.entries.get(self.entry_idx) {
+                self.entry_idx += 1;
+                let (start, end) = (block.base + e.start, block.base + e.end);
+                let overlaps = if start == end {
+                    self.start <= start && (start < self.end || self.start == self.end)
+                } else {
+                    start < self.end && end > self.start
+                };
+                if overlaps {
+                    return Some((start..end, &e.value));
+                }
+                if self.is_past(start) {
+                    break;
+                }
+            }
+            self.block_idx = spans.next_block(self.block_idx + 1, self.start);
+            self.entry_idx = 0;
+        }
+
+        self.block_idx = spans.blocks.len();
+        None
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::{ChangeSet, Rope, Spans};
+
+    const TEXT: &str = "Hello there!  How're you doing?\r\nIt's \
+                        a fine day, isn't it?\r\nAren't you glad \
+                        we're alive?\r\nこんにちは、みんなさん！";
+
+    /// Checks that the segment tree of block ends is up to date.
+    fn check(spans: &Spans<u32>) {
+        let leaves = spans.block_ends.len() / 2;
+        assert!(leaves >= spans.blocks.len());
+        for (i, block) in spans.blocks.iter().enumerate() {
+            assert_eq!(spans.block_ends[leaves + i], block.end());
+        }
+        for i in 1..leaves {
+            let max_end = spans.block_ends[i * 2].max(spans.block_ends[i * 2 + 1]);
+            assert_eq!(spans.block_ends[i], max_end);
+        }
+    }
+
+    fn all(spans: &Spans<u32>) -> Vec<(std::ops::Range<usize>, u32)> {
+        spans.iter().map(|(r, &v)| (r, v)).collect()
+    }
+
+    #[test]
+    fn spans_01() {
+        let mut r = Rope::from_str(TEXT);
+        let mut s = Spans::new();
+        s.add(0..5, 1);
+        s.add(6..11, 2);
+        s.add(3..8, 3);
+
+        s.insert(&mut r, 6, "big ");
+        assert_eq!(all(&s), vec![(0..5, 1), (3..12, 3), (10..15, 2)]);
+
+        s.insert(&mut r, 5, "!");
+        assert_eq!(all(&s), vec![(0..5, 1), (3..13, 3), (11..16, 2)]);
+
+        s.remove(&mut r, 2..12);
+        assert_eq!(all(&s), vec![(0..2, 1), (2..3, 3), (2..6, 2)]);
+
+        s.remove(&mut r, 2..3);
+        assert_eq!(all(&s), vec![(0..2, 1), (2..5, 2)]);
+        assert_eq!(s.len(), 2);
+    }
+
+    #[test]
+    fn spans_02() {
+        let mut r = Rope::from_str(TEXT);
+        let mut s = Spans::new();
+        s.set_split_on_insert(true);
+        s.add(0..10, 1);
+        s.add(4..4, 2);
+
+        s.insert(&mut r, 4, "xyz");
+        assert_eq!(all(&s), vec![(0..4, 1), (7..7, 2), (7..13, 1)]);
+
+        s.insert(&mut r, 13, "xyz");
+        s.insert(&mut r, 0, "xyz");
+        assert_eq!(all(&s), vec![(3..7, 1), (10..10, 2), (10..16, 1)]);
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn spans_03() {
+        let r = Rope::from_str(TEXT);
+        let mut s = Spans::new();
+        for i in 0..r.len_chars() {
+            s.add(i..(i + 1), i as u32);
+        }
+        s.add(30..60, 1000);
+        s.add(40..40, 1001);
+
+        let line_1: Vec<u32> = s.overlapping_line(&r, 1).map(|(_, &v)| v).collect();
+        let start = r.line_to_char(1) as u32;
+        let end = r.line_to_char(2) as u32;
+        let mut expected: Vec<u32> = (start..end).collect();
+        expected.insert(0, 1000);
+        let pos = expected.iter().position(|&v| v == 40).unwrap();
+        expected.insert(pos + 1, 1001);
+        assert_eq!(line_1, expected);
+
+        assert_eq!(
+            s.overlapping(40..40).map(|(_, &v)| v).collect::<Vec<_>>(),
+            vec![1000, 1001]
+        );
+        assert!(s.try_overlapping_line(&r, r.len_lines()).is_err());
+        assert!(s.try_add(5..4, 0).is_err());
+    }
+
+    #[test]
+    fn spans_04() {
+        let mut s = Spans::new();
+        for i in 0..100 {
+            s.add((i * 2)..(i * 2 + 3), i);
+        }
+        s.retain(|range, &v| range.start >= 50 && v % 2 == 0);
+        assert_eq!(s.len(), 37);
+        assert_eq!(s.iter().next(), Some((52..55, &26)));
+
+        let cs = ChangeSet::from_changes(202, vec![(0..60, None), (100..100, Some("x"))]);
+        s.apply_changes(&cs);
+        assert_eq!(s.iter().next(), Some((0..3, &30)));
+        assert_eq!(
+            s.overlapping(38..42).collect::<Vec<_>>(),
+            vec![(36..39, &48), (41..44, &50)]
+        );
+
+        s.clear();
+        assert!(s.is_empty());
+        assert_eq!(s.iter().next(), None);
+    }
+
+    #[test]
+    fn spans_05() {
+        // Lots of random edits and spans, checked against a simple model.
+        for &split in [false, true].iter() {
+            let mut rng = StdRng::seed_from_u64(15);
+            let mut r = Rope::from_str(TEXT);
+            let mut s = Spans::new();
+            s.set_split_on_insert(split);
+            let mut model: Vec<(usize, usize, u32)> = Vec::new();
+
+            for i in 0..2000 {
+                let len = r.len_chars();
+                match rng.gen_range(0, 4) {
+                    0 | 1 => {
+                        let start = rng.gen_range(0, len + 1);
+                        let end = rng.gen_range(start, start + (len - start).min(20) + 1);
+                        s.add(start..end, i);
+                        model.push((start, end, i));
+                    }
+                    2 => {
+                        let pos = rng.gen_range(0, len + 1);
+                        let text = ["a", "bc\r\n", "こんにちは"][rng.gen_range(0, 3)];
+                        s.insert(&mut r, pos, text);
+                        let n = text.chars().count();
+                        let mut new_model = Vec::new();
+                        for &(start, end, v) in model.iter() {
+                            if start >= pos {
+                                new_model.push((start + n, end + n, v));
+                            } else if end > pos {
+                                if split {
+                                    new_model.push((start, pos, v));
+                                    new_model.push((pos + n, end + n, v));
+                                } else {
+                                    new_model.push((start, end + n, v));
+                                }
+                            } else {
+                                new_model.push((start, end, v));
+                            }
+                        }
+                        model = new_model;
+                    }
+                    _ => {
+                        let start = rng.gen_range(0, len + 1);
+                        let end = rng.gen_range(start, start + (len - start).min(10) + 1);
+                        s.remove(&mut r, start..end);
+                        let map = |p: usize| {
+                            if p <= start {
+                                p
+                            } else if p < end {
+                                start
+                            } else {
+                                p - (end - start)
+                            }
+                        };
+                        model = model
+                            .into_iter()
+                            .filter(|&(a, b, _)| a == b || map(a) != map(b))
+                            .map(|(a, b, v)| (map(a), map(b), v))
+                            .collect();
+                    }
+                }
+                check(&s);
+            }
+
+            let mut got: Vec<_> = s.iter().map(|(r, &v)| (r.start, r.end, v)).collect();
+            got.sort();
+            model.sort();
+            assert_eq!(got, model);
+
+            // Overlap queries.
+            for q in 0..50 {
+                let a = q * r.len_chars() / 50;
+                let b = a + 7;
+                let mut got: Vec<_> = s
+                    .overlapping(a..b)
+                    .map(|(r, &v)| (r.start, r.end, v))
+                    .collect();
+                let mut expected: Vec<_> = model
+                    .iter()
+                    .cloned()
+                    .filter(|&(s, e, _)| {
+                        if s == e {
+                            a <= s && s < b
+                        } else {
+                            s < b && e > a
+                        }
+                    })
+                    .collect();
+                got.sort();
+                expected.sort();
+                assert_eq!(got, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn spans_06() {
+        // A long span near the start doesn't make later queries scan the
+        // blocks in between.
+        let mut s = Spans::new();
+        s.add(0..100_000, 0);
+        for i in 1..10_000 {
+            s.add((i * 10)..(i * 10 + 5), i as u32);
+        }
+        check(&s);
+        assert!(s.blocks.len() > 100);
+
+        let got: Vec<_> = s
+            .overlapping(50_002..50_012)
+            .map(|(r, &v)| (r, v))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                (0..100_000, 0),
+                (50_000..50_005, 5000),
+                (50_010..50_015, 5001)
+            ]
+        );
+
+        // Only the first block and the ones around the range are candidates.
+        let first = s.next_block(0, 50_002);
+        assert_eq!(first, 0);
+        let next = s.next_block(first + 1, 50_002);
+        assert!(s.blocks[next - 1].end() < 50_002);
+        assert!(s.blocks[next].base <= 50_002);
+        assert_eq!(s.next_block(0, 200_000), s.blocks.len());
+    }
+}
@@ -40,10 +40,14 @@ pub fn byte_to_line_idx(text: &str, byte_idx: usize) -> usize {
         byte_idx -= 1;
     }
     let nl_count = count_line_breaks(&text[..byte_idx]);
-    if crlf::is_break(byte_idx, text.as_bytes()) {
-        nl_count
-    } else {
+
+    // When CR is a line break on its own, a CR at the end of the prefix
+    // that's actually the first half of a CRLF pair was counted as a
+    // separate line break.
+    if cfg!(feature = "cr_lines") && !crlf::is_break(byte_idx, text.as_bytes()) {
         nl_count - 1
+    } else {
+        nl_count
     }
 }
 
@@ -301,6 +305,7 @@ pub(crate) fn utf16_code_unit_to_char_idx(text: &str, utf16_idx: usize) -> usize
 pub(crate) fn prev_line_end_char_idx(text: &str) -> usize {
     let mut itr = text.bytes().enumerate().rev();
 
+    #[cfg_attr(not(feature = "cr_lines"), allow(unused_variables))]
     let first_byte = if let Some((_, byte)) = itr.next() {
         byte
     } else {
@@ -309,19 +314,26 @@ pub(crate) fn prev_line_end_char_idx(text: &str) -> usize {
 
     while let Some((idx, byte)) = itr.next() {
         match byte {
-            0x0A | 0x0B | 0x0C => {
+            0x0A => {
                 return idx + 1;
             }
-            0x0D => {
-                if first_byte != 0x0A {
-                    return idx + 1;
-                }
+            #[cfg(feature = "unicode_lines")]
+            0x0B | 0x0C => {
+                return idx + 1;
+            }
+            #[cfg(feature = "cr_lines")]
+            // Skip the CR of a CRLF pair at the very end, since that
+            // pair is the line break being skipped over.
+            0x0D if first_byte != 0x0A || idx + 2 != text.len() => {
+                return idx + 1;
             }
+            #[cfg(feature = "unicode_lines")]
             0x85 => {
                 if let Some((_, 0xC2)) = itr.next() {
                     return idx + 1;
                 }
             }
+            #[cfg(feature = "unicode_lines")]
             0xA8 | 0xA9 => {
                 if let Some((_, 0x80)) = itr.next() {
                     if let Some((_, 0xE2)) = itr.next() {
@@ -350,8 +362,11 @@ pub(crate) fn ends_with_line_break(text: &str) -> bool {
 
     // Check if the last codepoint is a line break.
     match &text[i..] {
-        "\u{000A}" | "\u{000B}" | "\u{000C}" | "\u{000D}" | "\u{0085}" | "\u{2028}"
-        | "\u{2029}" => true,
+        "\u{000A}" => true,
+        #[cfg(feature = "cr_lines")]
+        "\u{000D}" => true,
+        #[cfg(feature = "unicode_lines")]
+        "\u{000B}" | "\u{000C}" | "\u{0085}" | "\u{2028}" | "\u{2029}" => true,
         _ => false,
     }
 }
@@ -418,13 +433,13 @@ fn count_chars_internal<T: ByteChunk>(text: &[u8]) -> usize {
 ///
 /// The following unicode sequences are considered newlines by this function:
 /// - u{000A}        (Line Feed)
-/// - u{000B}        (Vertical Tab)
-/// - u{000C}        (Form Feed)
-/// - u{000D}        (Carriage Return)
+/// - u{000B}        (Vertical Tab)        [`unicode_lines` only]
+/// - u{000C}        (Form Feed)           [`unicode_lines` only]
+/// - u{000D}        (Carriage Return)     [`cr_lines` only]
 /// - u{000D}u{000A} (Carriage Return + Line Feed)
-/// - u{0085}        (Next Line)
-/// - u{2028}        (Line Separator)
-/// - u{2029}        (Paragraph Separator)
+/// - u{0085}        (Next Line)           [`unicode_lines` only]
+/// - u{2028}        (Line Separator)      [`unicode_lines` only]
+/// - u{2029}        (Paragraph Separator) [`unicode_lines` only]
 #[inline]
 pub(crate) fn count_line_breaks(text: &str) -> usize {
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
@@ -485,28 +500,54 @@ fn count_line_breaks_up_to(bytes: &[u8], max_bytes: usize, max_breaks: usize) ->
     while ptr < max_bytes && count < max_breaks {
         let byte = bytes[ptr];
 
-        // Handle u{000A}, u{000B}, u{000C}, and u{000D}
-        if (byte <= 0x0D) && (byte >= 0x0A) {
-            count += 1;
+        #[cfg(feature = "unicode_lines")]
+        {
+            // Handle u{000A}, u{000B}, u{000C}, and u{000D}
+            if (byte <= 0x0D) && (byte >= 0x0A) {
+                count += 1;
 
-            // Check for CRLF and and subtract 1 if it is,
-            // since it will be caught in the next iteration
-            // with the LF.
-            if byte == 0x0D && (ptr + 1) < bytes.len() && bytes[ptr + 1] == 0x0A {
-                count -= 1;
+                // Check for CRLF and and subtract 1 if it is,
+                // since it will be caught in the next iteration
+                // with the LF.
+                if byte == 0x0D && (ptr + 1) < bytes.len() && bytes[ptr + 1] == 0x0A {
+                    count -= 1;
+                }
+            }
+            // Handle u{0085}
+            else if byte == 0xC2 && (ptr + 1) < bytes.len() && bytes[ptr + 1] == 0x85 {
+                count += 1;
+            }
+            // Handle u{2028} and u{2029}
+            else if byte == 0xE2
+                && (ptr + 2) < bytes.len()
+                && bytes[ptr + 1] == 0x80
+                && (bytes[ptr + 2] >> 1) == 0x54
+            {
+                count += 1;
             }
         }
-        // Handle u{0085}
-        else if byte == 0xC2 && (ptr + 1) < bytes.len() && bytes[ptr + 1] == 0x85 {
-            count += 1;
+
+        // Handle u{000A} and u{000D}
+        #[cfg(all(feature = "cr_lines", not(feature = "unicode_lines")))]
+        {
+            if byte == 0x0A || byte == 0x0D {
+                count += 1;
+
+                // Check for CRLF and and subtract 1 if it is,
+                // since it will be caught in the next iteration
+                // with the LF.
+                if byte == 0x0D && (ptr + 1) < bytes.len() && bytes[ptr + 1] == 0x0A {
+                    count -= 1;
+                }
+            }
         }
-        // Handle u{2028} and u{2029}
-        else if byte == 0xE2
-            && (ptr + 2) < bytes.len()
-            && bytes[ptr + 1] == 0x80
-            && (bytes[ptr + 2] >> 1) == 0x54
+
+        // Handle u{000A}
+        #[cfg(not(feature = "cr_lines"))]
         {
-            count += 1;
+            if byte == 0x0A {
+                count += 1;
+            }
         }
 
         ptr += 1;
@@ -530,55 +571,57 @@ unsafe fn count_line_breaks_in_chunk_from_ptr<T: ByteChunk>(bytes: &[u8]) -> T {
         // `align_to()` results in a significant drop in performance.
         *(bytes.as_ptr() as *const T)
     };
+    #[cfg(feature = "cr_lines")]
     let end_i = T::size();
 
     let mut acc = T::splat(0);
 
-    // Calculate the flags we're going to be working with.
-    let nl_1_flags = c.cmp_eq_byte(0xC2);
-    let sp_1_flags = c.cmp_eq_byte(0xE2);
-    let all_flags = c.bytes_between_127(0x09, 0x0E);
-    let cr_flags = c.cmp_eq_byte(0x0D);
-
-    // Next Line: u{0085}
-    if !nl_1_flags.is_zero() {
-        let nl_2_flags = c.cmp_eq_byte(0x85).shift_back_lex(1);
-        let flags = nl_1_flags.bitand(nl_2_flags);
-        acc = acc.add(flags);
-
-        // Handle ending boundary
-        if bytes.len() > end_i && bytes[end_i - 1] == 0xC2 && bytes[end_i] == 0x85 {
-            acc = acc.inc_nth_from_end_lex_byte(0);
+    #[cfg(feature = "unicode_lines")]
+    {
+        // Calculate the flags we're going to be working with.
+        let nl_1_flags = c.cmp_eq_byte(0xC2);
+        let sp_1_flags = c.cmp_eq_byte(0xE2);
+
+        // Next Line: u{0085}
+        if !nl_1_flags.is_zero() {
+            let nl_2_flags = c.cmp_eq_byte(0x85).shift_back_lex(1);
+            let flags = nl_1_flags.bitand(nl_2_flags);
+            acc = acc.add(flags);
+
+            // Handle ending boundary
+            if bytes.len() > end_i && bytes[end_i - 1] == 0xC2 && bytes[end_i] == 0x85 {
+                acc = acc.inc_nth_from_end_lex_byte(0);
+            }
         }
-    }
 
-    // Line Separator:      u{2028}
-    // Paragraph Separator: u{2029}
-    if !sp_1_flags.is_zero() {
-        let sp_2_flags = c.cmp_eq_byte(0x80).shift_back_lex(1).bitand(sp_1_flags);
-        if !sp_2_flags.is_zero() {
-            let sp_3_flags = c
-                .shr(1)
-                .bitand(T::splat(!0x80))
-                .cmp_eq_byte(0x54)
-                .shift_back_lex(2);
-            let sp_flags = sp_2_flags.bitand(sp_3_flags);
-            acc = acc.add(sp_flags);
-        }
+        // Line Separator:      u{2028}
+        // Paragraph Separator: u{2029}
+        if !sp_1_flags.is_zero() {
+            let sp_2_flags = c.cmp_eq_byte(0x80).shift_back_lex(1).bitand(sp_1_flags);
+            if !sp_2_flags.is_zero() {
+                let sp_3_flags = c
+                    .shr(1)
+                    .bitand(T::splat(!0x80))
+                    .cmp_eq_byte(0x54)
+                    .shift_back_lex(2);
+                let sp_flags = sp_2_flags.bitand(sp_3_flags);
+                acc = acc.add(sp_flags);
+            }
 
-        // Handle ending boundary
-        if bytes.len() > end_i
-            && bytes[end_i - 2] == 0xE2
-            && bytes[end_i - 1] == 0x80
-            && (bytes[end_i] >> 1) == 0x54
-        {
-            acc = acc.inc_nth_from_end_lex_byte(1);
-        } else if bytes.len() > (end_i + 1)
-            && bytes[end_i - 1] == 0xE2
-            && bytes[end_i] == 0x80
-            && (bytes[end_i + 1] >> 1) == 0x54
-        {
-            acc = acc.inc_nth_from_end_lex_byte(0);
+            // Handle ending boundary
+            if bytes.len() > end_i
+                && bytes[end_i - 2] == 0xE2
+                && bytes[end_i - 1] == 0x80
+                && (bytes[end_i] >> 1) == 0x54
+            {
+                acc = acc.inc_nth_from_end_lex_byte(1);
+            } else if bytes.len() > (end_i + 1)
+                && bytes[end_i - 1] == 0xE2
+                && bytes[end_i] == 0x80
+                && (bytes[end_i + 1] >> 1) == 0x54
+            {
+                acc = acc.inc_nth_from_end_lex_byte(0);
+            }
         }
     }
 
@@ -586,15 +629,31 @@ unsafe fn count_line_breaks_in_chunk_from_ptr<T: ByteChunk>(bytes: &[u8]) -> T {
     // Vertical Tab:                u{000B}
     // Form Feed:                   u{000C}
     // Carriage Return:             u{000D}
+    #[cfg(feature = "unicode_lines")]
+    let break_flags = c.bytes_between_127(0x09, 0x0E);
+
+    // Line Feed:                   u{000A}
+    // Carriage Return:             u{000D}
+    #[cfg(all(feature = "cr_lines", not(feature = "unicode_lines")))]
+    let break_flags = c.cmp_eq_byte(0x0A).add(c.cmp_eq_byte(0x0D));
+
+    // Line Feed:                   u{000A}
+    #[cfg(not(feature = "cr_lines"))]
+    let break_flags = c.cmp_eq_byte(0x0A);
+
+    acc = acc.add(break_flags);
+
     // Carriage Return + Line Feed: u{000D}u{000A}
-    acc = acc.add(all_flags);
-    if !cr_flags.is_zero() {
-        // Handle CRLF
-        let lf_flags = c.cmp_eq_byte(0x0A);
-        let crlf_flags = cr_flags.bitand(lf_flags.shift_back_lex(1));
-        acc = acc.sub(crlf_flags);
-        if bytes.len() > end_i && bytes[end_i - 1] == 0x0D && bytes[end_i] == 0x0A {
-            acc = acc.dec_last_lex_byte();
+    #[cfg(feature = "cr_lines")]
+    {
+        let cr_flags = c.cmp_eq_byte(0x0D);
+        if !cr_flags.is_zero() {
+            let lf_flags = c.cmp_eq_byte(0x0A);
+            let crlf_flags = cr_flags.bitand(lf_flags.shift_back_lex(1));
+            acc = acc.sub(crlf_flags);
+            if bytes.len() > end_i && bytes[end_i - 1] == 0x0D && bytes[end_i] == 0x0A {
+                acc = acc.dec_last_lex_byte();
+            }
         }
     }
 
@@ -620,6 +679,7 @@ fn alignment_diff<T>(bytes: &[u8]) -> usize {
 
 /// Interface for working with chunks of bytes at a time, providing the
 /// operations needed for the functionality in str_utils.
+#[cfg_attr(not(feature = "unicode_lines"), allow(dead_code))]
 trait ByteChunk: Copy + Clone + std::fmt::Debug {
     /// Returns the size of the chunk in bytes.
     fn size() -> usize;
@@ -1096,6 +1156,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "unicode_lines")]
     fn prev_line_end_char_idx_01() {
         let mut text = "\u{000A}Hello\u{000D}\u{000A}\u{000D}せ\u{000B}か\u{000C}い\u{0085}. \
                         There\u{2028}is something.\u{2029}";
@@ -1120,6 +1181,47 @@ mod tests {
     }
 
     #[test]
+    #[cfg(all(feature = "cr_lines", not(feature = "unicode_lines")))]
+    fn prev_line_end_char_idx_02() {
+        let mut text = "\u{000A}Hello\u{000D}\u{000A}\u{000D}せ\u{000B}か\u{000C}い\u{0085}. \
+                        There\u{2028}is something.\u{2029}";
+
+        assert_eq!(48, text.len());
+        text = &text[..prev_line_end_char_idx(text)];
+        assert_eq!(9, text.len());
+        text = &text[..prev_line_end_char_idx(text)];
+        assert_eq!(8, text.len());
+        text = &text[..prev_line_end_char_idx(text)];
+        assert_eq!(1, text.len());
+        text = &text[..prev_line_end_char_idx(text)];
+        assert_eq!(0, text.len());
+    }
+
+    #[test]
+    #[cfg(not(feature = "cr_lines"))]
+    fn prev_line_end_char_idx_03() {
+        let mut text = "\u{000A}Hello\u{000D}\u{000A}\u{000D}せ\u{000B}か\u{000C}い\u{0085}. \
+                        There\u{2028}is something.\u{2029}";
+
+        assert_eq!(48, text.len());
+        text = &text[..prev_line_end_char_idx(text)];
+        assert_eq!(8, text.len());
+        text = &text[..prev_line_end_char_idx(text)];
+        assert_eq!(1, text.len());
+        text = &text[..prev_line_end_char_idx(text)];
+        assert_eq!(0, text.len());
+    }
+
+    #[test]
+    #[cfg(feature = "cr_lines")]
+    fn prev_line_end_char_idx_04() {
+        assert_eq!(3, prev_line_end_char_idx("ab\rc\n"));
+        assert_eq!(3, prev_line_end_char_idx("ab\rc\r\n"));
+        assert_eq!(0, prev_line_end_char_idx("abc\r\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "unicode_lines")]
     fn count_line_breaks_01() {
         let text = "\u{000A}Hello\u{000D}\u{000A}\u{000D}せ\u{000B}か\u{000C}い\u{0085}. \
                     There\u{2028}is something.\u{2029}";
@@ -1128,11 +1230,50 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "unicode_lines")]
     fn count_line_breaks_02() {
         let text = "\u{000A}Hello world!  This is a longer text.\u{000D}\u{000A}\u{000D}To better test that skipping by usize doesn't mess things up.\u{000B}Hello せかい!\u{000C}\u{0085}Yet more text.  How boring.\u{2028}Hi.\u{2029}\u{000A}Hello world!  This is a longer text.\u{000D}\u{000A}\u{000D}To better test that skipping by usize doesn't mess things up.\u{000B}Hello せかい!\u{000C}\u{0085}Yet more text.  How boring.\u{2028}Hi.\u{2029}\u{000A}Hello world!  This is a longer text.\u{000D}\u{000A}\u{000D}To better test that skipping by usize doesn't mess things up.\u{000B}Hello せかい!\u{000C}\u{0085}Yet more text.  How boring.\u{2028}Hi.\u{2029}\u{000A}Hello world!  This is a longer text.\u{000D}\u{000A}\u{000D}To better test that skipping by usize doesn't mess things up.\u{000B}Hello せかい!\u{000C}\u{0085}Yet more text.  How boring.\u{2028}Hi.\u{2029}";
         assert_eq!(count_line_breaks(text), LineBreakIter::new(text).count());
     }
 
+    #[test]
+    #[cfg(all(feature = "cr_lines", not(feature = "unicode_lines")))]
+    fn count_line_breaks_03() {
+        let text = "\u{000A}Hello\u{000D}\u{000A}\u{000D}せ\u{000B}か\u{000C}い\u{0085}. \
+                    There\u{2028}is something.\u{2029}";
+        assert_eq!(48, text.len());
+        assert_eq!(3, count_line_breaks(text));
+    }
+
+    #[test]
+    #[cfg(all(feature = "cr_lines", not(feature = "unicode_lines")))]
+    fn count_line_breaks_04() {
+        let text = "\u{000A}Hello world!  This is a longer text.\u{000D}\u{000A}\u{000D}To better test that skipping by usize doesn't mess things up.\u{000B}Hello せかい!\u{000C}\u{0085}Yet more text.  How boring.\u{2028}Hi.\u{2029}\u{000A}Hello world!  This is a longer text.\u{000D}\u{000A}\u{000D}To better test that skipping by usize doesn't mess things up.\u{000B}Hello せかい!\u{000C}\u{0085}Yet more text.  How boring.\u{2028}Hi.\u{2029}\u{000D}";
+        let expected = text
+            .replace("\r\n", "\n")
+            .bytes()
+            .filter(|&b| b == 0x0A || b == 0x0D)
+            .count();
+        assert_eq!(expected, count_line_breaks(text));
+    }
+
+    #[test]
+    #[cfg(not(feature = "cr_lines"))]
+    fn count_line_breaks_05() {
+        let text = "\u{000A}Hello\u{000D}\u{000A}\u{000D}せ\u{000B}か\u{000C}い\u{0085}. \
+                    There\u{2028}is something.\u{2029}";
+        assert_eq!(48, text.len());
+        assert_eq!(2, count_line_breaks(text));
+    }
+
+    #[test]
+    #[cfg(not(feature = "cr_lines"))]
+    fn count_line_breaks_06() {
+        let text = "\u{000A}Hello world!  This is a longer text.\u{000D}\u{000A}\u{000D}To better test that skipping by usize doesn't mess things up.\u{000B}Hello せかい!\u{000C}\u{0085}Yet more text.  How boring.\u{2028}Hi.\u{2029}\u{000A}Hello world!  This is a longer text.\u{000D}\u{000A}\u{000D}To better test that skipping by usize doesn't mess things up.\u{000B}Hello せかい!\u{000C}\u{0085}Yet more text.  How boring.\u{2028}Hi.\u{2029}\u{000D}";
+        let expected = text.bytes().filter(|&b| b == 0x0A).count();
+        assert_eq!(expected, count_line_breaks(text));
+    }
+
     #[test]
     fn byte_to_char_idx_01() {
         let text = "Hello せかい!";
@@ -1233,6 +1374,17 @@ mod tests {
         assert_eq!(3, byte_to_line_idx(text, 17));
     }
 
+    #[test]
+    #[cfg(not(feature = "cr_lines"))]
+    fn byte_to_line_idx_05() {
+        let text = "Here\rare\r\nsome\u{2028}words";
+        assert_eq!(0, byte_to_line_idx(text, 0));
+        assert_eq!(0, byte_to_line_idx(text, 5));
+        assert_eq!(0, byte_to_line_idx(text, 9));
+        assert_eq!(1, byte_to_line_idx(text, 10));
+        assert_eq!(1, byte_to_line_idx(text, 22));
+    }
+
     #[test]
     fn byte_to_line_idx_04() {
         // Line 0
@@ -1466,6 +1618,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "unicode_lines")]
     fn ends_with_line_break_01() {
         assert_eq!(true, ends_with_line_break("\n"));
         assert_eq!(true, ends_with_line_break("\r"));
@@ -1479,6 +1632,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "unicode_lines")]
     fn ends_with_line_break_02() {
         assert_eq!(true, ends_with_line_break("Hi there!\n"));
         assert_eq!(true, ends_with_line_break("Hi there!\r"));
@@ -1498,6 +1652,32 @@ mod tests {
         assert_eq!(false, ends_with_line_break("Hi there!"));
     }
 
+    #[test]
+    #[cfg(all(feature = "cr_lines", not(feature = "unicode_lines")))]
+    fn ends_with_line_break_05() {
+        assert_eq!(true, ends_with_line_break("Hi there!\u{000A}"));
+        assert_eq!(true, ends_with_line_break("Hi there!\u{000D}"));
+        assert_eq!(true, ends_with_line_break("Hi there!\u{000D}\u{000A}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{000B}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{000C}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{0085}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{2028}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{2029}"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "cr_lines"))]
+    fn ends_with_line_break_06() {
+        assert_eq!(true, ends_with_line_break("Hi there!\u{000A}"));
+        assert_eq!(true, ends_with_line_break("Hi there!\u{000D}\u{000A}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{000B}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{000C}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{000D}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{0085}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{2028}"));
+        assert_eq!(false, ends_with_line_break("Hi there!\u{2029}"));
+    }
+
     #[test]
     fn ends_with_line_break_04() {
         assert_eq!(false, ends_with_line_break("\na"));
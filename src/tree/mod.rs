@@ -1,5 +1,6 @@
 mod node;
 mod node_children;
+mod node_summaries;
 mod node_text;
 mod text_info;
 
@@ -8,6 +9,9 @@ use std::{mem::size_of, sync::Arc};
 
 pub(crate) use self::node::Node;
 pub(crate) use self::node_children::NodeChildren;
+pub(crate) use self::node_summaries::NodeSummaries;
+#[cfg(test)]
+pub(crate) use self::node_summaries::MAX_SUMMARIES;
 pub(crate) use self::node_text::NodeText;
 pub(crate) use self::text_info::TextInfo;
 
@@ -15,6 +19,9 @@ pub(crate) use self::text_info::TextInfo;
 const PTR_SIZE: usize = size_of::<&u8>();
 #[cfg(not(test))]
 const CHILD_INFO_SIZE: usize = size_of::<Arc<Node>>() + size_of::<TextInfo>();
+// Every node also has a cache of metric summaries.
+#[cfg(not(test))]
+const SUMMARIES_SIZE: usize = size_of::<NodeSummaries>();
 
 // Aim for nodes to be 1024 bytes minus Arc counters.  Keeping the nodes
 // multiples of large powers of two makes it easier for the memory allocator
@@ -29,13 +36,13 @@ const TARGET_NODE_SIZE: usize = 1024 - (PTR_SIZE * 2);
 #[cfg(test)]
 pub(crate) const MAX_CHILDREN: usize = 5;
 #[cfg(not(test))]
-pub(crate) const MAX_CHILDREN: usize = (TARGET_NODE_SIZE - 1) / CHILD_INFO_SIZE;
+pub(crate) const MAX_CHILDREN: usize = (TARGET_NODE_SIZE - 1 - SUMMARIES_SIZE) / CHILD_INFO_SIZE;
 pub(crate) const MIN_CHILDREN: usize = MAX_CHILDREN / 2;
 
 #[cfg(test)]
 pub(crate) const MAX_BYTES: usize = 9; // Note: can't be 8, because 3-byte characters.
 #[cfg(not(test))]
-pub(crate) const MAX_BYTES: usize = TARGET_NODE_SIZE - 1 - (PTR_SIZE * 2);
+pub(crate) const MAX_BYTES: usize = TARGET_NODE_SIZE - 1 - (PTR_SIZE * 2) - SUMMARIES_SIZE;
 // MIN_BYTES is little smaller than half MAX_BYTES so that repeated
 // splitting/merging doesn't happen on alternating small insertions and
 // removals.
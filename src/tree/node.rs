@@ -1,9 +1,12 @@
 use std::sync::Arc;
 
-use crate::str_utils::{byte_to_line_idx, byte_to_utf16_surrogate_idx, char_to_byte_idx};
+use crate::str_utils::{
+    byte_to_line_idx, byte_to_utf16_surrogate_idx, char_to_byte_idx, count_chars, line_to_byte_idx,
+};
 use crate::tree::node_text::fix_segment_seam;
 use crate::tree::{
-    Count, NodeChildren, NodeText, TextInfo, MAX_BYTES, MAX_CHILDREN, MIN_BYTES, MIN_CHILDREN,
+    Count, NodeChildren, NodeSummaries, NodeText, TextInfo, MAX_BYTES, MAX_CHILDREN, MIN_BYTES,
+    MIN_CHILDREN,
 };
 
 #[derive(Debug, Clone)]
@@ -19,6 +22,17 @@ impl Node {
         Node::Leaf(NodeText::from_str(""))
     }
 
+    /// Same as `Node::make_mut()`, except that it also drops the node's
+    /// cached metric summaries, since the caller is about to edit it.
+    ///
+    /// All mutable access to nodes in a tree must go through this.
+    #[inline(always)]
+    pub fn make_mut(node: &mut Arc<Node>) -> &mut Node {
+        let node = Arc::make_mut(node);
+        node.summaries_mut().clear();
+        node
+    }
+
     /// Total number of bytes in the Rope.
     #[inline(always)]
     pub fn byte_count(&self) -> usize {
@@ -72,13 +86,58 @@ impl Node {
         &mut self,
         char_idx: usize,
         node_info: TextInfo,
+        edit: F,
+    ) -> (TextInfo, Option<(TextInfo, Arc<Node>)>)
+    where
+        F: FnMut(usize, TextInfo, &mut NodeText) -> (TextInfo, Option<(TextInfo, Arc<Node>)>),
+    {
+        self.edit_chunk_at_idx(
+            char_idx,
+            node_info,
+            NodeChildren::search_char_idx_only,
+            edit,
+        )
+    }
+
+    /// Same as `edit_chunk_at_char()` above, except that the chunk is
+    /// located by byte index, and the closure receives the byte offset
+    /// of `byte_idx` within the chunk.
+    ///
+    /// Note that `byte_idx` is not checked for being on a char boundary:
+    /// that is up to the closure.
+    pub fn edit_chunk_at_byte<F>(
+        &mut self,
+        byte_idx: usize,
+        node_info: TextInfo,
+        edit: F,
+    ) -> (TextInfo, Option<(TextInfo, Arc<Node>)>)
+    where
+        F: FnMut(usize, TextInfo, &mut NodeText) -> (TextInfo, Option<(TextInfo, Arc<Node>)>),
+    {
+        self.edit_chunk_at_idx(
+            byte_idx,
+            node_info,
+            NodeChildren::search_byte_idx_only,
+            edit,
+        )
+    }
+
+    /// Shared implementation of the `edit_chunk_at_*()` methods.
+    ///
+    /// `search` is one of the `NodeChildren::search_*_idx_only()` methods,
+    /// and determines which metric `idx` is in.
+    fn edit_chunk_at_idx<F>(
+        &mut self,
+        idx: usize,
+        node_info: TextInfo,
+        search: fn(&NodeChildren, usize) -> (usize, usize),
         mut edit: F,
     ) -> (TextInfo, Option<(TextInfo, Arc<Node>)>)
     where
         F: FnMut(usize, TextInfo, &mut NodeText) -> (TextInfo, Option<(TextInfo, Arc<Node>)>),
     {
         match *self {
-            Node::Leaf(ref mut leaf_text) => edit(char_idx, node_info, leaf_text),
+            Node::Leaf(ref mut leaf_text) => edit(idx, node_info, leaf_text),
             Node::Internal(ref mut children) => {
                 // Compact leaf children if we're very close to maximum leaf
                 // fragmentation.  This basically guards against excessive memory
@@ -94,12 +153,12 @@ impl Node {
                 }
 
                 // Find the child we care about.
-                let (child_i, acc_char_idx) = children.search_char_idx_only(char_idx);
+                let (child_i, acc_idx) = search(children, idx);
                 let info = children.info()[child_i];
 
                 // Recurse into the child.
-                let (l_info, residual) = Arc::make_mut(&mut children.nodes_mut()[child_i])
-                    .edit_chunk_at_char(char_idx - acc_char_idx, info, edit);
+                let (l_info, residual) = Node::make_mut(&mut children.nodes_mut()[child_i])
+                    .edit_chunk_at_idx(idx - acc_idx, info, search, edit);
                 children.info_mut()[child_i] = l_info;
 
                 // Handle the residual node if there is one and return.
@@ -160,21 +219,25 @@ impl Node {
                             node_info - TextInfo::from_str(&leaf_text[byte_start..byte_end]);
 
                         // Check for CRLF pairs on the removal seams, and
-                        // adjust line break counts accordingly.
-                        if byte_end < leaf_text.len()
-                            && leaf_text.as_bytes()[byte_end - 1] == 0x0D
-                            && leaf_text.as_bytes()[byte_end] == 0x0A
-                        {
-                            info.line_breaks += 1;
-                        }
-                        if byte_start > 0 && leaf_text.as_bytes()[byte_start - 1] == 0x0D {
-                            if leaf_text.as_bytes()[byte_start] == 0x0A {
+                        // adjust line break counts accordingly (only relevant
+                        // when CR is a line break on its own).
+                        if cfg!(feature = "cr_lines") {
+                            if byte_end < leaf_text.len()
+                                && leaf_text.as_bytes()[byte_end - 1] == 0x0D
+                                && leaf_text.as_bytes()[byte_end] == 0x0A
+                            {
                                 info.line_breaks += 1;
                             }
-
-                            if byte_end < leaf_text.len() && leaf_text.as_bytes()[byte_end] == 0x0A
-                            {
-                                info.line_breaks -= 1;
+                            if byte_start > 0 && leaf_text.as_bytes()[byte_start - 1] == 0x0D {
+                                if leaf_text.as_bytes()[byte_start] == 0x0A {
+                                    info.line_breaks += 1;
+                                }
+
+                                if byte_end < leaf_text.len()
+                                    && leaf_text.as_bytes()[byte_end] == 0x0A
+                                {
+                                    info.line_breaks -= 1;
+                                }
                             }
                         }
 
@@ -211,7 +274,7 @@ impl Node {
                     let tmp_info = children.info()[child_i];
                     let tmp_chars = children.info()[child_i].chars as usize;
                     let (new_info, seam, needs_fix) =
-                        Arc::make_mut(&mut children.nodes_mut()[child_i]).remove_char_range(
+                        Node::make_mut(&mut children.nodes_mut()[child_i]).remove_char_range(
                             start_idx - c_char_acc.min(start_idx),
                             (end_idx - c_char_acc).min(tmp_chars),
                             tmp_info,
@@ -327,7 +390,7 @@ impl Node {
                 }
                 Node::Internal(ref mut children_l) => {
                     let mut other = other;
-                    if let Node::Internal(ref mut children_r) = *Arc::make_mut(&mut other) {
+                    if let Node::Internal(ref mut children_r) = *Node::make_mut(&mut other) {
                         if (children_l.len() + children_r.len()) <= MAX_CHILDREN {
                             for _ in 0..children_r.len() {
                                 children_l.push(children_r.remove(0));
@@ -346,7 +409,7 @@ impl Node {
         } else if let Node::Internal(ref mut children) = *self {
             let last_i = children.len() - 1;
             let residual =
-                Arc::make_mut(&mut children.nodes_mut()[last_i]).append_at_depth(other, depth - 1);
+                Node::make_mut(&mut children.nodes_mut()[last_i]).append_at_depth(other, depth - 1);
             children.update_child_info(last_i);
             if let Some(extra_node) = residual {
                 if children.len() < MAX_CHILDREN {
@@ -376,7 +439,7 @@ impl Node {
                 }
                 Node::Internal(ref mut children_r) => {
                     let mut other = other;
-                    if let Node::Internal(ref mut children_l) = *Arc::make_mut(&mut other) {
+                    if let Node::Internal(ref mut children_l) = *Node::make_mut(&mut other) {
                         if (children_l.len() + children_r.len()) <= MAX_CHILDREN {
                             for _ in 0..children_l.len() {
                                 children_r.insert(0, children_l.pop());
@@ -394,7 +457,7 @@ impl Node {
             }
         } else if let Node::Internal(ref mut children) = *self {
             let residual =
-                Arc::make_mut(&mut children.nodes_mut()[0]).prepend_at_depth(other, depth - 1);
+                Node::make_mut(&mut children.nodes_mut()[0]).prepend_at_depth(other, depth - 1);
             children.update_child_info(0);
             if let Some(extra_node) = residual {
                 if children.len() < MAX_CHILDREN {
@@ -436,7 +499,7 @@ impl Node {
                     let mut r_children = children.split_off(child_i + 1);
 
                     // Recurse
-                    let r_node = Arc::make_mut(&mut children.nodes_mut()[child_i])
+                    let r_node = Node::make_mut(&mut children.nodes_mut()[child_i])
                         .split(char_idx - acc_info.chars as usize);
 
                     r_children.insert(0, (r_node.text_info(), Arc::new(r_node)));
@@ -541,6 +604,32 @@ impl Node {
         }
     }
 
+    /// Returns whether the given byte index is on a char boundary.
+    ///
+    /// One-past-the-end is considered a char boundary.
+    #[inline(always)]
+    pub fn is_char_boundary(&self, byte_idx: usize) -> bool {
+        let (chunk, info) = self.get_chunk_at_byte(byte_idx);
+        chunk.is_char_boundary(byte_idx - info.bytes as usize)
+    }
+
+    /// Returns the TextInfo at the given byte index.
+    ///
+    /// The byte index must be on a char boundary.
+    #[inline(always)]
+    pub fn byte_to_text_info(&self, byte_idx: usize) -> TextInfo {
+        let (chunk, info) = self.get_chunk_at_byte(byte_idx);
+        let bi = byte_idx - info.bytes as usize;
+        debug_assert!(chunk.is_char_boundary(bi));
+        TextInfo {
+            bytes: byte_idx as Count,
+            chars: info.chars + count_chars(&chunk[..bi]) as Count,
+            utf16_surrogates: info.utf16_surrogates
+                + byte_to_utf16_surrogate_idx(chunk, bi) as Count,
+            line_breaks: info.line_breaks + byte_to_line_idx(chunk, bi) as Count,
+        }
+    }
+
     /// Returns the TextInfo at the given char index.
     #[inline(always)]
     pub fn char_to_text_info(&self, char_idx: usize) -> TextInfo {
@@ -555,6 +644,135 @@ impl Node {
         }
     }
 
+    /// Returns the TextInfo at the start of the line that contains the
+    /// given index.
+    ///
+    /// `idx` is in the metric returned by `metric`, and `to_byte_idx`
+    /// converts an index in that metric within a chunk to a byte index
+    /// within the chunk.  The line index is the `line_breaks` field of the
+    /// returned TextInfo.
+    ///
+    /// This is done in a single descent of the tree: the nearest left
+    /// sibling with line breaks is remembered on the way down, and is only
+    /// descended into if the line doesn't start in the chunk containing
+    /// `idx`.
+    pub fn line_start_info_at(
+        &self,
+        idx: usize,
+        metric: fn(&TextInfo) -> usize,
+        to_byte_idx: fn(&str, usize) -> usize,
+    ) -> TextInfo {
+        let mut node = self;
+        let mut idx = idx;
+        let mut acc_info = TextInfo::new();
+        let mut left_candidate: Option<(&Node, TextInfo)> = None;
+
+        // Descend to the chunk containing `idx`.
+        loop {
+            match *node {
+                Node::Leaf(ref text) => {
+                    let byte_idx = to_byte_idx(text, idx);
+                    let line_idx = byte_to_line_idx(text, byte_idx);
+                    if line_idx > 0 {
+                        // The line starts within this chunk.
+                        let line_start = line_to_byte_idx(text, line_idx);
+                        return acc_info + TextInfo::from_str(&text[..line_start]);
+                    }
+                    break;
+                }
+                Node::Internal(ref children) => {
+                    let last = children.len() - 1;
+                    let mut child_i = last;
+                    for (i, info) in children.info()[..last].iter().enumerate() {
+                        if idx < metric(info) {
+                            child_i = i;
+                            break;
+                        }
+                        idx -= metric(info);
+                        if info.line_breaks > 0 {
+                            left_candidate = Some((&children.nodes()[i], acc_info));
+                        }
+                        acc_info += *info;
+                    }
+                    node = &children.nodes()[child_i];
+                }
+            }
+        }
+
+        // The line starts before the chunk containing `idx`, so find the
+        // last line break in the nearest node to the left that has any.
+        if let Some((mut node, mut acc_info)) = left_candidate {
+            loop {
+                match *node {
+                    Node::Leaf(ref text) => {
+                        let line_start = line_to_byte_idx(text, byte_to_line_idx(text, text.len()));
+                        return acc_info + TextInfo::from_str(&text[..line_start]);
+                    }
+                    Node::Internal(ref children) => {
+                        let child_i = children
+                            .info()
+                            .iter()
+                            .rposition(|info| info.line_breaks > 0)
+                            .unwrap();
+                        for info in children.info()[..child_i].iter() {
+                            acc_info += *info;
+                        }
+                        node = &children.nodes()[child_i];
+                    }
+                }
+            }
+        }
+
+        // No line breaks before `idx` at all.
+        TextInfo::new()
+    }
+
+    /// Returns the TextInfo at the start and at the end of the given line,
+    /// where the end includes the line's line break.
+    ///
+    /// This is done in a single descent of the tree, which only forks
+    /// below the node where the start and end of the line are in different
+    /// children.
+    pub fn line_bounds_info(&self, line_idx: usize) -> (TextInfo, TextInfo) {
+        let mut node = self;
+        let mut line_idx = line_idx;
+        let mut acc_info = TextInfo::new();
+        loop {
+            match *node {
+                Node::Leaf(ref text) => {
+                    let start = line_to_byte_idx(text, line_idx);
+                    let end = line_to_byte_idx(text, line_idx + 1);
+                    return (
+                        acc_info + TextInfo::from_str(&text[..start]),
+                        acc_info + TextInfo::from_str(&text[..end]),
+                    );
+                }
+                Node::Internal(ref children) => {
+                    let (start_i, start_acc) = children.search_line_break_idx(line_idx);
+                    let (end_i, end_acc) = children.search_line_break_idx(line_idx + 1);
+                    if start_i != end_i {
+                        let start = children.nodes()[start_i]
+                            .line_start_info(line_idx - start_acc.line_breaks as usize);
+                        let end = children.nodes()[end_i]
+                            .line_start_info(line_idx + 1 - end_acc.line_breaks as usize);
+                        return (acc_info + start_acc + start, acc_info + end_acc + end);
+                    }
+                    acc_info += start_acc;
+                    line_idx -= start_acc.line_breaks as usize;
+                    node = &children.nodes()[start_i];
+                }
+            }
+        }
+    }
+
+    /// Returns the TextInfo at the start of the given line, or of the end
+    /// of the node if it has fewer lines.
+    fn line_start_info(&self, line_idx: usize) -> TextInfo {
+        let (chunk, info) = self.get_chunk_at_line_break(line_idx);
+        let start = line_to_byte_idx(chunk, line_idx - info.line_breaks as usize);
+        info + TextInfo::from_str(&chunk[..start])
+    }
+
     pub fn text_info(&self) -> TextInfo {
         match *self {
             Node::Leaf(ref text) => TextInfo::from_str(text),
@@ -602,6 +820,23 @@ impl Node {
         }
     }
 
+    /// The cached metric summaries of the node.
+    #[inline(always)]
+    pub fn summaries(&self) -> &NodeSummaries {
+        match *self {
+            Node::Leaf(ref text) => text.summaries(),
+            Node::Internal(ref children) => children.summaries(),
+        }
+    }
+
+    #[inline(always)]
+    fn summaries_mut(&mut self) -> &mut NodeSummaries {
+        match *self {
+            Node::Leaf(ref mut text) => text.summaries_mut(),
+            Node::Internal(ref mut children) => children.summaries_mut(),
+        }
+    }
+
     pub fn is_leaf(&self) -> bool {
         match *self {
             Node::Leaf(_) => true,
@@ -708,12 +943,12 @@ impl Node {
         if let Node::Internal(ref mut children) = *self {
             if byte_pos == 0 {
                 // Special-case 1
-                Arc::make_mut(&mut children.nodes_mut()[0])
+                Node::make_mut(&mut children.nodes_mut()[0])
                     .fix_crlf_seam(byte_pos, must_be_boundary);
             } else if byte_pos == children.combined_info().bytes {
                 // Special-case 2
                 let (info, nodes) = children.data_mut();
-                Arc::make_mut(nodes.last_mut().unwrap())
+                Node::make_mut(nodes.last_mut().unwrap())
                     .fix_crlf_seam(info.last().unwrap().bytes, must_be_boundary);
             } else {
                 // Find the child to navigate into
@@ -739,8 +974,8 @@ impl Node {
                         let (mut l_child, mut r_child) =
                             children.get_two_mut(l_child_i, l_child_i + 1);
                         let l_child_bytes = l_child.0.bytes;
-                        let l_child = Arc::make_mut(&mut l_child.1);
-                        let r_child = Arc::make_mut(&mut r_child.1);
+                        let l_child = Node::make_mut(&mut l_child.1);
+                        let r_child = Node::make_mut(&mut r_child.1);
 
                         // Get the text of the two children and fix
                         // the seam between them.
@@ -775,7 +1010,7 @@ impl Node {
                     }
                 } else {
                     // Internal to child
-                    Arc::make_mut(&mut children.nodes_mut()[child_i])
+                    Node::make_mut(&mut children.nodes_mut()[child_i])
                         .fix_crlf_seam(pos_in_child, must_be_boundary);
 
                     children.update_child_info(child_i);
@@ -795,7 +1030,7 @@ impl Node {
             Node::Leaf(ref mut text) => return (text, byte_idx),
             Node::Internal(ref mut children) => {
                 let (child_i, acc_info) = children.search_byte_idx(byte_idx);
-                Arc::make_mut(&mut children.nodes_mut()[child_i])
+                Node::make_mut(&mut children.nodes_mut()[child_i])
                     .get_chunk_at_byte_mut(byte_idx - acc_info.bytes as usize)
             }
         }
@@ -807,7 +1042,7 @@ impl Node {
         match *self {
             Node::Leaf(_) => {}
             Node::Internal(ref mut children) => {
-                Arc::make_mut(&mut children.nodes_mut()[0]).fix_info_left();
+                Node::make_mut(&mut children.nodes_mut()[0]).fix_info_left();
                 children.update_child_info(0);
                 if children.info()[0].bytes == 0 {
                     children.remove(0);
@@ -823,7 +1058,7 @@ impl Node {
             Node::Leaf(_) => {}
             Node::Internal(ref mut children) => {
                 let idx = children.len() - 1;
-                Arc::make_mut(&mut children.nodes_mut()[idx]).fix_info_right();
+                Node::make_mut(&mut children.nodes_mut()[idx]).fix_info_right();
                 children.update_child_info(idx);
                 if children.info()[idx].bytes == 0 {
                     children.remove(idx);
@@ -850,7 +1085,7 @@ impl Node {
                     did_stuff |= children.merge_distribute(0, 1);
                 }
 
-                if !Arc::make_mut(&mut children.nodes_mut()[0]).zip_fix_left() {
+                if !Node::make_mut(&mut children.nodes_mut()[0]).zip_fix_left() {
                     break;
                 }
             }
@@ -879,7 +1114,7 @@ impl Node {
                     did_stuff |= children.merge_distribute(last_i - 1, last_i);
                 }
 
-                if !Arc::make_mut(&mut children.nodes_mut().last_mut().unwrap()).zip_fix_right() {
+                if !Node::make_mut(&mut children.nodes_mut().last_mut().unwrap()).zip_fix_right() {
                     break;
                 }
             }
@@ -939,13 +1174,13 @@ impl Node {
                 if end_info.chars as usize == char_idx && (child_i + 1) < children.len() {
                     let tmp = children.info()[child_i].chars as usize;
                     let effect_1 =
-                        Arc::make_mut(&mut children.nodes_mut()[child_i]).fix_after_remove(tmp);
+                        Node::make_mut(&mut children.nodes_mut()[child_i]).fix_after_remove(tmp);
                     let effect_2 =
-                        Arc::make_mut(&mut children.nodes_mut()[child_i + 1]).fix_after_remove(0);
+                        Node::make_mut(&mut children.nodes_mut()[child_i + 1]).fix_after_remove(0);
                     if (!effect_1) && (!effect_2) {
                         break;
                     }
-                } else if !Arc::make_mut(&mut children.nodes_mut()[child_i])
+                } else if !Node::make_mut(&mut children.nodes_mut()[child_i])
                     .fix_after_remove(char_idx - start_info.chars as usize)
                 {
                     break;
@@ -4,7 +4,7 @@ use std::slice;
 use std::sync::Arc;
 
 use crate::crlf;
-use crate::tree::{self, Node, TextInfo, MAX_BYTES};
+use crate::tree::{self, Node, NodeSummaries, TextInfo, MAX_BYTES};
 
 const MAX_LEN: usize = tree::MAX_CHILDREN;
 
@@ -12,13 +12,15 @@ const MAX_LEN: usize = tree::MAX_CHILDREN;
 ///
 /// The unsafe guts of this are implemented in NodeChildrenInternal
 /// lower down in this file.
+///
+/// It also holds the cached metric summaries of the internal node it's in.
 #[derive(Clone)]
-pub(crate) struct NodeChildren(inner::NodeChildrenInternal);
+pub(crate) struct NodeChildren(inner::NodeChildrenInternal, NodeSummaries);
 
 impl NodeChildren {
     /// Creates a new empty array.
     pub fn new() -> Self {
-        NodeChildren(inner::NodeChildrenInternal::new())
+        NodeChildren(inner::NodeChildrenInternal::new(), NodeSummaries::new())
     }
 
     /// Current length of the array.
@@ -56,6 +58,16 @@ impl NodeChildren {
         self.0.data_mut()
     }
 
+    /// The cached metric summaries of the node.
+    pub fn summaries(&self) -> &NodeSummaries {
+        &self.1
+    }
+
+    /// Mutable access to the cached metric summaries of the node.
+    pub fn summaries_mut(&mut self) -> &mut NodeSummaries {
+        &mut self.1
+    }
+
     /// Updates the text info of the child at `idx`.
     pub fn update_child_info(&mut self, idx: usize) {
         let (info, nodes) = self.0.data_mut();
@@ -94,8 +106,8 @@ impl NodeChildren {
         assert!(idx2 < self.len());
         let remove_right = {
             let ((_, node1), (_, node2)) = self.get_two_mut(idx1, idx2);
-            let node1 = Arc::make_mut(node1);
-            let node2 = Arc::make_mut(node2);
+            let node1 = Node::make_mut(node1);
+            let node2 = Node::make_mut(node2);
             match *node1 {
                 Node::Leaf(ref mut text1) => {
                     if let Node::Leaf(ref mut text2) = *node2 {
@@ -168,7 +180,7 @@ impl NodeChildren {
                 // Scope to contain borrows
                 {
                     let ((_, node_l), (_, node_r)) = self.get_two_mut(i - 1, i);
-                    let text_l = Arc::make_mut(node_l).leaf_text_mut();
+                    let text_l = Node::make_mut(node_l).leaf_text_mut();
                     let text_r = node_r.leaf_text();
                     text_l.push_str(text_r);
                 }
@@ -177,8 +189,8 @@ impl NodeChildren {
                 // Scope to contain borrows
                 {
                     let ((_, node_l), (_, node_r)) = self.get_two_mut(i - 1, i);
-                    let text_l = Arc::make_mut(node_l).leaf_text_mut();
-                    let text_r = Arc::make_mut(node_r).leaf_text_mut();
+                    let text_l = Node::make_mut(node_l).leaf_text_mut();
+                    let text_r = Node::make_mut(node_r).leaf_text_mut();
                     let split_idx_r = crlf::prev_break(MAX_BYTES - text_l.len(), text_r.as_bytes());
                     text_l.push_str(&text_r[..split_idx_r]);
                     text_r.truncate_front(split_idx_r);
@@ -408,6 +420,35 @@ impl NodeChildren {
         (idx, accum_char_idx)
     }
 
+    /// Same as `search_byte_idx()` above, except that it only calulates the
+    /// left-side-accumulated _byte_ index rather than the full text info.
+    ///
+    /// Return is (child_index, left_acc_byte_index)
+    ///
+    /// One-past-the end is valid, and will return the last child.
+    #[inline(always)]
+    pub fn search_byte_idx_only(&self, byte_idx: usize) -> (usize, usize) {
+        debug_assert!(self.len() > 0);
+
+        let mut accum_byte_idx = 0;
+        let mut idx = 0;
+        for info in self.info()[0..(self.len() - 1)].iter() {
+            let next_accum = accum_byte_idx + info.bytes as usize;
+            if byte_idx < next_accum {
+                break;
+            }
+            accum_byte_idx = next_accum;
+            idx += 1;
+        }
+
+        debug_assert!(
+            byte_idx <= (accum_byte_idx + self.info()[idx].bytes as usize),
+            "Index out of bounds."
+        );
+
+        (idx, accum_byte_idx)
+    }
+
     /// Returns the child index and left-side-accumulated text info of the
     /// child that contains the given line break.
     ///
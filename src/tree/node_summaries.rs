@@ -0,0 +1,101 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+
+/// The most summaries a single node keeps at a time.  Past this, the
+/// oldest one is evicted to make room.
+pub(crate) const MAX_SUMMARIES: usize = 8;
+
+/// The cached metric summaries of a node.
+///
+/// The values are type-erased, and are filled in lazily through a shared
+/// reference as the summaries are queried, so this uses a `Mutex` to stay
+/// `Sync`.  User code (e.g. a metric's `combine()`) is never called while
+/// the lock is held.
+///
+/// The cache is invalidated as a whole whenever its node is edited (see
+/// `Node::make_mut()`), and cloning it gives an empty cache.
+#[derive(Default)]
+pub(crate) struct NodeSummaries(Mutex<Vec<Box<dyn Any + Send + Sync>>>);
+
+impl NodeSummaries {
+    /// Creates an empty cache.
+    #[inline(always)]
+    pub fn new() -> Self {
+        NodeSummaries(Mutex::new(Vec::new()))
+    }
+
+    /// Returns the result of `f` for the first cached value of type `T`
+    /// that it returns `Some` for, if any.
+    pub fn get<T, R, F>(&self, f: F) -> Option<R>
+    where
+        T: Any,
+        F: FnMut(&T) -> Option<R>,
+    {
+        self.lock()
+            .iter()
+            .filter_map(|value| value.downcast_ref::<T>())
+            .find_map(f)
+    }
+
+    /// Caches `value`, unless `is_same` is true for an already cached
+    /// value of the same type.
+    pub fn insert<T, F>(&self, value: T, is_same: F)
+    where
+        T: Any + Send + Sync,
+        F: FnMut(&T) -> bool,
+    {
+        let mut values = self.lock();
+        let exists = values
+            .iter()
+            .filter_map(|value| value.downcast_ref::<T>())
+            .any(is_same);
+        if !exists {
+            if values.len() >= MAX_SUMMARIES {
+                values.remove(0);
+            }
+            values.push(Box::new(value));
+        }
+    }
+
+    /// Drops all cached values.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        let values = match self.0.get_mut() {
+            Ok(values) => values,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if !values.is_empty() {
+            *values = Vec::new();
+        }
+    }
+
+    /// Number of cached values.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Box<dyn Any + Send + Sync>>> {
+        // A panic while the lock was held can't have left the cache in an
+        // inconsistent state, so just carry on.
+        match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl Clone for NodeSummaries {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        NodeSummaries::new()
+    }
+}
+
+impl fmt::Debug for NodeSummaries {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NodeSummaries")
+            .field("len", &self.len())
+            .finish()
+    }
+}
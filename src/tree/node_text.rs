@@ -3,22 +3,28 @@ use std::ops::Deref;
 use std::str;
 
 use crate::crlf;
+use crate::tree::NodeSummaries;
 
 /// A custom small string.  The unsafe guts of this are in `NodeSmallString`
 /// further down in this file.
+///
+/// It also holds the cached metric summaries of the leaf node it's in.
 #[derive(Clone, Default)]
-pub(crate) struct NodeText(inner::NodeSmallString);
+pub(crate) struct NodeText(inner::NodeSmallString, NodeSummaries);
 
 impl NodeText {
     /// Creates a new empty `NodeText`
     #[inline(always)]
     pub fn new() -> Self {
-        NodeText(inner::NodeSmallString::new())
+        NodeText(inner::NodeSmallString::new(), NodeSummaries::new())
     }
 
     /// Creates a new `NodeText` with the same contents as the given `&str`.
     pub fn from_str(string: &str) -> Self {
-        NodeText(inner::NodeSmallString::from_str(string))
+        NodeText(
+            inner::NodeSmallString::from_str(string),
+            NodeSummaries::new(),
+        )
     }
 
     /// Inserts a `&str` at byte offset `byte_idx`.
@@ -124,10 +130,22 @@ impl NodeText {
     /// The left part remains in the original, and the right part is
     /// returned in a new `NodeText`.
     pub fn split_off(&mut self, byte_idx: usize) -> Self {
-        let other = NodeText(self.0.split_off(byte_idx));
+        let other = NodeText(self.0.split_off(byte_idx), NodeSummaries::new());
         self.0.inline_if_possible();
         other
     }
+
+    /// The cached metric summaries of the leaf.
+    #[inline(always)]
+    pub fn summaries(&self) -> &NodeSummaries {
+        &self.1
+    }
+
+    /// Mutable access to the cached metric summaries of the leaf.
+    #[inline(always)]
+    pub fn summaries_mut(&mut self) -> &mut NodeSummaries {
+        &mut self.1
+    }
 }
 
 impl std::cmp::PartialEq for NodeText {
@@ -0,0 +1,524 @@
+//! Display widths and visual columns of text, as shown in a terminal.
+//!
+//! Each char is as wide as `unicode-width` says (two columns for East Asian
+//! wide chars, zero for combining marks and control chars), except that a
+//! tab advances to the next tab stop and a line break goes back to column
+//! zero.
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::metric::{Metric, MetricIndex};
+use crate::rope::Rope;
+use crate::slice::RopeSlice;
+use crate::str_utils::ends_with_line_break;
+use crate::{Error, Result};
+
+/// How to round a visual column that falls in the middle of a char that is
+/// more than one column wide, such as a tab or an East Asian wide char.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ColumnRounding {
+    /// Round to the start of the char.
+    Down,
+    /// Round to the end of the char.
+    Up,
+    /// Round to whichever end of the char is closer, or the end if both
+    /// are equally close.
+    Nearest,
+}
+
+impl ColumnRounding {
+    /// Picks between the char index `char_idx` starting at column
+    /// `start_col`, and `char_idx + 1` at `end_col`, for the column `col`
+    /// that falls between them.
+    #[inline]
+    fn round(self, char_idx: usize, start_col: usize, end_col: usize, col: usize) -> usize {
+        match self {
+            ColumnRounding::Down => char_idx,
+            ColumnRounding::Up => char_idx + 1,
+            ColumnRounding::Nearest if col - start_col < end_col - col => char_idx,
+            ColumnRounding::Nearest => char_idx + 1,
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn is_line_break(c: char) -> bool {
+    let mut buf = [0u8; 4];
+    ends_with_line_break(c.encode_utf8(&mut buf))
+}
+
+/// Returns the column after `c`, if it starts at column `col`.  Line breaks
+/// go back to column zero.
+#[inline]
+fn advance(col: usize, c: char, tab_width: usize) -> usize {
+    if c == '\t' {
+        next_tab_stop(col, tab_width)
+    } else if is_line_break(c) {
+        0
+    } else {
+        col + c.width().unwrap_or(0)
+    }
+}
+
+//==============================================================
+// The scanning implementations used by `RopeSlice` and `Rope`.
+
+/// Returns the visual column after `slice`'s text, starting at column 0.
+pub(crate) fn width(slice: &RopeSlice, tab_width: usize) -> usize {
+    assert!(tab_width > 0, "tab_width must be at least 1");
+    slice.chars().fold(0, |col, c| advance(col, c, tab_width))
+}
+
+/// Returns the visual column of `char_idx` within its line.
+pub(crate) fn char_to_col(slice: &RopeSlice, char_idx: usize, tab_width: usize) -> usize {
+    let line_start = slice.line_to_char(slice.char_to_line(char_idx));
+    width(&slice.slice(line_start..char_idx), tab_width)
+}
+
+/// Returns the char index at visual column `col` of line `line_idx`.
+pub(crate) fn col_to_char(
+    slice: &RopeSlice,
+    line_idx: usize,
+    col: usize,
+    tab_width: usize,
+    rounding: ColumnRounding,
+) -> usize {
+    assert!(tab_width > 0, "tab_width must be at least 1");
+    let mut char_idx = slice.line_to_char(line_idx);
+    let mut cur_col = 0;
+    for c in slice.chars_at(char_idx) {
+        if is_line_break(c) {
+            break;
+        }
+        let next_col = advance(cur_col, c, tab_width);
+        if next_col > col {
+            if cur_col == col {
+                break;
+            }
+            return rounding.round(char_idx, cur_col, next_col, col);
+        }
+        cur_col = next_col;
+        char_idx += 1;
+    }
+    char_idx
+}
+
+//==============================================================
+
+/// How a piece of text without line breaks moves a visual column.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+struct Advance {
+    width: usize,             // Columns up to the first tab.
+    after_tab: Option<usize>, // Columns after the first tab's tab stop.
+}
+
+impl Advance {
+    #[inline]
+    fn apply(self, col: usize, tab_width: usize) -> usize {
+        match self.after_tab {
+            None => col + self.width,
+            Some(after_tab) => next_tab_stop(col + self.width, tab_width) + after_tab,
+        }
+    }
+
+    #[inline]
+    fn then(self, other: Advance, tab_width: usize) -> Advance {
+        match (self.after_tab, other.after_tab) {
+            (None, None) => Advance {
+                width: self.width + other.width,
+                after_tab: None,
+            },
+            (None, Some(_)) => Advance {
+                width: self.width + other.width,
+                after_tab: other.after_tab,
+            },
+            (Some(a), None) => Advance {
+                width: self.width,
+                after_tab: Some(a + other.width),
+            },
+            // The first advance leaves the column on a tab stop, so the
+            // second one's tab stop is always the same distance past it.
+            (Some(a), Some(b)) => Advance {
+                width: self.width,
+                after_tab: Some(next_tab_stop(a + other.width, tab_width) + b),
+            },
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn next_tab_stop(col: usize, tab_width: usize) -> usize {
+    col - (col % tab_width) + tab_width
+}
+
+/// The [`Metric`](trait.Metric.html) summary of text for visual columns.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+struct VisualSummary {
+    line_breaks: usize,
+    first: Advance, // Up to the first line break.
+    last: Advance,  // After the last line break.
+    starts_with_lf: bool,
+    ends_with_cr: bool,
+    non_empty: bool,
+}
+
+impl VisualSummary {
+    /// The visual column at the end of the text, if it starts at a line
+    /// start.
+    #[inline]
+    fn col(&self, tab_width: usize) -> usize {
+        self.last.apply(0, tab_width)
+    }
+}
+
+/// Measures text's visual columns, for a given tab width.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct VisualWidth {
+    tab_width: usize,
+}
+
+impl Metric for VisualWidth {
+    type Summary = VisualSummary;
+
+    fn measure(&self, text: &str) -> VisualSummary {
+        let mut summary = VisualSummary {
+            starts_with_lf: text.starts_with('\n'),
+            ends_with_cr: cfg!(feature = "cr_lines") && text.ends_with('\r'),
+            non_empty: !text.is_empty(),
+            ..VisualSummary::default()
+        };
+        let mut advance = Advance::default();
+        let mut prev_was_cr = false;
+        for c in text.chars() {
+            if c == '\t' {
+                advance = advance.then(
+                    Advance {
+                        width: 0,
+                        after_tab: Some(0),
+                    },
+                    self.tab_width,
+                );
+            } else if is_line_break(c) {
+                // A CRLF pair is a single line break.
+                if !(prev_was_cr && c == '\n') {
+                    if summary.line_breaks == 0 {
+                        summary.first = advance;
+                    }
+                    summary.line_breaks += 1;
+                }
+                advance = Advance::default();
+            } else {
+                advance = advance.then(
+                    Advance {
+                        width: c.width().unwrap_or(0),
+                        after_tab: None,
+                    },
+                    self.tab_width,
+                );
+            }
+            prev_was_cr = cfg!(feature = "cr_lines") && c == '\r';
+        }
+        if summary.line_breaks == 0 {
+            summary.first = advance;
+        }
+        summary.last = advance;
+        summary
+    }
+
+    fn combine(&self, left: VisualSummary, right: VisualSummary) -> VisualSummary {
+        if !left.non_empty {
+            return right;
+        } else if !right.non_empty {
+            return left;
+        }
+
+        let mut line_breaks = left.line_breaks + right.line_breaks;
+        if left.ends_with_cr && right.starts_with_lf {
+            line_breaks -= 1;
+        }
+        VisualSummary {
+            line_breaks: line_breaks,
+            first: if left.line_breaks == 0 {
+                left.first.then(right.first, self.tab_width)
+            } else {
+                left.first
+            },
+            last: if right.line_breaks == 0 {
+                left.last.then(right.last, self.tab_width)
+            } else {
+                right.last
+            },
+            starts_with_lf: left.starts_with_lf,
+            ends_with_cr: right.ends_with_cr,
+            non_empty: true,
+        }
+    }
+}
+
+//==============================================================
+
+/// A cache for converting between char indices and visual columns in a
+/// `Rope`, for a fixed tab width.
+///
+/// [`RopeSlice::char_to_visual_col()`](struct.RopeSlice.html#method.char_to_visual_col)
+/// and [`RopeSlice::visual_col_to_char()`](struct.RopeSlice.html#method.visual_col_to_char)
+/// scan the line from its start, which gets slow for very long lines.
+/// `VisualColumns` instead caches how each of the rope's tree nodes moves
+/// the column, using a [`MetricIndex`](struct.MetricIndex.html), so
+/// conversions run in O(log N) time no matter how long the line is.  After
+/// an edit, only the nodes along the edited path need to be re-measured.
+///
+/// # Example
+///
+/// ```
+/// # use ropey::{ColumnRounding, Rope, VisualColumns};
+/// let mut rope = Rope::from_str("a\tb\nこんにちは");
+/// let columns = VisualColumns::new(4);
+///
+/// assert_eq!(columns.char_to_visual_col(&rope, 2), 4);
+/// assert_eq!(columns.char_to_visual_col(&rope, 6), 4);
+/// assert_eq!(
+///     columns.visual_col_to_char(&rope, 1, 3, ColumnRounding::Down),
+///     5
+/// );
+///
+/// rope.insert(0, "\t");
+/// assert_eq!(columns.char_to_visual_col(&rope, 3), 8);
+/// ```
+#[derive(Debug)]
+pub struct VisualColumns {
+    index: MetricIndex<VisualWidth>,
+}
+
+impl VisualColumns {
+    /// Creates an empty cache for the given tab width.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tab_width` is zero.
+    #[inline]
+    pub fn new(tab_width: usize) -> VisualColumns {
+        assert!(tab_width > 0, "tab_width must be at least 1");
+        VisualColumns {
+            index: MetricIndex::with_metric(VisualWidth {
+                tab_width: tab_width,
+            }),
+        }
+    }
+
+    /// Returns the tab width.
+    #[inline]
+    pub fn tab_width(&self) -> usize {
+        self.index.metric().tab_width
+    }
+
+    /// Returns the visual column of `char_idx` within its line.
+    ///
+    /// Runs in O(log N) time, plus the length of one chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn char_to_visual_col(&self, rope: &Rope, char_idx: usize) -> usize {
+        self.try_char_to_visual_col(rope, char_idx).unwrap()
+    }
+
+    /// Returns the char index at visual column `col` of line `line_idx`.
+    ///
+    /// If `col` is past the end of the line, the index of the end of the
+    /// line (before its line break) is returned.  If `col` falls in the
+    /// middle of a char, `rounding` decides which side of it to return.
+    ///
+    /// Runs in O(log N) time, plus the length of one chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line_idx` is out of bounds (i.e. `line_idx >= len_lines()`).
+    #[inline]
+    pub fn visual_col_to_char(
+        &self,
+        rope: &Rope,
+        line_idx: usize,
+        col: usize,
+        rounding: ColumnRounding,
+    ) -> usize {
+        self.try_visual_col_to_char(rope, line_idx, col, rounding)
+            .unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Fallible versions of the above methods
+
+    /// Non-panicking version of [`char_to_visual_col()`](#method.char_to_visual_col).
+    pub fn try_char_to_visual_col(&self, rope: &Rope, char_idx: usize) -> Result<usize> {
+        let tab_width = self.tab_width();
+        let summary = self.index.try_summary_to_char(rope, char_idx)?;
+        Ok(summary.col(tab_width))
+    }
+
+    /// Non-panicking version of [`visual_col_to_char()`](#method.visual_col_to_char).
+    pub fn try_visual_col_to_char(
+        &self,
+        rope: &Rope,
+        line_idx: usize,
+        col: usize,
+        rounding: ColumnRounding,
+    ) -> Result<usize> {
+        // Bounds check
+        if line_idx >= rope.len_lines() {
+            return Err(Error::LineIndexOutOfBounds(line_idx, rope.len_lines()));
+        }
+
+        // The first char index past the column, or past the line.
+        let tab_width = self.tab_width();
+        let found = self.index.search(rope, |s| {
+            s.line_breaks > line_idx || (s.line_breaks == line_idx && s.col(tab_width) > col)
+        });
+        let char_idx = match found {
+            Some(char_idx) => char_idx,
+            None => return Ok(rope.len_chars()),
+        };
+
+        // The char before it is either the line break ending the line, or
+        // the char that the column falls on.
+        let end = self.index.summary_to_char(rope, char_idx);
+        let start_col = self
+            .index
+            .summary_to_char(rope, char_idx - 1)
+            .col(tab_width);
+        if end.line_breaks > line_idx || start_col == col {
+            Ok(char_idx - 1)
+        } else {
+            Ok(rounding.round(char_idx - 1, start_col, end.col(tab_width), col))
+        }
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Hello\tthere!  How're you doing?\r\n\tIt's \
+                        a fine day,\t isn't it?\r\nAren't you glad \
+                        we're alive?\n\u{3053}\u{3093}\tにちは、\u{1F468}\u{200D}\u{1F469}\
+                        みんなさん！\te\u{301}\u{302}x\r\n\t\t\n";
+
+    /// Column of `char_idx`, the slow and simple way.
+    fn col_slow(text: &str, char_idx: usize, tab_width: usize) -> usize {
+        let mut col = 0;
+        for c in text.chars().take(char_idx) {
+            col = advance(col, c, tab_width);
+        }
+        col
+    }
+
+    #[test]
+    fn visual_width_01() {
+        let r = Rope::from_str("a\tこんにちは\te\u{301}");
+        assert_eq!(r.slice(..).visual_width(4), 1 + 3 + 10 + 2 + 1);
+        assert_eq!(r.slice(..).visual_width(8), 1 + 7 + 10 + 6 + 1);
+        assert_eq!(r.slice(2..4).visual_width(4), 4);
+        assert_eq!(Rope::from_str("").slice(..).visual_width(4), 0);
+    }
+
+    #[test]
+    fn char_to_visual_col_01() {
+        let r = Rope::from_str(TEXT);
+        for &tab_width in &[1, 3, 4, 8] {
+            let columns = VisualColumns::new(tab_width);
+            for char_idx in 0..=r.len_chars() {
+                let expected = col_slow(TEXT, char_idx, tab_width);
+                assert_eq!(r.char_to_visual_col(char_idx, tab_width), expected);
+                assert_eq!(columns.char_to_visual_col(&r, char_idx), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn visual_col_to_char_01() {
+        let r = Rope::from_str("a\tこb\u{301}\tc\n");
+        let s = r.slice(..);
+        let cases = [
+            (0, 0, 0, 0),
+            (1, 1, 1, 1),
+            (2, 1, 2, 1), // In the tab.
+            (3, 1, 2, 2),
+            (4, 2, 2, 2),
+            (5, 2, 3, 3), // In the wide char.
+            (6, 3, 3, 3),
+            (7, 5, 5, 5), // After the combining mark.
+            (8, 6, 6, 6),
+            (9, 7, 7, 7), // Past the end of the line.
+            (100, 7, 7, 7),
+        ];
+        for &(col, down, up, nearest) in cases.iter() {
+            assert_eq!(s.visual_col_to_char(0, col, 4, ColumnRounding::Down), down);
+            assert_eq!(s.visual_col_to_char(0, col, 4, ColumnRounding::Up), up);
+            assert_eq!(
+                s.visual_col_to_char(0, col, 4, ColumnRounding::Nearest),
+                nearest
+            );
+        }
+        assert_eq!(s.visual_col_to_char(1, 5, 4, ColumnRounding::Down), 8);
+        assert!(s
+            .try_visual_col_to_char(2, 0, 4, ColumnRounding::Down)
+            .is_err());
+        assert!(s.try_char_to_visual_col(9, 4).is_err());
+    }
+
+    #[test]
+    fn visual_col_to_char_02() {
+        // The cached version matches the scanning one.
+        let r = Rope::from_str(&TEXT.repeat(3));
+        let s = r.slice(..);
+        let columns = VisualColumns::new(4);
+        for line_idx in 0..r.len_lines() {
+            for col in 0..50 {
+                for &rounding in &[
+                    ColumnRounding::Down,
+                    ColumnRounding::Up,
+                    ColumnRounding::Nearest,
+                ] {
+                    assert_eq!(
+                        columns.visual_col_to_char(&r, line_idx, col, rounding),
+                        s.visual_col_to_char(line_idx, col, 4, rounding)
+                    );
+                }
+            }
+        }
+        assert!(columns
+            .try_visual_col_to_char(&r, r.len_lines(), 0, ColumnRounding::Down)
+            .is_err());
+    }
+
+    #[test]
+    fn visual_columns_01() {
+        // Edits only re-measure what changed, and stay correct.
+        let mut r = Rope::from_str(&TEXT.repeat(5));
+        let columns = VisualColumns::new(4);
+        for i in 0..50 {
+            let len = r.len_chars();
+            r.insert(
+                (i * 37) % len,
+                ["\t", "こ", "ab\r", "\n", "e\u{301}"][i % 5],
+            );
+            if i % 3 == 0 {
+                let start = (i * 17) % r.len_chars();
+                r.remove(start..(start + 3).min(r.len_chars()));
+            }
+            let text = r.to_string();
+            for char_idx in (0..=r.len_chars()).step_by(7) {
+                let line_start = r.line_to_char(r.char_to_line(char_idx));
+                let expected = col_slow(
+                    &text[r.char_to_byte(line_start)..],
+                    char_idx - line_start,
+                    4,
+                );
+                assert_eq!(columns.char_to_visual_col(&r, char_idx), expected);
+            }
+        }
+    }
+}
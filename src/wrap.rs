@@ -0,0 +1,664 @@
+//! Soft wrapping of a `Rope`'s lines into visual rows of a given width.
+
+use std::cmp::Ordering;
+use std::ops::RangeBounds;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::rope::Rope;
+use crate::slice::{end_bound_to_num, start_bound_to_num, RopeSlice};
+use crate::str_utils::count_chars;
+use crate::visual::{is_line_break, next_tab_stop};
+use crate::{Error, Result};
+
+/// The maximum number of lines in a block.
+const MAX_BLOCK_LEN: usize = 64;
+
+/// An index of where a `Rope`'s lines wrap when shown with a given wrap
+/// width, for mapping between char indices and visual (wrapped) rows.
+///
+/// Lines are wrapped by display width, as measured by
+/// [`RopeSlice::visual_width()`](struct.RopeSlice.html#method.visual_width),
+/// with tabs expanding relative to the start of their row.  A line wraps
+/// after the last whitespace that fits in the row, so words are kept
+/// together, and whitespace at the end of a row is allowed to hang past the
+/// wrap width.  Words that don't fit in a row on their own are wrapped
+/// between chars, and every row has at least one char, even if it's wider
+/// than the wrap width.
+///
+/// Edits can be made through `insert()` and `remove()`, which edit the
+/// `Rope` and update the index together, or reported after the fact with
+/// `adjust_for_insert()` and `adjust_for_remove()`.  Either way, only the
+/// lines touched by the edit are re-wrapped.
+///
+/// The wrap points are stored per line, in blocks of up to 64 lines, along
+/// with a Fenwick tree of the blocks' line and row counts.  So mapping
+/// between rows and char indices is O(log N), and an edit takes O(log N)
+/// time plus the time to re-wrap the lines it touches.  The exception is an
+/// edit that splits or removes a block, which rebuilds the Fenwick tree in
+/// O(N / 64) time.
+///
+/// # Example
+///
+/// ```
+/// # use ropey::{Rope, WrapIndex};
+/// let mut rope = Rope::from_str("The quick brown fox\njumps.");
+/// let mut wraps = WrapIndex::new(&rope, 10, 4);
+///
+/// // "The quick " | "brown fox" | "jumps."
+/// assert_eq!(wraps.len_visual_rows(), 3);
+/// assert_eq!(wraps.char_to_visual_row(&rope, 12), 1);
+/// assert_eq!(wraps.visual_row_to_char(&rope, 1), 10);
+///
+/// wraps.insert(&mut rope, 0, "Look! ");
+/// // "Look! The " | "quick " | "brown fox" | "jumps."
+/// assert_eq!(wraps.len_visual_rows(), 4);
+/// assert_eq!(wraps.visual_row_to_char(&rope, 2), 16);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WrapIndex {
+    blocks: Vec<Block>,
+    sums: Vec<(usize, usize)>, // Fenwick tree of the blocks' (lines, rows).
+    wrap_width: usize,
+    tab_width: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Block {
+    rows: usize,
+    lines: Vec<Vec<usize>>, // The char offsets where each line wraps.
+}
+
+impl WrapIndex {
+    /// Creates an index of where `rope`'s lines wrap at `wrap_width`
+    /// columns, with tabs expanded to multiples of `tab_width`.
+    ///
+    /// Runs in O(N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `wrap_width` or `tab_width` is zero.
+    pub fn new(rope: &Rope, wrap_width: usize, tab_width: usize) -> WrapIndex {
+        assert!(wrap_width > 0, "wrap_width must be at least 1");
+        assert!(tab_width > 0, "tab_width must be at least 1");
+        let lines = rope
+            .lines()
+            .map(|line| wrap_line(line, wrap_width, tab_width))
+            .collect();
+        let mut wraps = WrapIndex {
+            blocks: make_blocks(lines),
+            sums: Vec::new(),
+            wrap_width: wrap_width,
+            tab_width: tab_width,
+        };
+        wraps.rebuild_sums();
+        wraps
+    }
+
+    /// Returns the wrap width.
+    #[inline]
+    pub fn wrap_width(&self) -> usize {
+        self.wrap_width
+    }
+
+    /// Returns the tab width.
+    #[inline]
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    /// Returns the total number of visual rows.
+    #[inline]
+    pub fn len_visual_rows(&self) -> usize {
+        self.prefix_sums(self.blocks.len()).1
+    }
+
+    /// Returns the visual row that `char_idx` is on.
+    ///
+    /// A char index at a wrap point is on the row that starts there.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn char_to_visual_row(&self, rope: &Rope, char_idx: usize) -> usize {
+        self.try_char_to_visual_row(rope, char_idx).unwrap()
+    }
+
+    /// Returns the char index of the start of visual row `row_idx`.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row_idx` is out of bounds (i.e. `row_idx >= len_visual_rows()`).
+    #[inline]
+    pub fn visual_row_to_char(&self, rope: &Rope, row_idx: usize) -> usize {
+        self.try_visual_row_to_char(rope, row_idx).unwrap()
+    }
+
+    /// Returns the first visual row of line `line_idx`.
+    ///
+    /// Runs in O(log N) time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line_idx` is out of bounds (i.e. `line_idx >= len_lines()`).
+    #[inline]
+    pub fn line_to_visual_row(&self, line_idx: usize) -> usize {
+        self.try_line_to_visual_row(line_idx).unwrap()
+    }
+
+    //-----------------------------------------------------------------------
+    // Editing
+
+    /// Inserts `text` into `rope` at char index `char_idx`, and updates the
+    /// index accordingly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_idx` is out of bounds (i.e. `char_idx > len_chars()`).
+    #[inline]
+    pub fn insert(&mut self, rope: &mut Rope, char_idx: usize, text: &str) {
+        self.try_insert(rope, char_idx, text).unwrap()
+    }
+
+    /// Removes the text in the given char index range from `rope`, and
+    /// updates the index accordingly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if the
+    /// end is out of bounds (i.e. `end > len_chars()`).
+    #[inline]
+    pub fn remove<R>(&mut self, rope: &mut Rope, char_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_remove(rope, char_range).unwrap()
+    }
+
+    /// Updates the index for `len` chars having been inserted into `rope`
+    /// at char index `char_idx`.  `rope` is the rope after the insertion.
+    pub fn adjust_for_insert(&mut self, rope: &Rope, char_idx: usize, len: usize) {
+        if len == 0 {
+            return;
+        }
+        let first = rope.char_to_line(char_idx);
+        let last = rope.char_to_line(char_idx + len);
+        self.rewrap_lines(rope, first, last + 1);
+    }
+
+    /// Updates the index for the text in the given char index range having
+    /// been removed from `rope`.  `rope` is the rope after the removal.
+    pub fn adjust_for_remove<R>(&mut self, rope: &Rope, char_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = start_bound_to_num(char_range.start_bound()).unwrap_or(0);
+        let end = end_bound_to_num(char_range.end_bound()).unwrap_or(usize::MAX);
+        if start >= end {
+            return;
+        }
+        let line_idx = rope.char_to_line(start);
+        self.rewrap_lines(rope, line_idx, line_idx + 1);
+    }
+
+    //-----------------------------------------------------------------------
+    // Fallible versions of the above methods
+
+    /// Non-panicking version of [`char_to_visual_row()`](#method.char_to_visual_row).
+    pub fn try_char_to_visual_row(&self, rope: &Rope, char_idx: usize) -> Result<usize> {
+        let line_idx = rope.try_char_to_line(char_idx)?;
+        let offset = char_idx - rope.line_to_char(line_idx);
+        let (row_idx, wraps) = self.line(line_idx);
+        Ok(row_idx + count_le(wraps, |&w| w, offset))
+    }
+
+    /// Non-panicking version of [`visual_row_to_char()`](#method.visual_row_to_char).
+    pub fn try_visual_row_to_char(&self, rope: &Rope, row_idx: usize) -> Result<usize> {
+        // Bounds check
+        if row_idx >= self.len_visual_rows() {
+            return Err(Error::VisualRowOutOfBounds(row_idx, self.len_visual_rows()));
+        }
+
+        let (block_idx, (first_line, first_row)) = self.find_block(|sums| sums.1, row_idx);
+        let mut line_row = first_row;
+        for (i, wraps) in self.blocks[block_idx].lines.iter().enumerate() {
+            if row_idx <= line_row + wraps.len() {
+                let line_start = rope.line_to_char(first_line + i);
+                return Ok(if row_idx == line_row {
+                    line_start
+                } else {
+                    line_start + wraps[row_idx - line_row - 1]
+                });
+            }
+            line_row += wraps.len() + 1;
+        }
+        unreachable!()
+    }
+
+    /// Non-panicking version of [`line_to_visual_row()`](#method.line_to_visual_row).
+    pub fn try_line_to_visual_row(&self, line_idx: usize) -> Result<usize> {
+        // Bounds check
+        if line_idx < self.len_lines() {
+            Ok(self.line(line_idx).0)
+        } else {
+            Err(Error::LineIndexOutOfBounds(line_idx, self.len_lines()))
+        }
+    }
+
+    /// Non-panicking version of [`insert()`](#method.insert).
+    pub fn try_insert(&mut self, rope: &mut Rope, char_idx: usize, text: &str) -> Result<()> {
+        rope.try_insert(char_idx, text)?;
+        self.adjust_for_insert(rope, char_idx, count_chars(text));
+        Ok(())
+    }
+
+    /// Non-panicking version of [`remove()`](#method.remove).
+    pub fn try_remove<R>(&mut self, rope: &mut Rope, char_range: R) -> Result<()>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = rope.check_char_range(char_range)?;
+        rope.remove(start..end);
+        self.adjust_for_remove(rope, start..end);
+        Ok(())
+    }
+
+    //-----------------------------------------------------------------------
+    // Internal utilities
+
+    /// The number of lines in the index.
+    #[inline]
+    fn len_lines(&self) -> usize {
+        self.prefix_sums(self.blocks.len()).0
+    }
+
+    /// Returns the first row of line `line_idx`, and the offsets where it
+    /// wraps.
+    fn line(&self, line_idx: usize) -> (usize, &[usize]) {
+        let (block_idx, (first_line, first_row)) = self.find_block(|sums| sums.0, line_idx);
+        let lines = &self.blocks[block_idx].lines[..=(line_idx - first_line)];
+        let (wraps, before) = lines.split_last().unwrap();
+        let row_idx = first_row + before.iter().map(|w| w.len() + 1).sum::<usize>();
+        (row_idx, wraps)
+    }
+
+    /// Re-wraps lines `first..last` of the edited `rope`, which replace
+    /// however many lines the index had there before the edit.
+    fn rewrap_lines(&mut self, rope: &Rope, first: usize, last: usize) {
+        let old_count = (last - first) + self.len_lines() - rope.len_lines();
+        let new_lines = (first..last)
+            .map(|i| wrap_line(rope.line(i), self.wrap_width, self.tab_width))
+            .collect::<Vec<_>>();
+
+        // Gather up the lines of the blocks that the replaced lines are in.
+        let (start_block, (first_line, _)) = self.find_block(|sums| sums.0, first);
+        let end_block = if old_count == 0 {
+            start_block + 1
+        } else {
+            self.find_block(|sums| sums.0, first + old_count - 1).0 + 1
+        };
+        let mut lines = Vec::new();
+        let mut old_sums = Vec::with_capacity(end_block - start_block);
+        for block in self.blocks.drain(start_block..end_block) {
+            old_sums.push((block.lines.len(), block.rows));
+            lines.extend(block.lines);
+        }
+
+        // Replace them and re-split them into blocks.
+        let offset = first - first_line;
+        lines.splice(offset..(offset + old_count), new_lines);
+        let new_blocks = make_blocks(lines);
+        if new_blocks.len() == old_sums.len() {
+            for (i, block) in new_blocks.iter().enumerate() {
+                self.update_sums(
+                    start_block + i,
+                    old_sums[i],
+                    (block.lines.len(), block.rows),
+                );
+            }
+            self.blocks.splice(start_block..start_block, new_blocks);
+        } else {
+            self.blocks.splice(start_block..start_block, new_blocks);
+            self.rebuild_sums();
+        }
+    }
+
+    //-----------------------------------------------------------------------
+    // Fenwick tree of the blocks' (lines, rows) counts.  `sums[k - 1]` holds
+    // the counts of the `k & -k` blocks ending with `blocks[k - 1]`.
+
+    /// Rebuilds the tree from the blocks, in O(number of blocks) time.
+    fn rebuild_sums(&mut self) {
+        let n = self.blocks.len();
+        self.sums = self
+            .blocks
+            .iter()
+            .map(|b| (b.lines.len(), b.rows))
+            .collect();
+        for k in 1..=n {
+            let parent = k + (k & k.wrapping_neg());
+            if parent <= n {
+                let (lines, rows) = self.sums[k - 1];
+                self.sums[parent - 1].0 += lines;
+                self.sums[parent - 1].1 += rows;
+            }
+        }
+    }
+
+    /// Updates the counts of `blocks[block_idx]` from `old` to `new`.
+    fn update_sums(&mut self, block_idx: usize, old: (usize, usize), new: (usize, usize)) {
+        let mut k = block_idx + 1;
+        while k <= self.sums.len() {
+            let sums = &mut self.sums[k - 1];
+            sums.0 = sums.0 - old.0 + new.0;
+            sums.1 = sums.1 - old.1 + new.1;
+            k += k & k.wrapping_neg();
+        }
+    }
+
+    /// Returns the total counts of `blocks[..block_idx]`.
+    fn prefix_sums(&self, block_idx: usize) -> (usize, usize) {
+        let mut acc = (0, 0);
+        let mut k = block_idx;
+        while k > 0 {
+            acc.0 += self.sums[k - 1].0;
+            acc.1 += self.sums[k - 1].1;
+            k &= k - 1;
+        }
+        acc
+    }
+
+    /// Returns the index of the block containing line or row `idx`, as
+    /// selected by `key`, along with the first line and row of the block.
+    ///
+    /// Every block has at least one line and row, so that's the last block
+    /// whose first line or row is at or before `idx`.
+    fn find_block<F: Fn((usize, usize)) -> usize>(
+        &self,
+        key: F,
+        idx: usize,
+    ) -> (usize, (usize, usize)) {
+        let n = self.sums.len();
+        let mut block_idx = 0;
+        let mut acc = (0, 0);
+        let mut step = n.next_power_of_two();
+        while step > 0 {
+            if block_idx + step <= n {
+                let sums = self.sums[block_idx + step - 1];
+                let next = (acc.0 + sums.0, acc.1 + sums.1);
+                if key(next) <= idx {
+                    block_idx += step;
+                    acc = next;
+                }
+            }
+            step >>= 1;
+        }
+        (block_idx, acc)
+    }
+}
+
+//==============================================================
+
+/// Splits `lines` into blocks of roughly equal size.
+fn make_blocks(lines: Vec<Vec<usize>>) -> Vec<Block> {
+    let block_count = lines.len().div_ceil(MAX_BLOCK_LEN);
+    let mut blocks = Vec::with_capacity(block_count);
+    let mut lines = lines.into_iter();
+    for i in 0..block_count {
+        let block_len = lines.len() / (block_count - i);
+        let block_lines: Vec<_> = lines.by_ref().take(block_len).collect();
+        blocks.push(Block {
+            rows: block_lines.iter().map(|w| w.len() + 1).sum(),
+            lines: block_lines,
+        });
+    }
+    blocks
+}
+
+/// Returns the char offsets where `line` wraps.
+fn wrap_line(line: RopeSlice, wrap_width: usize, tab_width: usize) -> Vec<usize> {
+    let mut wraps = Vec::new();
+    let mut row_start = 0;
+    let mut col = 0; // Within the current row.
+    let mut last_space_end = None; // The offset and column after the last whitespace.
+    let mut prev_is_space = false;
+    for (i, c) in line.chars().enumerate() {
+        if is_line_break(c) {
+            break;
+        }
+        let is_space = c.is_whitespace();
+        let width = if c == '\t' {
+            next_tab_stop(col, tab_width) - col
+        } else {
+            c.width().unwrap_or(0)
+        };
+
+        // Zero-width chars such as combining marks stay with the char
+        // before them.
+        if width == 0 && !is_space {
+            continue;
+        }
+
+        if !is_space {
+            if prev_is_space {
+                last_space_end = Some((i, col));
+            }
+            if col + width > wrap_width && i > row_start {
+                let (wrap, wrap_col) = match last_space_end {
+                    Some((wrap, wrap_col)) if wrap > row_start => (wrap, wrap_col),
+                    _ => (i, col),
+                };
+                wraps.push(wrap);
+                row_start = wrap;
+                col -= wrap_col;
+
+                // The rest of the word still doesn't fit.
+                if col + width > wrap_width && i > row_start {
+                    wraps.push(i);
+                    row_start = i;
+                    col = 0;
+                }
+            }
+        }
+        col += width;
+        prev_is_space = is_space;
+    }
+    wraps
+}
+
+/// Returns the number of items at the start of the sorted `items` whose key
+/// is less than or equal to `key`.
+fn count_le<T, F: Fn(&T) -> usize>(items: &[T], f: F, key: usize) -> usize {
+    match items.binary_search_by(|item| {
+        if f(item) <= key {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }) {
+        Ok(i) | Err(i) => i,
+    }
+}
+
+//==============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Hello there!  How're you doing?\tIt's a fine day, isn't it?\r\n\
+                        Aren't you glad we're alive?\n\
+                        こんにちは、みんなさん！ e\u{301}\u{302}xtraordinarily-long-words-go-here\r\n\
+                        \n\
+                        \t\tIndented by tabs, and then some more words.";
+
+    /// The rows of `text`, the slow and simple way.
+    fn rows_slow(text: &str, wrap_width: usize, tab_width: usize) -> Vec<String> {
+        let rope = Rope::from_str(text);
+        let mut rows = Vec::new();
+        for line in rope.lines() {
+            let mut start = 0;
+            for wrap in wrap_line(line, wrap_width, tab_width) {
+                rows.push(line.slice(start..wrap).to_string());
+                start = wrap;
+            }
+            rows.push(line.slice(start..).to_string());
+        }
+        rows
+    }
+
+    /// Checks that `wraps` is consistent with `rope`.
+    fn check(wraps: &WrapIndex, rope: &Rope) {
+        let fresh = WrapIndex::new(rope, wraps.wrap_width(), wraps.tab_width());
+        let lines: Vec<_> = wraps.blocks.iter().flat_map(|b| b.lines.iter()).collect();
+        let fresh_lines: Vec<_> = fresh.blocks.iter().flat_map(|b| b.lines.iter()).collect();
+        assert_eq!(lines, fresh_lines);
+
+        let mut line_idx = 0;
+        let mut row_idx = 0;
+        for (i, block) in wraps.blocks.iter().enumerate() {
+            assert!(!block.lines.is_empty() && block.lines.len() <= MAX_BLOCK_LEN);
+            assert_eq!(wraps.prefix_sums(i), (line_idx, row_idx));
+            assert_eq!(wraps.find_block(|s| s.0, line_idx).0, i);
+            assert_eq!(wraps.find_block(|s| s.1, row_idx).0, i);
+            assert_eq!(block.rows, block.lines.iter().map(|w| w.len() + 1).sum());
+            line_idx += block.lines.len();
+            row_idx += block.rows;
+        }
+        assert_eq!(line_idx, rope.len_lines());
+        assert_eq!(row_idx, wraps.len_visual_rows());
+    }
+
+    #[test]
+    fn wrap_line_01() {
+        let rows = rows_slow(TEXT, 12, 4);
+        assert_eq!(
+            rows,
+            vec![
+                "Hello there!  ",
+                "How're you ",
+                "doing?\tIt's ",
+                "a fine day, ",
+                "isn't it?\r\n",
+                "Aren't you ",
+                "glad we're ",
+                "alive?\n",
+                "こんにちは、",
+                "みんなさん！ ",
+                "e\u{301}\u{302}xtraordinar",
+                "ily-long-wor",
+                "ds-go-here\r\n",
+                "\n",
+                "\t\t",
+                "Indented by ",
+                "tabs, and ",
+                "then some ",
+                "more words.",
+            ]
+        );
+    }
+
+    #[test]
+    fn wrap_line_02() {
+        // Wide chars wider than the wrap width still get a row each.
+        let r = Rope::from_str("こんにちは");
+        assert_eq!(wrap_line(r.slice(..), 1, 4), vec![1, 2, 3, 4]);
+        assert_eq!(wrap_line(r.slice(..), 3, 4), vec![1, 2, 3, 4]);
+        assert_eq!(wrap_line(r.slice(..), 4, 4), vec![2, 4]);
+        assert_eq!(wrap_line(r.slice(..), 10, 4), vec![]);
+    }
+
+    #[test]
+    fn char_to_visual_row_01() {
+        let r = Rope::from_str(&TEXT.repeat(10));
+        for &wrap_width in &[1, 5, 12, 40, 1000] {
+            let wraps = WrapIndex::new(&r, wrap_width, 4);
+            check(&wraps, &r);
+            let rows = rows_slow(&r.to_string(), wrap_width, 4);
+            assert_eq!(wraps.len_visual_rows(), rows.len());
+
+            let mut char_idx = 0;
+            for (row_idx, row) in rows.iter().enumerate() {
+                assert_eq!(wraps.visual_row_to_char(&r, row_idx), char_idx);
+                for _ in row.chars() {
+                    assert_eq!(wraps.char_to_visual_row(&r, char_idx), row_idx);
+                    char_idx += 1;
+                }
+            }
+            assert_eq!(
+                wraps.char_to_visual_row(&r, char_idx),
+                wraps.len_visual_rows() - 1
+            );
+        }
+    }
+
+    #[test]
+    fn line_to_visual_row_01() {
+        let r = Rope::from_str(&TEXT.repeat(20));
+        let wraps = WrapIndex::new(&r, 12, 4);
+        for line_idx in 0..r.len_lines() {
+            assert_eq!(
+                wraps.line_to_visual_row(line_idx),
+                wraps.char_to_visual_row(&r, r.line_to_char(line_idx))
+            );
+        }
+    }
+
+    #[test]
+    fn edit_01() {
+        let mut r = Rope::from_str(&TEXT.repeat(30));
+        let mut wraps = WrapIndex::new(&r, 12, 4);
+        let inserts = ["\n", "a few words ", "\r", "\n\n\nx\ny\n", "こん", "\t"];
+        for i in 0..60 {
+            let char_idx = (i * 97) % (r.len_chars() + 1);
+            wraps.insert(&mut r, char_idx, inserts[i % inserts.len()]);
+            check(&wraps, &r);
+
+            let start = (i * 61) % r.len_chars();
+            let end = (start + [1, 2, 30, 500][i % 4]).min(r.len_chars());
+            wraps.remove(&mut r, start..end);
+            check(&wraps, &r);
+        }
+
+        wraps.remove(&mut r, ..);
+        check(&wraps, &r);
+        assert_eq!(wraps.len_visual_rows(), 1);
+        wraps.insert(&mut r, 0, TEXT);
+        check(&wraps, &r);
+    }
+
+    #[test]
+    fn edit_02() {
+        // Splitting and joining CRLF pairs.
+        let mut r = Rope::from_str("aaa bbb ccc\r\nddd eee fff\r\nggg");
+        let mut wraps = WrapIndex::new(&r, 4, 4);
+        wraps.insert(&mut r, 12, "x");
+        check(&wraps, &r);
+        wraps.remove(&mut r, 12..13);
+        check(&wraps, &r);
+        wraps.remove(&mut r, 11..12);
+        check(&wraps, &r);
+        wraps.insert(&mut r, 11, "\r");
+        check(&wraps, &r);
+    }
+
+    #[test]
+    fn errors_01() {
+        let mut r = Rope::from_str("Hello world!\nHi.");
+        let mut wraps = WrapIndex::new(&r, 8, 4);
+        assert_eq!(
+            wraps.try_visual_row_to_char(&r, 3),
+            Err(Error::VisualRowOutOfBounds(3, 3))
+        );
+        assert!(wraps.try_char_to_visual_row(&r, 17).is_err());
+        assert!(wraps.try_line_to_visual_row(2).is_err());
+        assert!(wraps.try_insert(&mut r, 17, "x").is_err());
+        assert!(wraps.try_remove(&mut r, 10..17).is_err());
+        check(&wraps, &r);
+    }
+}
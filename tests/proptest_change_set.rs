@@ -0,0 +1,103 @@
+#[macro_use]
+extern crate proptest;
+extern crate ropey;
+
+use proptest::collection::vec;
+use proptest::test_runner::Config;
+use ropey::{ChangeSet, Rope};
+
+/// Builds a valid `ChangeSet` for a document of `len` chars out of
+/// arbitrary (position, deletion length, inserted text) triples.
+fn make_change_set(len: usize, raw: &[(usize, usize, String)]) -> ChangeSet {
+    let mut edits: Vec<_> = raw
+        .iter()
+        .map(|&(pos, del, ref text)| (pos % (len + 1), del, text.as_str()))
+        .collect();
+    edits.sort_by_key(|e| e.0);
+
+    let mut changes = Vec::new();
+    let mut prev_end = 0;
+    for (pos, del, text) in edits {
+        let start = pos.max(prev_end);
+        let end = (start + del).min(len);
+        changes.push((start..end, if text.is_empty() { None } else { Some(text) }));
+        prev_end = end;
+    }
+    ChangeSet::from_changes(len, changes)
+}
+
+fn edits() -> impl proptest::strategy::Strategy<Value = Vec<(usize, usize, String)>> {
+    vec((0usize..10000, 0usize..8, "\\PC{0,4}"), 0..8)
+}
+
+//===========================================================================
+
+proptest! {
+    #![proptest_config(Config::with_cases(512))]
+
+    #[test]
+    fn pt_transform_converges(ref text in "\\PC{0,40}", ref raw_a in edits(), ref raw_b in edits()) {
+        let base = Rope::from_str(text);
+        let a = make_change_set(base.len_chars(), raw_a);
+        let b = make_change_set(base.len_chars(), raw_b);
+        let (a2, b2) = a.transform(&b);
+
+        let mut r1 = base.clone();
+        a.apply(&mut r1);
+        b2.apply(&mut r1);
+
+        let mut r2 = base.clone();
+        b.apply(&mut r2);
+        a2.apply(&mut r2);
+
+        assert_eq!(r1, r2);
+        r1.assert_integrity();
+        r1.assert_invariants();
+    }
+
+    #[test]
+    fn pt_transform_compose(ref text in "\\PC{0,40}", ref raw_a in edits(), ref raw_b in edits()) {
+        let base = Rope::from_str(text);
+        let a = make_change_set(base.len_chars(), raw_a);
+        let b = make_change_set(base.len_chars(), raw_b);
+        let (a2, b2) = a.transform(&b);
+
+        let mut r1 = base.clone();
+        a.compose(&b2).apply(&mut r1);
+
+        let mut r2 = base.clone();
+        b.compose(&a2).apply(&mut r2);
+
+        assert_eq!(r1, r2);
+    }
+
+    #[test]
+    fn pt_transform_identity(ref text in "\\PC{0,40}", ref raw_a in edits()) {
+        let base = Rope::from_str(text);
+        let a = make_change_set(base.len_chars(), raw_a);
+        let id = ChangeSet::identity(base.len_chars());
+        let (a2, id2) = a.transform(&id);
+
+        assert_eq!(a2, a);
+        assert!(id2.is_identity());
+        assert_eq!(id2.len_before(), a.len_after());
+    }
+
+    #[test]
+    fn pt_compose_invert(ref text in "\\PC{0,40}", ref raw_a in edits(), ref raw_b in edits()) {
+        let base = Rope::from_str(text);
+        let a = make_change_set(base.len_chars(), raw_a);
+        let mut r = base.clone();
+        a.apply(&mut r);
+        let b = make_change_set(r.len_chars(), raw_b);
+
+        let mut r1 = r.clone();
+        b.apply(&mut r1);
+        let mut r2 = base.clone();
+        a.compose(&b).apply(&mut r2);
+        assert_eq!(r1, r2);
+
+        a.invert(&base).apply(&mut r);
+        assert_eq!(r, base);
+    }
+}
@@ -4,10 +4,10 @@ extern crate ropey;
 
 use proptest::collection::vec;
 use proptest::test_runner::Config;
+#[cfg(feature = "unicode_lines")]
+use ropey::str_utils::line_to_byte_idx;
 use ropey::{
-    str_utils::{
-        byte_to_char_idx, byte_to_line_idx, char_to_byte_idx, char_to_line_idx, line_to_byte_idx,
-    },
+    str_utils::{byte_to_char_idx, byte_to_line_idx, char_to_byte_idx, char_to_line_idx},
     Rope,
 };
 
@@ -40,6 +40,7 @@ fn string_slice(text: &str, char_start: usize, char_end: usize) -> &str {
 /// For example, giving the byte index for the LF in a CRLF pair does not
 /// count the CR as a line ending, since we're not past the complete line
 /// ending.
+#[cfg(feature = "unicode_lines")]
 fn byte_to_line_index_slow(text: &str, byte_idx: usize) -> usize {
     assert!(byte_idx <= text.len());
 
@@ -100,6 +101,7 @@ fn byte_to_line_index_slow(text: &str, byte_idx: usize) -> usize {
 ///
 /// We use this to verify the faster-but-more-complex functions in
 /// Ropey.
+#[cfg(feature = "unicode_lines")]
 fn line_to_byte_index_slow(text: &str, line_idx: usize) -> usize {
     assert!(line_idx <= byte_to_line_idx(text, text.len()));
 
@@ -497,6 +499,7 @@ proptest! {
     }
 
     #[test]
+    #[cfg(feature = "unicode_lines")]
     fn pt_byte_to_line_idx(ref text in "[\\u{000A}\\u{000B}\\u{000C}\\u{000D}\\u{0085}\\u{2028}\\u{2029}]*[\\u{000A}\\u{000B}\\u{000C}\\u{000D}\\u{0085}\\u{2028}\\u{2029}]*", idx in 0usize..200) {
         let idx = idx % (text.len() + 1);
         assert_eq!(
@@ -506,6 +509,7 @@ proptest! {
     }
 
     #[test]
+    #[cfg(feature = "unicode_lines")]
     fn pt_line_to_byte_idx(ref text in "[\\u{000A}\\u{000B}\\u{000C}\\u{000D}\\u{0085}\\u{2028}\\u{2029}]*[\\u{000A}\\u{000B}\\u{000C}\\u{000D}\\u{0085}\\u{2028}\\u{2029}]*", idx in 0usize..200) {
         let line_count = byte_to_line_idx(text, text.len());
         let idx = idx % (line_count + 1);
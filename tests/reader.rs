@@ -0,0 +1,48 @@
+extern crate ropey;
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use ropey::Rope;
+
+const TEXT: &str = include_str!("test_text.txt");
+
+#[test]
+fn reader_01() {
+    // Round trip through a reader.
+    let rope = Rope::from_str(TEXT);
+    let rope2 = Rope::from_reader(rope.reader()).unwrap();
+
+    assert_eq!(rope2, TEXT);
+
+    // Make sure the tree is sound
+    rope2.assert_integrity();
+    rope2.assert_invariants();
+}
+
+#[test]
+fn reader_02() {
+    // Stream out a slice with `io::copy()`.
+    let rope = Rope::from_str(TEXT);
+    let slice = rope.slice(1000..5000);
+    let mut bytes = Vec::new();
+    let len = io::copy(&mut slice.reader(), &mut bytes).unwrap();
+
+    assert_eq!(len as usize, slice.len_bytes());
+    assert_eq!(bytes, slice.to_string().as_bytes());
+}
+
+#[test]
+fn reader_03() {
+    // Read a snapshot while editing the original.
+    let mut rope = Rope::from_str(TEXT);
+    let snapshot = rope.clone();
+    let mut reader = snapshot.reader();
+    reader.seek(SeekFrom::Start(100)).unwrap();
+
+    rope.remove(..);
+    rope.insert(0, "Hello!");
+
+    let mut text = String::new();
+    reader.read_to_string(&mut text).unwrap();
+    assert_eq!(text, &TEXT[100..]);
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "regex")]
+
+extern crate regex;
+extern crate ropey;
+
+use ropey::{Regex, Rope};
+
+const TEXT: &str = include_str!("test_text.txt");
+
+const PATTERNS: &[&str] = &[
+    r"[a-z]+",
+    r"(?i)the",
+    r"\s+\w",
+    r"(?m)^\w+",
+    r"(?m)\w+$",
+    r"[^\x00-\x7F]+",
+    r"(?-u:\b)[A-Z][a-z]*(?-u:\b)",
+    r"a*",
+    r"",
+    r"\n\n",
+    r"zzzz",
+];
+
+fn rope() -> Rope {
+    Rope::from_str(&TEXT[..22029].repeat(2))
+}
+
+#[test]
+fn find_regex_iter_matches_regex_crate() {
+    let r = rope();
+    let text = r.to_string();
+    for pattern in PATTERNS {
+        let ours = Regex::new(pattern).unwrap();
+        let theirs = regex::Regex::new(pattern).unwrap();
+
+        let found: Vec<_> = r.find_regex_iter(&ours).map(|m| m.byte_range()).collect();
+        let expected: Vec<_> = theirs.find_iter(&text).map(|m| m.range()).collect();
+        assert_eq!(found, expected, "pattern: {:?}", pattern);
+
+        for m in r.find_regex_iter(&ours).take(100) {
+            assert_eq!(r.byte_to_char(m.byte_range().start), m.char_range().start);
+            assert_eq!(r.byte_to_char(m.byte_range().end), m.char_range().end);
+        }
+    }
+}
+
+#[test]
+fn find_regex_in_slice() {
+    let r = rope();
+    let s = r.slice(1000..5000);
+    let text = s.to_string();
+    for pattern in PATTERNS {
+        let ours = Regex::new(pattern).unwrap();
+        let theirs = regex::Regex::new(pattern).unwrap();
+
+        assert_eq!(
+            s.find_regex(&ours).map(|m| m.byte_range()),
+            theirs.find(&text).map(|m| m.range()),
+            "pattern: {:?}",
+            pattern
+        );
+    }
+}
+
+#[test]
+fn rfind_regex_matches_regex_crate() {
+    let r = rope();
+    let s = r.slice(0..3000);
+    let text = s.to_string();
+    for pattern in PATTERNS {
+        let ours = Regex::new(pattern).unwrap();
+        let theirs = regex::Regex::new(pattern).unwrap();
+
+        // The match starting last.
+        let expected = (0..=text.len())
+            .rev()
+            .filter(|&i| text.is_char_boundary(i))
+            .filter_map(|i| theirs.find_at(&text, i).filter(|m| m.start() == i))
+            .next()
+            .map(|m| m.range());
+        assert_eq!(
+            s.rfind_regex(&ours).map(|m| m.byte_range()),
+            expected,
+            "pattern: {:?}",
+            pattern
+        );
+    }
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "serde")]
+
+extern crate ropey;
+extern crate serde_json;
+extern crate serde_test;
+
+use ropey::Rope;
+use serde_test::{assert_tokens, Token};
+
+const TEXT: &str = include_str!("test_text.txt");
+
+#[test]
+fn serde_tokens_01() {
+    let r = Rope::from_str("Hello world!\r\nこんにちは");
+    assert_tokens(&r, &[Token::Str("Hello world!\r\nこんにちは")]);
+}
+
+#[test]
+fn serde_tokens_02() {
+    let r = Rope::from_str("");
+    assert_tokens(&r, &[Token::Str("")]);
+}
+
+#[test]
+fn serde_json_01() {
+    let r = Rope::from_str(TEXT);
+    let json = serde_json::to_string(&r).unwrap();
+    assert_eq!(json, serde_json::to_string(TEXT).unwrap());
+
+    let r2: Rope = serde_json::from_str(&json).unwrap();
+    assert_eq!(r2, TEXT);
+    r2.assert_integrity();
+    r2.assert_invariants();
+}
+
+#[test]
+fn serde_json_02() {
+    let r = Rope::from_str(TEXT);
+    let s = r.slice(1000..40000);
+    let json = serde_json::to_string(&s).unwrap();
+    assert_eq!(json, serde_json::to_string(&s.to_string()).unwrap());
+
+    let r2: Rope = serde_json::from_str(&json).unwrap();
+    assert_eq!(r2, s);
+}
+
+#[test]
+fn serde_json_03() {
+    assert!(serde_json::from_str::<Rope>("42").is_err());
+    assert!(serde_json::from_str::<Rope>("[\"a\", \"b\"]").is_err());
+}